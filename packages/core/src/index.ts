@@ -15,13 +15,34 @@ export {
 
     UnsignedTx,
     UnsignedBitcoinTx,
+    BumpFee,
+    BatchSignedTx,
+    ContractCall,
+    EthereumTxPreview,
+    BitcoinTxPreview,
+    TxPreview,
+    SpendingPolicy,
+    PolicyRejectionCode,
+    BuiltBitcoinTx,
+    BitcoinTxSize,
     UnsignedEthereumTx,
     UnsignedBasicEthereumTx,
     UnsignedEIP1559EthereumTx,
+    UnsignedEIP7702EthereumTx,
+    Authorization,
+    UnsignedAuthorization,
+    UnsignedRawEthereumTx,
     isBitcoinTx,
     isEthereumTx,
     DEFAULT_BITCOIN_SEQ,
     SignedTx,
+    SignPsbtOptions,
+    SignedPsbt,
+    MultisigScript,
+    MultisigCosigner,
+    AddMultisig,
+    Multisig,
+    DecodedTx, DecodedEthereumTx, DecodedBitcoinTx,
 
     AddressSingle,
     AddressXPub,
@@ -47,10 +68,24 @@ export {
     SeedDefinition,
     ImportMnemonic,
     MnemonicSeed,
+    Slip39Seed,
+    Slip39Group,
+    Slip39SplitOptions,
+    Bip85Application,
+    Bip85Options,
+    Bip85Child,
     RawSeed,
 
     SeedDetails,
     SeedDescription,
+    SeedUsage,
+    SeedRemoveResult,
+    SeedAddress,
+    XpubFormat,
+    AccountXpub,
+    EntropySource,
+    MnemonicEntropyOptions,
+    MnemonicFromEntropy,
 
     SeedReference,
     LedgerSeedReference,
@@ -59,12 +94,14 @@ export {
     isIdSeedReference,
 
     MnemonicSeedDefinition,
+    Slip39SeedDefinition,
     RawSeedDefinition,
 
     HDPathAccount,
     HDPathAccounts,
     isLedger,
     isMnemonic,
+    isSlip39,
     isRawSeed,
     isSeedPkRef,
 
@@ -86,6 +123,7 @@ export {
 
     UnsignedMessage,
     SignedMessage,
+    MessageSignatureType,
 
     IconDetails,
 
@@ -2,10 +2,11 @@ export type BlockchainType = "ethereum" | "bitcoin";
 /**
  * Type of Seed:
  * - `mnemonic` series of words, as per BIP-39
+ * - `slip39` SLIP-39 shares
  * - `raw` BIP-39 key in bytes, that's what you get after processing BIP-39 phrase and that's what Vault stored on disk
  * - `ledger` Ledger Hardware Key
  */
-export type SeedType = "raw" | "ledger" | "mnemonic";
+export type SeedType = "raw" | "ledger" | "mnemonic" | "slip39";
 /**
  * Type of reference to a Seed:
  * - `ledger` a currently connected Ledger device
@@ -171,7 +172,68 @@ export interface UnsignedEIP1559EthereumTx {
     }[] | null
 }
 
-export type UnsignedEthereumTx = UnsignedBasicEthereumTx | UnsignedEIP1559EthereumTx;
+/**
+ * Signed EIP-7702 authorization, which sets the code of the signer account to a delegation to the `address`.
+ *
+ * See EIP-7702 - https://eips.ethereum.org/EIPS/eip-7702
+ */
+export interface Authorization {
+    /**
+     * Chain Id, or `0` if the authorization is valid on any chain
+     */
+    chainId: number,
+    /**
+     * Delegate, i.e., the contract with the code to use for the account
+     */
+    address: string,
+    /**
+     * Nonce of the signer account when the authorization is applied. If the same account sends the transaction it's the
+     * transaction nonce plus one.
+     */
+    nonce: number,
+    yParity: number,
+    r: string,
+    s: string,
+}
+
+/**
+ * EIP-7702 authorization to sign by an entry, i.e., a delegation of the entry account to the `address`
+ */
+export interface UnsignedAuthorization {
+    /**
+     * Chain Id, by default the chain of the entry. Set `0` explicitly to make it valid on any chain
+     */
+    chainId?: number,
+    address: string,
+    nonce: number,
+}
+
+/**
+ * EIP-1559 transaction that also carries authorizations to set the code of the accounts that signed them (EIP-7702).
+ * The authorizations may be signed by other accounts, so the transaction sender can pay for their delegation.
+ * Such transaction cannot create a contract.
+ */
+export interface UnsignedEIP7702EthereumTx extends UnsignedEIP1559EthereumTx {
+    to: string,
+    /**
+     * Signed authorizations, at least one
+     */
+    authorizationList: Authorization[],
+}
+
+/**
+ * Unsigned Ethereum transaction prepared by another tool, as RLP encoded hex. It's either an EIP-2718 envelope
+ * without the signature (i.e., `0x02 || rlp([chainId, nonce, ...])`), or a legacy transaction prepared for EIP-155 signing
+ * (i.e., `rlp([nonce, gasPrice, gas, to, value, data, chainId, 0, 0])`).
+ *
+ * The chain id of the transaction must match the blockchain of the entry.
+ * An EIP-2930 transaction (type `1`) is accepted only in this form, and it cannot be signed by a hardware key.
+ */
+export interface UnsignedRawEthereumTx {
+    raw: string,
+}
+
+export type UnsignedEthereumTx = UnsignedBasicEthereumTx | UnsignedEIP1559EthereumTx | UnsignedEIP7702EthereumTx | UnsignedRawEthereumTx;
 
 export interface UnsignedBitcoinTx {
     inputs: {
@@ -190,17 +252,73 @@ export interface UnsignedBitcoinTx {
         entryId?: EntryId;
         hdPath?: string;
     }[];
+    /**
+     * Fee in satoshi, which must be the difference between the inputs and the outputs
+     */
+    fee?: number;
+    /**
+     * Fee rate in sat/vB, as an alternative to the `fee`. The fee is calculated from the estimated size of the signed transaction
+     * and taken from the output to a change address of the entry. If there is no change output the difference between
+     * the inputs and the outputs must be enough for the rate.
+     */
+    feeRate?: number;
+    /**
+     * Opt in for BIP-125 replacement, so the fee can be increased later. Inputs with a final sequence get `0xfffffffd` instead
+     */
+    rbf?: boolean;
+}
+
+/**
+ * Estimated size of a signed Bitcoin transaction
+ */
+export interface BitcoinTxSize {
+    /**
+     * Size in vbytes
+     */
+    vsize: number;
+    /**
+     * Weight in weight units
+     */
+    weight: number;
+}
+
+/**
+ * Unsigned Bitcoin transaction made by the coin selection, ready to sign with `signTx`
+ */
+export interface BuiltBitcoinTx extends UnsignedBitcoinTx, BitcoinTxSize {
     fee: number;
 }
 
 export type UnsignedTx = UnsignedBitcoinTx | UnsignedEthereumTx;
 
+/**
+ * Replacement of a signed Bitcoin transaction with a higher fee (BIP-125)
+ */
+export interface BumpFee {
+    /**
+     * Original signed transaction as hex. It must signal replaceability
+     */
+    raw: string;
+    /**
+     * Fee rate of the replacement, in sat/vB
+     */
+    feeRate: number;
+    /**
+     * UTXOs spent by the original transaction, and optionally other UTXOs of the entry to add if the change is not enough
+     */
+    utxos: UnsignedBitcoinTx["inputs"];
+    /**
+     * Address for the change, if the original transaction has no change
+     */
+    changeAddress?: string;
+}
+
 export function isBitcoinTx(tx: UnsignedTx): tx is UnsignedBitcoinTx {
     return typeof tx == "object" && Object.keys(tx).indexOf("inputs") >= 0;
 }
 
 export function isEthereumTx(tx: UnsignedTx): tx is UnsignedEthereumTx {
-    return typeof tx == "object" && Object.keys(tx).indexOf("from") >= 0;
+    return typeof tx == "object" && (Object.keys(tx).indexOf("from") >= 0 || Object.keys(tx).indexOf("raw") >= 0);
 }
 
 /**
@@ -217,6 +335,352 @@ export interface SignedTx {
     txid: string;
 }
 
+/**
+ * Result of a transaction signed in a batch, which is either the signed transaction or the error for it
+ */
+export type BatchSignedTx = SignedTx | {
+    error: {
+        code: number;
+        message: string;
+    }
+};
+
+/**
+ * Token method decoded from the calldata of an Ethereum transaction. Amounts and token ids are decimal strings.
+ */
+export type ContractCall =
+    { type: "erc20Transfer", to: string, amount: string } |
+    /**
+     * ERC-20 or ERC-721 transfer, where the value is an amount or a token id
+     */
+    { type: "transferFrom", from: string, to: string, value: string } |
+    { type: "erc721Transfer", from: string, to: string, tokenId: string } |
+    { type: "erc1155Transfer", from: string, to: string, id: string, amount: string } |
+    { type: "erc1155BatchTransfer", from: string, to: string, ids: string[], amounts: string[] } |
+    { type: "approve", spender: string, amount: string, unlimited: boolean } |
+    { type: "setApprovalForAll", operator: string, approved: boolean };
+
+export interface EthereumTxPreview {
+    chainId: number;
+    from: string;
+    /**
+     * Target address, not set for a contract creation
+     */
+    to?: string;
+    toLabel?: string;
+    /**
+     * Value in wei, as a decimal string
+     */
+    value: string;
+    /**
+     * Gas limit by the max gas price, in wei
+     */
+    maxFee: string;
+    /**
+     * Value plus the max fee, in wei
+     */
+    maxCost: string;
+    call?: ContractCall;
+    /**
+     * Address that gets the tokens or the allowance of the call, or the target address otherwise
+     */
+    recipient?: string;
+    recipientLabel?: string;
+    /**
+     * The call gives an unlimited allowance, or access to all tokens of a collection
+     */
+    unlimitedApproval: boolean;
+    /**
+     * Accounts delegated by an EIP-7702 transaction. After that the account can be controlled by the code of the delegate
+     */
+    delegations?: {
+        /**
+         * Account that signed the authorization. Not set if the signature is invalid, in which case the authorization is skipped
+         */
+        authority?: string;
+        address: string;
+        /**
+         * Chain Id of the authorization, where `0` means any chain
+         */
+        chainId: number;
+    }[];
+}
+
+export interface BitcoinTxPreview {
+    outputs: {
+        address: string;
+        amount: number;
+        /**
+         * The output is to a change address of the entry
+         */
+        change: boolean;
+    }[];
+    /**
+     * Sum of the outputs except the change
+     */
+    sent: number;
+    fee: number;
+}
+
+/**
+ * What a transaction signs, as it's signed by `signTx`
+ */
+export type TxPreview = EthereumTxPreview | BitcoinTxPreview;
+
+/**
+ * Rules checked before an entry signs a transaction. Amounts are decimal strings in wei or satoshi.
+ * A transaction that breaks a rule is rejected with the code of `PolicyRejectionCode`.
+ */
+export interface SpendingPolicy {
+    /**
+     * Max total of the values and the fees signed during the last 24 hours. Tokens moved by a contract call are not counted
+     */
+    dailyLimit?: string;
+    /**
+     * Max fee of a transaction. For Ethereum it's the gas limit by the max gas price
+     */
+    maxFee?: string;
+    /**
+     * Max gas price of an Ethereum transaction, or the max fee per gas for EIP-1559
+     */
+    maxGasPrice?: string;
+    /**
+     * If set, only the recipients in the list can be paid. A contract creation is not allowed
+     */
+    allowlist?: {
+        addresses?: string[];
+    };
+    /**
+     * Selectors of the contract methods which cannot be called, ex. `0x095ea7b3` for `approve`
+     */
+    blockedMethods?: string[];
+}
+
+export enum PolicyRejectionCode {
+    DAILY_LIMIT = 170,
+    RECIPIENT = 171,
+    FEE = 172,
+    METHOD = 173,
+    /**
+     * Policy is modified outside of the Vault, or cannot be verified
+     */
+    INTEGRITY = 174,
+}
+
+export interface SignPsbtOptions {
+    /**
+     * Finalize the inputs and extract the signed transaction, if all inputs are signed after that. Default is `false`
+     */
+    finalize?: boolean;
+}
+
+/**
+ * PSBT (BIP-174) after signing with an entry
+ */
+export interface SignedPsbt {
+    /**
+     * Updated PSBT as base64
+     */
+    psbt: string;
+    /**
+     * Indexes of the inputs signed with the entry
+     */
+    signed: number[];
+    /**
+     * `true` if all the inputs are finalized
+     */
+    complete: boolean;
+    /**
+     * Signed transaction as hex, only if `finalize` was requested and the PSBT is complete
+     */
+    raw?: string;
+    txid?: string;
+}
+
+/**
+ * Script of a multisig: native SegWit `wsh(sortedmulti(...))` or nested `sh(wsh(sortedmulti(...)))`
+ */
+export type MultisigScript = "p2wsh" | "p2sh-p2wsh";
+
+export interface MultisigCosigner {
+    /**
+     * Account Extended Public Key. Any of SLIP-132 formats is accepted, including `Zpub` and `Ypub`
+     */
+    xpub: string;
+    /**
+     * Master Key fingerprint, as 8 hex chars
+     */
+    fingerprint: string;
+    /**
+     * Account path, ex. `m/48'/0'/0'/2'`
+     */
+    path: string;
+    /**
+     * Seed of this vault. Set only for the cosigner of this vault
+     */
+    seedId?: Uuid;
+}
+
+export interface AddMultisig {
+    blockchain: number;
+    label?: string;
+    script: MultisigScript;
+    /**
+     * Number of signatures required to spend
+     */
+    threshold: number;
+    /**
+     * Cosigners of other vaults
+     */
+    cosigners: MultisigCosigner[];
+    /**
+     * Seed of this vault, which becomes one of the cosigners
+     */
+    seed: {
+        seedId: Uuid;
+        password: string;
+        /**
+         * Account path, by default it's BIP-48 `m/48'/coin'/0'/type'`
+         */
+        hdPath?: string;
+    };
+}
+
+/**
+ * Multisig setup where this vault is one of the cosigners
+ */
+export interface Multisig {
+    id: Uuid;
+    walletId: Uuid;
+    blockchain: number;
+    label?: string;
+    script: MultisigScript;
+    threshold: number;
+    /**
+     * All cosigners, including the one of this vault
+     */
+    cosigners: MultisigCosigner[];
+    /**
+     * Output descriptor for the receive addresses
+     */
+    descriptor: string;
+    /**
+     * Output descriptor for the change addresses
+     */
+    changeDescriptor: string;
+}
+
+/**
+ * Ethereum transaction decoded from its raw form
+ */
+export interface DecodedEthereumTx {
+    /**
+     * EIP-2718 type: `0` for a legacy transaction, `1` for EIP-2930, `2` for EIP-1559 and `4` for EIP-7702
+     */
+    type: number;
+    /**
+     * Chain Id. Not set for a legacy transaction signed without EIP-155
+     */
+    chainId?: number;
+    nonce: number;
+    gas: number;
+    /**
+     * Gas Price for a legacy or EIP-2930 transaction, in Wei as a decimal string
+     */
+    gasPrice?: string;
+    /**
+     * Max Fee for EIP-1559 transaction, in Wei as a decimal string
+     */
+    maxGasPrice?: string;
+    /**
+     * Priority Fee for EIP-1559 transaction, in Wei as a decimal string
+     */
+    priorityGasPrice?: string;
+    /**
+     * Target address. Not set for a contract creation
+     */
+    to?: string;
+    /**
+     * Value in Wei as a decimal string
+     */
+    value: string;
+    /**
+     * Data as hex, `0x` if empty
+     */
+    data: string;
+    accessList?: {
+        address: string;
+        storage: string[];
+    }[];
+    /**
+     * Authorizations of an EIP-7702 transaction
+     */
+    authorizationList?: Authorization[];
+    /**
+     * Sender address recovered from the signature
+     */
+    from: string;
+    /**
+     * Transaction hash
+     */
+    hash: string;
+    v: number;
+    r: string;
+    s: string;
+}
+
+/**
+ * Bitcoin transaction decoded from its raw form
+ */
+export interface DecodedBitcoinTx {
+    txid: string;
+    wtxid: string;
+    version: number;
+    locktime: number;
+    /**
+     * Size in bytes
+     */
+    size: number;
+    /**
+     * Virtual size in vbytes
+     */
+    vsize: number;
+    weight: number;
+    inputs: {
+        txid: string;
+        vout: number;
+        sequence: number;
+        /**
+         * Script Sig as hex, empty for a native SegWit input
+         */
+        scriptSig: string;
+        /**
+         * Witness items as hex
+         */
+        witness: string[];
+        /**
+         * Address of the spent output, if it can be deduced from the input (i.e., P2PKH, P2SH-P2WPKH or P2WPKH spending)
+         */
+        address?: string;
+    }[];
+    outputs: {
+        /**
+         * Amount in Satoshi
+         */
+        amount: number;
+        /**
+         * Script Pubkey as hex
+         */
+        script: string;
+        /**
+         * Address, if the script is a standard one
+         */
+        address?: string;
+    }[];
+}
+
+export type DecodedTx = DecodedEthereumTx | DecodedBitcoinTx;
+
 export type ImportMnemonic = {
     name?: string | null,
     description?: string | null,
@@ -352,6 +816,10 @@ export type Wallet = {
     description?: string | undefined,
     entries: WalletEntry[],
     reserved?: HDPathAccount[] | undefined,
+    /**
+     * Multisig setups of the wallet (see `addMultisig`). They are not included into a Vault snapshot
+     */
+    multisig?: Multisig[] | undefined,
     createdAt: Date,
 }
 
@@ -473,6 +941,12 @@ export type AddEntry = AddJsonEntry | AddRawPkEntry | AddSeedEntry | AddRandomEn
 
 export type SeedEntry = {
     seed: SeedReference,
+    /**
+     * HD Path of the key. For Ethereum, a non-standard path (ex. legacy MEW `m/44'/60'/0'/1`) is supported only for a seed stored in the Vault,
+     * and such key is derived and stored as an individual private key.
+     * For Bitcoin, it's an account path, including Taproot (BIP-86) accounts `m/86'/0'/x'`, which require the seed password
+     * and are not supported for a Hardware Key.
+     */
     hdPath: string,
     /**
      * (optional) Expected Address on that path
@@ -496,7 +970,121 @@ export interface SeedDetails {
 export interface SeedDescription extends SeedDetails {
     id?: Uuid,
     type: SeedType,
+    /**
+     * For a Hardware Key it's `true` only if the connected device is the same as the seed. A Hardware Key seed added
+     * by an older version doesn't know its device, so it's matched to the first connected device and remembers it.
+     */
     available: boolean,
+    /**
+     * `true` if the mnemonic phrase was saved with the seed and can be revealed for a backup
+     */
+    revealable?: boolean,
+    /**
+     * BIP-32 Master Key fingerprint as 8 hex chars, ex. `73c5da0a`. It's known only after the seed is added or used with its password,
+     * and it's not available for a Hardware Key
+     */
+    fingerprint?: string,
+}
+
+/**
+ * Reference to a Seed from a Wallet. It's either an entry that uses the seed, or an account reserved on the seed.
+ */
+export interface SeedUsage {
+    walletId: Uuid;
+    /**
+     * Entry that uses the seed
+     */
+    entryId?: EntryId;
+    /**
+     * Account reserved by the wallet on the seed
+     */
+    accountId?: number;
+}
+
+/**
+ * Address (or XPub for a Bitcoin account) on a seed. Has either `address` or `error` if the address is not available for the path.
+ */
+export interface SeedAddress {
+    hdPath: string;
+    address?: string;
+    /**
+     * Reason why the address is not available, ex. invalid HD Path, or a Hardware Key failed to provide it
+     */
+    error?: string;
+}
+
+/**
+ * Format of an Extended Public Key, which defines the version bytes (SLIP-132). A testnet blockchain uses only the testnet
+ * formats (`tpub`, `upub`, `vpub`), and a mainnet only the mainnet ones. The name is case-sensitive, so the multisig formats
+ * (`Ypub`, `Zpub`) are not accepted.
+ */
+export type XpubFormat = "xpub" | "ypub" | "zpub" | "tpub" | "upub" | "vpub";
+
+export interface AccountXpub {
+    xpub: string;
+    format: XpubFormat;
+    accountPath: string;
+    /**
+     * Master Key fingerprint as 8 hex chars
+     */
+    fingerprint: string;
+    /**
+     * Key origin as `[fingerprint/path]`, ex. `[73c5da0a/84'/0'/0']`
+     */
+    keyOrigin: string;
+    /**
+     * Output descriptor for the receive addresses, ex. `wpkh([73c5da0a/84h/0h/0h]xpub.../0/*)#checksum`
+     */
+    descriptor?: string;
+    /**
+     * Output descriptor for the change addresses
+     */
+    changeDescriptor?: string;
+}
+
+/**
+ * Source of the entropy provided by the user:
+ * - `dice` - dice rolls, ex. `"3 6 1 4"` or `"3614"`
+ * - `coins` - coin flips as `H`/`T` or `1`/`0`
+ * - `hex` - entropy bytes as hex, used as is
+ */
+export type EntropySource = "dice" | "coins" | "hex";
+
+export interface MnemonicEntropyOptions {
+    type: EntropySource;
+    value: string;
+    /**
+     * Number of words in the mnemonic (12, 15, 18, 21 or 24)
+     */
+    size: number;
+    /**
+     * Number of sides of the dice. Default is 6
+     */
+    sides?: number;
+    /**
+     * Mix (XOR) the provided entropy with the system random. Default is `false`
+     */
+    mixSystem?: boolean;
+}
+
+export interface MnemonicFromEntropy {
+    mnemonic: string;
+    /**
+     * Steps of how the entropy was built, to be shown to the user for a manual verification
+     */
+    record: string[];
+}
+
+export interface SeedRemoveResult {
+    /**
+     * `true` if the seed was removed
+     */
+    removed: boolean;
+    /**
+     * Wallets that reference the seed. When the removal is not forced and the list is not empty the seed is kept in the Vault.
+     * With the forced removal it lists the references removed together with the seed.
+     */
+    usedBy: SeedUsage[];
 }
 
 export interface BaseSeedDefinition {
@@ -520,10 +1108,18 @@ export interface MnemonicSeedDefinition extends BaseSeedDefinition {
     value: MnemonicSeed;
 }
 
+/**
+ * Seed recovered from SLIP-39 shares
+ */
+export interface Slip39SeedDefinition extends BaseSeedDefinition {
+    type: "slip39";
+    value: Slip39Seed;
+}
+
 /**
  * Full Definition for a new Seed, i.e. to create a new one in the Vault
  */
-export type SeedDefinition = RawSeedDefinition | MnemonicSeedDefinition;
+export type SeedDefinition = RawSeedDefinition | MnemonicSeedDefinition | Slip39SeedDefinition;
 
 /**
  * Reference to a created Seed by its ID
@@ -559,6 +1155,110 @@ export interface MnemonicSeed {
      * Optional Mnemonic password
      */
     password?: string;
+    /**
+     * If `true` then the Vault keeps the encrypted mnemonic entropy, so the phrase can be revealed later for a backup.
+     * Default is `false`, i.e. only the seed produced from the mnemonic is stored.
+     */
+    revealable?: boolean;
+}
+
+export interface Slip39Seed {
+    /**
+     * SLIP-39 mnemonic shares, enough to recover the seed. Can be in any order and from any groups.
+     */
+    shares: string[];
+    /**
+     * Optional passphrase used to create the shares
+     */
+    passphrase?: string;
+}
+
+/**
+ * Group of SLIP-39 shares
+ */
+export interface Slip39Group {
+    /**
+     * Number of the member shares required to recover the group
+     */
+    threshold: number;
+    /**
+     * Number of the member shares to create
+     */
+    count: number;
+}
+
+export interface Slip39SplitOptions {
+    /**
+     * Number of the groups required to recover the seed
+     */
+    groupThreshold: number;
+    groups: Slip39Group[];
+    /**
+     * Optional passphrase to encrypt the shares. Note that any passphrase produces a valid seed, so a wrong one gives a different seed.
+     */
+    passphrase?: string;
+    /**
+     * Exponent of the PBKDF2 iterations (10000 * 2^e). Default is 1
+     */
+    iterationExponent?: number;
+}
+
+/**
+ * Type of BIP-85 child secret:
+ * - `bip39` - mnemonic phrase
+ * - `wif` - private key in WIF format
+ * - `hex` - raw entropy as hex
+ */
+export type Bip85Application = "bip39" | "wif" | "hex";
+
+export interface Bip85Options {
+    application: Bip85Application;
+    /**
+     * Index of the child, a different index gives a completely different secret
+     */
+    index: number;
+    /**
+     * Number of words for `bip39`. Default is 24
+     */
+    words?: 12 | 15 | 18 | 21 | 24;
+    /**
+     * Language for `bip39`, ex. `english`, `japanese`, `spanish`. Default is `english`
+     */
+    language?: string;
+    /**
+     * Number of bytes for `hex`, between 16 and 64. Default is 32
+     */
+    length?: number;
+    /**
+     * Import the derived mnemonic into the Vault as a new seed. Only an English `bip39` mnemonic can be imported
+     */
+    import?: {
+        /**
+         * Password of the Global Key to encrypt the new seed
+         */
+        password: string;
+        label?: string;
+        /**
+         * Keep the mnemonic, so it can be revealed later
+         */
+        revealable?: boolean;
+    };
+}
+
+export interface Bip85Child {
+    application: Bip85Application;
+    /**
+     * Derivation path of the child, ex. `m/83696968'/39'/0'/24'/0'`
+     */
+    path: string;
+    /**
+     * Derived mnemonic, WIF or hex
+     */
+    value: string;
+    /**
+     * Id of the new seed, if it was imported
+     */
+    seedId?: Uuid;
 }
 
 export type RawSeed = string;
@@ -567,14 +1267,18 @@ export function isReference(seed: Uuid | SeedDefinition | SeedReference): seed i
     return typeof seed === "string";
 }
 
-export function isRawSeed(value: RawSeed | MnemonicSeed, parent: SeedDefinition): value is RawSeed {
+export function isRawSeed(value: RawSeed | MnemonicSeed | Slip39Seed, parent: SeedDefinition): value is RawSeed {
     return typeof parent == "object" && typeof value == "string" && parent.type === "raw";
 }
 
-export function isMnemonic(value: RawSeed | MnemonicSeed, parent: SeedDefinition): value is MnemonicSeed {
+export function isMnemonic(value: RawSeed | MnemonicSeed | Slip39Seed, parent: SeedDefinition): value is MnemonicSeed {
     return typeof parent == "object" && typeof value == "object" && parent.type === "mnemonic";
 }
 
+export function isSlip39(value: RawSeed | MnemonicSeed | Slip39Seed, parent: SeedDefinition): value is Slip39Seed {
+    return typeof parent == "object" && typeof value == "object" && parent.type === "slip39";
+}
+
 export function isLedger(value: Uuid | SeedReference): value is LedgerSeedReference {
     return typeof value === "object" && isSeedReference(value) && value.type === "ledger";
 }
@@ -650,20 +1354,33 @@ export interface WalletState {
     accountIndexes: AccountIndex[];
 }
 
+/**
+ * Type of message signature:
+ * - `eip191` and `eip712` for Ethereum
+ * - `bip137` for Bitcoin P2PKH, P2SH-P2WPKH or P2WPKH address (the legacy "Signed Message" format)
+ * - `bip322` for Bitcoin P2WPKH or P2TR address (BIP-322 simple signature)
+ */
+export type MessageSignatureType = "eip191" | "eip712" | "bip137" | "bip322";
+
 export interface UnsignedMessage {
-    type: "eip191" | "eip712";
+    type: MessageSignatureType;
 
     /**
      * A message to sign. For EIP-712 it's a JSON encode to string
      */
     message: string;
+
+    /**
+     * Bitcoin address to sign with, or to verify the signature for. Required for `bip137` and `bip322`
+     */
+    address?: string;
 }
 
 export interface SignedMessage {
-    type: "eip191" | "eip712";
+    type: MessageSignatureType;
 
     /**
-     * Signature encoded as a string (hex)
+     * Signature encoded as a string (hex for Ethereum, base64 for Bitcoin)
      */
     signature: string;
 
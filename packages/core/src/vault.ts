@@ -14,10 +14,33 @@ import {
     SeedDescription,
     SeedDetails,
     SeedReference,
+    SeedRemoveResult,
+    SeedAddress,
+    XpubFormat,
+    AccountXpub,
+    Bip85Options,
+    Bip85Child,
+    MnemonicEntropyOptions,
+    MnemonicFromEntropy,
+    Slip39SplitOptions,
     SignedMessage,
     SignedTx,
+    SignPsbtOptions,
+    SignedPsbt,
+    AddMultisig,
+    Multisig,
+    DecodedTx,
     UnsignedMessage,
     UnsignedTx,
+    UnsignedBitcoinTx,
+    BumpFee,
+    BatchSignedTx,
+    TxPreview,
+    Authorization,
+    UnsignedAuthorization,
+    SpendingPolicy,
+    BuiltBitcoinTx,
+    BitcoinTxSize,
     Uuid,
     Wallet,
     WalletCreateOptions,
@@ -49,6 +72,27 @@ export interface IEmeraldVault {
 
     setEntryReceiveDisabled(entryFullId: EntryId, disabled: boolean): Promise<boolean>;
 
+    /**
+     * Add a multisig where a seed of this vault is one of the cosigners
+     *
+     * @param walletId wallet to add to
+     * @param multisig cosigners of other vaults and the seed of this vault
+     */
+    addMultisig(walletId: Uuid, multisig: AddMultisig): Promise<Multisig>;
+
+    listMultisig(walletId: Uuid): Promise<Multisig[]>;
+
+    /**
+     * Remove a multisig of the wallet. Fails if the wallet has an entry with a spending policy, in the same way as for the wallet itself.
+     *
+     * @param walletId wallet of the multisig
+     * @param id multisig id
+     * @return `false` if the wallet has no such multisig
+     */
+    removeMultisig(walletId: Uuid, id: Uuid): Promise<boolean>;
+
+    listMultisigAddresses(id: Uuid, role: AddressRole, start: number, limit: number): Promise<CurrentAddress[]>;
+
     /**
      * Sign transaction
      *
@@ -58,6 +102,97 @@ export interface IEmeraldVault {
      */
     signTx(entryId: EntryId, tx: UnsignedTx, password?: string): Promise<SignedTx>;
 
+    /**
+     * Sign multiple transactions of the same entry, ex. with consecutive nonces. The key is decrypted only once for all of them.
+     * A transaction that fails to sign doesn't stop the others, instead it gets an error in its result.
+     *
+     * @param entryId Wallet Entry that signs the transactions
+     * @param txs unsigned transactions
+     * @param password global password
+     * @return results in the same order as the transactions
+     */
+    signTxBatch(entryId: EntryId, txs: UnsignedTx[], password?: string): Promise<BatchSignedTx[]>;
+
+    /**
+     * Sign an EIP-7702 authorization, which delegates the entry account to a contract. The signed authorization goes to the
+     * `authorizationList` of a transaction, which may be sent by another account. Not allowed for an entry with a spending policy.
+     *
+     * @param entryId Ethereum entry which account is delegated
+     * @param authorization chain, delegate address and the nonce of the entry account
+     * @param password global password
+     */
+    signAuthorization(entryId: EntryId, authorization: UnsignedAuthorization, password: string): Promise<Authorization>;
+
+    /**
+     * Show what the transaction signs without signing it, i.e., the max cost and the decoded token call for Ethereum, or
+     * the outputs with the change and the fee for Bitcoin.
+     *
+     * @param entryId Wallet Entry that would sign the transaction
+     * @param tx unsigned transaction
+     */
+    previewTx(entryId: EntryId, tx: UnsignedTx): Promise<TxPreview>;
+
+    /**
+     * Sign a replacement of a Bitcoin transaction, which pays a higher fee (BIP-125). The fee is taken from the change,
+     * and if it's not enough more UTXOs are added.
+     *
+     * @param entryId Bitcoin entry that signed the original transaction
+     * @param options original transaction, new fee rate and the UTXOs
+     * @param password password to decrypt the seed
+     */
+    bumpFee(entryId: EntryId, options: BumpFee, password?: string): Promise<SignedTx>;
+
+    /**
+     * Make a Bitcoin transaction paying to the outputs. The UTXOs are selected to avoid the change if possible, otherwise
+     * the change goes to the current change address of the entry.
+     *
+     * @param entryId Bitcoin entry that owns the UTXOs
+     * @param utxos available UTXOs of the entry
+     * @param outputs payments to make
+     * @param feeRate fee rate in sat/vB
+     */
+    buildBitcoinTx(entryId: EntryId, utxos: UnsignedBitcoinTx["inputs"], outputs: UnsignedBitcoinTx["outputs"], feeRate: number): Promise<BuiltBitcoinTx>;
+
+    /**
+     * Estimate the size of a Bitcoin transaction when it's signed by the entry, to calculate the fee before signing.
+     * The fee of the transaction is not required.
+     *
+     * @param entryId Bitcoin entry that owns the inputs
+     * @param tx unsigned transaction
+     */
+    estimateBitcoinTx(entryId: EntryId, tx: UnsignedBitcoinTx): Promise<BitcoinTxSize>;
+
+    /**
+     * Sign a PSBT (BIP-174) made by another coordinator. Only the inputs with a BIP-32 derivation for the entry account
+     * are signed, the others are kept as is. Not available for a Hardware Key.
+     *
+     * @param entryId Bitcoin entry to sign with
+     * @param psbt PSBT as base64
+     * @param password password to decrypt the seed
+     * @param options signing options, ex. to finalize and extract the transaction
+     */
+    signPsbt(entryId: EntryId, psbt: string, password?: string, options?: SignPsbtOptions): Promise<SignedPsbt>;
+
+    /**
+     * Create a PSBT spending UTXOs of the multisig and add the signatures of this vault. The transaction is finalized
+     * if it's enough signatures, otherwise the PSBT must be passed to other cosigners.
+     *
+     * @param id multisig id
+     * @param tx unsigned transaction, with the inputs referenced by the multisig address or its HD Path
+     * @param password password to decrypt the seed
+     */
+    signMultisigTx(id: Uuid, tx: UnsignedBitcoinTx, password?: string): Promise<SignedPsbt>;
+
+    /**
+     * Add the signatures of this vault to a multisig PSBT made by another cosigner
+     *
+     * @param id multisig id
+     * @param psbt PSBT as base64
+     * @param password password to decrypt the seed
+     * @param options signing options, ex. to finalize and extract the transaction
+     */
+    signMultisigPsbt(id: Uuid, psbt: string, password?: string, options?: SignPsbtOptions): Promise<SignedPsbt>;
+
     /**
      * Sign a message using the private key.
      *
@@ -76,6 +211,33 @@ export interface IEmeraldVault {
      */
     extractMessageSigner(msg: UnsignedMessage, signature: string): Promise<string>;
 
+    /**
+     * Decode a raw signed transaction. For Ethereum it recovers the sender and fails if the transaction is for another chain.
+     *
+     * @param blockchain blockchain id of the transaction
+     * @param raw transaction as hex
+     */
+    decodeTx(blockchain: number, raw: string): Promise<DecodedTx>;
+
+
+    /**
+     * Get the spending policy of the entry
+     *
+     * @param entryId entry with the policy
+     * @return policy, or undefined if the entry has no policy
+     */
+    getPolicy(entryId: EntryId): Promise<SpendingPolicy | undefined>;
+
+    /**
+     * Set the spending policy checked before the entry signs a transaction. An entry with a policy signs only with the global
+     * password, and it cannot sign a PSBT. Its key cannot be exported, and the entry cannot be removed until the policy is removed.
+     *
+     * @param entryId entry to set the policy for
+     * @param policy new policy, or null to remove it
+     * @param globalPassword global password, required to change the policy
+     */
+    setPolicy(entryId: EntryId, policy: SpendingPolicy | null, globalPassword: string): Promise<boolean>;
+
     exportRawPk(entryId: EntryId, password: string): Promise<string>;
 
     /**
@@ -89,6 +251,14 @@ export interface IEmeraldVault {
 
     generateMnemonic(size: number): Promise<string>;
 
+    /**
+     * Generate a mnemonic from entropy provided by the user (dice rolls, coin flips or hex) instead of the internal RNG.
+     * Fails if the value doesn't have enough entropy for the requested size.
+     *
+     * @param options entropy source, its value and the mnemonic size
+     */
+    generateMnemonicFromEntropy(options: MnemonicEntropyOptions): Promise<MnemonicFromEntropy>;
+
     listAddressBook(blockchain: number): Promise<AddressBookItem[]>;
 
     removeFromAddressBook(blockchain: number, address: string): Promise<boolean>;
@@ -108,14 +278,28 @@ export interface IEmeraldVault {
 
     /**
      * Return addresses on the specified Seed.
-     * For ethereum, HD Path must be standard 5-element path (m/44'/0'/0'/0/0).
+     * For ethereum, it's a standard 5-element path (m/44'/60'/0'/0/0), including the Ledger Live layout (m/44'/60'/x'/0/0),
+     * or any other path (ex. legacy MEW m/44'/60'/0'/x) for a seed that is not a Hardware Key.
      * For bitcoin, in addition to a full path, it supports path to an account (m/84'/0'/0'). In this case it returns XPub address of that account.
+     * Taproot (BIP-86, `m/86'`) paths give P2TR addresses, but they are not available for a Hardware Key.
+     * A path may be a template with a range of indexes, ex. `m/44'/60'/0'/0/{0..49}` or `m/84'/0'/{0..4}'`, limited to 1000 paths in total.
      *
      * @param seed existing seed, or reference to hardware key
      * @param blockchain blockchain id
      * @param hdpath list of hdpath to address or account
+     * @return addresses in the same order as the requested paths, one for each path. A path that cannot be used gets an `error` instead of the address
      */
-    listSeedAddresses(seed: Uuid | SeedReference | SeedDefinition, blockchain: number, hdpath: string[]): Promise<{ [key: string]: string }>;
+    listSeedAddresses(seed: Uuid | SeedReference | SeedDefinition, blockchain: number, hdpath: string[]): Promise<SeedAddress[]>;
+
+    /**
+     * Export the Extended Public Key of a Bitcoin account, to use it in another wallet as a watch-only account.
+     *
+     * @param seed reference to a seed, or a seed definition
+     * @param blockchain Bitcoin blockchain
+     * @param accountPath HD Path of the account, ex. `m/84'/0'/0'`
+     * @param format format of the key; by default, it's chosen by the purpose of the account (`xpub` for 44' and 86', `ypub` for 49', `zpub` for 84')
+     */
+    exportAccountXpub(seed: Uuid | SeedReference | SeedDefinition, blockchain: number, accountPath: string, format?: XpubFormat): Promise<AccountXpub>;
 
     /**
      * Update seed details, such as `label`
@@ -125,6 +309,57 @@ export interface IEmeraldVault {
      */
     updateSeed(seed: Uuid | IdSeedReference, details: Partial<SeedDetails>): Promise<boolean>;
 
+    /**
+     * Remove seed from the Vault.
+     * By default, it refuses to remove a seed that is still used by a wallet entry or reserved by a wallet, and returns such references instead.
+     * With `force` it removes those entries and reserved accounts together with the seed.
+     *
+     * @param seed reference to a seed
+     * @param force remove the seed even if it's used by wallets
+     */
+    removeSeed(seed: Uuid | IdSeedReference, force?: boolean): Promise<SeedRemoveResult>;
+
+    /**
+     * Reveal the mnemonic phrase of a seed, to make a backup.
+     * Available only for seeds imported with the `revealable` option. For other seeds it fails with an error.
+     *
+     * @param seed reference to a seed
+     * @param password password to decrypt the seed
+     */
+    revealMnemonic(seed: Uuid | IdSeedReference, password: string): Promise<string>;
+
+    /**
+     * Split a seed into SLIP-39 shares, to make a distributed backup. The seed can be recovered later with `importSeed`
+     * using a `slip39` definition with enough shares.
+     *
+     * @param seed reference to a seed
+     * @param password password to decrypt the seed
+     * @param options groups of shares and thresholds
+     * @return list of mnemonic shares for each group, in the same order as in `options.groups`
+     */
+    splitSeedShares(seed: Uuid | IdSeedReference, password: string, options: Slip39SplitOptions): Promise<string[][]>;
+
+    /**
+     * Derive a child secret with BIP-85 (a mnemonic, a private key or hex entropy), which can be recovered later from the same seed
+     * and index. A derived mnemonic can be imported as a new seed with `options.import`.
+     *
+     * @param seed reference to a seed stored in the Vault
+     * @param password password to decrypt the seed
+     * @param options type of the child and its index
+     */
+    deriveBip85(seed: Uuid | IdSeedReference, password: string, options: Bip85Options): Promise<Bip85Child>;
+
+    /**
+     * Change the individual password of a seed (see `getOddPasswordItems`). Fails for a seed encrypted with the Global Key,
+     * which changes only together with the Global Key.
+     *
+     * @param seed reference to a seed
+     * @param existingPassword current password of the seed
+     * @param newPassword new password
+     * @return `false` if the current password is invalid
+     */
+    changeSeedPassword(seed: Uuid | IdSeedReference, existingPassword: string, newPassword: string): Promise<boolean>;
+
     /**
      * Create a Global Key that will used to encrypt all Secrets in the Vault. Can be created only once.
      * @param password
@@ -171,6 +406,7 @@ export interface IEmeraldVault {
      * Make a snapshot of the current Vault and copy its content to the specified file. The snapshot contains all critical
      * information stored in the Vault, including keys, seeds, and wallets. All sensitive details are encrypted, as in the Vault, and cannot
      * be used to make transactions without the password.
+     * It also contains multisig setups, spending policies and stored mnemonics, which are kept outside the Vault storage itself.
      * The file can be used to restore the Vault on a new machine or with a new installation.
      *
      * Expected extension: .emrldvault
@@ -181,6 +417,7 @@ export interface IEmeraldVault {
     /**
      * Restore from an existing snapshot, if the provided password is valid (i.e., can decrypt values). It's a potentially destructive
      * operation because it replaces all values in the current vault with data from the snapshot.
+     * A snapshot created by an older version has no multisig setups, spending policies or mnemonics, and they are kept as is.
      *
      * Returns `false` if password is invalid. Or error for other less expected errors, like IO Error, invalid data, etc.
      *
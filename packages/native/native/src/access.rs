@@ -1,6 +1,6 @@
 use neon::handle::Handle;
 use neon::object::{Object};
-use neon::prelude::{FunctionContext, JsObject, JsString, JsNumber, JsValue, NeonResult};
+use neon::prelude::{FunctionContext, JsObject, JsString, JsNumber, JsBoolean, JsValue, NeonResult};
 use neon::types::{JsNull, JsUndefined};
 
 use uuid::Uuid;
@@ -59,6 +59,22 @@ pub fn args_get_str(cx: &mut FunctionContext, pos: usize) -> Option<String> {
     }
 }
 
+pub fn args_get_bool(cx: &mut FunctionContext, pos: usize) -> Option<bool> {
+    match cx.argument_opt(pos) {
+        None => None,
+        Some(v) => {
+            if v.is_a::<JsBoolean, _>(cx) {
+                match v.downcast::<JsBoolean, _>(cx) {
+                    Ok(v) => Some(v.value(cx)),
+                    Err(_) => None,
+                }
+            } else {
+                None
+            }
+        }
+    }
+}
+
 pub fn args_require_str(cx: &mut FunctionContext, pos: usize, name: &str) -> Result<String, VaultNodeError> {
     args_get_str(cx, pos)
         .ok_or(VaultNodeError::ArgumentMissing(pos as usize, name.to_string()))
@@ -0,0 +1,103 @@
+//! Cache of the derived addresses of Bitcoin accounts, to find the HD path of an address without deriving them again.
+//!
+//! An account is searched up to its current receive and change index plus a gap, and the cache grows when the account
+//! indexes grow.
+
+use std::collections::HashMap;
+use crate::errors::VaultNodeError;
+
+/// Number of addresses after the current index to search, as the default gap limit of BIP-44
+pub const DEFAULT_ADDRESS_GAP: u32 = 20;
+
+#[derive(Default)]
+pub struct AddressCache {
+    accounts: HashMap<String, AccountAddresses>,
+}
+
+#[derive(Default)]
+struct AccountAddresses {
+    /// Number of derived receive and change addresses
+    derived: [u32; 2],
+    /// Address to its `(change, index)`
+    paths: HashMap<String, (u32, u32)>,
+}
+
+impl AddressCache {
+    /// Find `(change, index)` of the address on the account, checking the first `limits` receive and change addresses.
+    /// The addresses which are not in the cache yet are derived with `derive(change, start, limit)`, which returns
+    /// them with their index.
+    pub fn find_path<F>(&mut self,
+                        account: &str,
+                        address: &str,
+                        limits: [u32; 2],
+                        derive: F) -> Result<Option<(u32, u32)>, VaultNodeError>
+        where F: Fn(u32, u32, u32) -> Result<Vec<(u32, String)>, VaultNodeError> {
+        let cached = self.accounts.entry(account.to_string()).or_default();
+        if let Some(path) = cached.paths.get(address) {
+            return Ok(Some(*path));
+        }
+        for change in [0u32, 1] {
+            let derived = cached.derived[change as usize];
+            let limit = limits[change as usize];
+            if derived >= limit {
+                continue;
+            }
+            for (index, address) in derive(change, derived, limit - derived)? {
+                cached.paths.insert(address, (change, index));
+            }
+            cached.derived[change as usize] = limit;
+        }
+        Ok(cached.paths.get(address).copied())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use crate::address_cache::AddressCache;
+    use crate::errors::VaultNodeError;
+
+    fn derive(calls: &RefCell<Vec<(u32, u32, u32)>>) -> impl Fn(u32, u32, u32) -> Result<Vec<(u32, String)>, VaultNodeError> + '_ {
+        move |change, start, limit| {
+            calls.borrow_mut().push((change, start, limit));
+            Ok((start..start + limit).map(|i| (i, format!("{}/{}", change, i))).collect())
+        }
+    }
+
+    #[test]
+    fn finds_within_limits() {
+        let calls = RefCell::new(Vec::new());
+        let mut cache = AddressCache::default();
+        assert_eq!(cache.find_path("a", "0/3", [5, 2], derive(&calls)).unwrap(), Some((0, 3)));
+        assert_eq!(cache.find_path("a", "1/1", [5, 2], derive(&calls)).unwrap(), Some((1, 1)));
+        assert_eq!(cache.find_path("a", "1/2", [5, 2], derive(&calls)).unwrap(), None);
+        assert_eq!(*calls.borrow(), vec![(0, 0, 5), (1, 0, 2)]);
+    }
+
+    #[test]
+    fn derives_more_for_larger_limits() {
+        let calls = RefCell::new(Vec::new());
+        let mut cache = AddressCache::default();
+        assert_eq!(cache.find_path("a", "0/7", [5, 2], derive(&calls)).unwrap(), None);
+        assert_eq!(cache.find_path("a", "0/7", [10, 2], derive(&calls)).unwrap(), Some((0, 7)));
+        assert_eq!(*calls.borrow(), vec![(0, 0, 5), (1, 0, 2), (0, 5, 5)]);
+    }
+
+    #[test]
+    fn separates_accounts() {
+        let calls = RefCell::new(Vec::new());
+        let mut cache = AddressCache::default();
+        cache.find_path("a", "0/1", [5, 2], derive(&calls)).unwrap();
+        cache.find_path("b", "0/1", [5, 2], derive(&calls)).unwrap();
+        assert_eq!(calls.borrow().len(), 4);
+    }
+
+    #[test]
+    fn keeps_cache_on_error() {
+        let mut cache = AddressCache::default();
+        let failed = |_, _, _| Err(VaultNodeError::OtherProcessing("Failed to derive key".to_string()));
+        assert!(cache.find_path("a", "0/1", [5, 2], failed).is_err());
+        let calls = RefCell::new(Vec::new());
+        assert_eq!(cache.find_path("a", "0/1", [5, 2], derive(&calls)).unwrap(), Some((0, 1)));
+    }
+}
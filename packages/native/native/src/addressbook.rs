@@ -56,6 +56,13 @@ impl WrappedVault {
         for_chain
     }
 
+    /// Label of the address in the address book
+    pub(crate) fn addressbook_label(&self, address: &EthereumAddress) -> Option<String> {
+        self.list_addressbook().into_iter()
+            .find(|x| matches!(x.details.address, AddressRef::EthereumAddress(a) if a == *address))
+            .and_then(|x| x.details.label)
+    }
+
     //TODO support bitcoin addresses
     fn remove_addressbook_by_addr(&self, address: &EthereumAddress) -> bool {
         let storage = &self.cfg.get_storage();
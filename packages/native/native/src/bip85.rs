@@ -0,0 +1,267 @@
+//! BIP-85 Deterministic Entropy From BIP32 Keychains.
+//!
+//! Derives independent child secrets (a BIP-39 mnemonic, a WIF private key or raw entropy as hex) from a stored seed, so a single
+//! backup of the seed recovers all of them. See https://github.com/bitcoin/bips/blob/master/bip-0085.mediawiki
+
+use std::str::FromStr;
+
+use bitcoin::bip32::{DerivationPath, Xpriv};
+use bitcoin::hashes::{hmac, sha512, Hash, HashEngine};
+use bitcoin::secp256k1::Secp256k1;
+use bitcoin::{Network, NetworkKind, PrivateKey};
+use crate::errors::VaultNodeError;
+
+const PURPOSE: u32 = 83696968;
+const HMAC_KEY: &[u8] = b"bip-entropy-from-k";
+
+const APP_BIP39: u32 = 39;
+const APP_WIF: u32 = 2;
+const APP_HEX: u32 = 128169;
+
+#[derive(Deserialize, Clone, Copy, Debug, Eq, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Bip85Application {
+    Bip39,
+    Wif,
+    Hex,
+}
+
+#[derive(Deserialize, Clone, Debug, Eq, PartialEq)]
+pub struct Bip85ImportJson {
+    pub password: Option<String>,
+    pub label: Option<String>,
+    /// Keep the mnemonic so it can be revealed later
+    #[serde(default)]
+    pub revealable: bool,
+}
+
+#[derive(Deserialize, Clone, Debug, Eq, PartialEq)]
+pub struct Bip85OptionsJson {
+    pub application: Bip85Application,
+    pub index: u32,
+    /// Number of words for a BIP-39 mnemonic, default is 24
+    pub words: Option<usize>,
+    /// Language of a BIP-39 mnemonic, default is `english`
+    pub language: Option<String>,
+    /// Number of bytes for hex, default is 32
+    pub length: Option<usize>,
+    /// Import the derived mnemonic into the Vault as a new seed
+    pub import: Option<Bip85ImportJson>,
+}
+
+#[derive(Serialize, Clone, Debug, Eq, PartialEq)]
+pub struct Bip85ChildJson {
+    pub application: String,
+    pub path: String,
+    pub value: String,
+    /// Id of the imported seed, if it was requested
+    #[serde(rename = "seedId")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub seed_id: Option<String>,
+}
+
+fn invalid(msg: &str) -> VaultNodeError {
+    VaultNodeError::InvalidArgumentValue(msg.to_string())
+}
+
+/// Language with its BIP-85 code
+fn parse_language(value: &str) -> Result<(bip39::Language, u32), VaultNodeError> {
+    use bip39::Language;
+    let normalized = value.to_lowercase().replace(['-', '_', ' '], "");
+    match normalized.as_str() {
+        "english" | "en" => Ok((Language::English, 0)),
+        "japanese" | "ja" => Ok((Language::Japanese, 1)),
+        "korean" | "ko" => Ok((Language::Korean, 2)),
+        "spanish" | "es" => Ok((Language::Spanish, 3)),
+        "chinesesimplified" | "simplifiedchinese" | "zhhans" => Ok((Language::SimplifiedChinese, 4)),
+        "chinesetraditional" | "traditionalchinese" | "zhhant" => Ok((Language::TraditionalChinese, 5)),
+        "french" | "fr" => Ok((Language::French, 6)),
+        "italian" | "it" => Ok((Language::Italian, 7)),
+        "czech" | "cs" => Ok((Language::Czech, 8)),
+        "portuguese" | "pt" => Ok((Language::Portuguese, 9)),
+        _ => Err(invalid("Unsupported mnemonic language"))
+    }
+}
+
+fn hardened_path(indexes: &[u32]) -> Result<DerivationPath, VaultNodeError> {
+    let value = indexes.iter()
+        .map(|i| format!("{}'", i))
+        .collect::<Vec<String>>()
+        .join("/");
+    DerivationPath::from_str(format!("m/{}", value).as_str())
+        .map_err(|_| invalid("Index is out of range"))
+}
+
+/// 64 bytes of entropy for the path, i.e. HMAC-SHA512 of the private key at that path
+fn derive_entropy(master: &Xpriv, path: &DerivationPath) -> Result<[u8; 64], VaultNodeError> {
+    let secp = Secp256k1::new();
+    let key = master.derive_priv(&secp, path)
+        .map_err(|_| VaultNodeError::OtherProcessing("Failed to derive a key".to_string()))?;
+    let mut engine = hmac::HmacEngine::<sha512::Hash>::new(HMAC_KEY);
+    engine.input(&key.private_key.secret_bytes());
+    Ok(hmac::Hmac::<sha512::Hash>::from_engine(engine).to_byte_array())
+}
+
+fn derive_from_master(master: &Xpriv, options: &Bip85OptionsJson) -> Result<Bip85ChildJson, VaultNodeError> {
+    let (application, path, value) = match options.application {
+        Bip85Application::Bip39 => {
+            let words = options.words.unwrap_or(24);
+            if ![12, 15, 18, 21, 24].contains(&words) {
+                return Err(invalid("Invalid mnemonic size"));
+            }
+            let (language, code) = parse_language(options.language.as_deref().unwrap_or("english"))?;
+            let path = hardened_path(&[PURPOSE, APP_BIP39, code, words as u32, options.index])?;
+            let entropy = derive_entropy(master, &path)?;
+            let mnemonic = bip39::Mnemonic::from_entropy_in(language, &entropy[0..words * 4 / 3])
+                .map_err(|_| VaultNodeError::OtherProcessing("Failed to create mnemonic".to_string()))?;
+            ("bip39", path, mnemonic.to_string())
+        }
+        Bip85Application::Wif => {
+            let path = hardened_path(&[PURPOSE, APP_WIF, options.index])?;
+            let entropy = derive_entropy(master, &path)?;
+            let key = PrivateKey::from_slice(&entropy[0..32], NetworkKind::Main)
+                .map_err(|_| VaultNodeError::OtherProcessing("Invalid private key".to_string()))?;
+            ("wif", path, key.to_wif())
+        }
+        Bip85Application::Hex => {
+            let length = options.length.unwrap_or(32);
+            if !(16..=64).contains(&length) {
+                return Err(invalid("Length must be between 16 and 64 bytes"));
+            }
+            let path = hardened_path(&[PURPOSE, APP_HEX, length as u32, options.index])?;
+            let entropy = derive_entropy(master, &path)?;
+            ("hex", path, hex::encode(&entropy[0..length]))
+        }
+    };
+    Ok(Bip85ChildJson {
+        application: application.to_string(),
+        path: format!("m/{}", path),
+        value,
+        seed_id: None,
+    })
+}
+
+/// Derive a child secret from the raw seed bytes
+pub fn derive(seed: &[u8], options: &Bip85OptionsJson) -> Result<Bip85ChildJson, VaultNodeError> {
+    // the network doesn't change the derived values, WIF is always for mainnet
+    let master = Xpriv::new_master(Network::Bitcoin, seed)
+        .map_err(|_| VaultNodeError::OtherProcessing("Invalid seed".to_string()))?;
+    derive_from_master(&master, options)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+    use bitcoin::bip32::{DerivationPath, Xpriv};
+    use crate::bip85::{derive_entropy, derive_from_master, Bip85Application, Bip85OptionsJson};
+
+    // test vectors from BIP-85
+    fn master() -> Xpriv {
+        Xpriv::from_str("xprv9s21ZrQH143K2LBWUUQRFXhucrQqBpKdRRxNVq2zBqsx8HVqFk2uYo8kmbaLLHRdqtQpUm98uKfu3vca1LqdGhUtyoFnCNkfmXRyPXLjbKb").unwrap()
+    }
+
+    fn options(application: Bip85Application) -> Bip85OptionsJson {
+        Bip85OptionsJson {
+            application,
+            index: 0,
+            words: None,
+            language: None,
+            length: None,
+            import: None,
+        }
+    }
+
+    #[test]
+    fn derive_raw_entropy() {
+        let act = derive_entropy(&master(), &DerivationPath::from_str("m/83696968'/0'/0'").unwrap()).unwrap();
+        assert_eq!(
+            hex::encode(act),
+            "efecfbccffea313214232d29e71563d941229afb4338c21f9517c41aaa0d16f00b83d2a09ef747e7a64e8e2bd5a14869e693da66ce94ac2da570ab7ee48618f7"
+        );
+    }
+
+    #[test]
+    fn derive_bip39_12() {
+        let act = derive_from_master(&master(), &Bip85OptionsJson {
+            words: Some(12),
+            ..options(Bip85Application::Bip39)
+        }).unwrap();
+        assert_eq!(act.path, "m/83696968'/39'/0'/12'/0'");
+        assert_eq!(act.value, "girl mad pet galaxy egg matter matrix prison refuse sense ordinary nose");
+    }
+
+    #[test]
+    fn derive_bip39_18() {
+        let act = derive_from_master(&master(), &Bip85OptionsJson {
+            words: Some(18),
+            ..options(Bip85Application::Bip39)
+        }).unwrap();
+        assert_eq!(act.value, "near account window bike charge season chef number sketch tomorrow excuse sniff circle vital hockey outdoor supply token");
+    }
+
+    #[test]
+    fn derive_bip39_24() {
+        let act = derive_from_master(&master(), &options(Bip85Application::Bip39)).unwrap();
+        assert_eq!(act.value, "puppy ocean match cereal symbol another shed magic wrap hammer bulb intact gadget divorce twin tonight reason outdoor destroy simple truth cigar social volcano");
+    }
+
+    #[test]
+    fn derive_bip39_other_language() {
+        let english = derive_from_master(&master(), &options(Bip85Application::Bip39)).unwrap();
+        let act = derive_from_master(&master(), &Bip85OptionsJson {
+            language: Some("japanese".to_string()),
+            ..options(Bip85Application::Bip39)
+        }).unwrap();
+        assert_eq!(act.path, "m/83696968'/39'/1'/24'/0'");
+        assert_ne!(act.value, english.value);
+        assert!(bip39::Mnemonic::parse_in(bip39::Language::Japanese, act.value.as_str()).is_ok());
+    }
+
+    #[test]
+    fn derive_wif() {
+        let act = derive_from_master(&master(), &options(Bip85Application::Wif)).unwrap();
+        assert_eq!(act.path, "m/83696968'/2'/0'");
+        assert_eq!(act.value, "Kzyv4uF39d4Jrw2W7UryTHwZr1zQVNk4dAFyqE6BuMrMh1Za7uhp");
+    }
+
+    #[test]
+    fn derive_hex() {
+        let act = derive_from_master(&master(), &Bip85OptionsJson {
+            length: Some(64),
+            ..options(Bip85Application::Hex)
+        }).unwrap();
+        assert_eq!(act.path, "m/83696968'/128169'/64'/0'");
+        assert_eq!(act.value, "492db4698cf3b73a5a24998aa3e9d7fa96275d85724a91e71aa2d645442f878555d078fd1f1f67e368976f04137b1f7a0d19232136ca50c44614af72b5582a5c");
+    }
+
+    #[test]
+    fn different_for_index() {
+        let first = derive_from_master(&master(), &options(Bip85Application::Hex)).unwrap();
+        let second = derive_from_master(&master(), &Bip85OptionsJson {
+            index: 1,
+            ..options(Bip85Application::Hex)
+        }).unwrap();
+        assert_eq!(first.value.len(), 64);
+        assert_ne!(first.value, second.value);
+    }
+
+    #[test]
+    fn fails_for_invalid_options() {
+        assert!(derive_from_master(&master(), &Bip85OptionsJson {
+            words: Some(13),
+            ..options(Bip85Application::Bip39)
+        }).is_err());
+        assert!(derive_from_master(&master(), &Bip85OptionsJson {
+            language: Some("klingon".to_string()),
+            ..options(Bip85Application::Bip39)
+        }).is_err());
+        assert!(derive_from_master(&master(), &Bip85OptionsJson {
+            length: Some(8),
+            ..options(Bip85Application::Hex)
+        }).is_err());
+        assert!(derive_from_master(&master(), &Bip85OptionsJson {
+            index: 0x80000000,
+            ..options(Bip85Application::Hex)
+        }).is_err());
+    }
+}
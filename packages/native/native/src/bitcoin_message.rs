@@ -0,0 +1,353 @@
+//! Signing of a text message with a Bitcoin key, to prove the ownership of an address.
+//!
+//! - BIP-137 is the legacy format (`signmessage` of Bitcoin Core) extended for SegWit addresses. It's a recoverable signature,
+//!   i.e. the address is restored from the signature itself
+//! - BIP-322 (_simple_ variant) signs a virtual transaction spending from the address, so it works for any SegWit address
+//!   including Taproot. The signature can only be verified against a known address
+
+use base64::Engine;
+use bitcoin::address::AddressType;
+use bitcoin::blockdata::opcodes::all::OP_RETURN;
+use bitcoin::blockdata::opcodes::OP_0;
+use bitcoin::hashes::{sha256, Hash, HashEngine};
+use bitcoin::key::{Keypair, TapTweak};
+use bitcoin::secp256k1::ecdsa::{RecoverableSignature, RecoveryId};
+use bitcoin::secp256k1::{Message, PublicKey, Secp256k1, SecretKey};
+use bitcoin::sighash::{EcdsaSighashType, Prevouts, SighashCache, TapSighashType};
+use bitcoin::sign_message::signed_msg_hash;
+use bitcoin::transaction::Version;
+use bitcoin::{absolute::LockTime, Address, Amount, CompressedPublicKey, Network, NetworkKind, OutPoint, ScriptBuf, Sequence, Transaction, TxIn, TxOut, Txid, Witness, XOnlyPublicKey};
+use rand::RngCore;
+use crate::errors::VaultNodeError;
+use crate::taproot;
+
+const BIP322_TAG: &[u8] = b"BIP0322-signed-message";
+
+fn invalid_signature() -> VaultNodeError {
+    VaultNodeError::InvalidArgumentValue("Invalid signature".to_string())
+}
+
+/// Network of the address, as it's used to encode the address recovered from a signature
+pub fn address_network(address: &Address<bitcoin::address::NetworkUnchecked>) -> NetworkKind {
+    if address.is_valid_for_network(Network::Bitcoin) {
+        NetworkKind::Main
+    } else {
+        NetworkKind::Test
+    }
+}
+
+/// Address of the type `address_type` for the public key, if such type can be made with a single key
+fn address_of_type(address_type: AddressType, pk: &PublicKey, network: NetworkKind) -> Option<Address> {
+    let secp = Secp256k1::verification_only();
+    let compressed = CompressedPublicKey(*pk);
+    match address_type {
+        AddressType::P2pkh => Some(Address::p2pkh(compressed, network)),
+        AddressType::P2sh => Some(Address::p2shwpkh(&compressed, network)),
+        AddressType::P2wpkh => Some(Address::p2wpkh(&compressed, known_hrp(network))),
+        AddressType::P2tr => Some(taproot::address(&secp, pk, network)),
+        _ => None
+    }
+}
+
+/// Bech32 prefix for SegWit addresses
+pub fn known_hrp(network: NetworkKind) -> bitcoin::address::KnownHrp {
+    match network {
+        NetworkKind::Main => bitcoin::address::KnownHrp::Mainnet,
+        NetworkKind::Test => bitcoin::address::KnownHrp::Testnets,
+    }
+}
+
+/// Check that the key is the key of the address
+fn check_key(key: &SecretKey, address: &Address, network: NetworkKind) -> Result<AddressType, VaultNodeError> {
+    let secp = Secp256k1::new();
+    let address_type = address.address_type()
+        .ok_or(VaultNodeError::InvalidArgumentValue("Unsupported address".to_string()))?;
+    let actual = address_of_type(address_type, &key.public_key(&secp), network);
+    if actual.as_ref() != Some(address) {
+        return Err(VaultNodeError::OtherInput("Key doesn't match the address".to_string()));
+    }
+    Ok(address_type)
+}
+
+/// Sign with BIP-137. Supports P2PKH, P2SH-P2WPKH and P2WPKH addresses.
+pub fn sign_bip137(key: &SecretKey, address: &Address, network: NetworkKind, message: &str) -> Result<String, VaultNodeError> {
+    let header_base: u8 = match check_key(key, address, network)? {
+        AddressType::P2pkh => 31,
+        AddressType::P2sh => 35,
+        AddressType::P2wpkh => 39,
+        _ => return Err(VaultNodeError::InvalidArgumentValue("BIP-137 is not supported for the address, use BIP-322".to_string()))
+    };
+    let secp = Secp256k1::new();
+    let msg = Message::from_digest(signed_msg_hash(message).to_byte_array());
+    let (recovery_id, signature) = secp.sign_ecdsa_recoverable(&msg, key).serialize_compact();
+    let mut result = Vec::with_capacity(65);
+    result.push(header_base + recovery_id.to_i32() as u8);
+    result.extend_from_slice(&signature);
+    Ok(base64::engine::general_purpose::STANDARD.encode(result))
+}
+
+/// Recover the address which made a BIP-137 signature
+pub fn recover_bip137(message: &str, signature: &str, network: NetworkKind) -> Result<Address, VaultNodeError> {
+    let bytes = base64::engine::general_purpose::STANDARD.decode(signature.trim())
+        .map_err(|_| invalid_signature())?;
+    if bytes.len() != 65 {
+        return Err(invalid_signature());
+    }
+    let (address_type, compressed, offset) = match bytes[0] {
+        27..=30 => (AddressType::P2pkh, false, 27),
+        31..=34 => (AddressType::P2pkh, true, 31),
+        35..=38 => (AddressType::P2sh, true, 35),
+        39..=42 => (AddressType::P2wpkh, true, 39),
+        _ => return Err(invalid_signature())
+    };
+    let recovery_id = RecoveryId::from_i32((bytes[0] - offset) as i32)
+        .map_err(|_| invalid_signature())?;
+    let signature = RecoverableSignature::from_compact(&bytes[1..], recovery_id)
+        .map_err(|_| invalid_signature())?;
+    let secp = Secp256k1::verification_only();
+    let msg = Message::from_digest(signed_msg_hash(message).to_byte_array());
+    let pk = secp.recover_ecdsa(&msg, &signature)
+        .map_err(|_| invalid_signature())?;
+    if !compressed {
+        return Ok(Address::p2pkh(bitcoin::PublicKey::new_uncompressed(pk), network));
+    }
+    address_of_type(address_type, &pk, network).ok_or_else(invalid_signature)
+}
+
+fn bip322_message_hash(message: &str) -> [u8; 32] {
+    let tag = sha256::Hash::hash(BIP322_TAG);
+    let mut engine = sha256::Hash::engine();
+    engine.input(tag.as_ref());
+    engine.input(tag.as_ref());
+    engine.input(message.as_bytes());
+    sha256::Hash::from_engine(engine).to_byte_array()
+}
+
+/// The virtual transaction `to_spend` from BIP-322
+fn bip322_to_spend(address: &Address, message: &str) -> Transaction {
+    let script_sig = ScriptBuf::builder()
+        .push_opcode(OP_0)
+        .push_slice(bip322_message_hash(message))
+        .into_script();
+    Transaction {
+        version: Version(0),
+        lock_time: LockTime::ZERO,
+        input: vec![TxIn {
+            previous_output: OutPoint { txid: Txid::all_zeros(), vout: 0xffff_ffff },
+            script_sig,
+            sequence: Sequence::ZERO,
+            witness: Witness::new(),
+        }],
+        output: vec![TxOut {
+            value: Amount::ZERO,
+            script_pubkey: address.script_pubkey(),
+        }],
+    }
+}
+
+/// The virtual transaction `to_sign` from BIP-322, without the witness
+fn bip322_to_sign(to_spend: &Transaction) -> Transaction {
+    Transaction {
+        version: Version(0),
+        lock_time: LockTime::ZERO,
+        input: vec![TxIn {
+            previous_output: OutPoint { txid: to_spend.compute_txid(), vout: 0 },
+            script_sig: ScriptBuf::new(),
+            sequence: Sequence::ZERO,
+            witness: Witness::new(),
+        }],
+        output: vec![TxOut {
+            value: Amount::ZERO,
+            script_pubkey: ScriptBuf::builder().push_opcode(OP_RETURN).into_script(),
+        }],
+    }
+}
+
+fn bip322_p2wpkh_sighash(to_sign: &Transaction, script_pubkey: &ScriptBuf) -> Result<Message, VaultNodeError> {
+    SighashCache::new(to_sign)
+        .p2wpkh_signature_hash(0, script_pubkey, Amount::ZERO, EcdsaSighashType::All)
+        .map(|hash| Message::from_digest(hash.to_byte_array()))
+        .map_err(|_| VaultNodeError::OtherProcessing("Failed to calculate sighash".to_string()))
+}
+
+fn bip322_p2tr_sighash(to_sign: &Transaction, prevout: &TxOut, sighash_type: TapSighashType) -> Result<Message, VaultNodeError> {
+    SighashCache::new(to_sign)
+        .taproot_key_spend_signature_hash(0, &Prevouts::All(std::slice::from_ref(prevout)), sighash_type)
+        .map(|hash| Message::from_digest(hash.to_byte_array()))
+        .map_err(|_| VaultNodeError::OtherProcessing("Failed to calculate sighash".to_string()))
+}
+
+/// Sign with BIP-322 simple format. Supports P2WPKH and P2TR (key-path) addresses.
+pub fn sign_bip322(key: &SecretKey, address: &Address, network: NetworkKind, message: &str) -> Result<String, VaultNodeError> {
+    let address_type = check_key(key, address, network)?;
+    let secp = Secp256k1::new();
+    let to_spend = bip322_to_spend(address, message);
+    let to_sign = bip322_to_sign(&to_spend);
+    let witness = match address_type {
+        AddressType::P2wpkh => {
+            let msg = bip322_p2wpkh_sighash(&to_sign, &to_spend.output[0].script_pubkey)?;
+            let signature = bitcoin::ecdsa::Signature {
+                signature: secp.sign_ecdsa(&msg, key),
+                sighash_type: EcdsaSighashType::All,
+            };
+            Witness::p2wpkh(&signature, &key.public_key(&secp))
+        }
+        AddressType::P2tr => {
+            let msg = bip322_p2tr_sighash(&to_sign, &to_spend.output[0], TapSighashType::Default)?;
+            let tweaked = Keypair::from_secret_key(&secp, key).tap_tweak(&secp, None);
+            let mut aux = [0u8; 32];
+            rand::thread_rng().fill_bytes(&mut aux);
+            let signature = secp.sign_schnorr_with_aux_rand(&msg, &tweaked.to_keypair(), &aux);
+            Witness::p2tr_key_spend(&bitcoin::taproot::Signature {
+                signature,
+                sighash_type: TapSighashType::Default,
+            })
+        }
+        _ => return Err(VaultNodeError::InvalidArgumentValue("BIP-322 simple signature is not supported for the address, use BIP-137".to_string()))
+    };
+    Ok(base64::engine::general_purpose::STANDARD.encode(bitcoin::consensus::serialize(&witness)))
+}
+
+/// Verify BIP-322 simple signature for the address
+pub fn verify_bip322(address: &Address, message: &str, signature: &str) -> Result<bool, VaultNodeError> {
+    let bytes = base64::engine::general_purpose::STANDARD.decode(signature.trim())
+        .map_err(|_| invalid_signature())?;
+    let witness: Witness = bitcoin::consensus::deserialize(&bytes)
+        .map_err(|_| invalid_signature())?;
+    let secp = Secp256k1::verification_only();
+    let to_spend = bip322_to_spend(address, message);
+    let to_sign = bip322_to_sign(&to_spend);
+    let script_pubkey = &to_spend.output[0].script_pubkey;
+    match address.address_type() {
+        Some(AddressType::P2wpkh) => {
+            if witness.len() != 2 {
+                return Ok(false);
+            }
+            let signature = bitcoin::ecdsa::Signature::from_slice(&witness[0])
+                .map_err(|_| invalid_signature())?;
+            let pk = bitcoin::PublicKey::from_slice(&witness[1])
+                .map_err(|_| invalid_signature())?;
+            let compressed = match CompressedPublicKey::try_from(pk) {
+                Ok(pk) => pk,
+                Err(_) => return Ok(false),
+            };
+            if signature.sighash_type != EcdsaSighashType::All || ScriptBuf::new_p2wpkh(&compressed.wpubkey_hash()) != *script_pubkey {
+                return Ok(false);
+            }
+            let msg = bip322_p2wpkh_sighash(&to_sign, script_pubkey)?;
+            Ok(secp.verify_ecdsa(&msg, &signature.signature, &pk.inner).is_ok())
+        }
+        Some(AddressType::P2tr) => {
+            if witness.len() != 1 {
+                return Ok(false);
+            }
+            let signature = bitcoin::taproot::Signature::from_slice(&witness[0])
+                .map_err(|_| invalid_signature())?;
+            if !matches!(signature.sighash_type, TapSighashType::Default | TapSighashType::All) {
+                return Ok(false);
+            }
+            let output_key = XOnlyPublicKey::from_slice(&script_pubkey.as_bytes()[2..])
+                .map_err(|_| invalid_signature())?;
+            let msg = bip322_p2tr_sighash(&to_sign, &to_spend.output[0], signature.sighash_type)?;
+            Ok(secp.verify_schnorr(&signature.signature, &msg, &output_key).is_ok())
+        }
+        _ => Err(VaultNodeError::InvalidArgumentValue("BIP-322 simple signature is not supported for the address".to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+    use bitcoin::{Address, NetworkKind, PrivateKey};
+    use bitcoin::secp256k1::{Secp256k1, SecretKey};
+    use crate::bitcoin_message::{bip322_message_hash, recover_bip137, sign_bip137, sign_bip322, verify_bip322};
+
+    // test vectors from BIP-322
+    fn bip322_key() -> SecretKey {
+        PrivateKey::from_wif("L3VFeEujGtevx9w18HD1fhRbCH67Az2dpCymeRE1SoPK6XQtaN2k").unwrap().inner
+    }
+
+    fn address(value: &str) -> Address {
+        Address::from_str(value).unwrap().assume_checked()
+    }
+
+    #[test]
+    fn bip322_hash() {
+        assert_eq!(hex::encode(bip322_message_hash("")), "c90c269c4f8fcbe6880f72a721ddfbf1914268a794cbb21cfafee13770ae19f1");
+        assert_eq!(hex::encode(bip322_message_hash("Hello World")), "f0eb03b1a75ac6d9847f55c624a99169b5dccba2a31f5b23bea77ba270de0a7a");
+    }
+
+    #[test]
+    fn verify_bip322_p2wpkh() {
+        let address = address("bc1q9vza2e8x573nczrlzms0wvx3gsqjx7vavgkx0l");
+        assert!(verify_bip322(&address, "", "AkcwRAIgM2gBAQqvZX15ZiysmKmQpDrG83avLIT492QBzLnQIxYCIBaTpOaD20qRlEylyxFSeEA2ba9YOixpX8z46TSDtS40ASECx/EgAxlkQpQ9hYjgGu6EBCPMVPwVIVJqO4XCsMvViHI=").unwrap());
+        assert!(verify_bip322(&address, "Hello World", "AkcwRAIgZRfIY3p7/DoVTty6YZbWS71bc5Vct9p9Fia83eRmw2QCICK/ENGfwLtptFluMGs2KsqoNSk89pO7F29zJLUx9a/sASECx/EgAxlkQpQ9hYjgGu6EBCPMVPwVIVJqO4XCsMvViHI=").unwrap());
+        // signature for another message
+        assert!(!verify_bip322(&address, "Hello World", "AkcwRAIgM2gBAQqvZX15ZiysmKmQpDrG83avLIT492QBzLnQIxYCIBaTpOaD20qRlEylyxFSeEA2ba9YOixpX8z46TSDtS40ASECx/EgAxlkQpQ9hYjgGu6EBCPMVPwVIVJqO4XCsMvViHI=").unwrap());
+    }
+
+    #[test]
+    fn verify_bip322_p2tr() {
+        let address = address("bc1ppv609nr0vr25u07u95waq5lucwfm6tde4nydujnu8npg4q75mr5sxq8lt3");
+        assert!(verify_bip322(&address, "Hello World", "AUHd69PrJQEv+oKTfZ8l+WROBHuy9HKrbFCJu7U1iK2iiEy1vMU5EfMtjc+VSHM7aU0SDbak5IUZRVno2P5mjSafAQ==").unwrap());
+        assert!(!verify_bip322(&address, "Hello", "AUHd69PrJQEv+oKTfZ8l+WROBHuy9HKrbFCJu7U1iK2iiEy1vMU5EfMtjc+VSHM7aU0SDbak5IUZRVno2P5mjSafAQ==").unwrap());
+    }
+
+    #[test]
+    fn sign_and_verify_bip322() {
+        let key = bip322_key();
+        for value in ["bc1q9vza2e8x573nczrlzms0wvx3gsqjx7vavgkx0l", "bc1ppv609nr0vr25u07u95waq5lucwfm6tde4nydujnu8npg4q75mr5sxq8lt3"] {
+            let address = address(value);
+            let signature = sign_bip322(&key, &address, NetworkKind::Main, "Hello World").unwrap();
+            assert!(verify_bip322(&address, "Hello World", signature.as_str()).unwrap());
+            assert!(!verify_bip322(&address, "Hello World!", signature.as_str()).unwrap());
+        }
+    }
+
+    #[test]
+    fn bip322_fails_for_other_key() {
+        let key = SecretKey::from_slice(&[1u8; 32]).unwrap();
+        let address = address("bc1q9vza2e8x573nczrlzms0wvx3gsqjx7vavgkx0l");
+        assert!(sign_bip322(&key, &address, NetworkKind::Main, "Hello World").is_err());
+    }
+
+    #[test]
+    fn sign_bip137_p2pkh() {
+        // test vector from Bitcoin Core signmessage
+        let key = PrivateKey::from_wif("cUeKHd5orzT3mz8P9pxyREHfsWtVfgsfDjiZZBcjUBAaGk1BTj7N").unwrap().inner;
+        let address = address("mpLQjfK79b7CCV4VMJWEWAj5Mpx8Up5zxB");
+        let signature = sign_bip137(&key, &address, NetworkKind::Test, "This is just a test message").unwrap();
+        assert_eq!(signature, "INbVnW4e6PeRmsv2Qgu8NuopvrVjkcxob+sX8OcZG0SALhWybUjzMLPdAsXI46YZGb0KQTRii+wWIQzRpG/U+S0=");
+    }
+
+    #[test]
+    fn recover_bip137_p2pkh() {
+        let act = recover_bip137(
+            "This is just a test message",
+            "INbVnW4e6PeRmsv2Qgu8NuopvrVjkcxob+sX8OcZG0SALhWybUjzMLPdAsXI46YZGb0KQTRii+wWIQzRpG/U+S0=",
+            NetworkKind::Test,
+        ).unwrap();
+        assert_eq!(act.to_string(), "mpLQjfK79b7CCV4VMJWEWAj5Mpx8Up5zxB");
+    }
+
+    #[test]
+    fn sign_and_recover_bip137_segwit() {
+        let secp = Secp256k1::new();
+        let key = bip322_key();
+        let pk = bitcoin::CompressedPublicKey(key.public_key(&secp));
+        for address in [
+            Address::p2wpkh(&pk, bitcoin::Network::Bitcoin),
+            Address::p2shwpkh(&pk, NetworkKind::Main),
+        ] {
+            let signature = sign_bip137(&key, &address, NetworkKind::Main, "Hello World").unwrap();
+            let act = recover_bip137("Hello World", signature.as_str(), NetworkKind::Main).unwrap();
+            assert_eq!(act, address);
+        }
+    }
+
+    #[test]
+    fn bip137_not_for_taproot() {
+        let address = address("bc1ppv609nr0vr25u07u95waq5lucwfm6tde4nydujnu8npg4q75mr5sxq8lt3");
+        assert!(sign_bip137(&bip322_key(), &address, NetworkKind::Main, "Hello World").is_err());
+    }
+}
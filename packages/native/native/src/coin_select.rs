@@ -0,0 +1,248 @@
+//! Selection of UTXOs to pay for a Bitcoin transaction.
+//!
+//! First it tries Branch-and-Bound, which looks for a set of UTXOs paying the outputs and the fee almost exactly, so
+//! no change is needed. If there is no such set it falls back to the largest-first selection with a change output.
+//! The change is dropped in favor of the fee if it's dust.
+
+use bitcoin::{ScriptBuf, TxOut, Weight};
+use crate::errors::VaultNodeError;
+use crate::fee_bump::{checked_sum, fee_for};
+use crate::tx_size::{estimate, output_weight, InputType};
+
+/// Max number of steps for the Branch-and-Bound search, same as in Bitcoin Core
+const BNB_TRIES: usize = 100_000;
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Selection {
+    /// Positions of the selected UTXOs in the provided list
+    pub inputs: Vec<usize>,
+    /// Amount of the change output, if it's needed
+    pub change: Option<u64>,
+    pub fee: u64,
+    /// Estimated weight of the signed transaction
+    pub weight: Weight,
+}
+
+/// Select UTXOs of the specified `amounts`, all of the same `input_type`, to pay for `outputs` with the fee rate in sat/vB.
+pub fn select(amounts: &[u64],
+              input_type: InputType,
+              outputs: &[TxOut],
+              change_script: &ScriptBuf,
+              fee_rate: f64) -> Result<Selection, VaultNodeError> {
+    if !fee_rate.is_finite() || fee_rate <= 0.0 {
+        return Err(VaultNodeError::InvalidArgumentValue("Invalid fee rate".to_string()));
+    }
+    if outputs.is_empty() {
+        return Err(VaultNodeError::InvalidArgumentValue("No outputs".to_string()));
+    }
+    if let Some(pos) = outputs.iter().position(|o| o.value < o.script_pubkey.minimal_non_dust()) {
+        return Err(VaultNodeError::InvalidArgumentValue(format!("Output {} is dust", pos)));
+    }
+    let scripts: Vec<ScriptBuf> = outputs.iter().map(|o| o.script_pubkey.clone()).collect();
+    let total_output = checked_sum(outputs.iter().map(|o| o.value.to_sat()))?;
+    // with a valid total all the sums of the UTXOs below are valid too
+    checked_sum(amounts.iter().copied())?;
+    let input_fee = fee_rate * input_type.weight().to_wu() as f64 / 4.0;
+
+    // UTXOs which cost more to spend than they bring are never selected
+    let mut candidates: Vec<(usize, u64)> = amounts.iter().enumerate()
+        .filter(|(_, amount)| **amount as f64 > input_fee)
+        .map(|(i, amount)| (i, *amount))
+        .collect();
+    candidates.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+
+    let fee_of = |inputs: usize, with_change: bool| {
+        let mut outputs = scripts.clone();
+        if with_change {
+            outputs.push(change_script.clone());
+        }
+        let weight = estimate(&vec![input_type; inputs], &outputs);
+        (fee_for(fee_rate, weight.to_vbytes_ceil()), weight)
+    };
+
+    // a change is worth adding only if it pays for itself and for spending it later
+    let cost_of_change = (fee_rate * output_weight(change_script).to_wu() as f64 / 4.0 + input_fee).ceil() as u64;
+    let effective: Vec<u64> = candidates.iter().map(|(_, amount)| amount - input_fee.ceil() as u64).collect();
+    let (base_fee, _) = fee_of(0, false);
+    let target = checked_sum([total_output, base_fee])?;
+    if let Some(found) = branch_and_bound(&effective, target, cost_of_change) {
+        let inputs: Vec<usize> = found.iter().map(|i| candidates[*i].0).collect();
+        let total_input: u64 = inputs.iter().map(|i| amounts[*i]).sum();
+        let (fee, weight) = fee_of(inputs.len(), false);
+        if total_input >= checked_sum([total_output, fee])? {
+            return Ok(Selection { inputs, change: None, fee: total_input - total_output, weight });
+        }
+    }
+
+    let dust = change_script.minimal_non_dust().to_sat();
+    let mut inputs = Vec::new();
+    let mut total_input = 0u64;
+    for (pos, amount) in candidates {
+        inputs.push(pos);
+        total_input += amount;
+        let (fee, weight) = fee_of(inputs.len(), true);
+        if let Some(change) = total_input.checked_sub(checked_sum([total_output, fee])?)
+            && change >= dust {
+            return Ok(Selection { inputs, change: Some(change), fee, weight });
+        }
+        let (fee, weight) = fee_of(inputs.len(), false);
+        if total_input >= checked_sum([total_output, fee])? {
+            // the change would be dust, so it goes to the fee
+            return Ok(Selection { inputs, change: None, fee: total_input - total_output, weight });
+        }
+    }
+    Err(VaultNodeError::OtherInput("Not enough funds".to_string()))
+}
+
+/// Depth-first search for a subset of `values`, sorted in descending order, with the sum in `[target, target + tolerance]`.
+/// Returns the subset with the least excess among the found ones.
+fn branch_and_bound(values: &[u64], target: u64, tolerance: u64) -> Option<Vec<usize>> {
+    let mut search = BnbSearch {
+        values,
+        target,
+        tolerance,
+        tries: BNB_TRIES,
+        selected: Vec::new(),
+        best: None,
+    };
+    search.next(0, 0, values.iter().sum());
+    search.best.map(|(_, selected)| selected)
+}
+
+struct BnbSearch<'a> {
+    values: &'a [u64],
+    target: u64,
+    tolerance: u64,
+    tries: usize,
+    selected: Vec<usize>,
+    /// Excess and positions of the best found subset
+    best: Option<(u64, Vec<usize>)>,
+}
+
+impl BnbSearch<'_> {
+    fn next(&mut self, pos: usize, sum: u64, remaining: u64) {
+        if self.tries == 0 || self.best.as_ref().is_some_and(|(excess, _)| *excess == 0) {
+            return;
+        }
+        self.tries -= 1;
+        if sum > self.target + self.tolerance || sum + remaining < self.target {
+            return;
+        }
+        if sum >= self.target {
+            let excess = sum - self.target;
+            if self.best.as_ref().is_none_or(|(best, _)| excess < *best) {
+                self.best = Some((excess, self.selected.clone()));
+            }
+            return;
+        }
+        if pos >= self.values.len() {
+            return;
+        }
+        let value = self.values[pos];
+        self.selected.push(pos);
+        self.next(pos + 1, sum + value, remaining - value);
+        self.selected.pop();
+        self.next(pos + 1, sum, remaining - value);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bitcoin::{Amount, ScriptBuf, TxOut};
+    use bitcoin::hashes::Hash;
+    use crate::coin_select::{branch_and_bound, select};
+    use crate::errors::VaultNodeError;
+    use crate::fee_bump::fee_for;
+    use crate::tx_size::InputType;
+
+    fn p2wpkh(n: u8) -> ScriptBuf {
+        ScriptBuf::new_p2wpkh(&bitcoin::WPubkeyHash::from_byte_array([n; 20]))
+    }
+
+    fn payment(amount: u64) -> Vec<TxOut> {
+        vec![TxOut { value: Amount::from_sat(amount), script_pubkey: p2wpkh(1) }]
+    }
+
+    #[test]
+    fn bnb_finds_exact() {
+        assert_eq!(branch_and_bound(&[10, 7, 5, 3], 8, 0), Some(vec![2, 3]));
+        assert_eq!(branch_and_bound(&[10, 7, 5, 3], 15, 0), Some(vec![0, 2]));
+        assert_eq!(branch_and_bound(&[10, 7, 5, 3], 25, 0), Some(vec![0, 1, 2, 3]));
+    }
+
+    #[test]
+    fn bnb_finds_within_tolerance() {
+        let act = branch_and_bound(&[10, 7, 5], 11, 2).unwrap();
+        assert_eq!(act, vec![1, 2]);
+        assert_eq!(branch_and_bound(&[10, 7, 5], 11, 0), None);
+    }
+
+    #[test]
+    fn bnb_fails_for_large_target() {
+        assert_eq!(branch_and_bound(&[10, 7, 5], 23, 100), None);
+    }
+
+    #[test]
+    fn bnb_handles_same_values() {
+        assert_eq!(branch_and_bound(&[5, 5, 5, 5], 10, 0), Some(vec![0, 1]));
+        assert_eq!(branch_and_bound(&[5, 5, 5, 5], 11, 0), None);
+    }
+
+    #[test]
+    fn selects_without_change() {
+        // 1-in 1-out P2WPKH is 110 vB, and spending an input costs 68.25 vB
+        let amounts = [50_000, 1_000 + 1_100 + 200, 30_000];
+        let act = select(&amounts, InputType::P2wpkh, &payment(1_000), &p2wpkh(2), 10.0).unwrap();
+        assert_eq!(act.inputs, vec![1]);
+        assert_eq!(act.change, None);
+        assert_eq!(act.weight.to_vbytes_ceil(), 110);
+        assert_eq!(act.fee, 1_300);
+    }
+
+    #[test]
+    fn selects_largest_with_change() {
+        let amounts = [10_000, 50_000, 30_000];
+        let act = select(&amounts, InputType::P2wpkh, &payment(60_000), &p2wpkh(2), 10.0).unwrap();
+        assert_eq!(act.inputs, vec![1, 2]);
+        assert_eq!(act.weight.to_vbytes_ceil(), 209);
+        assert_eq!(act.fee, fee_for(10.0, act.weight.to_vbytes_ceil()));
+        assert_eq!(act.change, Some(80_000 - 60_000 - act.fee));
+    }
+
+    #[test]
+    fn uses_excess_as_fee() {
+        let amounts = [10_000];
+        let act = select(&amounts, InputType::P2wpkh, &payment(8_500), &p2wpkh(2), 10.0).unwrap();
+        assert_eq!(act.change, None);
+        assert_eq!(act.fee, 1_500);
+    }
+
+    #[test]
+    fn skips_uneconomic() {
+        let amounts = [500, 20_000];
+        let act = select(&amounts, InputType::P2pkh, &payment(10_000), &p2wpkh(2), 10.0).unwrap();
+        assert_eq!(act.inputs, vec![1]);
+        assert!(select(&[500], InputType::P2pkh, &payment(100), &p2wpkh(2), 10.0).is_err());
+    }
+
+    #[test]
+    fn fails_without_funds() {
+        let amounts = [10_000, 20_000];
+        assert!(select(&amounts, InputType::P2wpkh, &payment(30_000), &p2wpkh(2), 1.0).is_err());
+    }
+
+    #[test]
+    fn fails_for_dust_output() {
+        let amounts = [10_000];
+        assert!(select(&amounts, InputType::P2wpkh, &payment(100), &p2wpkh(2), 1.0).is_err());
+        assert!(select(&amounts, InputType::P2wpkh, &payment(1_000), &p2wpkh(2), 0.0).is_err());
+    }
+
+    #[test]
+    fn fails_on_overflow() {
+        let act = select(&[u64::MAX, 10_000], InputType::P2wpkh, &payment(1_000), &p2wpkh(2), 1.0);
+        assert!(matches!(act, Err(VaultNodeError::InvalidArgumentValue(_))));
+        let act = select(&[10_000], InputType::P2wpkh, &payment(1_000), &p2wpkh(2), 1e30);
+        assert!(matches!(act, Err(VaultNodeError::InvalidArgumentValue(_))));
+    }
+}
@@ -3,17 +3,24 @@ use std::str::FromStr;
 use neon::prelude::*;
 use uuid::Uuid;
 
-use crate::access::{args_get_str};
+use crate::access::{args_get_str, args_get_wallet_and_entry_ids};
 use emerald_vault::{
-    convert::json::keyfile::EthereumJsonV3File, error::VaultError, EthereumAddress,
+    convert::json::keyfile::EthereumJsonV3File, crypto::error::CryptoError, error::VaultError, EthereumAddress,
     EthereumPrivateKey,
 };
-use crate::wallets::CurrentAddressJson;
-use emerald_vault::structs::wallet::{AddressRole};
+use crate::policy;
+use crate::policy::{EntryPolicy, PolicyKey, SpendingPolicyJson};
+use crate::wallets::{get_taproot_addresses, taproot_account, CurrentAddressJson};
+use emerald_vault::structs::book::AddressRef;
+use emerald_vault::structs::wallet::{AddressRole, PKType, WalletEntry};
 use emerald_vault::chains::BlockchainType;
 use bitcoin::Address;
-use crate::errors::VaultNodeError;
+use bitcoin::hashes::{sha256, Hash};
+use hdpath::AccountHDPath;
+use rand::RngCore;
+use crate::errors::{PolicyRule, VaultNodeError};
 use crate::instance::{Instance, WrappedVault};
+use crate::secret::SecretKind;
 
 #[derive(Deserialize)]
 #[allow(dead_code)]
@@ -52,13 +59,59 @@ pub struct ExportedWeb3Json {
     pub json: String,
 }
 
+/// Id of the secret with the key which authenticates the policy of the entry. It's derived from the entry, so the entry stays
+/// protected by the secret even if the policy file is removed.
+fn policy_key_id(wallet_id: Uuid, entry_id: usize) -> Uuid {
+    let hash = sha256::Hash::hash(format!("policy:{}:{}", wallet_id, entry_id).as_bytes()).to_byte_array();
+    let mut id = [0u8; 16];
+    id.copy_from_slice(&hash[..16]);
+    uuid::Builder::from_random_bytes(id).into_uuid()
+}
+
+/// Identity of an Ethereum key by its address, see `key_identities`
+pub(crate) fn address_identity(address: &EthereumAddress) -> String {
+    address.to_string().to_lowercase()
+}
+
+/// Identity of a key on the seed, with the full HD Path for Ethereum or the account path for Bitcoin. See `key_identities`
+pub(crate) fn seed_identity(seed_id: Uuid, hd_path: String) -> String {
+    format!("{}/{}", seed_id, hd_path)
+}
+
+/// Values which identify the key of an entry, so entries with a common value sign with the same key
+fn key_identities(entry: &WalletEntry) -> Vec<String> {
+    let mut result = vec![];
+    if let Some(AddressRef::EthereumAddress(address)) = &entry.address {
+        result.push(address_identity(address));
+    }
+    match &entry.key {
+        PKType::SeedHd(seed) => {
+            let path = match entry.blockchain.get_type() {
+                BlockchainType::Ethereum => seed.hd_path.to_string(),
+                BlockchainType::Bitcoin => AccountHDPath::from(&seed.hd_path).to_string(),
+            };
+            result.push(seed_identity(seed.seed_id, path));
+        }
+        PKType::PrivateKeyRef(pk_id) => result.push(pk_id.to_string()),
+    }
+    result
+}
+
+fn rejected_by_policy(action: &str) -> VaultNodeError {
+    VaultNodeError::PolicyRejected(PolicyRule::Method, format!("{} is not allowed with a spending policy", action))
+}
+
 impl WrappedVault {
     fn list_entry_addresses(&self, wallet_id: Uuid, entry_id: usize, role: String, start: usize, limit: usize)
-                            -> Result<Vec<CurrentAddressJson>, VaultError> {
+                            -> Result<Vec<CurrentAddressJson>, VaultNodeError> {
         let storage = &self.cfg.get_storage();
         let wallet = storage.wallets().get(wallet_id)?;
         let entry = wallet.get_entry(entry_id)?;
-        let role = AddressRole::from_str(role.as_str())?;
+        let role = AddressRole::from_str(role.as_str()).map_err(VaultError::from)?;
+
+        if let Some((account, xpub)) = taproot_account(&entry) {
+            return get_taproot_addresses(&account, &xpub, &role, start as u32, limit as u32);
+        }
 
         let addresses = match entry.blockchain.get_type() {
             BlockchainType::Bitcoin => entry.get_addresses::<Address>(role, start as u32, limit as u32)?
@@ -93,6 +146,123 @@ impl WrappedVault {
         result.is_ok()
     }
 
+    fn get_policy(&self, wallet_id: Uuid, entry_id: usize) -> Result<Option<SpendingPolicyJson>, VaultNodeError> {
+        self.get_entry(wallet_id, entry_id)?;
+        self.cfg.get_policy_storage().get_rules(wallet_id, entry_id)
+    }
+
+    /// Entry has a spending policy. It's true if either the policy file or its key is in the Vault, so the entry is free
+    /// of the policy only after it's removed with the global password.
+    pub(crate) fn has_policy(&self, wallet_id: Uuid, entry_id: usize) -> bool {
+        self.cfg.get_policy_storage().exists(wallet_id, entry_id)
+            || self.cfg.get_secret_storage().exists(SecretKind::PolicyKey, policy_key_id(wallet_id, entry_id))
+    }
+
+    /// Key to verify the policy of the entry, which is unlocked by the global password. `None` if the entry has no policy.
+    pub(crate) fn policy_key(&self, wallet_id: Uuid, entry_id: usize, password: Option<&str>) -> Result<Option<PolicyKey>, VaultNodeError> {
+        if !self.has_policy(wallet_id, entry_id) {
+            return Ok(None);
+        }
+        let password = password.ok_or(VaultError::PasswordRequired)?;
+        let global = self.cfg.get_storage().global_key().get_if_exists()?.ok_or_else(policy::modified)?;
+        let key = self.cfg.get_secret_storage()
+            .load(SecretKind::PolicyKey, policy_key_id(wallet_id, entry_id), password, Some(global))?
+            .ok_or_else(policy::modified)?;
+        let key = PolicyKey::from_bytes(&key).ok_or_else(policy::modified)?;
+        Ok(Some(key))
+    }
+
+    /// Policy of the entry verified with its key. The policy file must exist while the key is in the Vault.
+    pub(crate) fn verified_policy(&self, wallet_id: Uuid, entry_id: usize, key: &PolicyKey) -> Result<EntryPolicy, VaultNodeError> {
+        self.cfg.get_policy_storage().get(wallet_id, entry_id, key)?.ok_or_else(policy::modified)
+    }
+
+    /// Save the policy with the next counter of its key, which is returned to verify the saved policy. The key is saved
+    /// first, so if the file is not written after it, the policy is refused as modified instead of using the previous file.
+    pub(crate) fn save_policy(&self, wallet_id: Uuid, entry_id: usize, policy: EntryPolicy, password: &str) -> Result<PolicyKey, VaultNodeError> {
+        let global = self.cfg.get_storage().global_key().get_if_exists()?
+            .ok_or(VaultNodeError::MissingData("global key".to_string()))?;
+        let key = policy.key().next();
+        self.cfg.get_secret_storage()
+            .save(SecretKind::PolicyKey, policy_key_id(wallet_id, entry_id), &key.to_bytes(), password, Some(global))?;
+        self.cfg.get_policy_storage().save(wallet_id, entry_id, &policy.with_key(key))?;
+        Ok(key)
+    }
+
+    /// Fails if the key of the entry is used by an entry with a spending policy, including the entry itself
+    pub(crate) fn ensure_no_policy_key(&self, entry: &WalletEntry, action: &str) -> Result<(), VaultNodeError> {
+        self.ensure_no_policy_identities(&key_identities(entry), action)
+    }
+
+    /// Fails if any of the `identities` (see `key_identities`) is of an entry with a spending policy
+    pub(crate) fn ensure_no_policy_identities(&self, identities: &[String], action: &str) -> Result<(), VaultNodeError> {
+        for wallet in self.load_wallets()? {
+            let protected = wallet.entries.iter()
+                .filter(|other| key_identities(other).iter().any(|id| identities.contains(id)))
+                .any(|other| self.has_policy(wallet.id, other.id));
+            if protected {
+                return Err(rejected_by_policy(action));
+            }
+        }
+        Ok(())
+    }
+
+    /// Fails if the seed is used by an entry with a spending policy
+    pub(crate) fn ensure_no_policy_seed(&self, seed_id: Uuid, action: &str) -> Result<(), VaultNodeError> {
+        for wallet in self.load_wallets()? {
+            let protected = wallet.entries.iter()
+                .filter(|entry| matches!(&entry.key, PKType::SeedHd(seed) if seed.seed_id == seed_id))
+                .any(|entry| self.has_policy(wallet.id, entry.id));
+            if protected {
+                return Err(rejected_by_policy(action));
+            }
+        }
+        Ok(())
+    }
+
+    /// Set or remove the spending policy of the entry, which is allowed only with the global password. With a policy the
+    /// entry signs only with the global password too, because it unlocks the key which authenticates the policy.
+    fn set_policy(&self, wallet_id: Uuid, entry_id: usize, policy: Option<SpendingPolicyJson>, password: String) -> Result<bool, VaultNodeError> {
+        let storage = &self.cfg.get_storage();
+        self.get_entry(wallet_id, entry_id)?;
+        if !storage.global_key().is_set() {
+            return Err(VaultNodeError::MissingData("global key".to_string()));
+        }
+        if !storage.global_key().verify_password(password.as_str())? {
+            return Err(VaultNodeError::from(VaultError::CryptoFailed(CryptoError::WrongKey)));
+        }
+        let policies = self.cfg.get_policy_storage();
+        let policy = match policy {
+            Some(policy) => policy,
+            None => {
+                let removed = policies.remove(wallet_id, entry_id)?;
+                let removed_key = self.cfg.get_secret_storage().remove(SecretKind::PolicyKey, policy_key_id(wallet_id, entry_id))?;
+                return Ok(removed || removed_key);
+            }
+        };
+        policy.validate()?;
+        let key = match self.policy_key(wallet_id, entry_id, Some(password.as_str())) {
+            Ok(Some(key)) => Some(key),
+            // a new policy, or a file without its key, so the key is created again
+            Ok(None) | Err(VaultNodeError::PolicyRejected(PolicyRule::Integrity, _)) => None,
+            Err(e) => return Err(e),
+        };
+        // keep the recent spending, so updating the rules doesn't reset the daily limit. A modified file is replaced.
+        let value = match key.map(|key| policies.get(wallet_id, entry_id, &key)) {
+            Some(Ok(Some(mut current))) => {
+                current.policy = policy;
+                current
+            },
+            _ => {
+                let mut key = [0u8; 32];
+                rand::thread_rng().fill_bytes(&mut key);
+                EntryPolicy::new(policy, PolicyKey::new(key))
+            },
+        };
+        self.save_policy(wallet_id, entry_id, value, password.as_str())?;
+        Ok(true)
+    }
+
     fn export_pk(&self, wallet_id: Uuid, entry_id: usize, password: String) -> Result<EthereumPrivateKey, VaultNodeError> {
         let storage = &self.cfg.get_storage();
 
@@ -102,6 +272,8 @@ impl WrappedVault {
             .map_err(|_| VaultNodeError::MissingData(format!("Wallet {}", wallet_id)))?;
         let account = wallet.get_entry(entry_id)
             .map_err(|_| VaultNodeError::MissingData(format!("Entry {} on wallet {}", entry_id, wallet_id)))?;
+        // with the key the transactions can be signed without the policy
+        self.ensure_no_policy_key(&account, "Private Key export")?;
         account
             .export_ethereum_pk(password, storage)
             .map_err(|_| VaultNodeError::VaultError("Private Kye Unavailable".to_string()))
@@ -122,6 +294,7 @@ impl WrappedVault {
         let account = wallet.get_entry(entry_id)
             .map_err(|_| VaultNodeError::MissingData(format!("Entry {} on wallet {}", entry_id, wallet_id)))?;
         let password = password.ok_or(VaultNodeError::OtherProcessing("Password is not provided".to_string()))?;
+        self.ensure_no_policy_key(&account, "Private Key export")?;
         account
             .export_ethereum_web3(password.as_str(), storage)
             .map_err(|_| VaultNodeError::VaultError("Private Kye Unavailable".to_string()))
@@ -297,9 +470,53 @@ pub fn list_addresses<H>(cx: &mut FunctionContext, handler: H) -> Result<(), Vau
     std::thread::spawn(move || {
         let vault = vault.lock().unwrap();
         let result = vault
-            .list_entry_addresses(wallet_id, entry_id, role, start, limit)
-            .map_err(|e| VaultNodeError::from(e));
+            .list_entry_addresses(wallet_id, entry_id, role, start, limit);
+
+        handler(result);
+    });
+
+    Ok(())
+}
+
+#[neon_frame_fn(channel=2)]
+pub fn get_policy<H>(cx: &mut FunctionContext, handler: H) -> Result<(), VaultNodeError>
+    where
+        H: FnOnce(Result<Option<SpendingPolicyJson>, VaultNodeError>) + Send + 'static {
+    let vault = Instance::get_vault()?;
 
+    let (wallet_id, entry_id) = args_get_wallet_and_entry_ids(cx, 0)?;
+
+    std::thread::spawn(move || {
+        let vault = vault.lock().unwrap();
+        let result = vault.get_policy(wallet_id, entry_id);
+        handler(result);
+    });
+
+    Ok(())
+}
+
+#[neon_frame_fn(channel=4)]
+pub fn set_policy<H>(cx: &mut FunctionContext, handler: H) -> Result<(), VaultNodeError>
+    where
+        H: FnOnce(Result<bool, VaultNodeError>) + Send + 'static {
+    let vault = Instance::get_vault()?;
+
+    let (wallet_id, entry_id) = args_get_wallet_and_entry_ids(cx, 0)?;
+    let policy = match args_get_str(cx, 2) {
+        Some(json) => Some(
+            serde_json::from_str::<SpendingPolicyJson>(json.as_str())
+                .map_err(|_| VaultNodeError::InvalidArgument(2))?
+        ),
+        None => None,
+    };
+    let password = cx
+        .argument::<JsString>(3)
+        .map_err(|_| VaultNodeError::ArgumentMissing(3, "password".to_string()))?
+        .value(cx);
+
+    std::thread::spawn(move || {
+        let vault = vault.lock().unwrap();
+        let result = vault.set_policy(wallet_id, entry_id, policy, password);
         handler(result);
     });
 
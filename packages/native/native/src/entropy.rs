@@ -0,0 +1,255 @@
+//! Mnemonic from entropy provided by the user (dice rolls, coin flips or hex), instead of the internal RNG.
+//!
+//! - Coins and hex are converted to bits as is, so the result can be verified manually
+//! - Dice rolls are hashed with SHA-256, because a base-6 number cannot be cut to bits without a bias
+//!
+//! Optionally the result is mixed (XOR) with the system random, so it's never weaker than a generated mnemonic.
+
+use bitcoin::hashes::{sha256, Hash};
+use rand::RngCore;
+use crate::errors::VaultNodeError;
+
+#[derive(Deserialize, Clone, Debug, Eq, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum EntropySource {
+    Dice,
+    Coins,
+    Hex,
+}
+
+#[derive(Deserialize, Clone, Debug, Eq, PartialEq)]
+pub struct EntropyJson {
+    #[serde(rename = "type")]
+    pub source: EntropySource,
+    pub value: String,
+    /// Number of words in the mnemonic
+    pub size: usize,
+    /// Number of sides of the dice, default is 6
+    pub sides: Option<u32>,
+    /// Mix with the system random
+    #[serde(rename = "mixSystem")]
+    #[serde(default)]
+    pub mix_system: bool,
+}
+
+#[derive(Serialize, Clone, Debug, Eq, PartialEq)]
+pub struct MnemonicFromEntropyJson {
+    pub mnemonic: String,
+    /// Human-readable steps of how the entropy was built
+    pub record: Vec<String>,
+}
+
+fn invalid(msg: &str) -> VaultNodeError {
+    VaultNodeError::InvalidArgumentValue(msg.to_string())
+}
+
+/// Entropy bits required for the mnemonic size
+fn required_bits(size: usize) -> Result<usize, VaultNodeError> {
+    match size {
+        12 | 15 | 18 | 21 | 24 => Ok(size * 32 / 3),
+        _ => Err(invalid("Invalid mnemonic size"))
+    }
+}
+
+fn parse_dice(value: &str, sides: u32) -> Result<Vec<u32>, VaultNodeError> {
+    if !(2..=256).contains(&sides) {
+        return Err(invalid("Invalid number of dice sides"));
+    }
+    // with separators each roll is a number, otherwise each char is a roll
+    let rolls: Vec<u32> = if value.contains(|c: char| c.is_whitespace() || c == ',') {
+        value.split(|c: char| c.is_whitespace() || c == ',')
+            .filter(|s| !s.is_empty())
+            .map(|s| s.parse::<u32>().map_err(|_| invalid("Invalid dice roll")))
+            .collect::<Result<Vec<u32>, VaultNodeError>>()?
+    } else {
+        value.chars()
+            .map(|c| c.to_digit(10).ok_or_else(|| invalid("Invalid dice roll")))
+            .collect::<Result<Vec<u32>, VaultNodeError>>()?
+    };
+    if rolls.iter().any(|r| *r < 1 || *r > sides) {
+        return Err(invalid("Dice roll is out of range"));
+    }
+    Ok(rolls)
+}
+
+fn parse_coins(value: &str) -> Result<Vec<bool>, VaultNodeError> {
+    value.chars()
+        .filter(|c| !c.is_whitespace())
+        .map(|c| match c.to_ascii_uppercase() {
+            'H' | '1' => Ok(true),
+            'T' | '0' => Ok(false),
+            _ => Err(invalid("Invalid coin flip, must be H/T or 1/0")),
+        })
+        .collect()
+}
+
+fn parse_hex(value: &str) -> Result<Vec<u8>, VaultNodeError> {
+    let value: String = value.trim_start_matches("0x").chars().filter(|c| !c.is_whitespace()).collect();
+    if value.is_empty() || !value.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(invalid("Invalid hex"));
+    }
+    // convert by nibbles, so an odd length is fine too
+    let mut bytes = vec![0u8; value.len().div_ceil(2)];
+    value.chars().enumerate().for_each(|(i, c)| {
+        let nibble = c.to_digit(16).unwrap() as u8;
+        bytes[i / 2] |= if i % 2 == 0 { nibble << 4 } else { nibble };
+    });
+    Ok(bytes)
+}
+
+fn bits_to_bytes(bits: &[bool]) -> Vec<u8> {
+    bits.chunks(8)
+        .map(|chunk| chunk.iter().enumerate().fold(0u8, |acc, (i, b)| if *b { acc | (0x80 >> i) } else { acc }))
+        .collect()
+}
+
+/// Make the entropy of `required` bits from the input, and describe how it was done
+fn build_entropy(options: &EntropyJson, required: usize) -> Result<(Vec<u8>, Vec<String>), VaultNodeError> {
+    let mut record = Vec::new();
+    let (entropy, provided) = match options.source {
+        EntropySource::Dice => {
+            let sides = options.sides.unwrap_or(6);
+            let rolls = parse_dice(options.value.as_str(), sides)?;
+            if rolls.len() > 1 && rolls.iter().all(|r| *r == rolls[0]) {
+                return Err(invalid("All dice rolls are the same"));
+            }
+            let provided = rolls.len() as f64 * (sides as f64).log2();
+            record.push(format!("Source: {} rolls of {}-sided dice, {:.1} bits of entropy", rolls.len(), sides, provided));
+            let normalized = rolls.iter().map(|r| r.to_string()).collect::<Vec<String>>().join(if sides > 9 { " " } else { "" });
+            let hash = sha256::Hash::hash(normalized.as_bytes()).to_byte_array();
+            record.push(format!("Entropy: SHA-256 of the rolls as \"{}\", first {} bits", normalized, required));
+            (hash.to_vec(), provided)
+        }
+        EntropySource::Coins => {
+            let flips = parse_coins(options.value.as_str())?;
+            record.push(format!("Source: {} coin flips, {} bits of entropy", flips.len(), flips.len()));
+            record.push(format!("Entropy: first {} flips as bits, heads is 1 and tails is 0", required));
+            (bits_to_bytes(&flips), flips.len() as f64)
+        }
+        EntropySource::Hex => {
+            let digits = options.value.trim_start_matches("0x").chars().filter(|c| !c.is_whitespace()).count();
+            let bytes = parse_hex(options.value.as_str())?;
+            record.push(format!("Source: {} hex digits, {} bits of entropy", digits, digits * 4));
+            record.push(format!("Entropy: first {} bits of the hex", required));
+            (bytes, (digits * 4) as f64)
+        }
+    };
+    if provided < required as f64 {
+        return Err(VaultNodeError::InvalidArgumentValue(
+            format!("Not enough entropy: provided {:.1} bits, but {} bits are required", provided, required)
+        ));
+    }
+    let entropy = entropy[0..required / 8].to_vec();
+    if entropy.iter().all(|b| *b == 0) || entropy.iter().all(|b| *b == 0xff) {
+        return Err(invalid("Entropy is not random"));
+    }
+    Ok((entropy, record))
+}
+
+/// Mix the entropy with the `random` bytes. XOR with a uniform random value gives a uniform random value
+fn mix(entropy: &[u8], random: &[u8]) -> Vec<u8> {
+    entropy.iter().zip(random.iter()).map(|(a, b)| a ^ b).collect()
+}
+
+pub fn generate_mnemonic(options: EntropyJson) -> Result<MnemonicFromEntropyJson, VaultNodeError> {
+    let required = required_bits(options.size)?;
+    let (mut entropy, mut record) = build_entropy(&options, required)?;
+    if options.mix_system {
+        let mut random = vec![0u8; entropy.len()];
+        rand::thread_rng().fill_bytes(&mut random);
+        entropy = mix(&entropy, &random);
+        record.push(format!("Mixed: XOR with {} bits from the system random generator, so the result cannot be reproduced from the input", required));
+    }
+    let mnemonic = bip39::Mnemonic::from_entropy_in(bip39::Language::English, &entropy)
+        .map_err(|_| VaultNodeError::OtherProcessing("Failed to create mnemonic".to_string()))?;
+    record.push(format!("Checksum: first {} bits of SHA-256 of the entropy", required / 32));
+    record.push(format!("Mnemonic: {} words from the BIP-39 English list", options.size));
+    Ok(MnemonicFromEntropyJson {
+        mnemonic: mnemonic.to_string(),
+        record,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::entropy::{generate_mnemonic, EntropyJson, EntropySource};
+
+    fn options(source: EntropySource, value: &str, size: usize) -> EntropyJson {
+        EntropyJson {
+            source,
+            value: value.to_string(),
+            size,
+            sides: None,
+            mix_system: false,
+        }
+    }
+
+    #[test]
+    fn from_hex() {
+        let act = generate_mnemonic(options(EntropySource::Hex, "0x7f7f7f7f7f7f7f7f7f7f7f7f7f7f7f7f", 12)).unwrap();
+        // BIP-39 test vector
+        assert_eq!(act.mnemonic, "legal winner thank year wave sausage worth useful legal winner thank yellow");
+        assert_eq!(act.record.len(), 4);
+    }
+
+    #[test]
+    fn from_coins() {
+        // same as 0x7f7f.. above
+        let act = generate_mnemonic(options(EntropySource::Coins, &"THHHHHHH".repeat(16), 12)).unwrap();
+        assert_eq!(act.mnemonic, "legal winner thank year wave sausage worth useful legal winner thank yellow");
+    }
+
+    #[test]
+    fn from_dice() {
+        let rolls = "3451245612345665432112345616253416253412563412562534612354162534162534612534165234165342163524";
+        let act = generate_mnemonic(options(EntropySource::Dice, &rolls[0..50], 12)).unwrap();
+        assert_eq!(act.mnemonic.split(' ').count(), 12);
+        // the same input gives the same mnemonic
+        assert_eq!(act, generate_mnemonic(options(EntropySource::Dice, &rolls[0..50], 12)).unwrap());
+        // with separators
+        let separated: Vec<String> = rolls[0..50].chars().map(|c| c.to_string()).collect();
+        let act_separated = generate_mnemonic(options(EntropySource::Dice, separated.join(", ").as_str(), 12)).unwrap();
+        assert_eq!(act.mnemonic, act_separated.mnemonic);
+        assert!(act.record[0].starts_with("Source: 50 rolls of 6-sided dice, 129.2 bits"));
+    }
+
+    #[test]
+    fn fails_without_enough_entropy() {
+        assert!(generate_mnemonic(options(EntropySource::Hex, "7f7f7f7f7f7f7f7f7f7f7f7f7f7f7f", 12)).is_err());
+        assert!(generate_mnemonic(options(EntropySource::Coins, &"THHHHHHH".repeat(15), 12)).is_err());
+        // 49 rolls give 126.6 bits
+        assert!(generate_mnemonic(options(EntropySource::Dice, &"1234561234".repeat(5)[0..49], 12)).is_err());
+        // 90 rolls give 232.6 bits
+        assert!(generate_mnemonic(options(EntropySource::Dice, &"1234561234".repeat(9), 24)).is_err());
+        assert!(generate_mnemonic(options(EntropySource::Dice, &"1234561234".repeat(10), 24)).is_ok());
+    }
+
+    #[test]
+    fn fails_on_invalid_input() {
+        assert!(generate_mnemonic(options(EntropySource::Dice, &"1234567".repeat(10), 12)).is_err());
+        assert!(generate_mnemonic(options(EntropySource::Dice, &"1".repeat(60), 12)).is_err());
+        assert!(generate_mnemonic(options(EntropySource::Coins, &"HTX".repeat(60), 12)).is_err());
+        assert!(generate_mnemonic(options(EntropySource::Hex, &"0g".repeat(20), 12)).is_err());
+        assert!(generate_mnemonic(options(EntropySource::Hex, &"00".repeat(16), 12)).is_err());
+        assert!(generate_mnemonic(options(EntropySource::Hex, &"7f".repeat(16), 13)).is_err());
+    }
+
+    #[test]
+    fn mixes_with_system() {
+        let mut opts = options(EntropySource::Hex, "0x7f7f7f7f7f7f7f7f7f7f7f7f7f7f7f7f", 12);
+        opts.mix_system = true;
+        let act = generate_mnemonic(opts.clone()).unwrap();
+        assert_ne!(act.mnemonic, "legal winner thank year wave sausage worth useful legal winner thank yellow");
+        assert_ne!(act.mnemonic, generate_mnemonic(opts).unwrap().mnemonic);
+        assert!(act.record.iter().any(|r| r.starts_with("Mixed:")));
+    }
+
+    #[test]
+    fn parse_json() {
+        let act: EntropyJson = serde_json::from_str(r#"{"type": "dice", "value": "123", "size": 24, "mixSystem": true}"#).unwrap();
+        assert_eq!(act.source, EntropySource::Dice);
+        assert!(act.mix_system);
+        let act: EntropyJson = serde_json::from_str(r#"{"type": "coins", "value": "HT", "size": 12}"#).unwrap();
+        assert!(!act.mix_system);
+    }
+}
@@ -13,6 +13,19 @@ pub enum VaultNodeError {
     OtherProcessing(String),
     MissingData(String),
     Misconfigured,
+    /// Transaction is rejected by the spending policy of the entry
+    PolicyRejected(PolicyRule, String),
+}
+
+/// Rule of a spending policy, each has its own error code
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum PolicyRule {
+    DailyLimit,
+    Recipient,
+    Fee,
+    Method,
+    /// Policy cannot be verified, so nothing is signed
+    Integrity,
 }
 
 #[derive(Debug, Clone)]
@@ -75,6 +88,13 @@ impl From<VaultNodeError> for (usize, String) {
             VaultNodeError::MissingData(name) => (162, format!("Missing data: {}", name)),
             VaultNodeError::VaultError(msg) => (200, msg),
             VaultNodeError::Misconfigured => (300, format!("Vault Access is not properly configured")),
+            VaultNodeError::PolicyRejected(rule, msg) => match rule {
+                PolicyRule::DailyLimit => (170, msg),
+                PolicyRule::Recipient => (171, msg),
+                PolicyRule::Fee => (172, msg),
+                PolicyRule::Method => (173, msg),
+                PolicyRule::Integrity => (174, msg),
+            },
         }
     }
 }
\ No newline at end of file
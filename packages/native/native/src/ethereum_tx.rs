@@ -0,0 +1,374 @@
+//! Encoding and signing of Ethereum transactions with a private key, without the Vault.
+//!
+//! It's used to sign many transactions with a key decrypted only once, and for EIP-2930 and EIP-7702 transactions and
+//! authorizations which are not supported by the Vault. Legacy transactions are always signed with the EIP-155 chain id.
+
+use std::str::FromStr;
+use bitcoin::secp256k1::{Message, Secp256k1, SecretKey};
+use emerald_vault::keccak256;
+use num_bigint::BigUint;
+use rlp::RlpStream;
+use crate::errors::VaultNodeError;
+use crate::tx_decode::{authorization_hash, parse_signature_value, AuthorizationJson, DecodedAccessListItemJson, EthereumTxFieldsJson, TYPE_EIP1559, TYPE_EIP2930, TYPE_EIP7702};
+
+fn invalid(name: &str) -> VaultNodeError {
+    VaultNodeError::InvalidArgumentValue(format!("Invalid {}", name))
+}
+
+fn parse_hex(value: &str, name: &str) -> Result<Vec<u8>, VaultNodeError> {
+    let value = value.strip_prefix("0x").unwrap_or(value);
+    hex::decode(value).map_err(|_| invalid(name))
+}
+
+fn parse_fixed<const N: usize>(value: &str, name: &str) -> Result<[u8; N], VaultNodeError> {
+    parse_hex(value, name)?.try_into().map_err(|_| invalid(name))
+}
+
+/// A decimal integer as a big-endian number without leading zeroes, as it's encoded in RLP
+fn parse_uint(value: Option<&String>, name: &str) -> Result<Vec<u8>, VaultNodeError> {
+    let value = value.ok_or(VaultNodeError::InvalidArgumentValue(format!("Missing {}", name)))?;
+    let value = BigUint::from_str(value.as_str()).map_err(|_| invalid(name))?;
+    if value.bits() > 256 {
+        return Err(invalid(name));
+    }
+    Ok(trim_zeroes(&value.to_bytes_be()).to_vec())
+}
+
+fn trim_zeroes(value: &[u8]) -> &[u8] {
+    let start = value.iter().position(|b| *b != 0).unwrap_or(value.len());
+    &value[start..]
+}
+
+fn append_access_list(stream: &mut RlpStream, access_list: &[DecodedAccessListItemJson]) -> Result<(), VaultNodeError> {
+    stream.begin_list(access_list.len());
+    for item in access_list {
+        stream.begin_list(2);
+        stream.append(&parse_fixed::<20>(item.address.as_str(), "accessList[].address")?.as_slice());
+        stream.begin_list(item.storage.len());
+        for key in &item.storage {
+            stream.append(&parse_fixed::<32>(key.as_str(), "accessList[].storage")?.as_slice());
+        }
+    }
+    Ok(())
+}
+
+fn append_authorization_list(stream: &mut RlpStream, authorization_list: &[AuthorizationJson]) -> Result<(), VaultNodeError> {
+    stream.begin_list(authorization_list.len());
+    for item in authorization_list {
+        if item.y_parity > 1 {
+            return Err(invalid("authorizationList[].yParity"));
+        }
+        stream.begin_list(6);
+        stream.append(&item.chain_id);
+        stream.append(&parse_fixed::<20>(item.address.as_str(), "authorizationList[].address")?.as_slice());
+        stream.append(&item.nonce);
+        stream.append(&item.y_parity);
+        stream.append(&trim_zeroes(&parse_signature_value(item.r.as_str(), "authorizationList[].r")?));
+        stream.append(&trim_zeroes(&parse_signature_value(item.s.as_str(), "authorizationList[].s")?));
+    }
+    Ok(())
+}
+
+/// RLP list with the fields of the transaction, without the signature. The list has `extra` more items for the caller to append.
+fn encode_fields(fields: &EthereumTxFieldsJson, extra: usize) -> Result<RlpStream, VaultNodeError> {
+    let chain_id = fields.chain_id.ok_or(VaultNodeError::InvalidArgumentValue("Missing chainId".to_string()))?;
+    let to = match &fields.to {
+        Some(to) if !to.is_empty() => parse_fixed::<20>(to.as_str(), "to")?.to_vec(),
+        _ => vec![],
+    };
+    let value = parse_uint(Some(&fields.value), "value")?;
+    let data = parse_hex(fields.data.as_str(), "data")?;
+    let stream = match fields.tx_type {
+        0 => {
+            let mut stream = RlpStream::new_list(6 + extra);
+            stream.append(&fields.nonce);
+            stream.append(&parse_uint(fields.gas_price.as_ref(), "gasPrice")?.as_slice());
+            stream.append(&fields.gas);
+            stream.append(&to.as_slice());
+            stream.append(&value.as_slice());
+            stream.append(&data.as_slice());
+            stream
+        }
+        TYPE_EIP2930 => {
+            let mut stream = RlpStream::new_list(8 + extra);
+            stream.append(&chain_id);
+            stream.append(&fields.nonce);
+            stream.append(&parse_uint(fields.gas_price.as_ref(), "gasPrice")?.as_slice());
+            stream.append(&fields.gas);
+            stream.append(&to.as_slice());
+            stream.append(&value.as_slice());
+            stream.append(&data.as_slice());
+            append_access_list(&mut stream, fields.access_list.as_deref().unwrap_or_default())?;
+            stream
+        }
+        TYPE_EIP1559 | TYPE_EIP7702 => {
+            let set_code = fields.tx_type == TYPE_EIP7702;
+            if set_code && to.is_empty() {
+                return Err(VaultNodeError::OtherInput("EIP-7702 transaction cannot create a contract".to_string()));
+            }
+            let mut stream = RlpStream::new_list(if set_code { 10 } else { 9 } + extra);
+            stream.append(&chain_id);
+            stream.append(&fields.nonce);
+            stream.append(&parse_uint(fields.priority_gas_price.as_ref(), "priorityGasPrice")?.as_slice());
+            stream.append(&parse_uint(fields.max_gas_price.as_ref(), "maxGasPrice")?.as_slice());
+            stream.append(&fields.gas);
+            stream.append(&to.as_slice());
+            stream.append(&value.as_slice());
+            stream.append(&data.as_slice());
+            append_access_list(&mut stream, fields.access_list.as_deref().unwrap_or_default())?;
+            if set_code {
+                let authorization_list = fields.authorization_list.as_deref().unwrap_or_default();
+                if authorization_list.is_empty() {
+                    return Err(VaultNodeError::OtherInput("EIP-7702 transaction without authorizations".to_string()));
+                }
+                append_authorization_list(&mut stream, authorization_list)?;
+            }
+            stream
+        }
+        other => return Err(VaultNodeError::OtherInput(format!("Unsupported transaction type: {}", other)))
+    };
+    Ok(stream)
+}
+
+/// Recovery id and the compact signature (`r` and `s`) of the hash
+fn sign_hash(hash: [u8; 32], key: &SecretKey) -> (u64, [u8; 64]) {
+    let (recovery_id, signature) = Secp256k1::signing_only()
+        .sign_ecdsa_recoverable(&Message::from_digest(hash), key)
+        .serialize_compact();
+    (recovery_id.to_i32() as u64, signature)
+}
+
+/// Sign the transaction and encode it, as a legacy RLP or an EIP-2718 envelope
+pub fn sign(fields: &EthereumTxFieldsJson, key: &SecretKey) -> Result<Vec<u8>, VaultNodeError> {
+    let chain_id = fields.chain_id.ok_or(VaultNodeError::InvalidArgumentValue("Missing chainId".to_string()))?;
+    let sign = |hash: [u8; 32]| sign_hash(hash, key);
+
+    let result = if fields.tx_type == 0 {
+        // EIP-155 signs the fields followed by [chainId, 0, 0]
+        let mut unsigned = encode_fields(fields, 3)?;
+        unsigned.append(&chain_id);
+        unsigned.append(&0u8);
+        unsigned.append(&0u8);
+        let (recovery_id, signature) = sign(keccak256(&unsigned.out()));
+        let mut signed = encode_fields(fields, 3)?;
+        signed.append(&(chain_id * 2 + 35 + recovery_id));
+        signed.append(&trim_zeroes(&signature[0..32]));
+        signed.append(&trim_zeroes(&signature[32..64]));
+        signed.out().to_vec()
+    } else {
+        let mut unsigned = vec![fields.tx_type];
+        unsigned.extend_from_slice(&encode_fields(fields, 0)?.out());
+        let (recovery_id, signature) = sign(keccak256(&unsigned));
+        let mut signed = encode_fields(fields, 3)?;
+        signed.append(&recovery_id);
+        signed.append(&trim_zeroes(&signature[0..32]));
+        signed.append(&trim_zeroes(&signature[32..64]));
+        let mut result = vec![fields.tx_type];
+        result.extend_from_slice(&signed.out());
+        result
+    };
+    Ok(result)
+}
+
+/// Sign an EIP-7702 authorization to delegate the key account to the `address`. The `chain_id` `0` makes it valid on any chain.
+pub fn sign_authorization(chain_id: u64, address: &str, nonce: u64, key: &SecretKey) -> Result<AuthorizationJson, VaultNodeError> {
+    let address = parse_fixed::<20>(address, "address")?;
+    let (recovery_id, signature) = sign_hash(authorization_hash(chain_id, &address, nonce), key);
+    Ok(AuthorizationJson {
+        chain_id,
+        address: format!("0x{}", hex::encode(address)),
+        nonce,
+        y_parity: recovery_id as u8,
+        r: format!("0x{}", hex::encode(&signature[0..32])),
+        s: format!("0x{}", hex::encode(&signature[32..64])),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use bitcoin::secp256k1::SecretKey;
+    use crate::ethereum_tx::{sign, sign_authorization};
+    use crate::tx_decode::{decode_ethereum, decode_unsigned_ethereum, recover_authority, DecodedAccessListItemJson, EthereumTxFieldsJson};
+
+    fn key() -> SecretKey {
+        SecretKey::from_slice(&[0x46; 32]).unwrap()
+    }
+
+    fn legacy() -> EthereumTxFieldsJson {
+        EthereumTxFieldsJson {
+            tx_type: 0,
+            chain_id: Some(1),
+            nonce: 9,
+            gas: 21000,
+            gas_price: Some("20000000000".to_string()),
+            max_gas_price: None,
+            priority_gas_price: None,
+            to: Some("0x3535353535353535353535353535353535353535".to_string()),
+            value: "1000000000000000000".to_string(),
+            data: "0x".to_string(),
+            access_list: None,
+            authorization_list: None,
+        }
+    }
+
+    #[test]
+    fn sign_eip155_example() {
+        // example from EIP-155
+        let act = sign(&legacy(), &key()).unwrap();
+        assert_eq!(
+            hex::encode(act),
+            "f86c098504a817c800825208943535353535353535353535353535353535353535880de0b6b3a76400008025a028ef61340bd939bc2195fe537567866003e1a15d3c71ff63e1590620aa636276a067cbe9d8997f761aecb703304b3800ccf555c9f3dc64214b297fb1966a3b6d83"
+        );
+    }
+
+    #[test]
+    fn sign_eip1559() {
+        let fields = EthereumTxFieldsJson {
+            tx_type: 2,
+            chain_id: Some(11155111),
+            nonce: 0,
+            gas: 100000,
+            gas_price: None,
+            max_gas_price: Some("30000000000".to_string()),
+            priority_gas_price: Some("1000000000".to_string()),
+            to: Some("0x3535353535353535353535353535353535353535".to_string()),
+            value: "0".to_string(),
+            data: "0xa9059cbb".to_string(),
+            access_list: Some(vec![DecodedAccessListItemJson {
+                address: "0x1111111111111111111111111111111111111111".to_string(),
+                storage: vec![format!("0x{}", "00".repeat(31) + "01")],
+            }]),
+            authorization_list: None,
+        };
+        let act = sign(&fields, &key()).unwrap();
+        assert_eq!(act[0], 0x02);
+        let decoded = decode_ethereum(&act).unwrap();
+        assert_eq!(decoded.fields, fields);
+        assert_eq!(decoded.from, "0x9d8a62f656a8d1615c1294fd71e9cfb3e4855a4f");
+    }
+
+    #[test]
+    fn sign_contract_creation() {
+        let mut fields = legacy();
+        fields.to = None;
+        fields.data = "0x6000".to_string();
+        let decoded = decode_ethereum(&sign(&fields, &key()).unwrap()).unwrap();
+        assert_eq!(decoded.fields, fields);
+    }
+
+    fn eip7702() -> EthereumTxFieldsJson {
+        EthereumTxFieldsJson {
+            tx_type: 4,
+            chain_id: Some(1),
+            nonce: 5,
+            gas: 100000,
+            gas_price: None,
+            max_gas_price: Some("30000000000".to_string()),
+            priority_gas_price: Some("1000000000".to_string()),
+            to: Some("0x9d8a62f656a8d1615c1294fd71e9cfb3e4855a4f".to_string()),
+            value: "0".to_string(),
+            data: "0x".to_string(),
+            access_list: Some(vec![]),
+            authorization_list: Some(vec![
+                sign_authorization(1, "0x63c0c19a282a1b52b07dd5a65b58948a07dae32b", 6, &key()).unwrap()
+            ]),
+        }
+    }
+
+    #[test]
+    fn sign_authorization_for_key() {
+        let act = sign_authorization(1, "0x63c0c19a282a1b52b07dd5a65b58948a07dae32b", 6, &key()).unwrap();
+        assert_eq!(act.chain_id, 1);
+        assert_eq!(act.address, "0x63c0c19a282a1b52b07dd5a65b58948a07dae32b");
+        assert_eq!(act.nonce, 6);
+        assert!(act.y_parity <= 1);
+        assert_eq!(recover_authority(&act).unwrap(), "0x9d8a62f656a8d1615c1294fd71e9cfb3e4855a4f");
+
+        let other_chain = sign_authorization(0, "0x63c0c19a282a1b52b07dd5a65b58948a07dae32b", 6, &key()).unwrap();
+        assert_ne!(other_chain.r, act.r);
+        assert_eq!(recover_authority(&other_chain).unwrap(), "0x9d8a62f656a8d1615c1294fd71e9cfb3e4855a4f");
+    }
+
+    #[test]
+    fn sign_eip7702() {
+        let fields = eip7702();
+        let act = sign(&fields, &key()).unwrap();
+        assert_eq!(act[0], 0x04);
+        let decoded = decode_ethereum(&act).unwrap();
+        assert_eq!(decoded.fields, fields);
+        assert_eq!(decoded.from, "0x9d8a62f656a8d1615c1294fd71e9cfb3e4855a4f");
+    }
+
+    #[test]
+    fn sign_eip7702_with_short_signature_values() {
+        let mut fields = eip7702();
+        let mut authorization = fields.authorization_list.as_ref().unwrap()[0].clone();
+        authorization.r = "0x01".to_string();
+        authorization.s = "0x02".to_string();
+        fields.authorization_list = Some(vec![authorization]);
+        let decoded = decode_ethereum(&sign(&fields, &key()).unwrap()).unwrap();
+        let act = &decoded.fields.authorization_list.unwrap()[0];
+        assert_eq!(act.r, format!("0x{}01", "00".repeat(31)));
+        assert_eq!(act.s, format!("0x{}02", "00".repeat(31)));
+    }
+
+    #[test]
+    fn decode_unsigned_eip7702() {
+        let fields = eip7702();
+        let signed = sign(&fields, &key()).unwrap();
+        let decoded = decode_ethereum(&signed).unwrap();
+        // the same tx without the signature, i.e., without the last 3 items of the list
+        let mut unsigned = rlp::RlpStream::new_list(10);
+        for item in rlp::Rlp::new(&signed[1..]).iter().take(10) {
+            unsigned.append_raw(item.as_raw(), 1);
+        }
+        let mut raw = vec![0x04];
+        raw.extend_from_slice(&unsigned.out());
+        assert_eq!(decode_unsigned_ethereum(&raw).unwrap(), decoded.fields);
+    }
+
+    #[test]
+    fn fails_eip7702_without_authorizations() {
+        let mut fields = eip7702();
+        fields.authorization_list = Some(vec![]);
+        assert!(sign(&fields, &key()).is_err());
+        fields.authorization_list = None;
+        assert!(sign(&fields, &key()).is_err());
+    }
+
+    #[test]
+    fn fails_eip7702_contract_creation() {
+        let mut fields = eip7702();
+        fields.to = None;
+        assert!(sign(&fields, &key()).is_err());
+    }
+
+    #[test]
+    fn sign_eip2930() {
+        let mut fields = legacy();
+        fields.tx_type = 1;
+        fields.access_list = Some(vec![DecodedAccessListItemJson {
+            address: "0x1111111111111111111111111111111111111111".to_string(),
+            storage: vec![],
+        }]);
+        let act = sign(&fields, &key()).unwrap();
+        assert_eq!(act[0], 0x01);
+        let decoded = decode_ethereum(&act).unwrap();
+        assert_eq!(decoded.fields, fields);
+        assert_eq!(decoded.from, "0x9d8a62f656a8d1615c1294fd71e9cfb3e4855a4f");
+    }
+
+    #[test]
+    fn fails_for_unknown_type() {
+        let mut fields = legacy();
+        fields.tx_type = 3;
+        assert!(sign(&fields, &key()).is_err());
+    }
+
+    #[test]
+    fn fails_without_chain_id() {
+        let mut fields = legacy();
+        fields.chain_id = None;
+        assert!(sign(&fields, &key()).is_err());
+    }
+}
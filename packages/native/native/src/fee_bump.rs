@@ -0,0 +1,232 @@
+//! Replacement of an unconfirmed Bitcoin transaction with a higher fee, as defined by BIP-125.
+//!
+//! The replacement spends the same inputs, so it conflicts with the original, and keeps all the outputs except the change.
+//! It must pay at least the original fee plus the incremental relay fee for its own size, and the difference is taken
+//! from the change. If the change is not enough (i.e. it becomes dust) more inputs are added.
+
+use bitcoin::{OutPoint, ScriptBuf, Sequence, Transaction, TxOut, Weight};
+use bitcoin::Amount;
+use crate::errors::VaultNodeError;
+
+/// Default incremental relay fee of Bitcoin Core, in sat/vB
+pub const INCREMENTAL_FEE_RATE: u64 = 1;
+
+pub struct Utxo {
+    pub output: OutPoint,
+    pub amount: u64,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Replacement {
+    /// Spent UTXOs as `(index, sequence)`, where the index is the position in the provided list
+    pub inputs: Vec<(usize, u32)>,
+    pub outputs: Vec<TxOut>,
+    pub fee: u64,
+    /// Estimated size of the signed replacement
+    pub vsize: u64,
+}
+
+/// Fee for the size with the rate in sat/vB, rounded up
+pub fn fee_for(fee_rate: f64, vsize: u64) -> u64 {
+    (fee_rate * vsize as f64).ceil() as u64
+}
+
+/// Sum of the amounts, which fails instead of overflowing on invalid amounts (ex. a fee for an enormous fee rate)
+pub fn checked_sum<I: IntoIterator<Item = u64>>(amounts: I) -> Result<u64, VaultNodeError> {
+    amounts.into_iter()
+        .try_fold(0u64, |sum, amount| sum.checked_add(amount))
+        .ok_or(VaultNodeError::InvalidArgumentValue("Amount overflow".to_string()))
+}
+
+/// Build a replacement for the signed `original`.
+///
+/// `utxos` must include all inputs of the original transaction, and the others are used in the same order only if the change
+/// is not enough to pay the fee. `change` is the position of the change output in the original, if there is no change
+/// `new_change` is used as the change script.
+pub fn build(original: &Transaction,
+             utxos: &[Utxo],
+             change: Option<usize>,
+             new_change: Option<ScriptBuf>,
+             fee_rate: f64) -> Result<Replacement, VaultNodeError> {
+    if !original.is_explicitly_rbf() {
+        return Err(VaultNodeError::OtherInput("Transaction doesn't signal BIP-125 replaceability".to_string()));
+    }
+    if !fee_rate.is_finite() || fee_rate <= 0.0 {
+        return Err(VaultNodeError::InvalidArgumentValue("Invalid fee rate".to_string()));
+    }
+
+    let mut inputs = Vec::with_capacity(original.input.len());
+    for (i, input) in original.input.iter().enumerate() {
+        let pos = utxos.iter().position(|u| u.output == input.previous_output)
+            .ok_or(VaultNodeError::OtherInput(format!("No UTXO details for input {}", i)))?;
+        inputs.push((pos, input.sequence.0));
+    }
+    let total_output = checked_sum(original.output.iter().map(|o| o.value.to_sat()))?;
+    let original_fee = checked_sum(inputs.iter().map(|(pos, _)| utxos[*pos].amount))?
+        .checked_sub(total_output)
+        .ok_or(VaultNodeError::OtherInput("Inputs are less than outputs".to_string()))?;
+
+    // an added input is of the same entry, so it's expected to be the same size as the existing ones
+    let input_weight = original.input.iter()
+        .map(|i| i.segwit_weight())
+        .max()
+        .unwrap_or(Weight::ZERO);
+    let mut weight = original.weight();
+    let mut outputs = original.output.clone();
+    let change = match (change, new_change) {
+        (Some(pos), _) if pos < outputs.len() => pos,
+        (None, Some(script)) => {
+            let output = TxOut { value: Amount::ZERO, script_pubkey: script };
+            weight += output.weight();
+            outputs.push(output);
+            outputs.len() - 1
+        }
+        _ => return Err(VaultNodeError::OtherInput("No change output to take the fee from".to_string()))
+    };
+    let others = checked_sum(outputs.iter().enumerate()
+        .filter(|(i, _)| *i != change)
+        .map(|(_, o)| o.value.to_sat()))?;
+    let dust = outputs[change].script_pubkey.minimal_non_dust().to_sat();
+
+    let mut extra = (0..utxos.len())
+        .filter(|pos| !inputs.iter().any(|(used, _)| used == pos))
+        .collect::<Vec<usize>>()
+        .into_iter();
+    loop {
+        let vsize = weight.to_vbytes_ceil();
+        let fee = fee_for(fee_rate, vsize).max(checked_sum([original_fee, INCREMENTAL_FEE_RATE * vsize])?);
+        let total_input = checked_sum(inputs.iter().map(|(pos, _)| utxos[*pos].amount))?;
+        if let Some(value) = total_input.checked_sub(checked_sum([others, fee])?)
+            && value >= dust {
+            outputs[change].value = Amount::from_sat(value);
+            return Ok(Replacement { inputs, outputs, fee, vsize });
+        }
+        match extra.next() {
+            Some(pos) => {
+                inputs.push((pos, Sequence::ENABLE_RBF_NO_LOCKTIME.0));
+                weight += input_weight;
+            }
+            None => return Err(VaultNodeError::OtherInput("Not enough funds to bump the fee".to_string()))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bitcoin::{Amount, OutPoint, ScriptBuf, Sequence, Transaction, TxIn, TxOut, Txid, Witness};
+    use bitcoin::absolute::LockTime;
+    use bitcoin::hashes::Hash;
+    use bitcoin::transaction::Version;
+    use crate::errors::VaultNodeError;
+    use crate::fee_bump::{build, checked_sum, fee_for, Utxo};
+
+    fn p2wpkh(n: u8) -> ScriptBuf {
+        ScriptBuf::new_p2wpkh(&bitcoin::WPubkeyHash::from_byte_array([n; 20]))
+    }
+
+    fn utxo(n: u8, amount: u64) -> Utxo {
+        Utxo {
+            output: OutPoint { txid: Txid::from_byte_array([n; 32]), vout: 0 },
+            amount,
+        }
+    }
+
+    /// Signed P2WPKH transaction with 1 input and 2 outputs (payment and change), which is 141 vbytes
+    fn original(sequence: Sequence, payment: u64, change: u64) -> Transaction {
+        let mut witness = Witness::new();
+        witness.push([1u8; 72]);
+        witness.push([2u8; 33]);
+        Transaction {
+            version: Version::TWO,
+            lock_time: LockTime::ZERO,
+            input: vec![TxIn {
+                previous_output: utxo(1, 0).output,
+                script_sig: ScriptBuf::new(),
+                sequence,
+                witness,
+            }],
+            output: vec![
+                TxOut { value: Amount::from_sat(payment), script_pubkey: p2wpkh(10) },
+                TxOut { value: Amount::from_sat(change), script_pubkey: p2wpkh(11) },
+            ],
+        }
+    }
+
+    #[test]
+    fn calculates_fee() {
+        assert_eq!(fee_for(1.0, 141), 141);
+        assert_eq!(fee_for(2.5, 141), 353);
+    }
+
+    #[test]
+    fn takes_fee_from_change() {
+        let tx = original(Sequence::ENABLE_RBF_NO_LOCKTIME, 50_000, 49_859);
+        assert_eq!(tx.weight().to_vbytes_ceil(), 141);
+        let act = build(&tx, &[utxo(1, 100_000)], Some(1), None, 10.0).unwrap();
+        assert_eq!(act.vsize, 141);
+        assert_eq!(act.fee, 1_410);
+        assert_eq!(act.inputs, vec![(0, 0xffff_fffd)]);
+        assert_eq!(act.outputs[0], tx.output[0]);
+        assert_eq!(act.outputs[1].value.to_sat(), 100_000 - 50_000 - 1_410);
+    }
+
+    #[test]
+    fn pays_at_least_incremental_fee() {
+        // original is 10 sat/vB, so the same rate is not enough to replace it
+        let tx = original(Sequence::ENABLE_RBF_NO_LOCKTIME, 50_000, 48_590);
+        let act = build(&tx, &[utxo(1, 100_000)], Some(1), None, 10.0).unwrap();
+        assert_eq!(act.fee, 1_410 + 141);
+    }
+
+    #[test]
+    fn adds_input_for_low_change() {
+        let tx = original(Sequence::ENABLE_RBF_NO_LOCKTIME, 99_000, 859);
+        let utxos = [utxo(5, 20_000), utxo(1, 100_000)];
+        let act = build(&tx, &utxos, Some(1), None, 20.0).unwrap();
+        // original input is kept first, with its sequence
+        assert_eq!(act.inputs, vec![(1, 0xffff_fffd), (0, 0xffff_fffd)]);
+        assert!(act.vsize > 141 + 60);
+        assert_eq!(act.fee, fee_for(20.0, act.vsize));
+        assert_eq!(act.outputs[1].value.to_sat(), 120_000 - 99_000 - act.fee);
+    }
+
+    #[test]
+    fn adds_change_output() {
+        let mut tx = original(Sequence::ENABLE_RBF_NO_LOCKTIME, 99_000, 0);
+        tx.output.pop();
+        let utxos = [utxo(1, 100_000), utxo(5, 20_000)];
+        assert!(build(&tx, &utxos, None, None, 20.0).is_err());
+        let act = build(&tx, &utxos, None, Some(p2wpkh(12)), 20.0).unwrap();
+        assert_eq!(act.inputs.len(), 2);
+        assert_eq!(act.outputs.len(), 2);
+        assert_eq!(act.outputs[1].script_pubkey, p2wpkh(12));
+        assert_eq!(act.outputs[1].value.to_sat(), 120_000 - 99_000 - act.fee);
+    }
+
+    #[test]
+    fn fails_without_funds() {
+        let tx = original(Sequence::ENABLE_RBF_NO_LOCKTIME, 99_000, 859);
+        assert!(build(&tx, &[utxo(1, 100_000)], Some(1), None, 20.0).is_err());
+    }
+
+    #[test]
+    fn fails_for_final_tx() {
+        let tx = original(Sequence::ENABLE_LOCKTIME_NO_RBF, 50_000, 49_859);
+        assert!(build(&tx, &[utxo(1, 100_000)], Some(1), None, 10.0).is_err());
+    }
+
+    #[test]
+    fn fails_without_utxo_details() {
+        let tx = original(Sequence::ENABLE_RBF_NO_LOCKTIME, 50_000, 49_859);
+        assert!(build(&tx, &[utxo(2, 100_000)], Some(1), None, 10.0).is_err());
+    }
+
+    #[test]
+    fn fails_on_overflow() {
+        let tx = original(Sequence::ENABLE_RBF_NO_LOCKTIME, 50_000, 49_859);
+        let act = build(&tx, &[utxo(1, 100_000)], Some(1), None, 1e30);
+        assert!(matches!(act, Err(VaultNodeError::InvalidArgumentValue(_))));
+        assert_eq!(checked_sum([u64::MAX, 1]).ok(), None);
+        assert_eq!(checked_sum([u64::MAX - 1, 1]).ok(), Some(u64::MAX));
+    }
+}
@@ -0,0 +1,115 @@
+//! Keeps the BIP-32 Master Key fingerprint of a seed.
+//!
+//! The fingerprint can be calculated only from the decrypted seed, so it's calculated when the seed is added or unlocked
+//! with a password, and kept in a separate file next to the seed to be shown without the password later.
+//! It's public information (ex. it's included in a key origin), so it's not encrypted.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use bitcoin::bip32::{Fingerprint, Xpriv};
+use bitcoin::secp256k1::Secp256k1;
+use bitcoin::Network;
+use uuid::Uuid;
+use crate::errors::VaultNodeError;
+
+pub(crate) const FILE_EXTENSION: &str = "fingerprint";
+
+/// BIP-32 Master Key fingerprint of the seed
+pub fn master_fingerprint(seed: &[u8]) -> Result<Fingerprint, VaultNodeError> {
+    let secp = Secp256k1::new();
+    // the network doesn't change the key, it's only for the serialization
+    Xpriv::new_master(Network::Bitcoin, seed)
+        .map(|master| master.fingerprint(&secp))
+        .map_err(|_| VaultNodeError::OtherProcessing("Invalid seed".to_string()))
+}
+
+pub struct FingerprintStorage {
+    dir: PathBuf,
+}
+
+impl FingerprintStorage {
+    pub fn new<P: AsRef<Path>>(dir: P) -> FingerprintStorage {
+        FingerprintStorage {
+            dir: dir.as_ref().to_path_buf()
+        }
+    }
+
+    fn path(&self, seed_id: Uuid) -> PathBuf {
+        self.dir.join(format!("{}.{}", seed_id, FILE_EXTENSION))
+    }
+
+    /// Returns the fingerprint as 8 hex chars, or `None` if it's not known yet
+    pub fn get(&self, seed_id: Uuid) -> Option<String> {
+        fs::read_to_string(self.path(seed_id)).ok()
+            .map(|value| value.trim().to_string())
+            .filter(|value| value.len() == 8 && hex::decode(value).is_ok())
+    }
+
+    /// Calculate and remember the fingerprint of the seed, if it's not known yet
+    pub fn remember(&self, seed_id: Uuid, seed: &[u8]) -> Result<String, VaultNodeError> {
+        if let Some(existing) = self.get(seed_id) {
+            return Ok(existing);
+        }
+        let value = master_fingerprint(seed)?.to_string();
+        // write to a temp file first and then move it, so there is never a partially written file
+        let target = self.path(seed_id);
+        let temp = target.with_extension(format!("{}.tmp", FILE_EXTENSION));
+        fs::write(&temp, value.as_str())
+            .and_then(|_| fs::rename(&temp, &target))
+            .map_err(|e| VaultNodeError::OtherProcessing(format!("Failed to write fingerprint. Error: {}", e)))?;
+        Ok(value)
+    }
+
+    pub fn remove(&self, seed_id: Uuid) -> Result<bool, VaultNodeError> {
+        let path = self.path(seed_id);
+        if !path.is_file() {
+            return Ok(false);
+        }
+        fs::remove_file(path)
+            .map(|_| true)
+            .map_err(|e| VaultNodeError::OtherProcessing(format!("Failed to remove fingerprint. Error: {}", e)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fingerprint::{master_fingerprint, FingerprintStorage};
+    use uuid::Uuid;
+
+    fn temp_storage() -> FingerprintStorage {
+        let dir = std::env::temp_dir().join(format!("emerald-fingerprint-{}", Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        FingerprintStorage::new(dir)
+    }
+
+    fn seed() -> Vec<u8> {
+        // "abandon abandon ... about"
+        hex::decode("5eb00bbddcf069084889a8ab9155568165f5c453ccb85e70811aaed6f6da5fc19a5ac40b389cd370d086206dec8aa6c43daea6690f20ad3d8d48b2d2ce9e38e4").unwrap()
+    }
+
+    #[test]
+    fn calculate_fingerprint() {
+        assert_eq!(master_fingerprint(&seed()).unwrap().to_string(), "73c5da0a");
+    }
+
+    #[test]
+    fn remember_and_get() {
+        let storage = temp_storage();
+        let id = Uuid::new_v4();
+        assert_eq!(storage.get(id), None);
+        assert_eq!(storage.remember(id, &seed()).unwrap(), "73c5da0a");
+        assert_eq!(storage.get(id), Some("73c5da0a".to_string()));
+    }
+
+    #[test]
+    fn remove_stored() {
+        let storage = temp_storage();
+        let id = Uuid::new_v4();
+        storage.remember(id, &seed()).unwrap();
+
+        assert!(storage.remove(id).unwrap());
+        assert_eq!(storage.get(id), None);
+        assert!(!storage.remove(id).unwrap());
+    }
+}
@@ -11,6 +11,12 @@ use emerald_vault::storage::default_path;
 use emerald_vault::storage::vault::VaultStorage;
 use emerald_vault::structs::wallet::{Wallet, WalletEntry};
 use crate::errors::{JsonError, VaultNodeError};
+use crate::mnemonic::MnemonicStorage;
+use crate::fingerprint::FingerprintStorage;
+use crate::multisig::MultisigStorage;
+use crate::policy::PolicyStorage;
+use crate::secret::SecretStorage;
+use crate::address_cache::{AddressCache, DEFAULT_ADDRESS_GAP};
 
 
 #[derive(Clone, Eq, PartialEq, Debug)]
@@ -18,6 +24,8 @@ pub struct VaultConfig {
   pub chain: Option<EthereumChainId>,
   pub dir: String,
   pub account_indexes: Vec<AccountIndex>,
+  /// Number of addresses after the current account index to search for an address of the entry
+  pub address_gap: u32,
 }
 
 
@@ -103,10 +111,19 @@ impl VaultConfig {
       None => None,
     };
 
+    let address_gap = match obj_get_number(cx, &config, "addressGap") {
+      Some(gap) => u32::try_from(gap)
+          .ok()
+          .filter(|v| *v > 0 && *v < 0x8fffffff)
+          .ok_or(VaultNodeError::JsonError(JsonError::InvalidValue("addressGap".to_string())))?,
+      None => DEFAULT_ADDRESS_GAP,
+    };
+
     return Ok(VaultConfig {
       chain,
       dir: dir.to_string(),
       account_indexes,
+      address_gap,
     });
   }
 
@@ -115,15 +132,37 @@ impl VaultConfig {
     let vault = VaultStorage::create(dir).expect("Vault is not created");
     vault
   }
+
+  pub fn get_mnemonic_storage(&self) -> MnemonicStorage {
+    MnemonicStorage::new(&self.dir)
+  }
+
+  pub fn get_fingerprint_storage(&self) -> FingerprintStorage {
+    FingerprintStorage::new(&self.dir)
+  }
+
+  pub fn get_multisig_storage(&self) -> MultisigStorage {
+    MultisigStorage::new(&self.dir)
+  }
+
+  pub fn get_policy_storage(&self) -> PolicyStorage {
+    PolicyStorage::new(&self.dir)
+  }
+
+  pub fn get_secret_storage(&self) -> SecretStorage {
+    SecretStorage::new(&self.dir)
+  }
 }
 
 pub struct WrappedVault {
   pub cfg: VaultConfig,
+  /// Derived addresses of the Bitcoin entries, to find an HD path of an address
+  pub address_cache: Mutex<AddressCache>,
 }
 
 impl WrappedVault {
   pub fn new(cfg: VaultConfig) -> WrappedVault {
-    WrappedVault { cfg }
+    WrappedVault { cfg, address_cache: Mutex::new(AddressCache::default()) }
   }
 
   pub fn load_wallets(&self) -> Result<Vec<Wallet>, VaultNodeError> {
@@ -17,6 +17,9 @@ extern crate num_bigint;
 extern crate neon_frame_macro;
 extern crate base64;
 extern crate lazy_static;
+extern crate bip39;
+extern crate rand;
+extern crate rlp;
 
 use neon::prelude::*;
 
@@ -34,6 +37,27 @@ mod snapshot;
 mod icons;
 mod watch;
 mod instance;
+mod mnemonic;
+mod slip39;
+mod path_template;
+mod xpub;
+mod taproot;
+mod fingerprint;
+mod entropy;
+mod bip85;
+mod psbt;
+mod bitcoin_message;
+mod tx_decode;
+mod multisig;
+mod fee_bump;
+mod tx_size;
+mod coin_select;
+mod ethereum_tx;
+mod address_cache;
+mod tx_preview;
+mod policy;
+mod secret;
+mod sidecar;
 
 use env_logger::Builder;
 use chrono::Local;
@@ -79,6 +103,14 @@ fn main(mut cx: ModuleContext) -> NeonResult<()> {
         .expect("wallets_updateLabel not exported");
     cx.export_function("wallets_removeEntry", wallets::remove_entry)
         .expect("wallets_removeEntry not exported");
+    cx.export_function("wallets_addMultisig", wallets::add_multisig)
+        .expect("wallets_addMultisig not exported");
+    cx.export_function("wallets_listMultisig", wallets::list_multisig)
+        .expect("wallets_listMultisig not exported");
+    cx.export_function("wallets_removeMultisig", wallets::remove_multisig)
+        .expect("wallets_removeMultisig not exported");
+    cx.export_function("wallets_listMultisigAddresses", wallets::list_multisig_addresses)
+        .expect("wallets_listMultisigAddresses not exported");
 
     cx.export_function("entries_export", entries::export)
         .expect("entries_export not exported");
@@ -93,13 +125,37 @@ fn main(mut cx: ModuleContext) -> NeonResult<()> {
     .expect("entries_updateReceiveDisabled not exported");
     cx.export_function("entries_listAddresses", entries::list_addresses)
         .expect("entries_listAddresses not exported");
+    cx.export_function("entries_getPolicy", entries::get_policy)
+        .expect("entries_getPolicy not exported");
+    cx.export_function("entries_setPolicy", entries::set_policy)
+        .expect("entries_setPolicy not exported");
 
     cx.export_function("sign_tx", sign::sign_tx)
         .expect("sign_tx not exported");
+    cx.export_function("sign_txBatch", sign::sign_tx_batch)
+        .expect("sign_txBatch not exported");
+    cx.export_function("sign_preview", sign::sign_preview)
+        .expect("sign_preview not exported");
+    cx.export_function("sign_authorization", sign::sign_authorization)
+        .expect("sign_authorization not exported");
+    cx.export_function("sign_bumpFee", sign::sign_bump_fee)
+        .expect("sign_bumpFee not exported");
+    cx.export_function("bitcoin_buildTx", sign::build_bitcoin_tx)
+        .expect("bitcoin_buildTx not exported");
+    cx.export_function("bitcoin_estimateTx", sign::estimate_bitcoin_tx)
+        .expect("bitcoin_estimateTx not exported");
+    cx.export_function("sign_psbt", sign::sign_psbt)
+        .expect("sign_psbt not exported");
+    cx.export_function("sign_multisigTx", sign::sign_multisig_tx)
+        .expect("sign_multisigTx not exported");
+    cx.export_function("sign_multisigPsbt", sign::sign_multisig_psbt)
+        .expect("sign_multisigPsbt not exported");
     cx.export_function("sign_message", sign::sign_message)
         .expect("sign_message not exported");
     cx.export_function("sign_signature_author", sign::signature_author)
         .expect("signature_author not exported");
+    cx.export_function("tx_decode", sign::decode_tx)
+        .expect("tx_decode not exported");
 
     cx.export_function("addrbook_list", addressbook::list)
         .expect("addrbook_list not exported");
@@ -108,6 +164,8 @@ fn main(mut cx: ModuleContext) -> NeonResult<()> {
 
     cx.export_function("seed_generateMnemonic", seeds::generate_mnemonic)
         .expect("seed_generateMnemonic not exported");
+    cx.export_function("seed_generateMnemonicFromEntropy", seeds::generate_mnemonic_from_entropy)
+        .expect("seed_generateMnemonicFromEntropy not exported");
     cx.export_function("seed_add", seeds::add)
         .expect("seed_add not exported");
     cx.export_function("seed_update", seeds::update)
@@ -116,6 +174,18 @@ fn main(mut cx: ModuleContext) -> NeonResult<()> {
         .expect("seed_list not exported");
     cx.export_function("seed_isAvailable", seeds::is_available)
         .expect("seed_isAvailable not exported");
+    cx.export_function("seed_remove", seeds::remove)
+        .expect("seed_remove not exported");
+    cx.export_function("seed_revealMnemonic", seeds::reveal_mnemonic)
+        .expect("seed_revealMnemonic not exported");
+    cx.export_function("seed_changePassword", seeds::change_password)
+        .expect("seed_changePassword not exported");
+    cx.export_function("seed_splitShares", seeds::split_shares)
+        .expect("seed_splitShares not exported");
+    cx.export_function("seed_exportAccountXpub", seeds::export_account_xpub)
+        .expect("seed_exportAccountXpub not exported");
+    cx.export_function("seed_deriveBip85", seeds::derive_bip85)
+        .expect("seed_deriveBip85 not exported");
     cx.export_function("seed_listAddresses", seeds::list_addresses)
         .expect("seed_listAddresses not exported");
 
@@ -0,0 +1,47 @@
+//! Keeps the BIP-39 entropy of a seed, so the recovery phrase can be shown again for a backup.
+//!
+//! The Vault itself stores only the seed bytes produced from the mnemonic, which cannot be converted back to the phrase.
+//! The entropy is encrypted by the Vault in the same way as the seed, i.e., with the Global Key or the individual password of
+//! the seed, and it's kept as a secret with the id of the seed (see `secret.rs`).
+
+use std::path::Path;
+
+use emerald_vault::structs::crypto::GlobalKey;
+use uuid::Uuid;
+use crate::errors::VaultNodeError;
+use crate::secret::{SecretKind, SecretStorage};
+
+pub struct MnemonicStorage {
+    secrets: SecretStorage,
+}
+
+impl MnemonicStorage {
+    pub fn new<P: AsRef<Path>>(dir: P) -> MnemonicStorage {
+        MnemonicStorage {
+            secrets: SecretStorage::new(dir)
+        }
+    }
+
+    pub fn exists(&self, seed_id: Uuid) -> bool {
+        self.secrets.exists(SecretKind::Mnemonic, seed_id)
+    }
+
+    /// Encrypt and keep the entropy, with the same `password` and `global` key as the seed
+    pub fn save(&self, seed_id: Uuid, entropy: &[u8], password: &str, global: Option<GlobalKey>) -> Result<(), VaultNodeError> {
+        // BIP-39 entropy is 128..256 bits
+        if entropy.len() < 16 || entropy.len() > 32 {
+            return Err(VaultNodeError::InvalidArgumentValue("Invalid entropy length".to_string()));
+        }
+        self.secrets.save(SecretKind::Mnemonic, seed_id, entropy, password, global)
+    }
+
+    /// Returns the original entropy, or `None` if it was never stored for the seed. It's decrypted with the same `password`
+    /// and `global` key as the seed.
+    pub fn load(&self, seed_id: Uuid, password: &str, global: Option<GlobalKey>) -> Result<Option<Vec<u8>>, VaultNodeError> {
+        self.secrets.load(SecretKind::Mnemonic, seed_id, password, global)
+    }
+
+    pub fn remove(&self, seed_id: Uuid) -> Result<bool, VaultNodeError> {
+        self.secrets.remove(SecretKind::Mnemonic, seed_id)
+    }
+}
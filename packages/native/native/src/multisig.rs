@@ -0,0 +1,636 @@
+//! Multisig (`sortedmulti`) P2WSH and P2SH-P2WSH setups, where this vault is one of the cosigners.
+//!
+//! Vault entries are always single-key, so a setup is kept in a separate `{id}.multisig` file next to the wallet. It contains
+//! only public information (cosigner xpubs with their key origins) and a reference to the local seed, which is used to add
+//! this vault's signatures. The setups are listed with the wallet, but the file is not a part of the Vault storage, so it's
+//! not included into a Vault snapshot and has to be backed up with the cosigners' descriptors. Other cosigners sign the same PSBT independently and any of them can finalize it.
+//!
+//! Keys are ordered as defined by BIP-67, i.e. by the serialized compressed public key, so all cosigners get the same script.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use bitcoin::absolute::LockTime;
+use bitcoin::bip32::{ChildNumber, DerivationPath, Fingerprint, Xpub};
+use bitcoin::blockdata::opcodes::all::OP_CHECKMULTISIG;
+use bitcoin::psbt::{Input, Psbt};
+use bitcoin::secp256k1::{Secp256k1, Verification};
+use bitcoin::script::Builder;
+use bitcoin::transaction::Version;
+use bitcoin::{Address, Amount, CompressedPublicKey, NetworkKind, OutPoint, ScriptBuf, Sequence, Transaction, TxIn, TxOut, Witness};
+use uuid::Uuid;
+use crate::bitcoin_message::known_hrp;
+use crate::errors::VaultNodeError;
+use crate::xpub;
+
+pub(crate) const FILE_EXTENSION: &str = "multisig";
+const STORAGE_VERSION: u32 = 1;
+/// Limit of keys for a standard CHECKMULTISIG
+const MAX_COSIGNERS: usize = 15;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Eq, PartialEq)]
+pub enum MultisigScript {
+    #[serde(rename = "p2wsh")]
+    P2wsh,
+    #[serde(rename = "p2sh-p2wsh")]
+    P2shP2wsh,
+}
+
+impl MultisigScript {
+    /// Script type of the BIP-48 account path, i.e. `m/48'/coin'/account'/type'`
+    pub fn bip48_script_type(&self) -> u32 {
+        match self {
+            MultisigScript::P2shP2wsh => 1,
+            MultisigScript::P2wsh => 2,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq)]
+pub struct CosignerJson {
+    /// Account Extended Public Key, in any of the SLIP-132 formats
+    pub xpub: String,
+    /// Master Key fingerprint, as 8 hex chars
+    pub fingerprint: String,
+    /// Account path, ex. `m/48'/0'/0'/2'`
+    pub path: String,
+    /// Seed of this vault, set only for the local cosigner
+    #[serde(rename = "seedId")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub seed_id: Option<Uuid>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq)]
+pub struct MultisigSetupJson {
+    pub id: Uuid,
+    #[serde(rename = "walletId")]
+    pub wallet_id: Uuid,
+    pub blockchain: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    pub script: MultisigScript,
+    pub threshold: usize,
+    pub cosigners: Vec<CosignerJson>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+struct StoredMultisigJson {
+    version: u32,
+    #[serde(flatten)]
+    setup: MultisigSetupJson,
+}
+
+#[derive(Serialize, Clone, Debug, Eq, PartialEq)]
+pub struct MultisigJson {
+    #[serde(flatten)]
+    pub setup: MultisigSetupJson,
+    /// Output descriptor for the receive addresses
+    pub descriptor: String,
+    /// Output descriptor for the change addresses
+    #[serde(rename = "changeDescriptor")]
+    pub change_descriptor: String,
+}
+
+/// Input of a multisig transaction, as a UTXO of the setup address at `change/index`
+pub struct MultisigInput {
+    pub output: OutPoint,
+    pub amount: u64,
+    pub sequence: u32,
+    pub change: u32,
+    pub index: u32,
+}
+
+struct Cosigner {
+    xpub: Xpub,
+    fingerprint: Fingerprint,
+    path: DerivationPath,
+}
+
+/// A validated multisig setup
+pub struct Multisig {
+    pub setup: MultisigSetupJson,
+    network: NetworkKind,
+    cosigners: Vec<Cosigner>,
+    local: usize,
+}
+
+/// `OP_m <keys> OP_n OP_CHECKMULTISIG` with the keys sorted as defined by BIP-67
+pub fn sorted_multisig_script(threshold: usize, keys: &[CompressedPublicKey]) -> ScriptBuf {
+    let mut keys = keys.to_vec();
+    keys.sort_by_key(|key| key.to_bytes());
+    let mut builder = Builder::new().push_int(threshold as i64);
+    for key in keys.iter() {
+        builder = builder.push_slice(key.to_bytes());
+    }
+    builder.push_int(keys.len() as i64)
+        .push_opcode(OP_CHECKMULTISIG)
+        .into_script()
+}
+
+fn parse_path(value: &str) -> Result<DerivationPath, VaultNodeError> {
+    DerivationPath::from_str(value)
+        .map_err(|_| VaultNodeError::InvalidArgumentValue(format!("Invalid cosigner path: {}", value)))
+}
+
+fn child(change: u32, index: u32) -> Result<[ChildNumber; 2], VaultNodeError> {
+    let invalid = || VaultNodeError::InvalidArgumentValue("Invalid address index".to_string());
+    Ok([
+        ChildNumber::from_normal_idx(change).map_err(|_| invalid())?,
+        ChildNumber::from_normal_idx(index).map_err(|_| invalid())?,
+    ])
+}
+
+impl Multisig {
+    pub fn parse(setup: MultisigSetupJson, network: NetworkKind) -> Result<Multisig, VaultNodeError> {
+        let count = setup.cosigners.len();
+        if !(2..=MAX_COSIGNERS).contains(&count) {
+            return Err(VaultNodeError::InvalidArgumentValue(format!("Multisig must have from 2 to {} cosigners", MAX_COSIGNERS)));
+        }
+        if !(1..=count).contains(&setup.threshold) {
+            return Err(VaultNodeError::InvalidArgumentValue(format!("Threshold must be from 1 to {}", count)));
+        }
+        let mut cosigners: Vec<Cosigner> = Vec::with_capacity(count);
+        for cosigner in setup.cosigners.iter() {
+            let key = xpub::decode_any(cosigner.xpub.as_str())?;
+            if key.network != network {
+                return Err(VaultNodeError::InvalidArgumentValue(format!("Cosigner key is for another network: {}", cosigner.xpub)));
+            }
+            let path = parse_path(cosigner.path.as_str())?;
+            if key.depth as usize != path.len() {
+                return Err(VaultNodeError::InvalidArgumentValue(format!("Cosigner key doesn't match the path: {}", cosigner.path)));
+            }
+            let fingerprint = Fingerprint::from_str(cosigner.fingerprint.as_str())
+                .map_err(|_| VaultNodeError::InvalidArgumentValue(format!("Invalid cosigner fingerprint: {}", cosigner.fingerprint)))?;
+            if cosigners.iter().any(|c| c.xpub.public_key == key.public_key) {
+                return Err(VaultNodeError::InvalidArgumentValue(format!("Duplicate cosigner: {}", cosigner.xpub)));
+            }
+            cosigners.push(Cosigner { xpub: key, fingerprint, path });
+        }
+        let local: Vec<usize> = setup.cosigners.iter().enumerate()
+            .filter(|(_, c)| c.seed_id.is_some())
+            .map(|(i, _)| i)
+            .collect();
+        if local.len() != 1 {
+            return Err(VaultNodeError::InvalidArgumentValue("Multisig must have exactly one cosigner of this vault".to_string()));
+        }
+        Ok(Multisig {
+            network,
+            cosigners,
+            local: local[0],
+            setup,
+        })
+    }
+
+    /// Seed of the local cosigner
+    pub fn seed_id(&self) -> Uuid {
+        self.setup.cosigners[self.local].seed_id.expect("local cosigner has a seed")
+    }
+
+    /// Fingerprint of the local cosigner Master Key
+    pub fn fingerprint(&self) -> Fingerprint {
+        self.cosigners[self.local].fingerprint
+    }
+
+    /// Account path of the local cosigner
+    pub fn local_path(&self) -> &DerivationPath {
+        &self.cosigners[self.local].path
+    }
+
+    pub fn network(&self) -> NetworkKind {
+        self.network
+    }
+
+    /// `(change, index)` of a full path of the local cosigner, or `None` if it's not on its account
+    pub fn local_child(&self, path: &DerivationPath) -> Option<(u32, u32)> {
+        let account = self.local_path();
+        match path.as_ref().split_at_checked(account.len()) {
+            Some((prefix, [ChildNumber::Normal { index: change }, ChildNumber::Normal { index }])) if prefix == account.as_ref() =>
+                Some((*change, *index)),
+            _ => None
+        }
+    }
+
+    fn derive<C: Verification>(&self, secp: &Secp256k1<C>, cosigner: &Cosigner, change: u32, index: u32) -> Result<CompressedPublicKey, VaultNodeError> {
+        cosigner.xpub.derive_pub(secp, &child(change, index)?)
+            .map(|key| CompressedPublicKey(key.public_key))
+            .map_err(|_| VaultNodeError::OtherProcessing("Failed to derive key".to_string()))
+    }
+
+    pub fn witness_script(&self, change: u32, index: u32) -> Result<ScriptBuf, VaultNodeError> {
+        let secp = Secp256k1::verification_only();
+        let keys = self.cosigners.iter()
+            .map(|cosigner| self.derive(&secp, cosigner, change, index))
+            .collect::<Result<Vec<CompressedPublicKey>, VaultNodeError>>()?;
+        Ok(sorted_multisig_script(self.setup.threshold, &keys))
+    }
+
+    pub fn address(&self, change: u32, index: u32) -> Result<Address, VaultNodeError> {
+        let witness_script = self.witness_script(change, index)?;
+        let address = match self.setup.script {
+            MultisigScript::P2wsh => Address::p2wsh(&witness_script, known_hrp(self.network)),
+            MultisigScript::P2shP2wsh => Address::p2shwsh(&witness_script, self.network),
+        };
+        Ok(address)
+    }
+
+    pub fn addresses(&self, change: u32, start: u32, limit: u32) -> Result<Vec<(u32, Address)>, VaultNodeError> {
+        let mut result = Vec::with_capacity(limit as usize);
+        for index in start..start.saturating_add(limit) {
+            result.push((index, self.address(change, index)?));
+        }
+        Ok(result)
+    }
+
+    pub fn descriptor(&self, chain: u32) -> String {
+        let keys = self.cosigners.iter()
+            .map(|c| format!("[{}/{}]{}/{}/*", c.fingerprint, xpub::path_string(&c.path, "h"), c.xpub, chain))
+            .collect::<Vec<String>>()
+            .join(",");
+        let multi = format!("sortedmulti({},{})", self.setup.threshold, keys);
+        let descriptor = match self.setup.script {
+            MultisigScript::P2wsh => format!("wsh({})", multi),
+            MultisigScript::P2shP2wsh => format!("sh(wsh({}))", multi),
+        };
+        xpub::with_checksum(descriptor)
+    }
+
+    pub fn to_json(&self) -> MultisigJson {
+        MultisigJson {
+            setup: self.setup.clone(),
+            descriptor: self.descriptor(0),
+            change_descriptor: self.descriptor(1),
+        }
+    }
+
+    /// Set the scripts and the key origins of all cosigners for the address at `change/index`
+    fn fill_input(&self, input: &mut Input, change: u32, index: u32) -> Result<(), VaultNodeError> {
+        let secp = Secp256k1::verification_only();
+        let witness_script = self.witness_script(change, index)?;
+        if self.setup.script == MultisigScript::P2shP2wsh {
+            input.redeem_script = Some(ScriptBuf::new_p2wsh(&witness_script.wscript_hash()));
+        }
+        input.witness_script = Some(witness_script);
+        for cosigner in self.cosigners.iter() {
+            let key = self.derive(&secp, cosigner, change, index)?;
+            let path = cosigner.path.extend(child(change, index)?);
+            input.bip32_derivation.insert(key.0, (cosigner.fingerprint, path));
+        }
+        Ok(())
+    }
+
+    /// Create a PSBT spending the multisig UTXOs. The outputs must already include the change,
+    /// so the difference between the inputs and outputs must be exactly the `expected_fee`
+    pub fn create_psbt(&self, inputs: &[MultisigInput], outputs: Vec<TxOut>, expected_fee: u64) -> Result<Psbt, VaultNodeError> {
+        let total_input: u64 = inputs.iter().map(|i| i.amount).sum();
+        let total_output: u64 = outputs.iter().map(|o| o.value.to_sat()).sum();
+        if total_input.checked_sub(total_output) != Some(expected_fee) {
+            return Err(VaultNodeError::OtherInput(format!("Invalid tx: fee is {} but expected {}", total_input as i128 - total_output as i128, expected_fee)));
+        }
+        let tx = Transaction {
+            version: Version::TWO,
+            lock_time: LockTime::ZERO,
+            input: inputs.iter()
+                .map(|i| TxIn {
+                    previous_output: i.output,
+                    script_sig: ScriptBuf::new(),
+                    sequence: Sequence(i.sequence),
+                    witness: Witness::new(),
+                })
+                .collect(),
+            output: outputs,
+        };
+        let mut psbt = Psbt::from_unsigned_tx(tx)
+            .map_err(|e| VaultNodeError::OtherProcessing(format!("Failed to create PSBT: {}", e)))?;
+        for (i, input) in inputs.iter().enumerate() {
+            let target = &mut psbt.inputs[i];
+            target.witness_utxo = Some(TxOut {
+                value: Amount::from_sat(input.amount),
+                script_pubkey: self.address(input.change, input.index)?.script_pubkey(),
+            });
+            self.fill_input(target, input.change, input.index)?;
+        }
+        Ok(psbt)
+    }
+
+    /// Check the inputs of a PSBT made by another cosigner, which have a key of this vault, and add the scripts if they are missing.
+    /// Fails if an input doesn't belong to the setup, because a key of the same seed may be used for something else.
+    pub fn prepare_psbt(&self, psbt: &mut Psbt) -> Result<(), VaultNodeError> {
+        for i in 0..psbt.inputs.len() {
+            let source = psbt.inputs[i].bip32_derivation.values()
+                .find(|(fp, _)| *fp == self.fingerprint())
+                .map(|(_, path)| path.clone());
+            let path = match source {
+                Some(path) => path,
+                None => continue
+            };
+            let (change, index) = self.local_child(&path)
+                .ok_or(VaultNodeError::OtherInput(format!("Input {} is not on the multisig account", i)))?;
+            let expected = self.address(change, index)?.script_pubkey();
+            let actual = psbt.spend_utxo(i)
+                .map(|utxo| utxo.script_pubkey.clone())
+                .map_err(|_| VaultNodeError::OtherInput(format!("No UTXO details for input {}", i)))?;
+            if actual != expected {
+                return Err(VaultNodeError::OtherInput(format!("Input {} is not an address of the multisig", i)));
+            }
+            self.fill_input(&mut psbt.inputs[i], change, index)?;
+        }
+        Ok(())
+    }
+}
+
+pub struct MultisigStorage {
+    dir: PathBuf,
+}
+
+impl MultisigStorage {
+    pub fn new<P: AsRef<Path>>(dir: P) -> MultisigStorage {
+        MultisigStorage {
+            dir: dir.as_ref().to_path_buf()
+        }
+    }
+
+    fn path(&self, id: Uuid) -> PathBuf {
+        self.dir.join(format!("{}.{}", id, FILE_EXTENSION))
+    }
+
+    pub fn save(&self, setup: &MultisigSetupJson) -> Result<(), VaultNodeError> {
+        let value = StoredMultisigJson {
+            version: STORAGE_VERSION,
+            setup: setup.clone(),
+        };
+        let json = serde_json::to_string_pretty(&value)
+            .map_err(|e| VaultNodeError::OtherProcessing(format!("Failed to encode multisig. Error: {}", e)))?;
+        // write to a temp file first and then move it, so there is never a partially written file
+        let target = self.path(setup.id);
+        let temp = target.with_extension(format!("{}.tmp", FILE_EXTENSION));
+        fs::write(&temp, json)
+            .and_then(|_| fs::rename(&temp, &target))
+            .map_err(|e| VaultNodeError::OtherProcessing(format!("Failed to write multisig. Error: {}", e)))
+    }
+
+    pub fn get(&self, id: Uuid) -> Result<MultisigSetupJson, VaultNodeError> {
+        let json = fs::read_to_string(self.path(id))
+            .map_err(|_| VaultNodeError::MissingData(format!("Multisig {} not found", id)))?;
+        serde_json::from_str::<StoredMultisigJson>(json.as_str())
+            .map(|stored| stored.setup)
+            .map_err(|e| VaultNodeError::OtherProcessing(format!("Invalid multisig file. Error: {}", e)))
+    }
+
+    pub fn list(&self) -> Result<Vec<MultisigSetupJson>, VaultNodeError> {
+        let files = fs::read_dir(&self.dir)
+            .map_err(|e| VaultNodeError::OtherProcessing(format!("Failed to read directory. Error: {}", e)))?;
+        let mut result: Vec<MultisigSetupJson> = files
+            .filter_map(|file| file.ok())
+            .map(|file| file.path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == FILE_EXTENSION))
+            .filter_map(|path| path.file_stem().and_then(|name| name.to_str()).and_then(|name| Uuid::parse_str(name).ok()))
+            .filter_map(|id| self.get(id).ok())
+            .collect();
+        result.sort_by_key(|setup| setup.id);
+        Ok(result)
+    }
+
+    pub fn list_for_wallet(&self, wallet_id: Uuid) -> Result<Vec<MultisigSetupJson>, VaultNodeError> {
+        self.list().map(|all| all.into_iter().filter(|setup| setup.wallet_id == wallet_id).collect())
+    }
+
+    pub fn remove(&self, id: Uuid) -> Result<bool, VaultNodeError> {
+        let path = self.path(id);
+        if !path.is_file() {
+            return Ok(false);
+        }
+        fs::remove_file(path)
+            .map(|_| true)
+            .map_err(|e| VaultNodeError::OtherProcessing(format!("Failed to remove multisig. Error: {}", e)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+    use bitcoin::{Address, Amount, EcdsaSighashType, Network, NetworkKind, OutPoint, TxOut, Txid};
+    use bitcoin::bip32::{DerivationPath, Xpriv, Xpub};
+    use bitcoin::hashes::Hash;
+    use bitcoin::secp256k1::{Message, Secp256k1};
+    use bitcoin::sighash::SighashCache;
+    use bitcoin::CompressedPublicKey;
+    use bitcoin::ScriptBuf;
+    use uuid::Uuid;
+    use crate::multisig::{sorted_multisig_script, CosignerJson, Multisig, MultisigInput, MultisigScript, MultisigSetupJson, MultisigStorage};
+    use crate::psbt::{extract, finalize, sign, AccountSigner};
+
+    const PATH: &str = "m/48'/0'/0'/2'";
+
+    fn seeds() -> Vec<Vec<u8>> {
+        vec![vec![1u8; 64], vec![2u8; 64], vec![3u8; 64]]
+    }
+
+    fn cosigner(seed: &[u8], local: bool) -> CosignerJson {
+        let secp = Secp256k1::new();
+        let master = Xpriv::new_master(Network::Bitcoin, seed).unwrap();
+        let account = master.derive_priv(&secp, &DerivationPath::from_str(PATH).unwrap()).unwrap();
+        CosignerJson {
+            xpub: Xpub::from_priv(&secp, &account).to_string(),
+            fingerprint: master.fingerprint(&secp).to_string(),
+            path: PATH.to_string(),
+            seed_id: if local { Some(Uuid::new_v4()) } else { None },
+        }
+    }
+
+    fn setup(script: MultisigScript) -> MultisigSetupJson {
+        MultisigSetupJson {
+            id: Uuid::new_v4(),
+            wallet_id: Uuid::new_v4(),
+            blockchain: 1,
+            label: None,
+            script,
+            threshold: 2,
+            cosigners: seeds().iter().enumerate().map(|(i, seed)| cosigner(seed, i == 0)).collect(),
+        }
+    }
+
+    #[test]
+    fn sorts_keys_as_bip67() {
+        let keys = vec![
+            CompressedPublicKey::from_str("02ff12471208c14bd580709cb2358d98975247d8765f92bc25eab3b2763ed605f8").unwrap(),
+            CompressedPublicKey::from_str("02fe6f0a5a297eb38c391581c4413e084773ea23954d93f7753db7dc0adc188b2f").unwrap(),
+        ];
+        let script = sorted_multisig_script(2, &keys);
+        assert_eq!(Address::p2sh(&script, Network::Bitcoin).unwrap().to_string(), "39bgKC7RFbpoCRbtD5KEdkYKtNyhpsNa3Z");
+        let reversed: Vec<CompressedPublicKey> = keys.into_iter().rev().collect();
+        assert_eq!(sorted_multisig_script(2, &reversed), script);
+    }
+
+    #[test]
+    fn derives_addresses() {
+        let multisig = Multisig::parse(setup(MultisigScript::P2wsh), NetworkKind::Main).unwrap();
+        let receive = multisig.address(0, 0).unwrap();
+        assert!(receive.to_string().starts_with("bc1q"));
+        assert_eq!(receive.to_string().len(), 62);
+        assert_ne!(multisig.address(1, 0).unwrap(), receive);
+        assert_eq!(multisig.addresses(1, 4, 2).unwrap(), vec![(4, multisig.address(1, 4).unwrap()), (5, multisig.address(1, 5).unwrap())]);
+
+        let nested = Multisig::parse(MultisigSetupJson { script: MultisigScript::P2shP2wsh, ..multisig.setup.clone() }, NetworkKind::Main).unwrap();
+        assert!(nested.address(0, 0).unwrap().to_string().starts_with('3'));
+        assert_eq!(
+            nested.address(0, 0).unwrap().script_pubkey(),
+            ScriptBuf::new_p2sh(&ScriptBuf::new_p2wsh(&multisig.witness_script(0, 0).unwrap().wscript_hash()).script_hash())
+        );
+    }
+
+    #[test]
+    fn same_address_for_any_cosigner_order() {
+        let first = setup(MultisigScript::P2wsh);
+        let mut second = first.clone();
+        second.cosigners.reverse();
+        assert_eq!(
+            Multisig::parse(first, NetworkKind::Main).unwrap().address(0, 3).unwrap(),
+            Multisig::parse(second, NetworkKind::Main).unwrap().address(0, 3).unwrap()
+        );
+    }
+
+    #[test]
+    fn creates_descriptor() {
+        let multisig = Multisig::parse(setup(MultisigScript::P2shP2wsh), NetworkKind::Main).unwrap();
+        let descriptor = multisig.descriptor(0);
+        assert!(descriptor.starts_with("sh(wsh(sortedmulti(2,["));
+        assert!(descriptor.contains("/48h/0h/0h/2h]xpub"));
+        assert_eq!(descriptor.matches("/0/*").count(), 3);
+        assert_eq!(descriptor.split('#').nth(1).unwrap().len(), 8);
+        assert!(multisig.descriptor(1).contains("/1/*,"));
+    }
+
+    #[test]
+    fn rejects_invalid_setup() {
+        let mut value = setup(MultisigScript::P2wsh);
+        value.threshold = 4;
+        assert!(Multisig::parse(value, NetworkKind::Main).is_err());
+
+        let mut value = setup(MultisigScript::P2wsh);
+        value.cosigners[1].seed_id = Some(Uuid::new_v4());
+        assert!(Multisig::parse(value, NetworkKind::Main).is_err());
+
+        let mut value = setup(MultisigScript::P2wsh);
+        value.cosigners[2] = value.cosigners[1].clone();
+        assert!(Multisig::parse(value, NetworkKind::Main).is_err());
+
+        let mut value = setup(MultisigScript::P2wsh);
+        value.cosigners[1].path = "m/48'/0'/0'".to_string();
+        assert!(Multisig::parse(value, NetworkKind::Main).is_err());
+
+        assert!(Multisig::parse(setup(MultisigScript::P2wsh), NetworkKind::Test).is_err());
+    }
+
+    fn spend(script: MultisigScript) {
+        let secp = Secp256k1::new();
+        let multisig = Multisig::parse(setup(script), NetworkKind::Main).unwrap();
+        let inputs = vec![MultisigInput {
+            output: OutPoint { txid: Txid::from_byte_array([7; 32]), vout: 1 },
+            amount: 100_000,
+            sequence: 0xffff_fffd,
+            change: 0,
+            index: 2,
+        }];
+        let outputs = vec![TxOut {
+            value: Amount::from_sat(99_000),
+            script_pubkey: multisig.address(1, 0).unwrap().script_pubkey(),
+        }];
+        assert!(multisig.create_psbt(&inputs, outputs.clone(), 500).is_err());
+        let mut psbt = multisig.create_psbt(&inputs, outputs, 1_000).unwrap();
+        assert_eq!(psbt.inputs[0].bip32_derivation.len(), 3);
+
+        let path = DerivationPath::from_str(PATH).unwrap();
+        let first = AccountSigner::new(&seeds()[0], path.clone(), NetworkKind::Main).unwrap();
+        assert_eq!(first.fingerprint(), multisig.fingerprint());
+        assert_eq!(sign(&mut psbt, &first, &secp).unwrap(), vec![0]);
+        // one of two signatures is not enough
+        assert!(!finalize(&mut psbt));
+
+        // the other cosigner gets a PSBT without scripts, and they are restored from the setup
+        let mut other = psbt.clone();
+        other.inputs[0].witness_script = None;
+        other.inputs[0].redeem_script = None;
+        multisig.prepare_psbt(&mut other).unwrap();
+        assert_eq!(other, psbt);
+
+        let third = AccountSigner::new(&seeds()[2], path, NetworkKind::Main).unwrap();
+        assert_eq!(sign(&mut psbt, &third, &secp).unwrap(), vec![0]);
+        assert!(finalize(&mut psbt));
+
+        let witness_script = multisig.witness_script(0, 2).unwrap();
+        let prevout = psbt.inputs[0].witness_utxo.clone().unwrap();
+        let tx = extract(psbt).unwrap();
+        let witness = tx.input[0].witness.to_vec();
+        assert_eq!(witness.len(), 4);
+        assert!(witness[0].is_empty());
+        assert_eq!(witness[3], witness_script.to_bytes());
+        let sighash = SighashCache::new(&tx)
+            .p2wsh_signature_hash(0, &witness_script, prevout.value, EcdsaSighashType::All)
+            .unwrap();
+        let msg = Message::from_digest(sighash.to_byte_array());
+        let keys: Vec<bitcoin::PublicKey> = witness_script.instructions()
+            .filter_map(|i| i.ok()?.push_bytes().and_then(|b| bitcoin::PublicKey::from_slice(b.as_bytes()).ok()))
+            .collect();
+        // each signature is made by a different key, in the order of the keys
+        let mut key_pos = 0;
+        for sig in witness[1..3].iter() {
+            let sig = bitcoin::ecdsa::Signature::from_slice(sig).unwrap();
+            while secp.verify_ecdsa(&msg, &sig.signature, &keys[key_pos].inner).is_err() {
+                key_pos += 1;
+                assert!(key_pos < keys.len());
+            }
+            key_pos += 1;
+        }
+        match script {
+            MultisigScript::P2wsh => assert!(tx.input[0].script_sig.is_empty()),
+            MultisigScript::P2shP2wsh => assert!(!tx.input[0].script_sig.is_empty()),
+        }
+    }
+
+    #[test]
+    fn spend_p2wsh() {
+        spend(MultisigScript::P2wsh);
+    }
+
+    #[test]
+    fn spend_p2sh_p2wsh() {
+        spend(MultisigScript::P2shP2wsh);
+    }
+
+    #[test]
+    fn rejects_psbt_of_other_account() {
+        let multisig = Multisig::parse(setup(MultisigScript::P2wsh), NetworkKind::Main).unwrap();
+        let inputs = vec![MultisigInput {
+            output: OutPoint { txid: Txid::from_byte_array([7; 32]), vout: 0 },
+            amount: 10_000,
+            sequence: 0xffff_fffd,
+            change: 0,
+            index: 0,
+        }];
+        let mut psbt = multisig.create_psbt(&inputs, vec![], 10_000).unwrap();
+        psbt.inputs[0].witness_utxo = Some(TxOut {
+            value: Amount::from_sat(10_000),
+            script_pubkey: multisig.address(0, 1).unwrap().script_pubkey(),
+        });
+        assert!(multisig.prepare_psbt(&mut psbt).is_err());
+    }
+
+    #[test]
+    fn save_and_list() {
+        let dir = std::env::temp_dir().join(format!("emerald-multisig-{}", Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let storage = MultisigStorage::new(dir);
+        let value = setup(MultisigScript::P2wsh);
+        storage.save(&value).unwrap();
+
+        assert_eq!(storage.get(value.id).unwrap(), value);
+        assert_eq!(storage.list().unwrap(), vec![value.clone()]);
+        assert_eq!(storage.list_for_wallet(value.wallet_id).unwrap().len(), 1);
+        assert!(storage.list_for_wallet(Uuid::new_v4()).unwrap().is_empty());
+
+        assert!(storage.remove(value.id).unwrap());
+        assert!(storage.get(value.id).is_err());
+        assert!(!storage.remove(value.id).unwrap());
+    }
+}
@@ -0,0 +1,131 @@
+//! Templates for HD Paths, which define a range of paths in one string. A range is set as `{from..to}` (inclusive) in
+//! place of an index, ex. `m/44'/60'/0'/0/{0..49}` or `m/84'/0'/{0..4}'`. A path may have multiple ranges.
+
+use crate::errors::VaultNodeError;
+
+/// Max number of paths produced by templates in one request
+pub const MAX_EXPANDED_PATHS: usize = 1000;
+
+enum Part {
+    Text(String),
+    Range(u32, u32),
+}
+
+fn parse(template: &str) -> Result<Vec<Part>, VaultNodeError> {
+    let invalid = || VaultNodeError::InvalidArgumentValue(format!("Invalid HD Path template: {}", template));
+    let mut parts = Vec::new();
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        if start > 0 {
+            parts.push(Part::Text(rest[..start].to_string()));
+        }
+        let end = rest[start..].find('}').ok_or_else(invalid)? + start;
+        let (from, to) = rest[start + 1..end].split_once("..").ok_or_else(invalid)?;
+        let from = from.trim().parse::<u32>().map_err(|_| invalid())?;
+        let to = to.trim().parse::<u32>().map_err(|_| invalid())?;
+        // the index must fit into 31 bits, because the top bit means a hardened index
+        if from > to || to >= 0x8000_0000 {
+            return Err(invalid());
+        }
+        parts.push(Part::Range(from, to));
+        rest = &rest[end + 1..];
+    }
+    if rest.contains('}') {
+        return Err(invalid());
+    }
+    if !rest.is_empty() {
+        parts.push(Part::Text(rest.to_string()));
+    }
+    Ok(parts)
+}
+
+fn expand_parts(parts: &[Part], prefix: String, limit: usize, result: &mut Vec<String>) -> Result<(), VaultNodeError> {
+    match parts.split_first() {
+        None => {
+            if result.len() >= limit {
+                return Err(VaultNodeError::InvalidArgumentValue(format!("Too many HD Paths, max is {}", limit)));
+            }
+            result.push(prefix);
+            Ok(())
+        }
+        Some((Part::Text(text), rest)) => expand_parts(rest, prefix + text, limit, result),
+        Some((Part::Range(from, to), rest)) => {
+            for i in *from..=*to {
+                expand_parts(rest, format!("{}{}", prefix, i), limit, result)?;
+            }
+            Ok(())
+        }
+    }
+}
+
+/// Expand the paths or templates into the list of paths, in the same order as provided and with ranges in increasing order.
+/// A duplicate path is kept only at its first position.
+pub fn expand(templates: &[String], limit: usize) -> Result<Vec<String>, VaultNodeError> {
+    let mut all = Vec::new();
+    for template in templates {
+        let parts = parse(template.as_str())?;
+        expand_parts(&parts, String::new(), limit, &mut all)?;
+    }
+    let mut seen = std::collections::HashSet::new();
+    all.retain(|p| seen.insert(p.clone()));
+    Ok(all)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::path_template::expand;
+
+    fn strings(values: &[&str]) -> Vec<String> {
+        values.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn keeps_plain_paths() {
+        let act = expand(&strings(&["m/44'/60'/0'/0/1", "m/44'/60'/0'/0/0"]), 100).unwrap();
+        assert_eq!(act, strings(&["m/44'/60'/0'/0/1", "m/44'/60'/0'/0/0"]));
+    }
+
+    #[test]
+    fn expands_address_range() {
+        let act = expand(&strings(&["m/44'/60'/0'/0/{0..3}"]), 100).unwrap();
+        assert_eq!(act, strings(&["m/44'/60'/0'/0/0", "m/44'/60'/0'/0/1", "m/44'/60'/0'/0/2", "m/44'/60'/0'/0/3"]));
+    }
+
+    #[test]
+    fn expands_hardened_range() {
+        let act = expand(&strings(&["m/84'/0'/{0..2}'"]), 100).unwrap();
+        assert_eq!(act, strings(&["m/84'/0'/0'", "m/84'/0'/1'", "m/84'/0'/2'"]));
+    }
+
+    #[test]
+    fn expands_multiple_ranges() {
+        let act = expand(&strings(&["m/44'/60'/{0..1}'/0/{5..6}", "m/44'/60'/7'/0/0"]), 100).unwrap();
+        assert_eq!(act, strings(&[
+            "m/44'/60'/0'/0/5", "m/44'/60'/0'/0/6", "m/44'/60'/1'/0/5", "m/44'/60'/1'/0/6", "m/44'/60'/7'/0/0"
+        ]));
+    }
+
+    #[test]
+    fn removes_duplicates() {
+        let act = expand(&strings(&["m/44'/60'/0'/0/{1..2}", "m/44'/60'/0'/0/{0..1}"]), 100).unwrap();
+        assert_eq!(act, strings(&["m/44'/60'/0'/0/1", "m/44'/60'/0'/0/2", "m/44'/60'/0'/0/0"]));
+    }
+
+    #[test]
+    fn fails_over_limit() {
+        assert!(expand(&strings(&["m/44'/60'/0'/0/{0..100}"]), 100).is_err());
+        assert!(expand(&strings(&["m/44'/60'/{0..9}'/0/{0..9}"]), 100).is_ok());
+        assert!(expand(&strings(&["m/44'/60'/{0..9}'/0/{0..9}", "m/44'/60'/0'/1/0"]), 100).is_err());
+        assert!(expand(&strings(&["m/44'/60'/{0..2147483646}'/0/0"]), 1000).is_err());
+    }
+
+    #[test]
+    fn fails_on_invalid_template() {
+        assert!(expand(&strings(&["m/44'/60'/0'/0/{0..}"]), 100).is_err());
+        assert!(expand(&strings(&["m/44'/60'/0'/0/{5..1}"]), 100).is_err());
+        assert!(expand(&strings(&["m/44'/60'/0'/0/{0..3"]), 100).is_err());
+        assert!(expand(&strings(&["m/44'/60'/0'/0/0..3}"]), 100).is_err());
+        assert!(expand(&strings(&["m/44'/60'/0'/0/{a..b}"]), 100).is_err());
+        assert!(expand(&strings(&["m/44'/60'/{0..2147483648}'/0/0"]), 100).is_err());
+    }
+}
@@ -0,0 +1,602 @@
+//! Spending policy of an entry, which the vault checks before signing a transaction, so it cannot be bypassed by the caller.
+//!
+//! A policy is kept in a separate `{walletId}-{entryId}.policy` file, together with the amounts the entry signed during the
+//! last 24 hours. Changing the rules requires the global password, while the spent amounts are updated after each signature.
+//! Amounts are in the base units of the entry blockchain, i.e. wei or satoshi, as decimal strings.
+//!
+//! The file is authenticated with HMAC-SHA256, with a key kept as a secret encrypted by the Global Key (see `secret.rs`). The
+//! secret has also a counter of the file updates, which is a part of the MAC, so a modified file, a file without the MAC or
+//! a previous copy of the file is never used for signing. The secret stays while the policy is set, so the entry is
+//! refused to sign if the file is removed.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use bitcoin::hashes::{sha256, Hash, HashEngine, Hmac, HmacEngine};
+use chrono::{DateTime, Duration, Utc};
+use num_bigint::BigUint;
+use uuid::Uuid;
+use crate::errors::{PolicyRule, VaultNodeError};
+use crate::tx_decode::EthereumTxFieldsJson;
+use crate::tx_preview;
+use crate::tx_preview::BitcoinPreviewJson;
+
+pub(crate) const FILE_EXTENSION: &str = "policy";
+const STORAGE_VERSION: u32 = 3;
+
+/// Key to authenticate a policy file, with the number of the file updates
+#[derive(Clone, Copy, Eq, PartialEq)]
+pub struct PolicyKey {
+    key: [u8; 32],
+    counter: u64,
+}
+
+impl PolicyKey {
+    pub fn new(key: [u8; 32]) -> PolicyKey {
+        PolicyKey { key, counter: 0 }
+    }
+
+    /// Same key for the next update of the file
+    pub fn next(&self) -> PolicyKey {
+        PolicyKey { key: self.key, counter: self.counter + 1 }
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut result = self.key.to_vec();
+        result.extend_from_slice(&self.counter.to_be_bytes());
+        result
+    }
+
+    pub fn from_bytes(value: &[u8]) -> Option<PolicyKey> {
+        let (key, counter) = value.split_first_chunk::<32>()?;
+        let counter: [u8; 8] = counter.try_into().ok()?;
+        Some(PolicyKey { key: *key, counter: u64::from_be_bytes(counter) })
+    }
+}
+
+impl std::fmt::Debug for PolicyKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PolicyKey")
+            .field("counter", &self.counter)
+            .finish_non_exhaustive()
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, Eq, PartialEq)]
+pub struct SpendingPolicyJson {
+    /// Max total of the values and the fees signed during the last 24 hours. Tokens moved by a contract call are not counted
+    #[serde(rename = "dailyLimit", default, skip_serializing_if = "Option::is_none")]
+    pub daily_limit: Option<String>,
+    /// Max fee of a transaction. For Ethereum it's the gas limit by the max gas price
+    #[serde(rename = "maxFee", default, skip_serializing_if = "Option::is_none")]
+    pub max_fee: Option<String>,
+    /// Max gas price of an Ethereum transaction, or the max fee per gas for EIP-1559
+    #[serde(rename = "maxGasPrice", default, skip_serializing_if = "Option::is_none")]
+    pub max_gas_price: Option<String>,
+    /// Only recipients allowed by the list, if it's set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub allowlist: Option<AllowlistJson>,
+    /// Selectors of the contract methods which cannot be called, as `0x` and 4 bytes of hex
+    #[serde(rename = "blockedMethods", default, skip_serializing_if = "Vec::is_empty")]
+    pub blocked_methods: Vec<String>,
+}
+
+/// Recipients are allowed only by the list kept in the policy, and not by the editable Address Book
+#[derive(Serialize, Deserialize, Clone, Debug, Default, Eq, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct AllowlistJson {
+    #[serde(default)]
+    pub addresses: Vec<String>,
+}
+
+/// What a transaction spends, as it's checked by the policy
+#[derive(Clone, Debug, Default)]
+pub struct Spending {
+    /// Addresses which get the value, the tokens or an allowance
+    pub recipients: Vec<String>,
+    /// Ethereum transaction deploys a contract, so there is no recipient to check
+    pub creates_contract: bool,
+    /// Value plus the fee
+    pub amount: BigUint,
+    pub fee: BigUint,
+    pub gas_price: Option<BigUint>,
+    /// Selector of the called method
+    pub method: Option<[u8; 4]>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq)]
+struct SpentJson {
+    timestamp: DateTime<Utc>,
+    amount: String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct StoredPolicyJson {
+    version: u32,
+    policy: SpendingPolicyJson,
+    #[serde(default)]
+    spent: Vec<SpentJson>,
+    /// Number of the file updates, same as in the key
+    #[serde(default)]
+    counter: u64,
+    /// HMAC of the policy, the spent amounts and the counter, as hex
+    #[serde(default)]
+    mac: String,
+}
+
+/// Policy of an entry with the amounts it spent recently, and the key to authenticate them
+#[derive(Clone, Eq, PartialEq)]
+pub struct EntryPolicy {
+    pub policy: SpendingPolicyJson,
+    spent: Vec<SpentJson>,
+    key: PolicyKey,
+}
+
+impl std::fmt::Debug for EntryPolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EntryPolicy")
+            .field("policy", &self.policy)
+            .field("spent", &self.spent)
+            .finish_non_exhaustive()
+    }
+}
+
+fn parse_amount(value: &str, name: &str) -> Result<BigUint, VaultNodeError> {
+    BigUint::from_str(value).map_err(|_| VaultNodeError::InvalidArgumentValue(format!("Invalid {}", name)))
+}
+
+fn parse_selector(value: &str) -> Option<[u8; 4]> {
+    let value = value.strip_prefix("0x").unwrap_or(value);
+    hex::decode(value).ok()?.try_into().ok()
+}
+
+/// Ethereum and bech32 addresses are case-insensitive, but Base58 ones are not
+fn normalize_address(value: &str) -> String {
+    let lower = value.to_lowercase();
+    if lower.starts_with("0x") || lower.starts_with("bc1") || lower.starts_with("tb1") || lower.starts_with("bcrt1") {
+        lower
+    } else {
+        value.to_string()
+    }
+}
+
+impl Spending {
+    /// Spending of an Ethereum transaction as it's signed, with the max fee it can pay
+    pub fn ethereum(fields: &EthereumTxFieldsJson) -> Result<Spending, VaultNodeError> {
+        let preview = tx_preview::ethereum(fields, String::new())?;
+        let mut recipients: Vec<String> = preview.to.iter().cloned().collect();
+        if let Some(recipient) = preview.recipient.filter(|r| preview.to.as_ref() != Some(r)) {
+            recipients.push(recipient);
+        }
+        let gas_price = if fields.is_dynamic_fee() { &fields.max_gas_price } else { &fields.gas_price };
+        let data = hex::decode(fields.data.strip_prefix("0x").unwrap_or(fields.data.as_str()))
+            .map_err(|_| VaultNodeError::InvalidArgumentValue("Invalid data".to_string()))?;
+        Ok(Spending {
+            recipients,
+            creates_contract: preview.to.is_none(),
+            amount: parse_amount(&preview.max_cost, "maxCost")?,
+            fee: parse_amount(&preview.max_fee, "maxFee")?,
+            gas_price: gas_price.as_ref().map(|v| parse_amount(v, "gasPrice")).transpose()?,
+            method: data.get(..4).filter(|_| preview.to.is_some()).and_then(|m| m.try_into().ok()),
+        })
+    }
+}
+
+impl From<&BitcoinPreviewJson> for Spending {
+    fn from(value: &BitcoinPreviewJson) -> Self {
+        Spending {
+            recipients: value.outputs.iter().filter(|o| !o.change).map(|o| o.address.clone()).collect(),
+            creates_contract: false,
+            amount: BigUint::from(value.sent) + value.fee,
+            fee: BigUint::from(value.fee),
+            gas_price: None,
+            method: None,
+        }
+    }
+}
+
+fn rejected(rule: PolicyRule, message: String) -> VaultNodeError {
+    VaultNodeError::PolicyRejected(rule, message)
+}
+
+/// Policy file doesn't match its MAC, or cannot be verified
+pub fn modified() -> VaultNodeError {
+    rejected(PolicyRule::Integrity, "Policy is modified outside of the Vault".to_string())
+}
+
+/// Compare without an early exit, so the time doesn't tell how much of the MAC is correct
+fn same_mac(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+impl SpendingPolicyJson {
+    /// Check the rules are valid before saving them
+    pub fn validate(&self) -> Result<(), VaultNodeError> {
+        if let Some(value) = &self.daily_limit {
+            parse_amount(value, "dailyLimit")?;
+        }
+        if let Some(value) = &self.max_fee {
+            parse_amount(value, "maxFee")?;
+        }
+        if let Some(value) = &self.max_gas_price {
+            parse_amount(value, "maxGasPrice")?;
+        }
+        if let Some(value) = self.blocked_methods.iter().find(|m| parse_selector(m).is_none()) {
+            return Err(VaultNodeError::InvalidArgumentValue(format!("Invalid method selector: {}", value)));
+        }
+        Ok(())
+    }
+}
+
+impl EntryPolicy {
+    pub fn new(policy: SpendingPolicyJson, key: PolicyKey) -> EntryPolicy {
+        EntryPolicy { policy, spent: vec![], key }
+    }
+
+    pub fn key(&self) -> &PolicyKey {
+        &self.key
+    }
+
+    /// Same policy authenticated with the `key`, ex. with the next counter to save it
+    pub fn with_key(self, key: PolicyKey) -> EntryPolicy {
+        EntryPolicy { key, ..self }
+    }
+
+    /// MAC of the policy for the entry, so it cannot be copied to another entry or replaced by its previous copy
+    fn mac(&self, wallet_id: Uuid, entry_id: usize) -> Result<[u8; 32], VaultNodeError> {
+        let content = serde_json::to_vec(&(wallet_id, entry_id, self.key.counter, &self.policy, &self.spent))
+            .map_err(|e| VaultNodeError::OtherProcessing(format!("Failed to encode policy. Error: {}", e)))?;
+        let mut engine = HmacEngine::<sha256::Hash>::new(&self.key.key);
+        engine.input(&content);
+        Ok(Hmac::<sha256::Hash>::from_engine(engine).to_byte_array())
+    }
+
+    /// Total amount spent during the 24 hours before `now`
+    pub fn spent(&self, now: DateTime<Utc>) -> BigUint {
+        self.spent.iter()
+            .filter(|s| s.timestamp > now - Duration::days(1))
+            .filter_map(|s| BigUint::from_str(s.amount.as_str()).ok())
+            .sum()
+    }
+
+    /// Check the spending is allowed by the rules
+    pub fn check(&self, spending: &Spending, now: DateTime<Utc>) -> Result<(), VaultNodeError> {
+        let policy = &self.policy;
+        if let Some(method) = spending.method
+            && policy.blocked_methods.iter().any(|m| parse_selector(m) == Some(method)) {
+            return Err(rejected(PolicyRule::Method, format!("Method 0x{} is blocked", hex::encode(method))));
+        }
+        if let Some(allowlist) = &policy.allowlist {
+            if spending.creates_contract {
+                return Err(rejected(PolicyRule::Recipient, "Contract creation is not allowed".to_string()));
+            }
+            let allowed = |recipient: &String| {
+                let recipient = normalize_address(recipient);
+                allowlist.addresses.iter().any(|a| normalize_address(a) == recipient)
+            };
+            if let Some(recipient) = spending.recipients.iter().find(|r| !allowed(r)) {
+                return Err(rejected(PolicyRule::Recipient, format!("Recipient {} is not allowed", recipient)));
+            }
+        }
+        if let (Some(limit), Some(gas_price)) = (&policy.max_gas_price, &spending.gas_price)
+            && *gas_price > parse_amount(limit, "maxGasPrice")? {
+            return Err(rejected(PolicyRule::Fee, format!("Gas price {} is above the limit {}", gas_price, limit)));
+        }
+        if let Some(limit) = &policy.max_fee
+            && spending.fee > parse_amount(limit, "maxFee")? {
+            return Err(rejected(PolicyRule::Fee, format!("Fee {} is above the limit {}", spending.fee, limit)));
+        }
+        if let Some(limit) = &policy.daily_limit {
+            let total = self.spent(now) + &spending.amount;
+            if total > parse_amount(limit, "dailyLimit")? {
+                return Err(rejected(PolicyRule::DailyLimit, format!("Daily limit {} is exceeded", limit)));
+            }
+        }
+        Ok(())
+    }
+
+    /// Remember the signed amount, and forget the amounts older than 24 hours
+    pub fn record(&mut self, amount: &BigUint, now: DateTime<Utc>) {
+        self.spent.retain(|s| s.timestamp > now - Duration::days(1));
+        self.spent.push(SpentJson { timestamp: now, amount: amount.to_string() });
+    }
+}
+
+pub struct PolicyStorage {
+    dir: PathBuf,
+}
+
+impl PolicyStorage {
+    pub fn new<P: AsRef<Path>>(dir: P) -> PolicyStorage {
+        PolicyStorage {
+            dir: dir.as_ref().to_path_buf()
+        }
+    }
+
+    fn path(&self, wallet_id: Uuid, entry_id: usize) -> PathBuf {
+        self.dir.join(format!("{}-{}.{}", wallet_id, entry_id, FILE_EXTENSION))
+    }
+
+    pub fn exists(&self, wallet_id: Uuid, entry_id: usize) -> bool {
+        self.path(wallet_id, entry_id).is_file()
+    }
+
+    fn read(&self, wallet_id: Uuid, entry_id: usize) -> Result<Option<StoredPolicyJson>, VaultNodeError> {
+        let path = self.path(wallet_id, entry_id);
+        if !path.is_file() {
+            return Ok(None);
+        }
+        let json = fs::read_to_string(path)
+            .map_err(|e| VaultNodeError::OtherProcessing(format!("Failed to read policy. Error: {}", e)))?;
+        // an unreadable policy must not allow signing without it
+        let stored = serde_json::from_str::<StoredPolicyJson>(json.as_str())
+            .map_err(|e| VaultNodeError::OtherProcessing(format!("Invalid policy file. Error: {}", e)))?;
+        Ok(Some(stored))
+    }
+
+    /// Rules of the policy to show them. They are not verified, so it must not be used to sign.
+    pub fn get_rules(&self, wallet_id: Uuid, entry_id: usize) -> Result<Option<SpendingPolicyJson>, VaultNodeError> {
+        Ok(self.read(wallet_id, entry_id)?.map(|stored| stored.policy))
+    }
+
+    /// Policy verified with the `key`. Fails if the file is modified, has no MAC or has another counter than the key.
+    pub fn get(&self, wallet_id: Uuid, entry_id: usize, key: &PolicyKey) -> Result<Option<EntryPolicy>, VaultNodeError> {
+        let stored = match self.read(wallet_id, entry_id)? {
+            Some(stored) => stored,
+            None => return Ok(None),
+        };
+        let value = EntryPolicy { policy: stored.policy, spent: stored.spent, key: *key };
+        let mac = hex::decode(stored.mac).map_err(|_| modified())?;
+        if stored.version != STORAGE_VERSION || stored.counter != key.counter || !same_mac(&mac, &value.mac(wallet_id, entry_id)?) {
+            return Err(modified());
+        }
+        Ok(Some(value))
+    }
+
+    pub fn save(&self, wallet_id: Uuid, entry_id: usize, value: &EntryPolicy) -> Result<(), VaultNodeError> {
+        let stored = StoredPolicyJson {
+            version: STORAGE_VERSION,
+            policy: value.policy.clone(),
+            spent: value.spent.clone(),
+            counter: value.key.counter,
+            mac: hex::encode(value.mac(wallet_id, entry_id)?),
+        };
+        let json = serde_json::to_string_pretty(&stored)
+            .map_err(|e| VaultNodeError::OtherProcessing(format!("Failed to encode policy. Error: {}", e)))?;
+        // write to a temp file first and then move it, so there is never a partially written file
+        let target = self.path(wallet_id, entry_id);
+        let temp = target.with_extension(format!("{}.tmp", FILE_EXTENSION));
+        fs::write(&temp, json)
+            .and_then(|_| fs::rename(&temp, &target))
+            .map_err(|e| VaultNodeError::OtherProcessing(format!("Failed to write policy. Error: {}", e)))
+    }
+
+    pub fn remove(&self, wallet_id: Uuid, entry_id: usize) -> Result<bool, VaultNodeError> {
+        let path = self.path(wallet_id, entry_id);
+        if !path.is_file() {
+            return Ok(false);
+        }
+        fs::remove_file(path)
+            .map(|_| true)
+            .map_err(|e| VaultNodeError::OtherProcessing(format!("Failed to remove policy. Error: {}", e)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, Utc};
+    use num_bigint::BigUint;
+    use uuid::Uuid;
+    use crate::errors::{PolicyRule, VaultNodeError};
+    use crate::policy::{AllowlistJson, EntryPolicy, PolicyKey, PolicyStorage, Spending, SpendingPolicyJson};
+    use crate::tx_decode::EthereumTxFieldsJson;
+
+    const ALICE: &str = "0x3535353535353535353535353535353535353535";
+    const BOB: &str = "0x1111111111111111111111111111111111111111";
+    const KEY: PolicyKey = PolicyKey { key: [7; 32], counter: 0 };
+
+    fn temp_storage() -> PolicyStorage {
+        let dir = std::env::temp_dir().join(format!("emerald-policy-{}", Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        PolicyStorage::new(dir)
+    }
+
+    fn spending(amount: u64) -> Spending {
+        Spending {
+            recipients: vec![ALICE.to_string()],
+            creates_contract: false,
+            amount: BigUint::from(amount),
+            fee: BigUint::from(21_000u64),
+            gas_price: Some(BigUint::from(1u64)),
+            method: None,
+        }
+    }
+
+    fn rule(result: Result<(), VaultNodeError>) -> Option<PolicyRule> {
+        match result {
+            Err(VaultNodeError::PolicyRejected(rule, _)) => Some(rule),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn allows_without_rules() {
+        let policy = EntryPolicy::new(SpendingPolicyJson::default(), KEY);
+        assert!(policy.check(&spending(1_000_000), Utc::now()).is_ok());
+    }
+
+    #[test]
+    fn limits_daily_amount() {
+        let now = Utc::now();
+        let mut policy = EntryPolicy::new(SpendingPolicyJson {
+            daily_limit: Some("1000".to_string()),
+            ..SpendingPolicyJson::default()
+        }, KEY);
+        assert!(policy.check(&spending(600), now).is_ok());
+        policy.record(&BigUint::from(600u64), now - Duration::hours(2));
+        assert_eq!(rule(policy.check(&spending(600), now)), Some(PolicyRule::DailyLimit));
+        assert!(policy.check(&spending(400), now).is_ok());
+        // a day later the old amount doesn't count
+        assert!(policy.check(&spending(600), now + Duration::hours(23)).is_ok());
+    }
+
+    #[test]
+    fn forgets_old_amounts() {
+        let now = Utc::now();
+        let mut policy = EntryPolicy::new(SpendingPolicyJson::default(), KEY);
+        policy.record(&BigUint::from(100u64), now - Duration::hours(30));
+        policy.record(&BigUint::from(200u64), now - Duration::hours(1));
+        policy.record(&BigUint::from(300u64), now);
+        assert_eq!(policy.spent.len(), 2);
+        assert_eq!(policy.spent(now), BigUint::from(500u64));
+    }
+
+    #[test]
+    fn allows_listed_recipients() {
+        let policy = EntryPolicy::new(SpendingPolicyJson {
+            allowlist: Some(AllowlistJson { addresses: vec![ALICE.to_uppercase().replace("0X", "0x")] }),
+            ..SpendingPolicyJson::default()
+        }, KEY);
+        assert!(policy.check(&spending(1), Utc::now()).is_ok());
+        let mut to_bob = spending(1);
+        to_bob.recipients = vec![BOB.to_string()];
+        assert_eq!(rule(policy.check(&to_bob, Utc::now())), Some(PolicyRule::Recipient));
+        let mut deploy = spending(1);
+        deploy.recipients = vec![];
+        deploy.creates_contract = true;
+        assert_eq!(rule(policy.check(&deploy, Utc::now())), Some(PolicyRule::Recipient));
+    }
+
+    #[test]
+    fn refuses_address_book_allowlist() {
+        let act = serde_json::from_str::<SpendingPolicyJson>(r#"{"allowlist": {"addressBook": true, "addresses": []}}"#);
+        assert!(act.is_err());
+    }
+
+    #[test]
+    fn limits_fee_and_gas_price() {
+        let policy = EntryPolicy::new(SpendingPolicyJson {
+            max_fee: Some("21000".to_string()),
+            max_gas_price: Some("1".to_string()),
+            ..SpendingPolicyJson::default()
+        }, KEY);
+        assert!(policy.check(&spending(1), Utc::now()).is_ok());
+        let mut expensive = spending(1);
+        expensive.fee = BigUint::from(21_001u64);
+        assert_eq!(rule(policy.check(&expensive, Utc::now())), Some(PolicyRule::Fee));
+        let mut expensive = spending(1);
+        expensive.gas_price = Some(BigUint::from(2u64));
+        assert_eq!(rule(policy.check(&expensive, Utc::now())), Some(PolicyRule::Fee));
+    }
+
+    #[test]
+    fn blocks_methods() {
+        let policy = EntryPolicy::new(SpendingPolicyJson {
+            blocked_methods: vec!["0x095ea7b3".to_string()],
+            ..SpendingPolicyJson::default()
+        }, KEY);
+        let mut approve = spending(0);
+        approve.method = Some([0x09, 0x5e, 0xa7, 0xb3]);
+        assert_eq!(rule(policy.check(&approve, Utc::now())), Some(PolicyRule::Method));
+        approve.method = Some([0xa9, 0x05, 0x9c, 0xbb]);
+        assert!(policy.check(&approve, Utc::now()).is_ok());
+    }
+
+    #[test]
+    fn ethereum_token_spending() {
+        let fields = EthereumTxFieldsJson {
+            tx_type: 2,
+            chain_id: Some(1),
+            nonce: 0,
+            gas: 50_000,
+            gas_price: None,
+            max_gas_price: Some("30000000000".to_string()),
+            priority_gas_price: Some("1000000000".to_string()),
+            to: Some(BOB.to_string()),
+            value: "1".to_string(),
+            data: format!("0xa9059cbb{:0>64}{:064x}", ALICE.trim_start_matches("0x"), 1_000),
+            access_list: None,
+            authorization_list: None,
+        };
+        let act = Spending::ethereum(&fields).unwrap();
+        assert_eq!(act.recipients, vec![BOB.to_string(), ALICE.to_string()]);
+        assert_eq!(act.fee, BigUint::from(1_500_000_000_000_000u64));
+        assert_eq!(act.amount, BigUint::from(1_500_000_000_000_001u64));
+        assert_eq!(act.gas_price, Some(BigUint::from(30_000_000_000u64)));
+        assert_eq!(act.method, Some([0xa9, 0x05, 0x9c, 0xbb]));
+    }
+
+    #[test]
+    fn validates_rules() {
+        assert!(SpendingPolicyJson { daily_limit: Some("-1".to_string()), ..SpendingPolicyJson::default() }.validate().is_err());
+        assert!(SpendingPolicyJson { blocked_methods: vec!["0x095ea7".to_string()], ..SpendingPolicyJson::default() }.validate().is_err());
+        assert!(SpendingPolicyJson { max_fee: Some("100".to_string()), ..SpendingPolicyJson::default() }.validate().is_ok());
+    }
+
+    #[test]
+    fn save_and_get() {
+        let storage = temp_storage();
+        let wallet_id = Uuid::new_v4();
+        assert_eq!(storage.get(wallet_id, 0, &KEY).unwrap(), None);
+
+        let mut policy = EntryPolicy::new(SpendingPolicyJson { daily_limit: Some("1000".to_string()), ..SpendingPolicyJson::default() }, KEY);
+        policy.record(&BigUint::from(100u64), Utc::now());
+        storage.save(wallet_id, 0, &policy).unwrap();
+        assert!(storage.exists(wallet_id, 0));
+        assert_eq!(storage.get(wallet_id, 0, &KEY).unwrap(), Some(policy));
+        assert_eq!(storage.get(wallet_id, 1, &KEY).unwrap(), None);
+
+        assert!(storage.remove(wallet_id, 0).unwrap());
+        assert!(!storage.remove(wallet_id, 0).unwrap());
+        assert!(!storage.exists(wallet_id, 0));
+    }
+
+    #[test]
+    fn fails_on_modified_file() {
+        let storage = temp_storage();
+        let wallet_id = Uuid::new_v4();
+        let policy = EntryPolicy::new(SpendingPolicyJson { daily_limit: Some("1000".to_string()), ..SpendingPolicyJson::default() }, KEY);
+        storage.save(wallet_id, 0, &policy).unwrap();
+
+        assert_eq!(rule(storage.get(wallet_id, 0, &PolicyKey::new([8; 32])).map(|_| ())), Some(PolicyRule::Integrity));
+
+        let path = storage.path(wallet_id, 0);
+        let json = std::fs::read_to_string(&path).unwrap();
+        std::fs::write(&path, json.replace("\"1000\"", "\"1000000\"")).unwrap();
+        assert_eq!(storage.get_rules(wallet_id, 0).unwrap().and_then(|p| p.daily_limit), Some("1000000".to_string()));
+        assert_eq!(rule(storage.get(wallet_id, 0, &KEY).map(|_| ())), Some(PolicyRule::Integrity));
+
+        // a policy for another entry doesn't match either
+        std::fs::write(storage.path(wallet_id, 1), json).unwrap();
+        assert_eq!(rule(storage.get(wallet_id, 1, &KEY).map(|_| ())), Some(PolicyRule::Integrity));
+    }
+
+    #[test]
+    fn fails_on_previous_file() {
+        let storage = temp_storage();
+        let wallet_id = Uuid::new_v4();
+        let now = Utc::now();
+        let policy = EntryPolicy::new(SpendingPolicyJson { daily_limit: Some("1000".to_string()), ..SpendingPolicyJson::default() }, KEY);
+        storage.save(wallet_id, 0, &policy).unwrap();
+        let previous = std::fs::read_to_string(storage.path(wallet_id, 0)).unwrap();
+
+        let mut updated = storage.get(wallet_id, 0, &KEY).unwrap().unwrap();
+        updated.record(&BigUint::from(900u64), now);
+        let next = KEY.next();
+        storage.save(wallet_id, 0, &updated.with_key(next)).unwrap();
+        assert_eq!(storage.get(wallet_id, 0, &next).unwrap().map(|p| p.spent(now)), Some(BigUint::from(900u64)));
+        assert_eq!(rule(storage.get(wallet_id, 0, &KEY).map(|_| ())), Some(PolicyRule::Integrity));
+
+        // the previous copy has a valid MAC, but with the previous counter
+        std::fs::write(storage.path(wallet_id, 0), previous).unwrap();
+        assert_eq!(rule(storage.get(wallet_id, 0, &next).map(|_| ())), Some(PolicyRule::Integrity));
+    }
+
+    #[test]
+    fn key_to_bytes() {
+        let key = KEY.next().next();
+        assert_eq!(PolicyKey::from_bytes(&key.to_bytes()), Some(key));
+        assert_eq!(PolicyKey::from_bytes(&[7; 32]), None);
+    }
+}
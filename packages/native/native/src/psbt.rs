@@ -0,0 +1,489 @@
+//! Signing of a PSBT (BIP-174) built by another coordinator.
+//!
+//! The inputs are matched to the entry by the BIP-32 derivation fields (`bip32_derivation` or `tap_key_origins`), i.e. an input is
+//! signed only if it has a key with the fingerprint of the seed and a path on the entry account. Everything else is left as is,
+//! so the PSBT can be passed to another signer after that.
+//!
+//! The finalizer supports single-key inputs (P2PKH, P2WPKH, P2SH-P2WPKH and P2TR key-path) and multisig P2WSH or P2SH-P2WSH
+//! inputs, other inputs must be finalized by the coordinator.
+
+use base64::Engine;
+use bitcoin::bip32::{DerivationPath, Fingerprint, KeySource, Xpriv};
+use bitcoin::psbt::{GetKey, GetKeyError, Input, KeyRequest, Psbt, SigningKeys};
+use bitcoin::blockdata::opcodes::all::OP_CHECKMULTISIG;
+use bitcoin::script::{Instruction, PushBytesBuf};
+use bitcoin::secp256k1::{Secp256k1, Signing, Verification};
+use bitcoin::{CompressedPublicKey, NetworkKind, PrivateKey, ScriptBuf, Transaction, Witness};
+use crate::errors::VaultNodeError;
+
+#[derive(Serialize, Clone, Debug, Eq, PartialEq)]
+pub struct SignedPsbtJson {
+    /// Updated PSBT as base64
+    pub psbt: String,
+    /// Indexes of the inputs signed by the entry
+    pub signed: Vec<usize>,
+    /// If all the inputs are finalized
+    pub complete: bool,
+    /// Extracted transaction as hex, if it was requested and the PSBT is complete
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub raw: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub txid: Option<String>,
+}
+
+#[derive(Deserialize, Clone, Debug, Default, Eq, PartialEq)]
+pub struct SignPsbtOptionsJson {
+    /// Finalize the inputs and extract the signed transaction
+    #[serde(default)]
+    pub finalize: bool,
+}
+
+/// Provides the keys of a single account of the seed
+pub struct AccountSigner {
+    master: Xpriv,
+    fingerprint: Fingerprint,
+    account: DerivationPath,
+}
+
+impl AccountSigner {
+    pub fn new(seed: &[u8], account: DerivationPath, network: NetworkKind) -> Result<AccountSigner, VaultNodeError> {
+        let secp = Secp256k1::new();
+        let master = Xpriv::new_master(network, seed)
+            .map_err(|_| VaultNodeError::OtherProcessing("Invalid seed".to_string()))?;
+        Ok(AccountSigner {
+            fingerprint: master.fingerprint(&secp),
+            master,
+            account,
+        })
+    }
+
+    /// Fingerprint of the seed Master Key
+    pub fn fingerprint(&self) -> Fingerprint {
+        self.fingerprint
+    }
+
+    fn owns(&self, source: &KeySource) -> bool {
+        let (fingerprint, path) = source;
+        *fingerprint == self.fingerprint
+            && path.len() > self.account.len()
+            && path.as_ref().starts_with(self.account.as_ref())
+    }
+
+    fn derive<C: Signing>(&self, secp: &Secp256k1<C>, path: &DerivationPath) -> Result<PrivateKey, VaultNodeError> {
+        self.master.derive_priv(secp, path)
+            .map(|key| key.to_priv())
+            .map_err(|_| VaultNodeError::OtherProcessing("Failed to derive key".to_string()))
+    }
+
+    /// Indexes of the inputs which have a key of the account. Fails if a key doesn't match its derivation path,
+    /// because it means the PSBT was made for another seed or is corrupted.
+    fn owned_inputs<C: Signing>(&self, psbt: &Psbt, secp: &Secp256k1<C>) -> Result<Vec<usize>, VaultNodeError> {
+        let mut result = Vec::new();
+        for (i, input) in psbt.inputs.iter().enumerate() {
+            let mut owned = false;
+            for (pk, source) in input.bip32_derivation.iter().filter(|(_, source)| self.owns(source)) {
+                if self.derive(secp, &source.1)?.public_key(secp).inner != *pk {
+                    return Err(VaultNodeError::OtherInput(format!("Invalid key derivation for input {}", i)));
+                }
+                owned = true;
+            }
+            for (xonly, (_, source)) in input.tap_key_origins.iter().filter(|(_, (_, source))| self.owns(source)) {
+                if self.derive(secp, &source.1)?.inner.x_only_public_key(secp).0 != *xonly {
+                    return Err(VaultNodeError::OtherInput(format!("Invalid key derivation for input {}", i)));
+                }
+                owned = true;
+            }
+            if owned {
+                result.push(i);
+            }
+        }
+        Ok(result)
+    }
+}
+
+impl GetKey for AccountSigner {
+    type Error = GetKeyError;
+
+    fn get_key<C: Signing>(&self, key_request: KeyRequest, secp: &Secp256k1<C>) -> Result<Option<PrivateKey>, Self::Error> {
+        match key_request {
+            KeyRequest::Bip32(source) if self.owns(&source) => {
+                let key = self.master.derive_priv(secp, &source.1)?;
+                Ok(Some(key.to_priv()))
+            }
+            _ => Ok(None)
+        }
+    }
+}
+
+pub fn decode(value: &str) -> Result<Psbt, VaultNodeError> {
+    let bytes = base64::engine::general_purpose::STANDARD.decode(value.trim())
+        .map_err(|_| VaultNodeError::InvalidArgumentValue("PSBT is not a valid base64".to_string()))?;
+    Psbt::deserialize(&bytes)
+        .map_err(|e| VaultNodeError::InvalidArgumentValue(format!("Invalid PSBT: {}", e)))
+}
+
+pub fn encode(psbt: &Psbt) -> String {
+    base64::engine::general_purpose::STANDARD.encode(psbt.serialize())
+}
+
+/// Sign all inputs of the account. Returns indexes of the signed inputs.
+pub fn sign<C: Signing + Verification>(psbt: &mut Psbt, signer: &AccountSigner, secp: &Secp256k1<C>) -> Result<Vec<usize>, VaultNodeError> {
+    let owned = signer.owned_inputs(psbt, secp)?;
+    for i in owned.iter() {
+        // only SIGHASH_ALL (or DEFAULT for taproot), because other types allow to change the transaction after the signature
+        if let Some(sighash_type) = psbt.inputs[*i].sighash_type
+            && !matches!(sighash_type.to_u32(), 0 | 1) {
+            return Err(VaultNodeError::OtherInput(format!("Unsupported sighash type for input {}: {}", i, sighash_type)));
+        }
+    }
+    let used = match psbt.sign(signer, secp) {
+        Ok(used) => used,
+        Err((used, errors)) => {
+            // errors for inputs of other accounts are expected, ex. they may not have the UTXO details for us
+            if let Some((i, e)) = errors.iter().find(|(i, _)| owned.contains(i)) {
+                return Err(VaultNodeError::OtherInput(format!("Failed to sign input {}: {}", i, e)));
+            }
+            used
+        }
+    };
+    let signed = used.iter()
+        .filter(|(_, keys)| match keys {
+            SigningKeys::Ecdsa(keys) => !keys.is_empty(),
+            SigningKeys::Schnorr(keys) => !keys.is_empty(),
+        })
+        .map(|(i, _)| *i)
+        .collect();
+    Ok(signed)
+}
+
+fn is_final(input: &Input) -> bool {
+    input.final_script_sig.is_some() || input.final_script_witness.is_some()
+}
+
+/// Signature made with the key for the script, as (sig, pubkey)
+fn single_sig<F>(input: &Input, matches: F) -> Option<(bitcoin::ecdsa::Signature, bitcoin::PublicKey)>
+    where F: Fn(&bitcoin::PublicKey) -> bool {
+    input.partial_sigs.iter()
+        .find(|(pk, _)| matches(pk))
+        .map(|(pk, sig)| (*sig, *pk))
+}
+
+fn is_p2wpkh_of(script: &ScriptBuf, pk: &bitcoin::PublicKey) -> bool {
+    CompressedPublicKey::try_from(*pk)
+        .map(|pk| ScriptBuf::new_p2wpkh(&pk.wpubkey_hash()) == *script)
+        .unwrap_or(false)
+}
+
+fn push_script(values: &[&[u8]]) -> Option<ScriptBuf> {
+    let mut builder = ScriptBuf::builder();
+    for value in values {
+        builder = builder.push_slice(PushBytesBuf::try_from(value.to_vec()).ok()?);
+    }
+    Some(builder.into_script())
+}
+
+fn small_int(instruction: &Instruction) -> Option<usize> {
+    match instruction {
+        Instruction::Op(op) if (0x51..=0x60).contains(&op.to_u8()) => Some((op.to_u8() - 0x50) as usize),
+        _ => None
+    }
+}
+
+/// Witness for a `multi` (or `sortedmulti`) script, with the signatures in the order of the keys.
+/// `None` if it's not a multisig script or there are not enough signatures.
+fn multisig_witness(input: &Input, witness_script: &ScriptBuf) -> Option<Witness> {
+    let instructions = witness_script.instructions()
+        .collect::<Result<Vec<Instruction>, _>>().ok()?;
+    if instructions.len() < 4 || instructions.last() != Some(&Instruction::Op(OP_CHECKMULTISIG)) {
+        return None;
+    }
+    let threshold = small_int(&instructions[0])?;
+    let keys = &instructions[1..instructions.len() - 2];
+    if small_int(&instructions[instructions.len() - 2])? != keys.len() || threshold > keys.len() {
+        return None;
+    }
+    let mut witness = Witness::new();
+    // the extra value consumed by CHECKMULTISIG
+    witness.push(Vec::<u8>::new());
+    let mut signed = 0;
+    for key in keys {
+        let key = bitcoin::PublicKey::from_slice(key.push_bytes()?.as_bytes()).ok()?;
+        if signed < threshold && let Some(sig) = input.partial_sigs.get(&key) {
+            witness.push(sig.serialize());
+            signed += 1;
+        }
+    }
+    if signed < threshold {
+        return None;
+    }
+    witness.push(witness_script.as_bytes());
+    Some(witness)
+}
+
+fn witness_script_of<'a>(input: &'a Input, script_pubkey: &ScriptBuf) -> Option<&'a ScriptBuf> {
+    input.witness_script.as_ref()
+        .filter(|witness_script| ScriptBuf::new_p2wsh(&witness_script.wscript_hash()) == *script_pubkey)
+}
+
+fn finalize_input(input: &Input, script_pubkey: &ScriptBuf) -> Option<(Option<ScriptBuf>, Option<Witness>)> {
+    if script_pubkey.is_p2wpkh() {
+        let (sig, pk) = single_sig(input, |pk| is_p2wpkh_of(script_pubkey, pk))?;
+        return Some((None, Some(Witness::p2wpkh(&sig, &pk.inner))));
+    }
+    if script_pubkey.is_p2wsh() {
+        let witness_script = witness_script_of(input, script_pubkey)?;
+        return Some((None, Some(multisig_witness(input, witness_script)?)));
+    }
+    if script_pubkey.is_p2sh() {
+        let redeem = input.redeem_script.as_ref()
+            .filter(|redeem| ScriptBuf::new_p2sh(&redeem.script_hash()) == *script_pubkey)?;
+        let script_sig = push_script(&[redeem.as_bytes()])?;
+        if redeem.is_p2wpkh() {
+            let (sig, pk) = single_sig(input, |pk| is_p2wpkh_of(redeem, pk))?;
+            return Some((Some(script_sig), Some(Witness::p2wpkh(&sig, &pk.inner))));
+        }
+        if redeem.is_p2wsh() {
+            let witness_script = witness_script_of(input, redeem)?;
+            return Some((Some(script_sig), Some(multisig_witness(input, witness_script)?)));
+        }
+        return None;
+    }
+    if script_pubkey.is_p2pkh() {
+        let (sig, pk) = single_sig(input, |pk| ScriptBuf::new_p2pkh(&pk.pubkey_hash()) == *script_pubkey)?;
+        let script_sig = push_script(&[sig.serialize().as_ref(), &pk.to_bytes()])?;
+        return Some((Some(script_sig), None));
+    }
+    if script_pubkey.is_p2tr() {
+        let sig = input.tap_key_sig?;
+        return Some((None, Some(Witness::p2tr_key_spend(&sig))));
+    }
+    None
+}
+
+pub fn is_complete(psbt: &Psbt) -> bool {
+    psbt.inputs.iter().all(is_final)
+}
+
+/// Finalize all inputs that have enough signatures. Returns `true` if all inputs are final after that.
+pub fn finalize(psbt: &mut Psbt) -> bool {
+    let mut complete = true;
+    for i in 0..psbt.inputs.len() {
+        if is_final(&psbt.inputs[i]) {
+            continue;
+        }
+        let script_pubkey = match psbt.spend_utxo(i) {
+            Ok(utxo) => utxo.script_pubkey.clone(),
+            Err(_) => {
+                complete = false;
+                continue;
+            }
+        };
+        match finalize_input(&psbt.inputs[i], &script_pubkey) {
+            Some((script_sig, witness)) => {
+                // BIP-174 says to remove everything except the UTXO and the final scripts
+                let input = &psbt.inputs[i];
+                psbt.inputs[i] = Input {
+                    non_witness_utxo: input.non_witness_utxo.clone(),
+                    witness_utxo: input.witness_utxo.clone(),
+                    final_script_sig: script_sig,
+                    final_script_witness: witness,
+                    unknown: input.unknown.clone(),
+                    proprietary: input.proprietary.clone(),
+                    ..Input::default()
+                };
+            }
+            None => complete = false,
+        }
+    }
+    complete
+}
+
+/// Extract the signed transaction from a finalized PSBT. Fails if the fee is absurdly high.
+pub fn extract(psbt: Psbt) -> Result<Transaction, VaultNodeError> {
+    psbt.extract_tx()
+        .map_err(|e| VaultNodeError::OtherProcessing(format!("Failed to extract transaction: {}", e)))
+}
+
+/// Result of signing. If `finalize` is requested it finalizes the inputs, and extracts the transaction when all of them are final.
+pub fn into_result(mut psbt: Psbt, signed: Vec<usize>, finalize: bool) -> Result<SignedPsbtJson, VaultNodeError> {
+    let complete = if finalize {
+        self::finalize(&mut psbt)
+    } else {
+        is_complete(&psbt)
+    };
+    let encoded = encode(&psbt);
+    let tx = if finalize && complete {
+        Some(extract(psbt)?)
+    } else {
+        None
+    };
+    Ok(SignedPsbtJson {
+        psbt: encoded,
+        signed,
+        complete,
+        raw: tx.as_ref().map(|tx| hex::encode(bitcoin::consensus::serialize(tx))),
+        txid: tx.as_ref().map(|tx| tx.compute_txid().to_string()),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+    use std::str::FromStr;
+    use bitcoin::{Address, Amount, EcdsaSighashType, Network, NetworkKind, OutPoint, ScriptBuf, Sequence, Transaction, TxIn, TxOut, Txid, Witness};
+    use bitcoin::absolute::LockTime;
+    use bitcoin::bip32::{DerivationPath, Xpriv};
+    use bitcoin::hashes::Hash;
+    use bitcoin::psbt::{Input, Psbt, PsbtSighashType};
+    use bitcoin::secp256k1::{Message, Secp256k1};
+    use bitcoin::sighash::{Prevouts, SighashCache, TapSighashType};
+    use bitcoin::transaction::Version;
+    use crate::psbt::{decode, encode, extract, finalize, sign, AccountSigner};
+
+    // "abandon abandon ... about"
+    fn seed() -> Vec<u8> {
+        hex::decode("5eb00bbddcf069084889a8ab9155568165f5c453ccb85e70811aaed6f6da5fc19a5ac40b389cd370d086206dec8aa6c43daea6690f20ad3d8d48b2d2ce9e38e4").unwrap()
+    }
+
+    fn signer(account: &str) -> AccountSigner {
+        AccountSigner::new(&seed(), DerivationPath::from_str(account).unwrap(), NetworkKind::Main).unwrap()
+    }
+
+    /// PSBT with an input for each path, 100_000 sat each
+    fn create_psbt(paths: &[&str]) -> Psbt {
+        let secp = Secp256k1::new();
+        let master = Xpriv::new_master(Network::Bitcoin, &seed()).unwrap();
+        let fingerprint = master.fingerprint(&secp);
+        let tx = Transaction {
+            version: Version::TWO,
+            lock_time: LockTime::ZERO,
+            input: paths.iter().enumerate().map(|(i, _)| TxIn {
+                previous_output: OutPoint { txid: Txid::from_byte_array([i as u8 + 1; 32]), vout: 0 },
+                script_sig: ScriptBuf::new(),
+                sequence: Sequence::ENABLE_RBF_NO_LOCKTIME,
+                witness: Witness::new(),
+            }).collect(),
+            output: vec![TxOut {
+                value: Amount::from_sat(90_000 * paths.len() as u64),
+                script_pubkey: Address::from_str("bc1qcr8te4kr609gcawutmrza0j4xv80jy8z306fyu").unwrap().assume_checked().script_pubkey(),
+            }],
+        };
+        let mut psbt = Psbt::from_unsigned_tx(tx).unwrap();
+        for (i, path) in paths.iter().enumerate() {
+            let path = DerivationPath::from_str(path).unwrap();
+            let key = master.derive_priv(&secp, &path).unwrap().to_priv();
+            let pk = key.public_key(&secp);
+            let mut input = Input::default();
+            if path.to_string().starts_with("86'") {
+                let (xonly, _) = pk.inner.x_only_public_key();
+                input.witness_utxo = Some(TxOut {
+                    value: Amount::from_sat(100_000),
+                    script_pubkey: ScriptBuf::new_p2tr(&secp, xonly, None),
+                });
+                input.tap_internal_key = Some(xonly);
+                input.tap_key_origins.insert(xonly, (vec![], (fingerprint, path)));
+            } else {
+                input.witness_utxo = Some(TxOut {
+                    value: Amount::from_sat(100_000),
+                    script_pubkey: ScriptBuf::new_p2wpkh(&pk.wpubkey_hash().unwrap()),
+                });
+                input.bip32_derivation = BTreeMap::from([(pk.inner, (fingerprint, path))]);
+            }
+            psbt.inputs[i] = input;
+        }
+        psbt
+    }
+
+    #[test]
+    fn encode_decode() {
+        let psbt = create_psbt(&["m/84'/0'/0'/0/0"]);
+        let act = decode(&encode(&psbt)).unwrap();
+        assert_eq!(act, psbt);
+        assert!(decode("not a psbt").is_err());
+    }
+
+    #[test]
+    fn sign_and_extract_p2wpkh() {
+        let secp = Secp256k1::new();
+        let mut psbt = create_psbt(&["m/84'/0'/0'/0/0", "m/84'/0'/0'/1/3"]);
+        let signed = sign(&mut psbt, &signer("m/84'/0'/0'"), &secp).unwrap();
+        assert_eq!(signed, vec![0, 1]);
+        assert!(finalize(&mut psbt));
+
+        let prevouts: Vec<TxOut> = psbt.inputs.iter().map(|i| i.witness_utxo.clone().unwrap()).collect();
+        let tx = extract(psbt).unwrap();
+        let mut cache = SighashCache::new(&tx);
+        for (i, prevout) in prevouts.iter().enumerate() {
+            let witness = tx.input[i].witness.to_vec();
+            assert_eq!(witness.len(), 2);
+            let pk = bitcoin::PublicKey::from_slice(&witness[1]).unwrap();
+            let sig = bitcoin::ecdsa::Signature::from_slice(&witness[0]).unwrap();
+            let sighash = cache.p2wpkh_signature_hash(i, &prevout.script_pubkey, prevout.value, EcdsaSighashType::All).unwrap();
+            secp.verify_ecdsa(&Message::from_digest(sighash.to_byte_array()), &sig.signature, &pk.inner).unwrap();
+        }
+    }
+
+    #[test]
+    fn sign_and_extract_taproot() {
+        let secp = Secp256k1::new();
+        let mut psbt = create_psbt(&["m/86'/0'/0'/0/0"]);
+        let signed = sign(&mut psbt, &signer("m/86'/0'/0'"), &secp).unwrap();
+        assert_eq!(signed, vec![0]);
+        assert!(finalize(&mut psbt));
+
+        let prevout = psbt.inputs[0].witness_utxo.clone().unwrap();
+        let tx = extract(psbt).unwrap();
+        let witness = tx.input[0].witness.to_vec();
+        assert_eq!(witness.len(), 1);
+        let sig = bitcoin::taproot::Signature::from_slice(&witness[0]).unwrap();
+        let sighash = SighashCache::new(&tx)
+            .taproot_key_spend_signature_hash(0, &Prevouts::All(std::slice::from_ref(&prevout)), TapSighashType::Default)
+            .unwrap();
+        let output_key = bitcoin::XOnlyPublicKey::from_slice(&prevout.script_pubkey.as_bytes()[2..]).unwrap();
+        secp.verify_schnorr(&sig.signature, &Message::from_digest(sighash.to_byte_array()), &output_key).unwrap();
+    }
+
+    #[test]
+    fn signs_only_account_inputs() {
+        let secp = Secp256k1::new();
+        let mut psbt = create_psbt(&["m/84'/0'/0'/0/0", "m/84'/0'/1'/0/0"]);
+        let signed = sign(&mut psbt, &signer("m/84'/0'/0'"), &secp).unwrap();
+        assert_eq!(signed, vec![0]);
+        assert_eq!(psbt.inputs[0].partial_sigs.len(), 1);
+        assert!(psbt.inputs[1].partial_sigs.is_empty());
+
+        assert!(!finalize(&mut psbt));
+        assert!(psbt.inputs[0].final_script_witness.is_some());
+        assert!(psbt.inputs[1].final_script_witness.is_none());
+        // the other input keeps its details for the next signer
+        assert_eq!(psbt.inputs[1].bip32_derivation.len(), 1);
+    }
+
+    #[test]
+    fn signs_nothing_for_other_seed() {
+        let secp = Secp256k1::new();
+        let mut psbt = create_psbt(&["m/84'/0'/0'/0/0"]);
+        let other = AccountSigner::new(&[1u8; 64], DerivationPath::from_str("m/84'/0'/0'").unwrap(), NetworkKind::Main).unwrap();
+        let signed = sign(&mut psbt, &other, &secp).unwrap();
+        assert!(signed.is_empty());
+        assert!(psbt.inputs[0].partial_sigs.is_empty());
+    }
+
+    #[test]
+    fn rejects_non_standard_sighash() {
+        let secp = Secp256k1::new();
+        let mut psbt = create_psbt(&["m/84'/0'/0'/0/0"]);
+        psbt.inputs[0].sighash_type = Some(PsbtSighashType::from(EcdsaSighashType::None));
+        assert!(sign(&mut psbt, &signer("m/84'/0'/0'"), &secp).is_err());
+        assert!(psbt.inputs[0].partial_sigs.is_empty());
+    }
+
+    #[test]
+    fn rejects_invalid_derivation() {
+        let secp = Secp256k1::new();
+        let mut psbt = create_psbt(&["m/84'/0'/0'/0/0"]);
+        let (pk, (fp, _)) = psbt.inputs[0].bip32_derivation.pop_first().unwrap();
+        psbt.inputs[0].bip32_derivation.insert(pk, (fp, DerivationPath::from_str("m/84'/0'/0'/0/1").unwrap()));
+        assert!(sign(&mut psbt, &signer("m/84'/0'/0'"), &secp).is_err());
+    }
+}
@@ -0,0 +1,122 @@
+//! Secrets which are not a part of the Vault data model, such as the mnemonic entropy of a seed or the key of a spending
+//! policy.
+//!
+//! A secret is encrypted by the Vault in the same way as a seed, i.e., with the Global Key or an individual password, but it's
+//! kept in a separate `{id}.{kind}` file instead of the Vault key storage. So it's never listed, upgraded or exported as a
+//! Private Key of an entry. The Vault has no encoding for an encrypted value alone, so the file is a record of encrypted raw
+//! bytes in the format of a Vault seed (`SeedSource::Bytes`), which is never added to the seed storage.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use chrono::Utc;
+use emerald_vault::error::VaultError;
+use emerald_vault::structs::crypto::{Encrypted, GlobalKey};
+use emerald_vault::structs::seed::{Seed, SeedSource};
+use uuid::Uuid;
+use crate::errors::VaultNodeError;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum SecretKind {
+    /// BIP-39 entropy of a seed
+    Mnemonic,
+    /// Key and counter which authenticate the spending policy of an entry
+    PolicyKey,
+    /// Copy of a seed record kept only while the seed is changed
+    SeedBackup,
+}
+
+impl SecretKind {
+    pub const fn extension(&self) -> &'static str {
+        match self {
+            SecretKind::Mnemonic => "mnemonic",
+            SecretKind::PolicyKey => "policykey",
+            SecretKind::SeedBackup => "seedbackup",
+        }
+    }
+}
+
+pub struct SecretStorage {
+    dir: PathBuf,
+}
+
+impl SecretStorage {
+    pub fn new<P: AsRef<Path>>(dir: P) -> SecretStorage {
+        SecretStorage {
+            dir: dir.as_ref().to_path_buf()
+        }
+    }
+
+    fn path(&self, kind: SecretKind, id: Uuid) -> PathBuf {
+        self.dir.join(format!("{}.{}", id, kind.extension()))
+    }
+
+    pub fn exists(&self, kind: SecretKind, id: Uuid) -> bool {
+        self.path(kind, id).is_file()
+    }
+
+    fn write(&self, kind: SecretKind, record: Seed) -> Result<(), VaultNodeError> {
+        let target = self.path(kind, record.id);
+        let data = Vec::<u8>::try_from(record)?;
+        // write to a temp file first and then move it, so there is never a partially written file
+        let temp = target.with_extension(format!("{}.tmp", kind.extension()));
+        fs::write(&temp, data)
+            .and_then(|_| fs::rename(&temp, &target))
+            .map_err(|e| VaultNodeError::OtherProcessing(format!("Failed to write secret. Error: {}", e)))
+    }
+
+    fn read(&self, kind: SecretKind, id: Uuid) -> Result<Option<Seed>, VaultNodeError> {
+        let path = self.path(kind, id);
+        if !path.is_file() {
+            return Ok(None);
+        }
+        let data = fs::read(path)
+            .map_err(|e| VaultNodeError::OtherProcessing(format!("Failed to read secret. Error: {}", e)))?;
+        Ok(Some(Seed::try_from(data)?))
+    }
+
+    /// Encrypt and keep the `value`, with the same `password` and `global` key as used for a seed
+    pub fn save(&self, kind: SecretKind, id: Uuid, value: &[u8], password: &str, global: Option<GlobalKey>) -> Result<(), VaultNodeError> {
+        let encrypted = Encrypted::encrypt(value.to_vec(), password.as_bytes(), global)
+            .map_err(VaultError::from)?;
+        self.write(kind, Seed {
+            id,
+            source: SeedSource::Bytes(encrypted),
+            label: None,
+            created_at: Utc::now(),
+        })
+    }
+
+    /// Decrypted value, or `None` if nothing is stored
+    pub fn load(&self, kind: SecretKind, id: Uuid, password: &str, global: Option<GlobalKey>) -> Result<Option<Vec<u8>>, VaultNodeError> {
+        let record = match self.read(kind, id)? {
+            Some(record) => record,
+            None => return Ok(None),
+        };
+        match &record.source {
+            SeedSource::Bytes(encrypted) => encrypted.decrypt(password.as_bytes(), global)
+                .map(Some)
+                .map_err(|e| VaultNodeError::from(VaultError::from(e))),
+            SeedSource::Ledger(_) => Err(VaultNodeError::OtherProcessing("Invalid secret".to_string())),
+        }
+    }
+
+    /// Keep a copy of the seed record as is, i.e., still encrypted with its current password
+    pub fn backup_seed(&self, seed: &Seed) -> Result<(), VaultNodeError> {
+        self.write(SecretKind::SeedBackup, seed.clone())
+    }
+
+    pub fn seed_backup(&self, seed_id: Uuid) -> Result<Option<Seed>, VaultNodeError> {
+        self.read(SecretKind::SeedBackup, seed_id)
+    }
+
+    pub fn remove(&self, kind: SecretKind, id: Uuid) -> Result<bool, VaultNodeError> {
+        let path = self.path(kind, id);
+        if !path.is_file() {
+            return Ok(false);
+        }
+        fs::remove_file(path)
+            .map(|_| true)
+            .map_err(|e| VaultNodeError::OtherProcessing(format!("Failed to remove secret. Error: {}", e)))
+    }
+}
@@ -6,18 +6,22 @@ use emerald_vault::util::none_if_empty;
 use emerald_vault::{
     mnemonic::{Language, Mnemonic, MnemonicSize},
     error::VaultError,
+    crypto::error::CryptoError,
     structs::{
-        crypto::Encrypted,
+        crypto::{Encrypted, GlobalKey},
         seed::{LedgerSource, Seed, SeedSource},
     },
-    EthereumAddress
+    EthereumAddress,
+    EthereumPrivateKey,
 };
 use hdpath::{StandardHDPath, AccountHDPath, CustomHDPath, HDPath};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::convert::TryFrom;
 use emerald_vault::blockchain::chains::BlockchainType;
 use emerald_vault::chains::Blockchain;
-use bitcoin::Address;
+use bitcoin::{Address, Network};
+use bitcoin::bip32::{DerivationPath, Xpriv};
+use bitcoin::secp256k1::Secp256k1;
 use std::str::FromStr;
 use emerald_hwkey::{
     ledger::{
@@ -28,14 +32,51 @@ use emerald_hwkey::{
     }
 };
 use emerald_vault::structs::seed::WithFingerprint;
+use emerald_vault::structs::types::UsesOddKey;
+use emerald_vault::structs::wallet::{EntryId, PKType};
 use emerald_vault::crypto::fingerprint::Fingerprints;
+use crate::access::{args_get_bool, args_get_str, args_require_str};
+use crate::bip85;
+use crate::bip85::{Bip85Application, Bip85ChildJson, Bip85OptionsJson};
 use crate::errors::{VaultNodeError};
+use crate::entropy;
+use crate::entropy::{EntropyJson, MnemonicFromEntropyJson};
 use crate::instance::{Instance, WrappedVault};
+use crate::path_template;
+use crate::secret::SecretKind;
+use crate::slip39;
+use crate::slip39::GroupSpec;
+use crate::taproot;
+use crate::xpub;
+use crate::xpub::{AccountXpubJson, XpubFormat};
 
 #[derive(Serialize, Deserialize, Clone)]
-struct HDPathAddress {
-    address: String,
+pub struct HDPathAddress {
+    #[serde(rename = "hdPath")]
     hd_path: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    address: Option<String>,
+    /// Reason why the address is not available for the path
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+impl HDPathAddress {
+    fn ok(hd_path: String, address: String) -> HDPathAddress {
+        HDPathAddress {
+            hd_path,
+            address: Some(address),
+            error: None,
+        }
+    }
+
+    fn failed(hd_path: String, error: &str) -> HDPathAddress {
+        HDPathAddress {
+            hd_path,
+            address: None,
+            error: Some(error.to_string()),
+        }
+    }
 }
 
 #[derive(Deserialize, Clone)]
@@ -44,6 +85,24 @@ pub struct SeedUpdateJson {
 }
 
 
+/// A reference to a seed from a wallet, either through an entry or a reserved account
+#[derive(Serialize, Clone, Debug, Eq, PartialEq)]
+pub struct SeedUsageJson {
+    #[serde(rename = "walletId")]
+    pub wallet_id: Uuid,
+    #[serde(rename = "entryId", skip_serializing_if = "Option::is_none")]
+    pub entry_id: Option<String>,
+    #[serde(rename = "accountId", skip_serializing_if = "Option::is_none")]
+    pub account_id: Option<u32>,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct SeedRemoveJson {
+    pub removed: bool,
+    #[serde(rename = "usedBy")]
+    pub used_by: Vec<SeedUsageJson>,
+}
+
 #[derive(Serialize, Deserialize, Clone)]
 pub struct SeedJson {
     pub id: String,
@@ -54,6 +113,13 @@ pub struct SeedJson {
     pub label: Option<String>,
     #[serde(rename = "createdAt")]
     pub created_at: DateTime<Utc>,
+    /// If the mnemonic phrase was saved with the seed and can be revealed for a backup
+    #[serde(default)]
+    pub revealable: bool,
+    /// BIP-32 Master Key fingerprint (8 hex chars). It's known only after the seed was added or unlocked with the password,
+    /// and it's never known for a Hardware Key
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fingerprint: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Clone)]
@@ -77,6 +143,8 @@ pub struct SeedDefinitionOrReferenceJson {
 pub enum SeedDefinitionOrReferenceType {
     #[serde(rename = "mnemonic")]
     Mnemonic(MnemonicSeedJson),
+    #[serde(rename = "slip39")]
+    Slip39(Slip39SeedJson),
     #[serde(rename = "id")]
     Reference(Uuid),
     #[serde(rename = "ledger")]
@@ -87,6 +155,27 @@ pub enum SeedDefinitionOrReferenceType {
 pub struct MnemonicSeedJson {
     pub value: String,
     pub password: Option<String>,
+    /// Keep the encrypted mnemonic entropy in the Vault, so the phrase can be revealed later
+    #[serde(default)]
+    pub revealable: bool,
+}
+
+/// SLIP-39 shares to recover a seed
+#[derive(Deserialize, Clone, Debug, Eq, PartialEq)]
+pub struct Slip39SeedJson {
+    pub shares: Vec<String>,
+    pub passphrase: Option<String>,
+}
+
+/// Options to split a seed into SLIP-39 shares
+#[derive(Deserialize, Clone, Debug, Eq, PartialEq)]
+pub struct SplitSharesJson {
+    #[serde(rename = "groupThreshold")]
+    pub group_threshold: u8,
+    pub groups: Vec<GroupSpec>,
+    pub passphrase: Option<String>,
+    #[serde(rename = "iterationExponent")]
+    pub iteration_exponent: Option<u8>,
 }
 
 #[derive(Serialize, Clone, Debug, Eq, PartialEq)]
@@ -123,10 +212,13 @@ impl From<Seed> for SeedJson {
             },
             is_available: match value.source {
                 SeedSource::Bytes(_) => true,
-                SeedSource::Ledger(_) => false, //TODO
+                // depends on the connected device, see `list_internal`
+                SeedSource::Ledger(_) => false,
             },
             label: value.label,
             created_at: value.created_at,
+            revealable: false,
+            fingerprint: None,
         }
     }
 }
@@ -171,7 +263,7 @@ pub fn is_available<H>(cx: &mut FunctionContext, handler: H) -> Result<(), Vault
 #[neon_frame_fn(channel=3)]
 pub fn list_addresses<H>(cx: &mut FunctionContext, handler: H) -> Result<(), VaultNodeError>
     where
-        H: FnOnce(Result<HashMap<String, String>, VaultNodeError>) + Send + 'static {
+        H: FnOnce(Result<Vec<HDPathAddress>, VaultNodeError>) + Send + 'static {
     let cfg = Instance::get_vault()?;
 
     let json = cx
@@ -201,23 +293,12 @@ pub fn list_addresses<H>(cx: &mut FunctionContext, handler: H) -> Result<(), Vau
             .value(cx);
         hd_path_all.push(s)
     }
+    let hd_path_all = path_template::expand(&hd_path_all, path_template::MAX_EXPANDED_PATHS)?;
 
     std::thread::spawn(move || {
         let vault = cfg.lock().unwrap();
-
-        let result: Result<HashMap<String, String>, VaultNodeError> = match vault.list_addresses(parsed.clone(), hd_path_all.clone(), blockchain) {
-            Ok(addresses) => {
-                let mut result = HashMap::new();
-                for address in addresses {
-                    result.insert(
-                        address.hd_path.as_str().to_string(),
-                        address.address.to_string(),
-                    );
-                }
-                Ok(result)
-            }
-            Err(e) => Err(VaultNodeError::from(e))
-        };
+        let result = vault.list_addresses(parsed, hd_path_all, blockchain)
+            .map_err(VaultNodeError::from);
         handler(result);
     });
     Ok(())
@@ -250,27 +331,27 @@ pub fn add<H>(cx: &mut FunctionContext, handler: H) -> Result<(), VaultNodeError
 
 fn list_internal(vault: &WrappedVault) -> Result<Vec<SeedJson>, VaultNodeError> {
     let seeds = vault.list_seeds().map_err(VaultNodeError::from)?;
-    let mut result: Vec<SeedJson> = seeds.iter().map(|s| SeedJson::from(s.clone())).collect();
-
-    let has_ledger = result.iter().any(|e| match e.seed_type {
-        SeedType::Ledger => true,
-        SeedType::Bytes => false,
-    });
-
-    if has_ledger {
-        let ledger_connected = WrappedVault::is_ledger_connected().map_or(false, |v| v);
-        result = result
-            .iter()
-            .cloned()
-            .map(|e| match e.seed_type {
-                SeedType::Ledger => SeedJson {
-                    is_available: ledger_connected,
-                    ..e
-                },
-                SeedType::Bytes => e,
-            })
-            .collect()
-    }
+    let mnemonics = vault.cfg.get_mnemonic_storage();
+    let fingerprints = vault.cfg.get_fingerprint_storage();
+
+    let has_ledger = seeds.iter().any(|s| matches!(s.source, SeedSource::Ledger(_)));
+    let connected_ledger = if has_ledger {
+        vault.connected_ledger_seed(&seeds)
+    } else {
+        None
+    };
+
+    let result: Vec<SeedJson> = seeds.iter()
+        .map(|s| SeedJson {
+            revealable: mnemonics.exists(s.id),
+            fingerprint: fingerprints.get(s.id),
+            is_available: match s.source {
+                SeedSource::Bytes(_) => true,
+                SeedSource::Ledger(_) => connected_ledger == Some(s.id),
+            },
+            ..SeedJson::from(s.clone())
+        })
+        .collect();
     Ok(result)
 }
 
@@ -313,23 +394,37 @@ pub fn generate_mnemonic<H>(cx: &mut FunctionContext, handler: H) -> Result<(),
     Ok(())
 }
 
+#[neon_frame_fn(channel=1)]
+pub fn generate_mnemonic_from_entropy<H>(cx: &mut FunctionContext, handler: H) -> Result<(), VaultNodeError>
+    where
+        H: FnOnce(Result<MnemonicFromEntropyJson, VaultNodeError>) + Send + 'static {
+    let json = args_require_str(cx, 0, "entropy")?;
+    let options: EntropyJson = serde_json::from_str(json.as_str())
+        .map_err(|_| VaultNodeError::InvalidArgument(0))?;
+
+    std::thread::spawn(move || {
+        handler(entropy::generate_mnemonic(options));
+    });
+
+    Ok(())
+}
+
+/// Source of a new Ledger seed, with the fingerprints of the connected device to recognize it later
+pub(crate) fn connected_ledger_source() -> LedgerSource {
+    let mut source = LedgerSource::default();
+    if let Some(fps) = LedgerKeyShared::instance().ok().and_then(|k| k.find_fingerprints().ok()) {
+        source.fingerprints = fps;
+    }
+    source
+}
+
 fn list_hwkey_internal(vault: &WrappedVault) -> Result<Vec<LedgerDetails>, VaultNodeError> {
     match LedgerKeyShared::instance() {
         Ok(k) => {
             let mut result: Vec<LedgerDetails> = Vec::new();
             let app = k.get_app_details().ok();
-            let seed_id = if let Ok(fps) = k.find_fingerprints() {
-                if let Ok(seeds) = vault.list_seeds() {
-                    let seed = seeds.iter().find(|seed| {
-                        fps.iter().any(|fp| seed.is_same(fp))
-                    });
-                    seed.map(|seed| seed.id)
-                } else {
-                    None
-                }
-            } else {
-                None
-            };
+            let seed_id = vault.list_seeds().ok()
+                .and_then(|seeds| vault.connected_ledger_seed(&seeds));
             result.push(LedgerDetails {
                 connected: true,
                 app: app.clone().map(|a| a.name),
@@ -395,7 +490,258 @@ pub fn update<H>(cx: &mut FunctionContext, handler: H) -> Result<(), VaultNodeEr
     Ok(())
 }
 
+#[neon_frame_fn(channel=2)]
+pub fn remove<H>(cx: &mut FunctionContext, handler: H) -> Result<(), VaultNodeError>
+    where
+        H: FnOnce(Result<SeedRemoveJson, VaultNodeError>) + Send + 'static {
+
+    let vault = Instance::get_vault()?;
+
+    let seed_id = cx
+        .argument::<JsString>(0)
+        .map_err(|_| VaultNodeError::ArgumentMissing(0, "seed".to_string()))?
+        .value(cx);
+    let seed_id = Uuid::parse_str(seed_id.as_str())
+        .map_err(|_| VaultNodeError::InvalidArgumentByName("seed".to_string()))?;
+    let force = args_get_bool(cx, 1).unwrap_or(false);
+
+    std::thread::spawn(move || {
+        let vault = vault.lock().unwrap();
+        let result = vault.remove_seed(seed_id, force);
+        handler(result);
+    });
+
+    Ok(())
+}
+
+#[neon_frame_fn(channel=2)]
+pub fn reveal_mnemonic<H>(cx: &mut FunctionContext, handler: H) -> Result<(), VaultNodeError>
+    where
+        H: FnOnce(Result<String, VaultNodeError>) + Send + 'static {
+
+    let vault = Instance::get_vault()?;
+
+    let seed_id = cx
+        .argument::<JsString>(0)
+        .map_err(|_| VaultNodeError::ArgumentMissing(0, "seed".to_string()))?
+        .value(cx);
+    let seed_id = Uuid::parse_str(seed_id.as_str())
+        .map_err(|_| VaultNodeError::InvalidArgumentByName("seed".to_string()))?;
+    let password = args_require_str(cx, 1, "password")?;
+
+    std::thread::spawn(move || {
+        let vault = vault.lock().unwrap();
+        let result = vault.reveal_mnemonic(seed_id, password);
+        handler(result);
+    });
+
+    Ok(())
+}
+
+#[neon_frame_fn(channel=3)]
+pub fn split_shares<H>(cx: &mut FunctionContext, handler: H) -> Result<(), VaultNodeError>
+    where
+        H: FnOnce(Result<Vec<Vec<String>>, VaultNodeError>) + Send + 'static {
+
+    let vault = Instance::get_vault()?;
+
+    let seed_id = cx
+        .argument::<JsString>(0)
+        .map_err(|_| VaultNodeError::ArgumentMissing(0, "seed".to_string()))?
+        .value(cx);
+    let seed_id = Uuid::parse_str(seed_id.as_str())
+        .map_err(|_| VaultNodeError::InvalidArgumentByName("seed".to_string()))?;
+    let password = args_require_str(cx, 1, "password")?;
+    let options = args_require_str(cx, 2, "options")?;
+    let options: SplitSharesJson = serde_json::from_str(options.as_str())
+        .map_err(|_| VaultNodeError::InvalidArgument(2))?;
+
+    std::thread::spawn(move || {
+        let vault = vault.lock().unwrap();
+        let result = vault.split_seed_shares(seed_id, password, options);
+        handler(result);
+    });
+
+    Ok(())
+}
+
+#[neon_frame_fn(channel=4)]
+pub fn export_account_xpub<H>(cx: &mut FunctionContext, handler: H) -> Result<(), VaultNodeError>
+    where
+        H: FnOnce(Result<AccountXpubJson, VaultNodeError>) + Send + 'static {
+    let vault = Instance::get_vault()?;
+
+    let json = args_require_str(cx, 0, "seed")?;
+    let parsed: SeedDefinitionOrReferenceJson = serde_json::from_str(json.as_str())
+        .map_err(|_| VaultNodeError::InvalidArgument(0))?;
+
+    let blockchain = cx
+        .argument::<JsNumber>(1)
+        .map_err(|_| VaultNodeError::ArgumentMissing(1, "blockchain".to_string()))?
+        .value(cx);
+    let blockchain = Blockchain::try_from(blockchain as u32)
+        .map_err(|_| VaultNodeError::InvalidArgument(1))?;
+
+    let account = args_require_str(cx, 2, "accountPath")?;
+    let account = CustomHDPath::from_str(account.as_str())
+        .ok()
+        .filter(|p| p.len() == 3)
+        .and_then(|p| AccountHDPath::try_from(p).ok())
+        .ok_or(VaultNodeError::InvalidArgumentByName("accountPath".to_string()))?;
+
+    let format = match args_get_str(cx, 3) {
+        Some(value) => Some(XpubFormat::parse(value.as_str(), blockchain.as_bitcoin_network_kind())?),
+        None => None,
+    };
+
+    std::thread::spawn(move || {
+        let vault = vault.lock().unwrap();
+        let result = vault.export_account_xpub(parsed, blockchain, account, format);
+        handler(result);
+    });
+
+    Ok(())
+}
+
+#[neon_frame_fn(channel=3)]
+pub fn derive_bip85<H>(cx: &mut FunctionContext, handler: H) -> Result<(), VaultNodeError>
+    where
+        H: FnOnce(Result<Bip85ChildJson, VaultNodeError>) + Send + 'static {
+
+    let vault = Instance::get_vault()?;
+
+    let seed_id = cx
+        .argument::<JsString>(0)
+        .map_err(|_| VaultNodeError::ArgumentMissing(0, "seed".to_string()))?
+        .value(cx);
+    let seed_id = Uuid::parse_str(seed_id.as_str())
+        .map_err(|_| VaultNodeError::InvalidArgumentByName("seed".to_string()))?;
+    let password = args_require_str(cx, 1, "password")?;
+    let options = args_require_str(cx, 2, "options")?;
+    let options: Bip85OptionsJson = serde_json::from_str(options.as_str())
+        .map_err(|_| VaultNodeError::InvalidArgument(2))?;
+
+    std::thread::spawn(move || {
+        let vault = vault.lock().unwrap();
+        let result = vault.derive_bip85(seed_id, password, options);
+        handler(result);
+    });
+
+    Ok(())
+}
+
+#[neon_frame_fn(channel=3)]
+pub fn change_password<H>(cx: &mut FunctionContext, handler: H) -> Result<(), VaultNodeError>
+    where
+        H: FnOnce(Result<bool, VaultNodeError>) + Send + 'static {
+
+    let vault = Instance::get_vault()?;
+
+    let seed_id = cx
+        .argument::<JsString>(0)
+        .map_err(|_| VaultNodeError::ArgumentMissing(0, "seed".to_string()))?
+        .value(cx);
+    let seed_id = Uuid::parse_str(seed_id.as_str())
+        .map_err(|_| VaultNodeError::InvalidArgumentByName("seed".to_string()))?;
+    let current_password = args_require_str(cx, 1, "current_password")?;
+    let new_password = args_require_str(cx, 2, "new_password")?;
+
+    std::thread::spawn(move || {
+        let vault = vault.lock().unwrap();
+        let result = vault.change_seed_password(seed_id, current_password, new_password);
+        handler(result);
+    });
+
+    Ok(())
+}
+
+/// Derive an Ethereum key on any HD Path, including non-standard paths used by some wallets (ex. legacy MEW `m/44'/60'/0'/x`)
+pub(crate) fn derive_ethereum_key(seed: &[u8], hd_path: &CustomHDPath) -> Result<EthereumPrivateKey, VaultNodeError> {
+    let secp = Secp256k1::new();
+    let path = DerivationPath::from_str(hd_path.to_string().as_str())
+        .map_err(|_| VaultNodeError::InvalidArgumentValue("Invalid HDPath".to_string()))?;
+    let key = Xpriv::new_master(Network::Bitcoin, seed)
+        .and_then(|master| master.derive_priv(&secp, &path))
+        .map_err(|_| VaultNodeError::OtherProcessing("Failed to derive key".to_string()))?;
+    EthereumPrivateKey::try_from(&key.private_key.secret_bytes()[..])
+        .map_err(|_| VaultNodeError::OtherProcessing("Invalid derived key".to_string()))
+}
+
+fn normalize_hd_path(value: &str) -> String {
+    CustomHDPath::from_str(value)
+        .map(|p| p.to_string())
+        .unwrap_or(value.to_string())
+}
+
+/// Check if the path can be used on the blockchain, i.e. it's a standard path to an address, a path to a Bitcoin account,
+/// or any path for Ethereum
+fn is_supported_path(hd_path: &str, blockchain: Blockchain) -> bool {
+    if StandardHDPath::from_str(hd_path).is_ok() {
+        return true;
+    }
+    match CustomHDPath::from_str(hd_path) {
+        Ok(p) => match blockchain.get_type() {
+            BlockchainType::Bitcoin => p.len() == 3 && AccountHDPath::try_from(p).is_ok(),
+            BlockchainType::Ethereum => true,
+        },
+        Err(_) => false,
+    }
+}
+
+/// Put the results in the same order as the requested paths, and with the same path format as requested.
+/// A path without a result gets an error, so there is exactly one result for each requested path
+fn order_by_request(requested: &[String], results: Vec<HDPathAddress>) -> Vec<HDPathAddress> {
+    let mut by_path: HashMap<String, HDPathAddress> = results.into_iter()
+        .map(|a| (normalize_hd_path(a.hd_path.as_str()), a))
+        .collect();
+    requested.iter()
+        .map(|hd_path| match by_path.remove(&normalize_hd_path(hd_path.as_str())) {
+            Some(result) => HDPathAddress {
+                hd_path: hd_path.clone(),
+                ..result
+            },
+            None => HDPathAddress::failed(hd_path.clone(), "Address is not available for the path"),
+        })
+        .collect()
+}
+
 impl WrappedVault {
+    /// Find the seed of the connected Hardware Key, i.e. the seed which has the same fingerprint as the device.
+    /// A Ledger seed added without the fingerprints matches any device, as before. It doesn't change the storage, see
+    /// `connect_ledger_seed` to record the fingerprints.
+    fn connected_ledger_seed(&self, seeds: &[Seed]) -> Option<Uuid> {
+        self.find_connected_ledger_seed(seeds).map(|(seed, _)| seed.id)
+    }
+
+    /// The seed, and the source with the fingerprints of the connected device
+    fn find_connected_ledger_seed<'a>(&self, seeds: &'a [Seed]) -> Option<(&'a Seed, LedgerSource)> {
+        let k = LedgerKeyShared::instance().ok()?;
+        let mut source = LedgerSource::default();
+        source.fingerprints = k.find_fingerprints().ok()?;
+        if let Some(seed) = seeds.iter().find(|seed| source.fingerprints.iter().any(|fp| seed.is_same(fp))) {
+            return Some((seed, source));
+        }
+        seeds.iter()
+            .find(|seed| matches!(&seed.source, SeedSource::Ledger(ledger) if ledger.fingerprints.is_empty()))
+            .map(|seed| (seed, source))
+    }
+
+    /// Seed of the connected Hardware Key to add an entry with. A Ledger seed added without the fingerprints gets the
+    /// fingerprints of the device, so later it's matched only with the same device.
+    pub(crate) fn connect_ledger_seed(&self, seeds: &[Seed]) -> Result<Option<Uuid>, VaultNodeError> {
+        let (seed, source) = match self.find_connected_ledger_seed(seeds) {
+            Some(found) => found,
+            None => return Ok(None),
+        };
+        if matches!(&seed.source, SeedSource::Ledger(ledger) if ledger.fingerprints.is_empty()) {
+            self.cfg.get_storage().seeds().update(Seed {
+                source: SeedSource::Ledger(source),
+                ..seed.clone()
+            })?;
+        }
+        Ok(Some(seed.id))
+    }
+
     pub fn is_ledger_connected() -> Result<bool, VaultError> {
         Ok(LedgerKeyShared::instance()
             .map_err(|_| VaultError::PrivateKeyUnavailable)?
@@ -412,13 +758,17 @@ impl WrappedVault {
                 let seed = storage.seeds().get(id)?;
                 match seed.source {
                     SeedSource::Bytes(_) => true,
-                    SeedSource::Ledger(_) => WrappedVault::is_ledger_connected()?,
+                    // a connected device may be a different one, so it must be the same as the seed
+                    SeedSource::Ledger(_) => self.connected_ledger_seed(&storage.seeds().list_entries()?) == Some(id),
                 }
             }
             SeedDefinitionOrReferenceType::Ledger => WrappedVault::is_ledger_connected()?,
             SeedDefinitionOrReferenceType::Mnemonic(m) => {
                 Mnemonic::try_from(Language::English, m.value.as_str()).is_ok()
             }
+            SeedDefinitionOrReferenceType::Slip39(s) => {
+                slip39::combine(&s.shares, s.passphrase.unwrap_or_default().as_str()).is_ok()
+            }
         };
         Ok(connected)
     }
@@ -455,19 +805,16 @@ impl WrappedVault {
                 let mut result: Vec<HDPathAddress> = Vec::with_capacity(hd_path_std.len() + hd_path_acc.len());
                 if !hd_path_acc.is_empty() {
                     seed.get_xpub(password.clone(), &global, &hd_path_acc, blockchain)?.iter()
-                        .map(|a| HDPathAddress {
-                            hd_path: a.0.as_custom().to_string(), // convert to custom to encode as standrd hd path
-                            address: a.1.to_string(),
-                        })
+                        .map(|a| HDPathAddress::ok(
+                            a.0.as_custom().to_string(), // convert to custom to encode as standrd hd path
+                            a.1.to_string(),
+                        ))
                         .for_each(|a| result.push(a));
                 };
 
                 seed.get_addresses::<Address>(password, global, &hd_path_std, blockchain)?
                     .iter()
-                    .map(|a| HDPathAddress {
-                        hd_path: a.0.to_string(),
-                        address: a.1.to_string(),
-                    })
+                    .map(|a| HDPathAddress::ok(a.0.to_string(), a.1.to_string()))
                     .for_each(|a| result.push(a));
 
                 result
@@ -475,10 +822,7 @@ impl WrappedVault {
             BlockchainType::Ethereum => {
                 seed.get_addresses::<EthereumAddress>(password, global, &hd_path_std, blockchain)?
                     .iter()
-                    .map(|a| HDPathAddress {
-                        hd_path: a.0.to_string(),
-                        address: a.1.to_string(),
-                    })
+                    .map(|a| HDPathAddress::ok(a.0.to_string(), a.1.to_string()))
                     .collect()
             }
         };
@@ -490,24 +834,105 @@ impl WrappedVault {
         seed_ref: SeedDefinitionOrReferenceJson,
         hd_path_all: Vec<String>,
         blockchain: Blockchain,
-    ) -> Result<Vec<HDPathAddress>, VaultError> {
+    ) -> Result<Vec<HDPathAddress>, VaultNodeError> {
         let storage = &self.cfg.get_storage();
-        let addresses = match seed_ref.value {
+        let requested = hd_path_all.clone();
+        let (hd_path_all, invalid): (Vec<String>, Vec<String>) = hd_path_all.into_iter()
+            .partition(|s| is_supported_path(s.as_str(), blockchain));
+        // Vault derives addresses only on standard paths. Others (ex. `m/44'/60'/0'/1`) are derived here directly from the seed
+        let hd_path_custom: Vec<CustomHDPath> = match blockchain.get_type() {
+            BlockchainType::Ethereum => hd_path_all.iter()
+                .filter(|s| StandardHDPath::from_str(s.as_str()).is_err())
+                .filter_map(|s| CustomHDPath::from_str(s.as_str()).ok())
+                .collect(),
+            BlockchainType::Bitcoin => vec![],
+        };
+        // Vault doesn't support Taproot, so those are derived here too
+        let (hd_path_taproot, hd_path_all): (Vec<String>, Vec<String>) = hd_path_all.into_iter()
+            .partition(|s| matches!(blockchain.get_type(), BlockchainType::Bitcoin) && taproot::is_taproot_path(s.as_str()));
+        let derive_locally = !hd_path_custom.is_empty() || !hd_path_taproot.is_empty();
+        let (mut addresses, raw) = match seed_ref.value {
             SeedDefinitionOrReferenceType::Reference(id) => {
                 let seed = storage.seeds().get(id)?;
-                self.list_seed_addresses(seed.source, seed_ref.password, hd_path_all, blockchain)?
+                // the seed is decrypted here when the fingerprint is not known yet, to calculate it on the same seed bytes
+                let unknown_fingerprint = seed_ref.password.is_some() && self.cfg.get_fingerprint_storage().get(id).is_none();
+                let raw = match seed.source {
+                    SeedSource::Bytes(_) if derive_locally || unknown_fingerprint => {
+                        let password = seed_ref.password.clone().ok_or(VaultError::PasswordRequired)?;
+                        Some(self.get_seed_bytes(id, password)?)
+                    },
+                    _ => None
+                };
+                let addresses = match &raw {
+                    Some(raw) => {
+                        let temp_seed = SeedSource::create_raw(raw.clone())?;
+                        self.list_seed_addresses(temp_seed, Some("NONE".to_string()), hd_path_all, blockchain)?
+                    }
+                    None => self.list_seed_addresses(seed.source, seed_ref.password, hd_path_all, blockchain)?,
+                };
+                (addresses, raw)
             }
             SeedDefinitionOrReferenceType::Mnemonic(m) => {
                 let mnemonic = Mnemonic::try_from(Language::English, m.value.as_str())
                     .map_err(|_| VaultError::InvalidDataError("Failed to parse mnemonic phrase".to_string()))?;
-                let temp_seed = SeedSource::create_raw(mnemonic.seed(m.password))?;
-                self.list_seed_addresses(temp_seed, Some("NONE".to_string()), hd_path_all, blockchain)?
+                let raw = mnemonic.seed(m.password);
+                let temp_seed = SeedSource::create_raw(raw.clone())?;
+                (self.list_seed_addresses(temp_seed, Some("NONE".to_string()), hd_path_all, blockchain)?, Some(raw))
+            }
+            SeedDefinitionOrReferenceType::Slip39(s) => {
+                let raw = slip39::combine(&s.shares, s.passphrase.unwrap_or_default().as_str())
+                    .map_err(|_| VaultError::InvalidDataError("Failed to recover seed from SLIP-39 shares".to_string()))?;
+                let temp_seed = SeedSource::create_raw(raw.clone())?;
+                (self.list_seed_addresses(temp_seed, Some("NONE".to_string()), hd_path_all, blockchain)?, Some(raw))
             }
             SeedDefinitionOrReferenceType::Ledger => {
-                self.list_seed_addresses(SeedSource::Ledger(LedgerSource::default()), None, hd_path_all, blockchain)?
+                (self.list_ledger_addresses(hd_path_all, blockchain), None)
             }
         };
-        Ok(addresses)
+        invalid.into_iter()
+            .for_each(|hd_path| addresses.push(HDPathAddress::failed(hd_path, "Invalid HD Path")));
+        match raw {
+            Some(raw) => {
+                for hd_path in hd_path_custom {
+                    let address = match derive_ethereum_key(&raw, &hd_path) {
+                        Ok(key) => HDPathAddress::ok(hd_path.to_string(), key.to_address().to_string()),
+                        Err(_) => HDPathAddress::failed(hd_path.to_string(), "Address is not available for the path"),
+                    };
+                    addresses.push(address)
+                }
+                for hd_path in hd_path_taproot {
+                    let address = match taproot::derive_from_seed(&raw, hd_path.as_str(), blockchain.as_bitcoin_network_kind()) {
+                        Ok(value) => HDPathAddress::ok(hd_path, value),
+                        Err(_) => HDPathAddress::failed(hd_path, "Address is not available for the path"),
+                    };
+                    addresses.push(address)
+                }
+            }
+            None => hd_path_taproot.into_iter()
+                .for_each(|hd_path| addresses.push(HDPathAddress::failed(hd_path, "Taproot is not supported by Hardware Key")))
+        }
+        Ok(order_by_request(&requested, addresses))
+    }
+
+    /// Hardware Key may fail on a particular path (ex. when a wrong app is opened), so it requests each path separately
+    /// to report the error for that path instead of failing all of them
+    fn list_ledger_addresses(&self, hd_path_all: Vec<String>, blockchain: Blockchain) -> Vec<HDPathAddress> {
+        hd_path_all.into_iter()
+            .map(|hd_path| {
+                if matches!(blockchain.get_type(), BlockchainType::Ethereum) && StandardHDPath::from_str(hd_path.as_str()).is_err() {
+                    return HDPathAddress::failed(hd_path, "Non-standard HD Path is not supported by Hardware Key");
+                }
+                let seed = SeedSource::Ledger(LedgerSource::default());
+                match self.list_seed_addresses(seed, None, vec![hd_path.clone()], blockchain) {
+                    Ok(mut addresses) => addresses.pop()
+                        .unwrap_or(HDPathAddress::failed(hd_path, "Address is not available for the path")),
+                    Err(e) => {
+                        let (_, message): (usize, String) = VaultNodeError::from(e).into();
+                        HDPathAddress::failed(hd_path, message.as_str())
+                    }
+                }
+            })
+            .collect()
     }
 
     pub fn list_seeds(&self) -> Result<Vec<Seed>, VaultError> {
@@ -515,27 +940,309 @@ impl WrappedVault {
         storage.seeds().list_entries()
     }
 
-    pub fn add_seed(&self, seed: SeedDefinitionOrReferenceJson) -> Result<Uuid, VaultError> {
+    /// Find all wallet entries and reserved accounts that refer to the seed
+    fn find_seed_usage(&self, seed_id: Uuid) -> Result<Vec<SeedUsageJson>, VaultNodeError> {
+        let mut result = Vec::new();
+        for wallet in self.load_wallets()? {
+            wallet.entries.iter()
+                .filter(|e| match &e.key {
+                    PKType::SeedHd(seed) => seed.seed_id == seed_id,
+                    _ => false
+                })
+                .for_each(|e| result.push(SeedUsageJson {
+                    wallet_id: wallet.id,
+                    entry_id: Some(EntryId::from(&wallet, e).to_string()),
+                    account_id: None,
+                }));
+            wallet.reserved.iter()
+                .filter(|r| r.seed_id == seed_id)
+                .for_each(|r| result.push(SeedUsageJson {
+                    wallet_id: wallet.id,
+                    entry_id: None,
+                    account_id: Some(r.account_id),
+                }));
+        }
+        Ok(result)
+    }
+
+    /// Remove the seed from the vault. If it's still used by a wallet the seed is kept and the result lists all the references,
+    /// unless `force` is set, in which case the referencing entries and reserved accounts are removed together with the seed.
+    pub fn remove_seed(&self, seed_id: Uuid, force: bool) -> Result<SeedRemoveJson, VaultNodeError> {
+        let storage = &self.cfg.get_storage();
+        // fail early on an unknown seed, so we don't touch any wallet for it
+        let _ = storage.seeds().get(seed_id)?;
+
+        let used_by = self.find_seed_usage(seed_id)?;
+        if !used_by.is_empty() && !force {
+            return Ok(SeedRemoveJson {
+                removed: false,
+                used_by,
+            });
+        }
+
+        self.ensure_no_policy_seed(seed_id, "Seed removal")?;
+        let wallet_ids: HashSet<Uuid> = used_by.iter().map(|u| u.wallet_id).collect();
+        for wallet_id in wallet_ids {
+            let mut wallet = storage.wallets().get(wallet_id)?;
+            wallet.entries.retain(|e| match &e.key {
+                PKType::SeedHd(seed) => seed.seed_id != seed_id,
+                _ => true
+            });
+            wallet.reserved.retain(|r| r.seed_id != seed_id);
+            storage.wallets().update(wallet)?;
+        }
+
+        let removed = storage.seeds().remove(seed_id)?;
+        if removed {
+            self.cfg.get_mnemonic_storage().remove(seed_id)?;
+            self.cfg.get_fingerprint_storage().remove(seed_id)?;
+            // the icon is optional, and it's fine if there is nothing to remove
+            let _ = storage.icons().update(seed_id, None);
+        }
+        Ok(SeedRemoveJson {
+            removed,
+            used_by,
+        })
+    }
+
+    /// Global Key used by the seed, or `None` for a seed with an individual password
+    fn seed_global_key(&self, seed: &Seed) -> Result<Option<GlobalKey>, VaultNodeError> {
+        if seed.is_odd_key() {
+            Ok(None)
+        } else {
+            Ok(self.cfg.get_storage().global_key().get_if_exists()?)
+        }
+    }
+
+    /// Decrypt the raw seed bytes. Available only for a seed stored in the Vault, not for a Hardware Key.
+    pub(crate) fn get_seed_bytes(&self, seed_id: Uuid, password: String) -> Result<Vec<u8>, VaultNodeError> {
+        let storage = &self.cfg.get_storage();
+        let seed = storage.seeds().get(seed_id)?;
+        match &seed.source {
+            SeedSource::Bytes(encrypted) => {
+                let global = self.seed_global_key(&seed)?;
+                let seed = encrypted.decrypt(password.as_bytes(), global)
+                    .map_err(|e| VaultNodeError::from(VaultError::from(e)))?;
+                // it's only an additional detail, so a failure to save it doesn't affect the actual operation
+                let _ = self.cfg.get_fingerprint_storage().remember(seed_id, &seed);
+                Ok(seed)
+            }
+            SeedSource::Ledger(_) => Err(VaultNodeError::OtherInput("Not available for a Hardware Key".to_string()))
+        }
+    }
+
+    /// Re-encrypt a seed that uses an individual password (i.e., not the Global Key) with a new password. A seed encrypted
+    /// with the Global Key gets a new password only with the Global Key itself.
+    /// The original seed is copied to a hidden backup before the change and is restored from it if anything fails. The backup
+    /// is removed in both cases.
+    ///
+    /// Returns `false` if the current password is invalid.
+    pub fn change_seed_password(&self, seed_id: Uuid, current_password: String, new_password: String) -> Result<bool, VaultNodeError> {
+        if new_password.is_empty() {
+            return Err(VaultNodeError::InvalidArgumentByName("new_password".to_string()));
+        }
+        let storage = &self.cfg.get_storage();
+        let original = storage.seeds().get(seed_id)?;
+        let encrypted = match &original.source {
+            SeedSource::Bytes(encrypted) => encrypted,
+            SeedSource::Ledger(_) => return Err(VaultNodeError::OtherInput("Not available for a Hardware Key".to_string()))
+        };
+        if !original.is_odd_key() {
+            return Err(VaultNodeError::OtherInput("Seed is encrypted with the Global Key, change the Global Key password instead".to_string()));
+        }
+
+        let raw = match encrypted.decrypt(current_password.as_bytes(), None) {
+            Ok(raw) => raw,
+            // if CryptoFailed with WrongKey then the current_password is invalid, so return just false
+            Err(CryptoError::WrongKey) => return Ok(false),
+            Err(e) => return Err(VaultNodeError::from(VaultError::from(e))),
+        };
+        // the stored mnemonic is encrypted in the same way as the seed, so it gets the new password too
+        let mnemonics = self.cfg.get_mnemonic_storage();
+        let entropy = mnemonics.load(seed_id, current_password.as_str(), None)?;
+        let reencrypted = Encrypted::encrypt(raw.clone(), new_password.as_bytes(), None)
+            .map_err(VaultError::from)?;
+        let secrets = self.cfg.get_secret_storage();
+        secrets.backup_seed(&original)?;
+        let updated = Seed {
+            source: SeedSource::Bytes(reencrypted),
+            ..original.clone()
+        };
+
+        let verified = storage.seeds().update(updated)
+            .and_then(|_| storage.seeds().get(seed_id))
+            .map(|stored| match &stored.source {
+                SeedSource::Bytes(encrypted) => encrypted.decrypt(new_password.as_bytes(), None)
+                    .map_or(false, |value| value == raw),
+                SeedSource::Ledger(_) => false,
+            })
+            .map_err(VaultNodeError::from)
+            .and_then(|verified| match (&entropy, verified) {
+                (Some(entropy), true) => mnemonics.save(seed_id, entropy, new_password.as_str(), None).map(|_| true),
+                _ => Ok(verified),
+            });
+        let failure = match verified {
+            Ok(true) => None,
+            Ok(false) => Some(VaultNodeError::OtherProcessing("Failed to verify the re-encrypted seed".to_string())),
+            Err(e) => Some(e),
+        };
+        // the mnemonic is saved only after the seed, so if it failed it's still encrypted with the current password
+        let restored = match &failure {
+            Some(_) => {
+                let backup = secrets.seed_backup(seed_id).ok().flatten().unwrap_or(original);
+                storage.seeds().update(backup).map(|_| ())
+            }
+            None => Ok(()),
+        };
+        let _ = secrets.remove(SecretKind::SeedBackup, seed_id);
+        match (failure, restored) {
+            (None, _) => Ok(true),
+            (Some(failure), Ok(_)) => Err(failure),
+            (Some(failure), Err(e)) => {
+                let (_, message): (usize, String) = failure.into();
+                Err(VaultNodeError::OtherProcessing(format!("{}. Failed to restore the seed. Error: {:?}", message, e)))
+            }
+        }
+    }
+
+    pub fn reveal_mnemonic(&self, seed_id: Uuid, password: String) -> Result<String, VaultNodeError> {
+        self.ensure_no_policy_seed(seed_id, "Mnemonic export")?;
+        let seed = self.cfg.get_storage().seeds().get(seed_id)?;
+        // it's encrypted in the same way as the seed, so the decryption verifies the seed password
+        let entropy = self.cfg.get_mnemonic_storage().load(seed_id, password.as_str(), self.seed_global_key(&seed)?)?
+            .ok_or(VaultNodeError::MissingData("Mnemonic is not stored for the seed".to_string()))?;
+        let mnemonic = bip39::Mnemonic::from_entropy_in(bip39::Language::English, &entropy)
+            .map_err(|_| VaultNodeError::OtherProcessing("Invalid stored mnemonic".to_string()))?;
+        Ok(mnemonic.to_string())
+    }
+
+    /// Encrypt raw seed bytes for a new seed. It always uses the Global Key, so a new seed never gets an individual password
+    fn encrypt_seed(&self, raw: Vec<u8>, password: Option<String>) -> Result<Encrypted, VaultNodeError> {
         let storage = &self.cfg.get_storage();
+        let password = password.ok_or(VaultError::PasswordRequired)?;
+        if !storage.global_key().is_set() {
+            return Err(VaultError::GlobalKeyRequired.into());
+        }
+        let global = storage.global_key().get_if_exists()?;
+        let encrypted = Encrypted::encrypt(raw, password.as_bytes(), global)
+            .map_err(VaultError::from)?;
+        Ok(encrypted)
+    }
+
+    /// Split a stored seed into SLIP-39 shares
+    pub fn split_seed_shares(&self, seed_id: Uuid, password: String, options: SplitSharesJson) -> Result<Vec<Vec<String>>, VaultNodeError> {
+        self.ensure_no_policy_seed(seed_id, "Seed export")?;
+        let raw = self.get_seed_bytes(seed_id, password)?;
+        slip39::split(
+            &raw,
+            options.passphrase.unwrap_or_default().as_str(),
+            options.group_threshold,
+            &options.groups,
+            options.iteration_exponent.unwrap_or(1),
+        )
+    }
+
+    /// Derive a BIP-85 child secret from a stored seed, and optionally import a derived mnemonic as a new seed
+    pub fn derive_bip85(&self, seed_id: Uuid, password: String, options: Bip85OptionsJson) -> Result<Bip85ChildJson, VaultNodeError> {
+        if options.import.is_some() {
+            if options.application != Bip85Application::Bip39 {
+                return Err(VaultNodeError::InvalidArgumentValue("Only a mnemonic can be imported as a seed".to_string()));
+            }
+            let english = options.language.as_ref()
+                .map_or(true, |l| l.eq_ignore_ascii_case("english") || l.eq_ignore_ascii_case("en"));
+            if !english {
+                return Err(VaultNodeError::InvalidArgumentValue("Only an English mnemonic can be imported as a seed".to_string()));
+            }
+        }
+        // a raw key can be imported as an entry, which would have no policy
+        if matches!(options.application, Bip85Application::Wif | Bip85Application::Hex) {
+            self.ensure_no_policy_seed(seed_id, "Private Key export")?;
+        }
+        let raw = self.get_seed_bytes(seed_id, password)?;
+        let mut child = bip85::derive(&raw, &options)?;
+        if let Some(import) = options.import {
+            let seed_id = self.add_seed(SeedDefinitionOrReferenceJson {
+                value: SeedDefinitionOrReferenceType::Mnemonic(MnemonicSeedJson {
+                    value: child.value.clone(),
+                    password: None,
+                    revealable: import.revealable,
+                }),
+                password: import.password,
+                label: import.label,
+            }.clean())?;
+            child.seed_id = Some(seed_id.to_string());
+        }
+        Ok(child)
+    }
+
+    /// Export the Extended Public Key of a Bitcoin account, with its key origin. It's not available for a Hardware Key,
+    /// because the device doesn't provide the Master Key fingerprint.
+    pub fn export_account_xpub(&self,
+                               seed_ref: SeedDefinitionOrReferenceJson,
+                               blockchain: Blockchain,
+                               account: AccountHDPath,
+                               format: Option<XpubFormat>) -> Result<AccountXpubJson, VaultNodeError> {
+        if blockchain.get_type() != BlockchainType::Bitcoin {
+            return Err(VaultNodeError::InvalidArgumentValue("Extended Public Key is available only for Bitcoin".to_string()));
+        }
+        let path = DerivationPath::from_str(account.to_string().as_str())
+            .map_err(|_| VaultNodeError::InvalidArgumentByName("accountPath".to_string()))?;
+        let network = blockchain.as_bitcoin_network_kind();
+        let hardware_key = || VaultNodeError::OtherInput("Not available for a Hardware Key".to_string());
+        let raw = match seed_ref.value {
+            SeedDefinitionOrReferenceType::Reference(id) => {
+                let seed = self.cfg.get_storage().seeds().get(id)?;
+                match seed.source {
+                    SeedSource::Bytes(_) => {
+                        let password = seed_ref.password.ok_or(VaultError::PasswordRequired)?;
+                        self.get_seed_bytes(id, password)?
+                    }
+                    SeedSource::Ledger(_) => return Err(hardware_key())
+                }
+            }
+            SeedDefinitionOrReferenceType::Mnemonic(m) => {
+                let mnemonic = Mnemonic::try_from(Language::English, m.value.as_str())
+                    .map_err(|_| VaultError::InvalidDataError("Failed to parse mnemonic phrase".to_string()))?;
+                mnemonic.seed(m.password)
+            }
+            SeedDefinitionOrReferenceType::Slip39(s) => {
+                slip39::combine(&s.shares, s.passphrase.unwrap_or_default().as_str())?
+            }
+            SeedDefinitionOrReferenceType::Ledger => return Err(hardware_key())
+        };
+        xpub::export_from_seed(&raw, &path, network, format)
+    }
+
+    pub fn add_seed(&self, seed: SeedDefinitionOrReferenceJson) -> Result<Uuid, VaultNodeError> {
+        let storage = &self.cfg.get_storage();
+        // entropy to keep the mnemonic, if requested
+        let mut revealable: Option<Vec<u8>> = None;
+        // raw seed to calculate its fingerprint
+        let mut added_raw: Option<Vec<u8>> = None;
         let seed_source = match seed.value {
-            SeedDefinitionOrReferenceType::Ledger => SeedSource::Ledger(LedgerSource::default()),
+            SeedDefinitionOrReferenceType::Ledger => SeedSource::Ledger(connected_ledger_source()),
             SeedDefinitionOrReferenceType::Mnemonic(value) => {
-                if seed.password.is_none() {
-                    return Err(VaultError::PasswordRequired);
-                }
-                if !storage.global_key().is_set() {
-                    return Err(VaultError::GlobalKeyRequired);
-                }
-                let global = storage.global_key().get_if_exists()?;
                 let mnemonic = Mnemonic::try_from(Language::English, value.value.as_str())
                     .map_err(|_| VaultError::InvalidDataError("mnemonic".to_string()))?;
                 let raw = mnemonic.seed(value.password);
-                SeedSource::Bytes(Encrypted::encrypt(raw, seed.password.unwrap().as_bytes(), global)?)
+                if value.revealable {
+                    let entropy = bip39::Mnemonic::parse_in(bip39::Language::English, value.value.as_str())
+                        .map_err(|_| VaultError::InvalidDataError("mnemonic".to_string()))?
+                        .to_entropy();
+                    revealable = Some(entropy);
+                }
+                added_raw = Some(raw.clone());
+                SeedSource::Bytes(self.encrypt_seed(raw, seed.password.clone())?)
+            }
+            SeedDefinitionOrReferenceType::Slip39(value) => {
+                let raw = slip39::combine(&value.shares, value.passphrase.unwrap_or_default().as_str())?;
+                added_raw = Some(raw.clone());
+                SeedSource::Bytes(self.encrypt_seed(raw, seed.password.clone())?)
             }
             SeedDefinitionOrReferenceType::Reference(_) => {
                 return Err(VaultError::UnsupportedDataError(
                     "Cannot create Seed from existing seed".to_string(),
-                ))
+                ).into())
             }
         };
         let id = storage.seeds().add(Seed {
@@ -544,13 +1251,24 @@ impl WrappedVault {
             label: seed.label,
             created_at: Utc::now(),
         })?;
+        if let Some(entropy) = revealable {
+            // a new seed is always encrypted with the Global Key, see `encrypt_seed`
+            let password = seed.password.ok_or(VaultError::PasswordRequired)?;
+            let global = storage.global_key().get_if_exists()?;
+            self.cfg.get_mnemonic_storage().save(id, &entropy, password.as_str(), global)?;
+        }
+        if let Some(raw) = added_raw {
+            self.cfg.get_fingerprint_storage().remember(id, &raw)?;
+        }
         Ok(id)
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::seeds::{MnemonicSeedJson, SeedDefinitionOrReferenceJson, SeedDefinitionOrReferenceType};
+    use crate::seeds::{is_supported_path, order_by_request, HDPathAddress, MnemonicSeedJson, SeedDefinitionOrReferenceJson, SeedDefinitionOrReferenceType, Slip39SeedJson, SplitSharesJson};
+    use crate::slip39::GroupSpec;
+    use emerald_vault::chains::Blockchain;
     use std::str::FromStr;
     use uuid::Uuid;
 
@@ -613,6 +1331,7 @@ mod tests {
                 value: SeedDefinitionOrReferenceType::Mnemonic(MnemonicSeedJson {
                     value: "test test".to_string(),
                     password: None,
+                    revealable: false,
                 }),
                 password: None,
                 label: None,
@@ -631,6 +1350,7 @@ mod tests {
                 value: SeedDefinitionOrReferenceType::Mnemonic(MnemonicSeedJson {
                     value: "test test".to_string(),
                     password: None,
+                    revealable: false,
                 }),
                 password: None,
                 label: Some("My Seed".to_string()),
@@ -639,6 +1359,25 @@ mod tests {
         );
     }
 
+    #[test]
+    fn parse_revealable_mnemonic() {
+        let json = "{\"type\": \"mnemonic\", \"value\": {\"value\": \"test test\", \"revealable\": true}}";
+        let parsed: SeedDefinitionOrReferenceJson = serde_json::from_str(json).expect("parsed");
+
+        assert_eq!(
+            SeedDefinitionOrReferenceJson {
+                value: SeedDefinitionOrReferenceType::Mnemonic(MnemonicSeedJson {
+                    value: "test test".to_string(),
+                    password: None,
+                    revealable: true,
+                }),
+                password: None,
+                label: None,
+            },
+            parsed
+        );
+    }
+
     #[test]
     fn parse_mnemonic_with_passphrase() {
         let json = "{\"type\": \"mnemonic\", \"value\": {\"value\": \"test test\", \"password\": \"hello\"}}";
@@ -649,6 +1388,7 @@ mod tests {
                 value: SeedDefinitionOrReferenceType::Mnemonic(MnemonicSeedJson {
                     value: "test test".to_string(),
                     password: Some("hello".to_string()),
+                    revealable: false,
                 }),
                 password: None,
                 label: None
@@ -667,6 +1407,7 @@ mod tests {
                 value: SeedDefinitionOrReferenceType::Mnemonic(MnemonicSeedJson {
                     value: "test test".to_string(),
                     password: Some("hello".to_string()),
+                    revealable: false,
                 }),
                 password: Some("word!".to_string()),
                 label: None
@@ -674,4 +1415,81 @@ mod tests {
             parsed
         );
     }
+
+    #[test]
+    fn parse_slip39() {
+        let json = "{\"type\": \"slip39\", \"value\": {\"shares\": [\"test one\", \"test two\"]}, \"password\": \"1234\"}";
+        let parsed: SeedDefinitionOrReferenceJson = serde_json::from_str(json).expect("parsed");
+
+        assert_eq!(
+            SeedDefinitionOrReferenceJson {
+                value: SeedDefinitionOrReferenceType::Slip39(Slip39SeedJson {
+                    shares: vec!["test one".to_string(), "test two".to_string()],
+                    passphrase: None,
+                }),
+                password: Some("1234".to_string()),
+                label: None,
+            },
+            parsed
+        );
+    }
+
+    #[test]
+    fn parse_split_shares() {
+        let json = "{\"groupThreshold\": 2, \"groups\": [{\"threshold\": 1, \"count\": 1}, {\"threshold\": 2, \"count\": 3}]}";
+        let parsed: SplitSharesJson = serde_json::from_str(json).expect("parsed");
+
+        assert_eq!(
+            SplitSharesJson {
+                group_threshold: 2,
+                groups: vec![
+                    GroupSpec { threshold: 1, count: 1 },
+                    GroupSpec { threshold: 2, count: 3 },
+                ],
+                passphrase: None,
+                iteration_exponent: None,
+            },
+            parsed
+        );
+    }
+
+    #[test]
+    fn order_addresses_as_requested() {
+        let addresses = vec![
+            HDPathAddress::ok("m/84'/0'/0'/0/1".to_string(), "addr-1".to_string()),
+            HDPathAddress::ok("m/84'/0'/1'".to_string(), "xpub-1".to_string()),
+            HDPathAddress::failed("m/84'/0'/2'".to_string(), "Test Error"),
+            HDPathAddress::ok("m/84'/0'/0'/0/0".to_string(), "addr-0".to_string()),
+        ];
+        let requested = vec![
+            "m/84'/0'/0'/0/0".to_string(),
+            "m/84'/0'/1'".to_string(),
+            "m/84'/0'/5'/0/0/0/0".to_string(),
+            "m/84'/0'/2'".to_string(),
+            "m/84'/0'/0'/0/1".to_string(),
+        ];
+        let act: Vec<(String, Option<String>, Option<String>)> = order_by_request(&requested, addresses).into_iter()
+            .map(|a| (a.hd_path, a.address, a.error))
+            .collect();
+        assert_eq!(act, vec![
+            ("m/84'/0'/0'/0/0".to_string(), Some("addr-0".to_string()), None),
+            ("m/84'/0'/1'".to_string(), Some("xpub-1".to_string()), None),
+            ("m/84'/0'/5'/0/0/0/0".to_string(), None, Some("Address is not available for the path".to_string())),
+            ("m/84'/0'/2'".to_string(), None, Some("Test Error".to_string())),
+            ("m/84'/0'/0'/0/1".to_string(), Some("addr-1".to_string()), None),
+        ]);
+    }
+
+    #[test]
+    fn check_supported_paths() {
+        assert!(is_supported_path("m/44'/60'/0'/0/0", Blockchain::Ethereum));
+        assert!(is_supported_path("m/44'/60'/0'/1", Blockchain::Ethereum));
+        assert!(is_supported_path("m/84'/0'/0'/0/0", Blockchain::Bitcoin));
+        assert!(is_supported_path("m/84'/0'/0'", Blockchain::Bitcoin));
+        assert!(is_supported_path("m/86'/0'/0'", Blockchain::Bitcoin));
+        assert!(is_supported_path("m/86'/0'/0'/0/0", Blockchain::Bitcoin));
+        assert!(!is_supported_path("m/44'/0'/0'/1", Blockchain::Bitcoin));
+        assert!(!is_supported_path("m/44'/60'/x'/0/0", Blockchain::Ethereum));
+        assert!(!is_supported_path("", Blockchain::Ethereum));
+    }
 }
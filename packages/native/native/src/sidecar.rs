@@ -0,0 +1,186 @@
+//! Files which the node keeps next to the Vault storage, such as multisig setups or spending policies.
+//!
+//! The Vault snapshot includes only the Vault data, so a snapshot created by the node has these files appended to it:
+//! `MAGIC | length of the Vault snapshot (u64, big endian) | Vault snapshot | JSON with the files`.
+//! A plain Vault snapshot, ex. created by an older version, is still accepted on restore, and it doesn't change the files.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use base64::Engine;
+use crate::errors::VaultNodeError;
+
+const MAGIC: &[u8; 8] = b"EMVNODE1";
+const VERSION: u32 = 1;
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, Eq, PartialEq)]
+pub struct SideFiles {
+    version: u32,
+    /// File name -> base64 of the content
+    files: BTreeMap<String, String>,
+}
+
+/// Name of a file in the storage directory, i.e. not a path, with one of the `extensions`. A temp file has an extension
+/// like `multisig.tmp`, so it never matches.
+fn is_side_file(name: &str, extensions: &[&str]) -> bool {
+    let valid_name = !name.starts_with('.') && !name.contains('/') && !name.contains('\\');
+    valid_name && name.rsplit_once('.').is_some_and(|(_, ext)| extensions.contains(&ext))
+}
+
+impl SideFiles {
+    /// Read all files with any of the `extensions` from the directory
+    pub fn collect(dir: &Path, extensions: &[&str]) -> Result<SideFiles, VaultNodeError> {
+        let entries = fs::read_dir(dir)
+            .map_err(|e| VaultNodeError::OtherProcessing(format!("Failed to read directory. Error: {}", e)))?;
+        let mut files = BTreeMap::new();
+        for path in entries.filter_map(|entry| entry.ok()).map(|entry| entry.path()).filter(|path| path.is_file()) {
+            let name = match path.file_name().and_then(|name| name.to_str()) {
+                Some(name) if is_side_file(name, extensions) => name.to_string(),
+                _ => continue,
+            };
+            let content = fs::read(&path)
+                .map_err(|e| VaultNodeError::OtherProcessing(format!("Failed to read {}. Error: {}", name, e)))?;
+            files.insert(name, base64::engine::general_purpose::STANDARD.encode(content));
+        }
+        Ok(SideFiles {
+            version: VERSION,
+            files,
+        })
+    }
+
+    /// Replace all files with any of the `extensions` in the directory with these files
+    pub fn replace(&self, dir: &Path, extensions: &[&str]) -> Result<(), VaultNodeError> {
+        // check everything before any change, so an invalid snapshot doesn't remove the current files
+        let mut decoded = Vec::with_capacity(self.files.len());
+        for (name, content) in &self.files {
+            if !is_side_file(name, extensions) {
+                return Err(VaultNodeError::InvalidArgumentValue(format!("Invalid file in the snapshot: {}", name)));
+            }
+            let content = base64::engine::general_purpose::STANDARD.decode(content)
+                .map_err(|_| VaultNodeError::InvalidArgumentValue(format!("Invalid file in the snapshot: {}", name)))?;
+            decoded.push((name, content));
+        }
+        let current = SideFiles::collect(dir, extensions)?;
+        for name in current.files.keys().filter(|name| !self.files.contains_key(*name)) {
+            fs::remove_file(dir.join(name))
+                .map_err(|e| VaultNodeError::OtherProcessing(format!("Failed to remove {}. Error: {}", name, e)))?;
+        }
+        for (name, content) in decoded {
+            let target = dir.join(name);
+            let temp = dir.join(format!("{}.tmp", name));
+            fs::write(&temp, content)
+                .and_then(|_| fs::rename(&temp, &target))
+                .map_err(|e| VaultNodeError::OtherProcessing(format!("Failed to write {}. Error: {}", name, e)))?;
+        }
+        Ok(())
+    }
+}
+
+/// Vault snapshot followed by the side files
+pub fn pack(snapshot: &[u8], files: &SideFiles) -> Result<Vec<u8>, VaultNodeError> {
+    let json = serde_json::to_vec(files)
+        .map_err(|e| VaultNodeError::OtherProcessing(format!("Failed to encode snapshot. Error: {}", e)))?;
+    let mut result = Vec::with_capacity(MAGIC.len() + 8 + snapshot.len() + json.len());
+    result.extend_from_slice(MAGIC);
+    result.extend_from_slice(&(snapshot.len() as u64).to_be_bytes());
+    result.extend_from_slice(snapshot);
+    result.extend_from_slice(&json);
+    Ok(result)
+}
+
+/// Split into the Vault snapshot and the side files, which are `None` for a plain Vault snapshot
+pub fn unpack(data: &[u8]) -> Result<(&[u8], Option<SideFiles>), VaultNodeError> {
+    let rest = match data.strip_prefix(MAGIC.as_slice()) {
+        Some(rest) => rest,
+        None => return Ok((data, None)),
+    };
+    let invalid = || VaultNodeError::InvalidArgumentValue("Invalid snapshot".to_string());
+    let (length, rest) = rest.split_first_chunk::<8>().ok_or_else(invalid)?;
+    let length = usize::try_from(u64::from_be_bytes(*length)).map_err(|_| invalid())?;
+    if rest.len() < length {
+        return Err(invalid());
+    }
+    let (snapshot, json) = rest.split_at(length);
+    let files = serde_json::from_slice::<SideFiles>(json).map_err(|_| invalid())?;
+    Ok((snapshot, Some(files)))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::path::PathBuf;
+    use uuid::Uuid;
+    use crate::sidecar::{pack, unpack, SideFiles};
+
+    const EXTENSIONS: [&str; 2] = ["multisig", "policy"];
+
+    fn temp_dir() -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("emerald-sidecar-{}", Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn packs_and_unpacks() {
+        let dir = temp_dir();
+        fs::write(dir.join("a.multisig"), "multisig").unwrap();
+        fs::write(dir.join("b-0.policy"), "policy").unwrap();
+        let files = SideFiles::collect(&dir, &EXTENSIONS).unwrap();
+
+        let packed = pack(b"vault snapshot", &files).unwrap();
+        let (snapshot, unpacked) = unpack(&packed).unwrap();
+        assert_eq!(snapshot, b"vault snapshot");
+        assert_eq!(unpacked, Some(files));
+    }
+
+    #[test]
+    fn accepts_plain_snapshot() {
+        let (snapshot, files) = unpack(b"vault snapshot").unwrap();
+        assert_eq!(snapshot, b"vault snapshot");
+        assert_eq!(files, None);
+    }
+
+    #[test]
+    fn fails_on_truncated_snapshot() {
+        let packed = pack(b"vault snapshot", &SideFiles::default()).unwrap();
+        assert!(unpack(&packed[..20]).is_err());
+    }
+
+    #[test]
+    fn collects_only_side_files() {
+        let dir = temp_dir();
+        fs::write(dir.join("a.multisig"), "multisig").unwrap();
+        fs::write(dir.join("a.multisig.tmp"), "temp").unwrap();
+        fs::write(dir.join("b.wallet"), "wallet").unwrap();
+        let files = SideFiles::collect(&dir, &EXTENSIONS).unwrap();
+        assert_eq!(files.files.keys().collect::<Vec<_>>(), vec!["a.multisig"]);
+    }
+
+    #[test]
+    fn replaces_files() {
+        let source = temp_dir();
+        fs::write(source.join("a.multisig"), "restored").unwrap();
+        let files = SideFiles::collect(&source, &EXTENSIONS).unwrap();
+
+        let target = temp_dir();
+        fs::write(target.join("a.multisig"), "current").unwrap();
+        fs::write(target.join("b-0.policy"), "current").unwrap();
+        fs::write(target.join("c.wallet"), "wallet").unwrap();
+        files.replace(&target, &EXTENSIONS).unwrap();
+
+        assert_eq!(fs::read_to_string(target.join("a.multisig")).unwrap(), "restored");
+        assert!(!target.join("b-0.policy").exists());
+        assert!(target.join("c.wallet").exists());
+    }
+
+    #[test]
+    fn refuses_path_in_snapshot() {
+        let dir = temp_dir();
+        fs::write(dir.join("a.multisig"), "current").unwrap();
+        let mut files = SideFiles::default();
+        files.files.insert("../a.multisig".to_string(), "cmVzdG9yZWQ=".to_string());
+        assert!(files.replace(&dir, &EXTENSIONS).is_err());
+        assert_eq!(fs::read_to_string(dir.join("a.multisig")).unwrap(), "current");
+    }
+}
@@ -4,13 +4,14 @@ use hex::FromHex;
 use neon::prelude::{FunctionContext, JsNumber, JsString};
 use uuid::Uuid;
 
-use crate::access::{args_get_str};
-use crate::errors::{JsonError, VaultNodeError};
+use crate::access::{args_get_str, args_get_uuid, args_get_wallet_and_entry_ids};
+use crate::errors::{JsonError, PolicyRule, VaultNodeError};
 use emerald_vault::{
     blockchain::{
-        bitcoin::{BitcoinTransferProposal, InputReference, InputScriptSource, KeyMapping, XPub},
+        bitcoin::{BitcoinTransferProposal, InputReference, InputScriptSource, KeyMapping},
         chains::BlockchainType
     },
+    error::VaultError,
     to_even_str,
     trim_hex,
     EthereumAddress,
@@ -18,12 +19,13 @@ use emerald_vault::{
     to_32bytes,
     keccak256,
     structs::{
-        wallet::PKType,
+        wallet::{AddressRole, PKType, WalletEntry},
         book::AddressRef,
+        seed::SeedSource,
         types::UsesOddKey
     },
     sign::ethereum::SignMessage,
-    chains::EthereumChainId,
+    chains::{Blockchain, EthereumChainId},
     ethereum::{
         transaction::{EthereumEIP1559Transaction, TxAccess},
         signature::{EthereumBasicSignature, SignableHash},
@@ -33,15 +35,41 @@ use emerald_vault::{
 use hdpath::{StandardHDPath, AccountHDPath};
 use bitcoin::{
     Address,
+    ScriptBuf,
     TxOut,
     OutPoint,
     Txid,
     Transaction,
     consensus::Decodable,
-    Amount
+    Amount,
+    Network,
+    NetworkKind,
+    Sequence,
+    Weight,
+    bip32::DerivationPath,
+    psbt::Psbt,
+    secp256k1::{Secp256k1, SecretKey},
 };
+use chrono::Utc;
 use num_bigint::BigUint;
+use crate::bitcoin_message;
+use crate::coin_select;
+use crate::ethereum_tx;
+use crate::fee_bump;
 use crate::instance::{Instance, WrappedVault};
+use crate::multisig::{Multisig, MultisigInput};
+use crate::psbt;
+use crate::psbt::{AccountSigner, SignPsbtOptionsJson, SignedPsbtJson};
+use crate::taproot;
+use crate::taproot::TaprootInput;
+use crate::tx_decode;
+use crate::tx_decode::{AuthorizationJson, DecodedAccessListItemJson, DecodedTxJson, EthereumTxFieldsJson};
+use crate::tx_preview;
+use crate::tx_preview::{BitcoinPreviewJson, BitcoinPreviewOutputJson, EthereumPreviewJson, TxPreviewJson};
+use crate::policy::{EntryPolicy, PolicyKey, Spending};
+use crate::tx_size;
+use crate::tx_size::{InputType, TxSizeJson};
+use crate::wallets::{taproot_account, with_std_addresses};
 
 #[derive(Deserialize, Debug, Clone)]
 pub struct AccessListItemJson {
@@ -69,14 +97,76 @@ pub struct UnsignedEthereumTxJson {
     #[serde(default)]
     pub passphrase: Option<String>,
     #[serde(rename = "accessList")]
-    pub access_list: Option<Vec<AccessListItemJson>>
+    pub access_list: Option<Vec<AccessListItemJson>>,
+    /// Signed authorizations, which make it an EIP-7702 transaction
+    #[serde(rename = "authorizationList")]
+    pub authorization_list: Option<Vec<AuthorizationJson>>,
+    /// Set for an EIP-2930 transaction, which can be supplied only as RLP
+    #[serde(skip)]
+    pub eip2930: bool,
 }
 
+/// EIP-7702 authorization to sign, i.e., a delegation of the entry account to the `address`
 #[derive(Deserialize, Debug, Clone)]
+pub struct UnsignedAuthorizationJson {
+    /// Chain Id, by default the chain of the entry. Set `0` explicitly to make it valid on any chain
+    #[serde(rename = "chainId")]
+    pub chain_id: Option<u64>,
+    pub address: String,
+    pub nonce: u64,
+}
+
+/// Unsigned Ethereum transaction as RLP hex, i.e., an EIP-2718 envelope without the signature or a legacy transaction
+/// prepared for EIP-155 signing
+#[derive(Deserialize, Debug, Clone)]
+pub struct UnsignedRawEthereumTxJson {
+    pub raw: String,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(untagged)]
+pub enum EthereumTxSourceJson {
+    Raw(UnsignedRawEthereumTxJson),
+    Fields(UnsignedEthereumTxJson),
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct UnsignedBitcoinTxJson {
     pub inputs: Vec<InputJson>,
     pub outputs: Vec<OutputJson>,
-    pub fee: u64,
+    /// Fee in satoshi, which must be the difference between the inputs and outputs
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fee: Option<u64>,
+    /// Fee rate in sat/vB, an alternative to the `fee`. The fee is calculated from the estimated size of the signed transaction
+    /// and taken from the change output. Without a change output the difference between the inputs and outputs must be enough.
+    #[serde(rename = "feeRate", default, skip_serializing_if = "Option::is_none")]
+    pub fee_rate: Option<f64>,
+    /// Opt in for BIP-125 replacement. Inputs with a final sequence get `0xfffffffd` instead
+    #[serde(default)]
+    pub rbf: bool,
+}
+
+/// Unsigned transaction made by the coin selection, with the estimated size when it's signed
+#[derive(Serialize, Debug, Clone)]
+pub struct BuiltBitcoinTxJson {
+    #[serde(flatten)]
+    pub tx: UnsignedBitcoinTxJson,
+    #[serde(flatten)]
+    pub size: TxSizeJson,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct BumpFeeJson {
+    /// Signed transaction to replace, as hex
+    pub raw: String,
+    /// Fee rate of the replacement, in sat/vB
+    #[serde(rename = "feeRate")]
+    pub fee_rate: f64,
+    /// UTXOs spent by the original transaction, and optionally other UTXOs of the entry to add if the change is not enough
+    pub utxos: Vec<InputJson>,
+    /// Address for the change, if the original transaction has no change
+    #[serde(rename = "changeAddress")]
+    pub change_address: Option<String>,
 }
 
 #[derive(Serialize, Debug, Clone)]
@@ -85,15 +175,30 @@ pub struct SignedTxJson {
     pub txid: String,
 }
 
-#[derive(Deserialize, Debug, Clone)]
+/// Result of a transaction signed in a batch
+#[derive(Serialize, Debug, Clone)]
+#[serde(untagged)]
+pub enum BatchSignedTxJson {
+    Signed(SignedTxJson),
+    Failed { error: BatchErrorJson },
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct BatchErrorJson {
+    pub code: usize,
+    pub message: String,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct InputJson {
     pub txid: String,
     pub vout: u32,
     pub amount: u64,
     #[serde(default = "default_sequence")]
     pub sequence: u32,
-    #[serde(rename = "hdPath")]
+    #[serde(rename = "hdPath", skip_serializing_if = "Option::is_none")]
     pub hd_path: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub address: Option<String>,
 }
 
@@ -101,7 +206,7 @@ fn default_sequence() -> u32 {
     0xffff_fffe
 }
 
-#[derive(Deserialize, Debug, Clone)]
+#[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct OutputJson {
     pub address: String,
     pub amount: u64,
@@ -114,6 +219,12 @@ pub enum UnsignedMessageJson {
     EIP191 { message: String },
     #[serde(rename = "eip712")]
     EIP712 { message: String },
+    /// Bitcoin signed message for P2PKH, P2SH-P2WPKH or P2WPKH address
+    #[serde(rename = "bip137")]
+    BIP137 { message: String, address: String },
+    /// Bitcoin BIP-322 simple signature for P2WPKH or P2TR address
+    #[serde(rename = "bip322")]
+    BIP322 { message: String, address: String },
 }
 
 #[derive(Serialize, Debug, Clone)]
@@ -123,15 +234,82 @@ pub enum SignedMessageJson {
     EIP191 { signature: String, address: String },
     #[serde(rename = "eip712")]
     EIP712 { signature: String, address: String },
+    #[serde(rename = "bip137")]
+    BIP137 { signature: String, address: String },
+    #[serde(rename = "bip322")]
+    BIP322 { signature: String, address: String },
 }
 
 
+impl From<Result<SignedTxJson, VaultNodeError>> for BatchSignedTxJson {
+    fn from(value: Result<SignedTxJson, VaultNodeError>) -> Self {
+        match value {
+            Ok(signed) => BatchSignedTxJson::Signed(signed),
+            Err(e) => {
+                let (code, message) = e.into();
+                BatchSignedTxJson::Failed { error: BatchErrorJson { code, message } }
+            }
+        }
+    }
+}
+
+impl UnsignedBitcoinTxJson {
+    fn with_rbf(mut self) -> Self {
+        if self.rbf {
+            self.inputs.iter_mut()
+                .filter(|input| !Sequence(input.sequence).is_rbf())
+                .for_each(|input| input.sequence = Sequence::ENABLE_RBF_NO_LOCKTIME.0);
+        }
+        self
+    }
+
+    /// Estimated size of the transaction when it's signed by an entry with the `input_type` addresses. An input with an address
+    /// of another type is estimated by its address.
+    fn estimate_size(&self, input_type: InputType) -> Result<Weight, VaultNodeError> {
+        let mut inputs = Vec::with_capacity(self.inputs.len());
+        for input in &self.inputs {
+            let input_type = match &input.address {
+                Some(value) => {
+                    let address = Address::from_str(value)
+                        .map_err(|_| VaultNodeError::OtherInput("Invalid input bitcoin address".to_string()))?
+                        .assume_checked();
+                    InputType::of_address(&address).unwrap_or(input_type)
+                },
+                None => input_type
+            };
+            inputs.push(input_type);
+        }
+        let outputs: Vec<ScriptBuf> = convert_output(self.outputs.clone())?
+            .into_iter()
+            .map(|o| o.script_pubkey)
+            .collect();
+        Ok(tx_size::estimate(&inputs, &outputs))
+    }
+
+    /// The fee, when the transaction specifies it explicitly
+    fn required_fee(&self) -> Result<u64, VaultNodeError> {
+        match (self.fee, self.fee_rate) {
+            (Some(fee), None) => Ok(fee),
+            (None, Some(_)) => Err(VaultNodeError::OtherInput("Fee rate is not supported, use fee".to_string())),
+            _ => Err(VaultNodeError::OtherInput("Specify either fee or feeRate".to_string())),
+        }
+    }
+}
+
 impl UnsignedEthereumTxJson {
 
     fn is_eip1559(&self) -> bool {
         self.priority_gas_price.is_some() && self.max_gas_price.is_some()
     }
 
+    fn is_eip7702(&self) -> bool {
+        self.authorization_list.is_some()
+    }
+
+    fn is_eip2930(&self) -> bool {
+        self.eip2930
+    }
+
     fn as_eip1559(&self, chain_id: &EthereumChainId) -> Result<EthereumEIP1559Transaction, JsonError> {
         let max_gas_price = self.max_gas_price.as_ref().ok_or(JsonError::MissingField("maxGasPrice".to_string()))?;
         let priority_gas_price = self.priority_gas_price.as_ref().ok_or(JsonError::MissingField("priorityGasPrice".to_string()))?;
@@ -168,6 +346,43 @@ impl UnsignedEthereumTxJson {
         Ok(result)
     }
 
+    /// Fields of the transaction for the chain, as they are signed
+    fn as_fields(&self, chain_id: u64) -> EthereumTxFieldsJson {
+        let tx_type = if self.is_eip7702() {
+            tx_decode::TYPE_EIP7702
+        } else if self.is_eip1559() {
+            tx_decode::TYPE_EIP1559
+        } else if self.is_eip2930() {
+            tx_decode::TYPE_EIP2930
+        } else {
+            0
+        };
+        EthereumTxFieldsJson {
+            tx_type,
+            chain_id: Some(chain_id),
+            nonce: self.nonce,
+            gas: self.gas,
+            gas_price: if tx_type == 0 || tx_type == tx_decode::TYPE_EIP2930 { self.gas_price.clone() } else { None },
+            max_gas_price: self.max_gas_price.clone(),
+            priority_gas_price: self.priority_gas_price.clone(),
+            to: Some(self.to.clone()).filter(|to| !to.is_empty()),
+            value: self.value.clone(),
+            data: format!("0x{}", to_even_str(trim_hex(self.data.as_str()))),
+            access_list: self.access_list.as_ref().map(|list| {
+                list.iter()
+                    .map(|item| DecodedAccessListItemJson {
+                        address: item.address.clone(),
+                        storage: item.storage.as_ref().unwrap_or(&Vec::new())
+                            .iter()
+                            .map(|s| format!("0x{}", hex::encode(to_32bytes(trim_hex(s)))))
+                            .collect(),
+                    })
+                    .collect()
+            }),
+            authorization_list: self.authorization_list.clone(),
+        }
+    }
+
     fn as_legacy(&self, chain_id: &EthereumChainId) -> Result<EthereumLegacyTransaction, JsonError> {
         let gas_price = self.gas_price.as_ref().ok_or(JsonError::MissingField("gasPrice".to_string()))?;
         let data = to_even_str(trim_hex(self.data.as_str()));
@@ -189,39 +404,70 @@ impl UnsignedEthereumTxJson {
 
 }
 
-fn convert_inputs(inputs: Vec<InputJson>, xpub: &XPub, seed_id: Uuid, hd_account: &AccountHDPath) -> Result<Vec<InputReference>, VaultNodeError> {
-    let mut result = Vec::with_capacity(inputs.len());
-    for input in inputs {
-        let hd_path = match &input.hd_path {
-            Some(value) => StandardHDPath::from_str(value.as_str())
-                .map_err(|_| VaultNodeError::OtherInput("Invalid HDPath for input".to_string()))?,
-            None => match &input.address {
-                Some(value) => {
-                    let address = Address::from_str(value)
-                        .map_err(|_| VaultNodeError::OtherInput("Invalid input bitcoin address".to_string()))?
-                        .assume_checked();
-                    //TODO use actual number of used address from current state
-                    match xpub.find_path(hd_account, &address, 1000) {
-                        Some(path) => path,
-                        None => return Err(VaultNodeError::OtherInput(format!("Unknown address: {:?}", address)))
+impl From<EthereumTxFieldsJson> for UnsignedEthereumTxJson {
+    fn from(value: EthereumTxFieldsJson) -> Self {
+        UnsignedEthereumTxJson {
+            // not a part of the transaction, the sender is always the entry
+            from: String::new(),
+            to: value.to.unwrap_or_default(),
+            gas: value.gas,
+            gas_price: value.gas_price,
+            max_gas_price: value.max_gas_price,
+            priority_gas_price: value.priority_gas_price,
+            value: value.value,
+            data: value.data,
+            nonce: value.nonce,
+            passphrase: None,
+            access_list: value.access_list.map(|items| {
+                items.into_iter()
+                    .map(|item| AccessListItemJson { address: item.address, storage: Some(item.storage) })
+                    .collect()
+            }),
+            eip2930: value.tx_type == tx_decode::TYPE_EIP2930,
+            authorization_list: value.authorization_list,
+        }
+    }
+}
+
+fn check_chain_id(chain_id: u64, blockchain: Blockchain) -> Result<(), VaultNodeError> {
+    let expected = EthereumChainId::from(blockchain).as_chainid() as u64;
+    if chain_id != expected {
+        return Err(VaultNodeError::OtherInput(format!("Transaction is for chain {}, expected {}", chain_id, expected)));
+    }
+    Ok(())
+}
+
+/// Transaction to sign by the entry, checking it's for the entry address and chain
+fn ethereum_tx_fields(entry: &WalletEntry, unsigned_tx: EthereumTxSourceJson) -> Result<UnsignedEthereumTxJson, VaultNodeError> {
+    let unsigned_tx = match unsigned_tx {
+        EthereumTxSourceJson::Fields(unsigned_tx) => {
+            let from_address =
+                EthereumAddress::from_str(unsigned_tx.from.as_str())?;
+
+            if let Some(address) = &entry.address {
+                match address {
+                    AddressRef::EthereumAddress(current) => if current.ne(&from_address) {
+                        return Err(VaultNodeError::OtherInput("Different from address".to_string()))
+                    },
+                    _ => {
+                        return Err(VaultNodeError::OtherInput("Unsupported wallet from address".to_string()))
                     }
-                },
-                None => return Err(VaultNodeError::OtherInput("Neither HDPath nor Address is specified".to_string()))
+                }
             }
-        };
-        let value = InputReference {
-            output: OutPoint {
-                txid: Txid::from_str(input.txid.as_str())
-                    .map_err(|_| VaultNodeError::OtherInput("Invalid txid".to_string()))?,
-                vout: input.vout,
-            },
-            script_source: InputScriptSource::HD(seed_id, hd_path),
-            expected_value: input.amount,
-            sequence: input.sequence,
-        };
-        result.push(value);
-    }
-    Ok(result)
+            unsigned_tx
+        },
+        EthereumTxSourceJson::Raw(unsigned_tx) => {
+            let raw = Vec::from_hex(to_even_str(trim_hex(unsigned_tx.raw.as_str())))
+                .map_err(|_| VaultNodeError::InvalidArgumentValue("Transaction is not a hex".to_string()))?;
+            let fields = tx_decode::decode_unsigned_ethereum(&raw)?;
+            // a legacy transaction is always signed with EIP-155 chain id, so it must be set
+            let chain_id = fields.chain_id
+                .ok_or(VaultNodeError::InvalidArgumentValue("Transaction without chain id".to_string()))?;
+            check_chain_id(chain_id, entry.blockchain)?;
+            UnsignedEthereumTxJson::from(fields)
+        }
+    };
+    Ok(unsigned_tx)
 }
 
 fn convert_output(outputs: Vec<OutputJson>) -> Result<Vec<TxOut>, VaultNodeError> {
@@ -239,11 +485,96 @@ fn convert_output(outputs: Vec<OutputJson>) -> Result<Vec<TxOut>, VaultNodeError
 }
 
 impl WrappedVault {
+    /// Inputs of a transaction of the entry. An input without HD Path must have an address of the entry.
+    fn convert_inputs(
+        &self,
+        wallet_id: Uuid,
+        entry: &WalletEntry,
+        inputs: Vec<InputJson>,
+        seed_id: Uuid,
+        hd_account: &AccountHDPath,
+    ) -> Result<Vec<InputReference>, VaultNodeError> {
+        let mut result = Vec::with_capacity(inputs.len());
+        for (i, input) in inputs.into_iter().enumerate() {
+            let hd_path = match &input.hd_path {
+                Some(value) => StandardHDPath::from_str(value.as_str())
+                    .map_err(|_| VaultNodeError::OtherInput(format!("Invalid HDPath for input {}", i)))?,
+                None => match &input.address {
+                    Some(value) => {
+                        let address = Address::from_str(value)
+                            .map_err(|_| VaultNodeError::OtherInput(format!("Invalid bitcoin address for input {}", i)))?
+                            .assume_checked();
+                        let (change, index) = self.entry_address_path(wallet_id, entry, &address)?
+                            .ok_or(VaultNodeError::OtherInput(format!("Unknown address for input {}: {}", i, address)))?;
+                        StandardHDPath::from_str(format!("{}/{}/{}", hd_account, change, index).as_str())
+                            .map_err(|_| VaultNodeError::OtherProcessing("Invalid HDPath".to_string()))?
+                    },
+                    None => return Err(VaultNodeError::OtherInput(format!("Neither HDPath nor Address is specified for input {}", i)))
+                }
+            };
+            let value = InputReference {
+                output: OutPoint {
+                    txid: Txid::from_str(input.txid.as_str())
+                        .map_err(|_| VaultNodeError::OtherInput(format!("Invalid txid for input {}", i)))?,
+                    vout: input.vout,
+                },
+                script_source: InputScriptSource::HD(seed_id, hd_path),
+                expected_value: input.amount,
+                sequence: input.sequence,
+            };
+            result.push(value);
+        }
+        Ok(result)
+    }
+
+    /// Number of receive and change addresses of the entry to search for an address, i.e. the current indexes plus the gap
+    fn address_limits(&self, wallet_id: Uuid, entry_id: usize) -> [u32; 2] {
+        let gap = self.cfg.address_gap;
+        match self.cfg.account_indexes.iter().find(|i| i.wallet_id == wallet_id && i.entry_id == entry_id) {
+            Some(index) => [index.receive.saturating_add(gap), index.change.saturating_add(gap)],
+            None => [gap, gap],
+        }
+    }
+
+    /// Finds `(change, index)` of the address of the entry. The derived addresses are cached for the next search.
+    fn entry_address_path(&self, wallet_id: Uuid, entry: &WalletEntry, address: &Address) -> Result<Option<(u32, u32)>, VaultNodeError> {
+        let limits = self.address_limits(wallet_id, entry.id);
+        let mut cache = self.address_cache.lock().unwrap();
+        if let Some((_, account)) = taproot_account(entry) {
+            return cache.find_path(account.to_string().as_str(), address.to_string().as_str(), limits, |change, start, limit| {
+                let addresses = taproot::derive_addresses(&account, change, start, limit)?;
+                Ok(addresses.into_iter().map(|(index, address)| (index, address.to_string())).collect())
+            });
+        }
+        let xpub = match &entry.address {
+            Some(AddressRef::ExtendedPub(xpub)) => xpub,
+            _ => return Err(VaultNodeError::OtherInput("Unsupported type of address".to_string()))
+        };
+        cache.find_path(xpub.to_string().as_str(), address.to_string().as_str(), limits, |change, start, limit| {
+            let role = if change == 1 { AddressRole::Change } else { AddressRole::Receive };
+            let addresses = entry.get_addresses::<Address>(role, start, limit)?;
+            Ok(addresses.into_iter()
+                .enumerate()
+                .map(|(i, a)| (start + i as u32, a.to_string()))
+                .collect())
+        })
+    }
+
+    /// Finds `(change, index)` of the multisig address, searching the first receive and change addresses within the gap
+    fn multisig_address_path(&self, multisig: &Multisig, address: &Address) -> Result<Option<(u32, u32)>, VaultNodeError> {
+        let gap = self.cfg.address_gap;
+        let mut cache = self.address_cache.lock().unwrap();
+        cache.find_path(multisig.setup.id.to_string().as_str(), address.to_string().as_str(), [gap, gap], |change, start, limit| {
+            let addresses = multisig.addresses(change, start, limit)?;
+            Ok(addresses.into_iter().map(|(index, address)| (index, address.to_string())).collect())
+        })
+    }
+
     fn sign_ethereum_tx(
         &self,
         wallet_id: Uuid,
         entry_id: usize,
-        unsigned_tx: UnsignedEthereumTxJson,
+        unsigned_tx: EthereumTxSourceJson,
         password: Option<String>,
     ) -> Result<Vec<u8>, VaultNodeError> {
         let storage = &self.cfg.get_storage();
@@ -251,33 +582,210 @@ impl WrappedVault {
         if entry.blockchain.get_type() != BlockchainType::Ethereum {
             return Err(VaultNodeError::OtherInput("Not an ethereum entry".to_string()));
         }
-
-        let from_address =
-            EthereumAddress::from_str(unsigned_tx.from.as_str())?;
-
-        if let Some(address) = &entry.address {
-            match address {
-                AddressRef::EthereumAddress(current) => if current.ne(&from_address) {
-                    return Err(VaultNodeError::OtherInput("Different from address".to_string()))
-                },
-                _ => {
-                    return Err(VaultNodeError::OtherInput("Unsupported wallet from address".to_string()))
-                }
-            }
-        }
+        let unsigned_tx = ethereum_tx_fields(&entry, unsigned_tx)?;
 
         let chain_id = EthereumChainId::from(entry.blockchain);
-        let result = if unsigned_tx.is_eip1559() {
+        let policy_key = self.policy_key(wallet_id, entry_id, password.as_deref())?;
+        let policy = self.check_policy(wallet_id, &entry, policy_key.as_ref(), || {
+            Spending::ethereum(&unsigned_tx.as_fields(chain_id.as_chainid() as u64))
+        })?;
+        let result = if unsigned_tx.is_eip7702() || unsigned_tx.is_eip2930() {
+            // not supported by the Vault, so it's signed with the decrypted key
+            let password = password.clone().ok_or(VaultError::PasswordRequired)?;
+            let key = self.ethereum_key(&entry, password)?;
+            ethereum_tx::sign(&unsigned_tx.as_fields(chain_id.as_chainid() as u64), &key)?
+        } else if unsigned_tx.is_eip1559() {
             let tx = unsigned_tx.as_eip1559(&chain_id)?;
-            entry.sign_tx(tx, password, &storage)?
+            entry.sign_tx(tx, password.clone(), &storage)?
         } else {
             let tx = unsigned_tx.as_legacy(&chain_id)?;
-            entry.sign_tx(tx, password, &storage)?
+            entry.sign_tx(tx, password.clone(), &storage)?
         };
+        self.record_spending(wallet_id, entry_id, policy, password.as_deref())?;
 
         Ok(result)
     }
 
+    /// Fails for an entry on a Hardware Key, which never reveals the private key
+    fn ensure_software_key(&self, entry: &WalletEntry) -> Result<(), VaultNodeError> {
+        if let PKType::SeedHd(seed) = &entry.key {
+            let storage = &self.cfg.get_storage();
+            if let SeedSource::Ledger(_) = storage.seeds().get(seed.seed_id)?.source {
+                return Err(VaultNodeError::OtherInput("Not supported for a Hardware Key".to_string()));
+            }
+        }
+        Ok(())
+    }
+
+    /// Decrypted private key of an Ethereum entry
+    fn ethereum_key(&self, entry: &WalletEntry, password: String) -> Result<SecretKey, VaultNodeError> {
+        self.ensure_software_key(entry)?;
+        let storage = &self.cfg.get_storage();
+        let key = entry.export_ethereum_pk(password, storage)
+            .map_err(|_| VaultNodeError::VaultError("Private Key Unavailable".to_string()))?;
+        SecretKey::from_slice(&key.0)
+            .map_err(|_| VaultNodeError::VaultError("Private Key Unavailable".to_string()))
+    }
+
+    /// Sign an EIP-7702 authorization, which delegates the entry account to a contract. It's not allowed for an entry with
+    /// a spending policy, because the delegate can spend from the account without the policy checks.
+    fn sign_authorization(
+        &self,
+        wallet_id: Uuid,
+        entry_id: usize,
+        authorization: UnsignedAuthorizationJson,
+        password: String,
+    ) -> Result<AuthorizationJson, VaultNodeError> {
+        let entry = self.get_entry(wallet_id, entry_id)?;
+        if entry.blockchain.get_type() != BlockchainType::Ethereum {
+            return Err(VaultNodeError::OtherInput("Not an ethereum entry".to_string()));
+        }
+        let entry_chain_id = EthereumChainId::from(entry.blockchain).as_chainid() as u64;
+        let chain_id = authorization.chain_id.unwrap_or(entry_chain_id);
+        if chain_id != 0 {
+            check_chain_id(chain_id, entry.blockchain)?;
+        }
+        if self.has_policy(wallet_id, entry_id) {
+            return Err(VaultNodeError::PolicyRejected(PolicyRule::Method, "Delegation is not allowed with a spending policy".to_string()));
+        }
+        let key = self.ethereum_key(&entry, password)?;
+        ethereum_tx::sign_authorization(chain_id, authorization.address.as_str(), authorization.nonce, &key)
+    }
+
+    /// Check the transaction with the spending policy of the entry, if it has the `key` of a policy (see `policy_key`). The
+    /// `spending` is evaluated only when there is a policy. Returns the policy with the spending to record after the
+    /// transaction is signed.
+    fn check_policy<F>(&self, wallet_id: Uuid, entry: &WalletEntry, key: Option<&PolicyKey>, spending: F) -> Result<Option<(EntryPolicy, Spending)>, VaultNodeError>
+        where F: FnOnce() -> Result<Spending, VaultNodeError> {
+        let key = match key {
+            Some(key) => key,
+            None => return Ok(None),
+        };
+        let policy = self.verified_policy(wallet_id, entry.id, key)?;
+        let spending = spending()?;
+        policy.check(&spending, Utc::now())?;
+        Ok(Some((policy, spending)))
+    }
+
+    /// Save the signed amount with the policy. Returns the new key of the policy, because each update of the policy changes
+    /// its counter (see `save_policy`).
+    fn record_spending(&self, wallet_id: Uuid, entry_id: usize, policy: Option<(EntryPolicy, Spending)>, password: Option<&str>) -> Result<Option<PolicyKey>, VaultNodeError> {
+        match policy {
+            Some((mut policy, spending)) => {
+                policy.record(&spending.amount, Utc::now());
+                let password = password.ok_or(VaultError::PasswordRequired)?;
+                self.save_policy(wallet_id, entry_id, policy, password).map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn ethereum_preview(&self, entry: &WalletEntry, unsigned_tx: &UnsignedEthereumTxJson) -> Result<EthereumPreviewJson, VaultNodeError> {
+        let chain_id = EthereumChainId::from(entry.blockchain).as_chainid() as u64;
+        let from = match &entry.address {
+            Some(AddressRef::EthereumAddress(address)) => address.to_string(),
+            _ => unsigned_tx.from.clone(),
+        };
+        let mut preview = tx_preview::ethereum(&unsigned_tx.as_fields(chain_id), from)?;
+        let label = |address: &Option<String>| {
+            address.as_ref()
+                .and_then(|a| EthereumAddress::from_str(a).ok())
+                .and_then(|a| self.addressbook_label(&a))
+        };
+        preview.to_label = label(&preview.to);
+        preview.recipient_label = label(&preview.recipient);
+        Ok(preview)
+    }
+
+    /// Preview of a Bitcoin transaction with the resolved fee
+    fn bitcoin_preview(&self, wallet_id: Uuid, entry: &WalletEntry, unsigned_tx: &UnsignedBitcoinTxJson, fee: u64) -> Result<BitcoinPreviewJson, VaultNodeError> {
+        let mut outputs = Vec::with_capacity(unsigned_tx.outputs.len());
+        for output in &unsigned_tx.outputs {
+            let address = Address::from_str(output.address.as_str())
+                .map_err(|_| VaultNodeError::OtherInput("Invalid output bitcoin address".to_string()))?
+                .assume_checked();
+            let change = matches!(self.entry_address_path(wallet_id, entry, &address)?, Some((1, _)));
+            outputs.push(BitcoinPreviewOutputJson { address: output.address.clone(), amount: output.amount, change });
+        }
+        let sent = outputs.iter().filter(|o| !o.change).map(|o| o.amount).sum();
+        Ok(BitcoinPreviewJson { outputs, sent, fee })
+    }
+
+    /// What the entry would sign for the transaction, without signing it. The transaction is resolved the same way as for
+    /// signing, i.e., an Ethereum transaction for the entry address and chain, and a Bitcoin fee rate applied to the change.
+    fn preview_tx(&self, wallet_id: Uuid, entry_id: usize, tx_json: String) -> Result<TxPreviewJson, VaultNodeError> {
+        let entry = self.get_entry(wallet_id, entry_id)?;
+        let preview = match entry.blockchain.get_type() {
+            BlockchainType::Ethereum => {
+                let unsigned_tx = serde_json::from_str::<EthereumTxSourceJson>(tx_json.as_str())
+                    .map_err(|_| VaultNodeError::InvalidArgument(2))?;
+                let unsigned_tx = ethereum_tx_fields(&entry, unsigned_tx)?;
+                TxPreviewJson::Ethereum(Box::new(self.ethereum_preview(&entry, &unsigned_tx)?))
+            },
+            BlockchainType::Bitcoin => {
+                let mut unsigned_tx = serde_json::from_str::<UnsignedBitcoinTxJson>(tx_json.as_str())
+                    .map_err(|_| VaultNodeError::InvalidArgument(2))?;
+                let fee = self.resolve_fee(wallet_id, &entry, &mut unsigned_tx)?;
+                TxPreviewJson::Bitcoin(self.bitcoin_preview(wallet_id, &entry, &unsigned_tx, fee)?)
+            }
+        };
+        Ok(preview)
+    }
+
+    /// Sign a list of transactions of the same entry. An Ethereum key is decrypted only once for all of them, so an entry on
+    /// a Hardware Key is not supported, and a failed transaction doesn't stop the others. A Bitcoin transaction is signed
+    /// as usual, so it only saves the entry lookup.
+    fn sign_tx_batch(
+        &self,
+        wallet_id: Uuid,
+        entry_id: usize,
+        txs: Vec<serde_json::Value>,
+        password: Option<String>,
+    ) -> Result<Vec<BatchSignedTxJson>, VaultNodeError> {
+        let entry = self.get_entry(wallet_id, entry_id)?;
+        let results = match entry.blockchain.get_type() {
+            BlockchainType::Ethereum => {
+                self.ensure_software_key(&entry)?;
+                let password = password.ok_or(VaultError::PasswordRequired)?;
+                let mut policy_key = self.policy_key(wallet_id, entry_id, Some(password.as_str()))?;
+                let key = self.ethereum_key(&entry, password.clone())?;
+                let chain_id = EthereumChainId::from(entry.blockchain).as_chainid() as u64;
+                txs.into_iter()
+                    .map(|tx| -> Result<SignedTxJson, VaultNodeError> {
+                        let tx = serde_json::from_value::<EthereumTxSourceJson>(tx)
+                            .map_err(|_| VaultNodeError::InvalidArgumentValue("Invalid transaction".to_string()))?;
+                        let fields = ethereum_tx_fields(&entry, tx)?.as_fields(chain_id);
+                        let policy = self.check_policy(wallet_id, &entry, policy_key.as_ref(), || Spending::ethereum(&fields))?;
+                        let signed = ethereum_tx::sign(&fields, &key)?;
+                        if let Some(next) = self.record_spending(wallet_id, entry_id, policy, Some(password.as_str()))? {
+                            policy_key = Some(next);
+                        }
+                        Ok(SignedTxJson {
+                            txid: format!("0x{}", hex::encode(keccak256(signed.as_slice()))),
+                            raw: format!("0x{}", hex::encode(signed)),
+                        })
+                    })
+                    .map(BatchSignedTxJson::from)
+                    .collect()
+            }
+            BlockchainType::Bitcoin => {
+                txs.into_iter()
+                    .map(|tx| -> Result<SignedTxJson, VaultNodeError> {
+                        let tx = serde_json::from_value::<UnsignedBitcoinTxJson>(tx)
+                            .map_err(|_| VaultNodeError::InvalidArgumentValue("Invalid transaction".to_string()))?;
+                        let raw = self.sign_bitcoin_tx(wallet_id, entry_id, tx, password.clone())?;
+                        Ok(SignedTxJson {
+                            txid: bitcoin_tx_hash(&raw)?,
+                            raw: hex::encode(raw),
+                        })
+                    })
+                    .map(BatchSignedTxJson::from)
+                    .collect()
+            }
+        };
+        Ok(results)
+    }
+
     fn sign_bitcoin_tx(
         &self,
         wallet_id: Uuid,
@@ -285,12 +793,31 @@ impl WrappedVault {
         unsigned_tx: UnsignedBitcoinTxJson,
         password: Option<String>,
     ) -> Result<Vec<u8>, VaultNodeError> {
-        let storage = &self.cfg.get_storage();
         let entry = self.get_entry(wallet_id, entry_id)?;
         if entry.blockchain.get_type() != BlockchainType::Bitcoin {
             return Err(VaultNodeError::OtherInput("Not a bitcoin entry".to_string()));
         }
+        let mut unsigned_tx = unsigned_tx.with_rbf();
+        let fee = self.resolve_fee(wallet_id, &entry, &mut unsigned_tx)?;
+        let policy_key = self.policy_key(wallet_id, entry_id, password.as_deref())?;
+        let policy = self.check_policy(wallet_id, &entry, policy_key.as_ref(), || {
+            self.bitcoin_preview(wallet_id, &entry, &unsigned_tx, fee).map(|preview| Spending::from(&preview))
+        })?;
+        let signed = self.sign_resolved_bitcoin_tx(wallet_id, &entry, unsigned_tx, fee, password.clone())?;
+        self.record_spending(wallet_id, entry_id, policy, password.as_deref())?;
+        Ok(signed)
+    }
 
+    /// Sign a transaction with the fee already resolved
+    fn sign_resolved_bitcoin_tx(
+        &self,
+        wallet_id: Uuid,
+        entry: &WalletEntry,
+        unsigned_tx: UnsignedBitcoinTxJson,
+        fee: u64,
+        password: Option<String>,
+    ) -> Result<Vec<u8>, VaultNodeError> {
+        let storage = &self.cfg.get_storage();
         let seed_ref = match &entry.key {
             PKType::SeedHd(seed) => seed,
             _ => return Err(VaultNodeError::OtherInput("Unsupported PK".to_string()))
@@ -298,11 +825,12 @@ impl WrappedVault {
         let seed = storage.seeds().get(seed_ref.seed_id)?;
         let seed_id = seed.id.clone();
         let hd_account = AccountHDPath::from(&seed_ref.hd_path);
-        let xpub = match &entry.address {
-            Some(ar) => match ar {
-                AddressRef::ExtendedPub(value) => value,
-                _ => return Err(VaultNodeError::OtherInput("Unsupported type of address".to_string()))
-            },
+        if taproot_account(entry).is_some() {
+            return self.sign_taproot_tx(wallet_id, entry, seed_id, &hd_account, unsigned_tx, fee, password);
+        }
+        match &entry.address {
+            Some(AddressRef::ExtendedPub(_)) => {},
+            Some(_) => return Err(VaultNodeError::OtherInput("Unsupported type of address".to_string())),
             None => return Err(VaultNodeError::OtherInput("No address for the entry".to_string()))
         };
 
@@ -321,10 +849,10 @@ impl WrappedVault {
             network: entry.blockchain.as_bitcoin_network_kind(),
             seed: vec![seed],
             keys,
-            input: convert_inputs(unsigned_tx.inputs, &xpub, seed_id, &hd_account)?,
+            input: self.convert_inputs(wallet_id, entry, unsigned_tx.inputs, seed_id, &hd_account)?,
             output: convert_output(unsigned_tx.outputs)?,
             change: entry.clone(),
-            expected_fee: unsigned_tx.fee,
+            expected_fee: fee,
         };
         let valid = proposal.validate();
         if valid.is_ok() {
@@ -334,6 +862,393 @@ impl WrappedVault {
             Err(VaultNodeError::VaultError(format!("Invalid tx: {:?}", valid.expect_err("no_error_on_invalid"))))
         }
     }
+
+    /// Sign inputs of a PSBT that belong to the entry account. The keys are derived from the seed, so it's not available for a Hardware Key.
+    fn sign_psbt(
+        &self,
+        wallet_id: Uuid,
+        entry_id: usize,
+        psbt_base64: String,
+        password: Option<String>,
+        options: SignPsbtOptionsJson,
+    ) -> Result<SignedPsbtJson, VaultNodeError> {
+        let storage = &self.cfg.get_storage();
+        let entry = self.get_entry(wallet_id, entry_id)?;
+        if entry.blockchain.get_type() != BlockchainType::Bitcoin {
+            return Err(VaultNodeError::OtherInput("Not a bitcoin entry".to_string()));
+        }
+        let seed_ref = match &entry.key {
+            PKType::SeedHd(seed) => seed,
+            _ => return Err(VaultNodeError::OtherInput("Unsupported PK".to_string()))
+        };
+        let seed = storage.seeds().get(seed_ref.seed_id)?;
+        if let SeedSource::Ledger(_) = seed.source {
+            return Err(VaultNodeError::OtherInput("PSBT signing is not available for a Hardware Key".to_string()));
+        }
+        let account = DerivationPath::from_str(AccountHDPath::from(&seed_ref.hd_path).to_string().as_str())
+            .map_err(|_| VaultNodeError::OtherProcessing("Invalid entry HDPath".to_string()))?;
+        // a PSBT may be only partially signed by the entry, so the policy cannot tell what it spends
+        if self.has_policy(wallet_id, entry_id) {
+            return Err(VaultNodeError::PolicyRejected(PolicyRule::Method, "PSBT signing is not allowed with a spending policy".to_string()));
+        }
+
+        let mut psbt = psbt::decode(psbt_base64.as_str())?;
+        let password = password.ok_or(VaultError::PasswordRequired)?;
+        let raw_seed = self.get_seed_bytes(seed.id, password)?;
+        let signer = AccountSigner::new(&raw_seed, account, entry.blockchain.as_bitcoin_network_kind())?;
+        let secp = Secp256k1::new();
+        let signed = psbt::sign(&mut psbt, &signer, &secp)?;
+        psbt::into_result(psbt, signed, options.finalize)
+    }
+
+    /// Create a PSBT spending UTXOs of the multisig and add the signatures of this vault. If it's enough signatures
+    /// the transaction is finalized, otherwise the PSBT must be passed to other cosigners.
+    fn sign_multisig_tx(
+        &self,
+        id: Uuid,
+        unsigned_tx: UnsignedBitcoinTxJson,
+        password: Option<String>,
+    ) -> Result<SignedPsbtJson, VaultNodeError> {
+        let multisig = self.get_multisig(id)?;
+        let unsigned_tx = unsigned_tx.with_rbf();
+        let mut inputs = Vec::with_capacity(unsigned_tx.inputs.len());
+        for (i, input) in unsigned_tx.inputs.into_iter().enumerate() {
+            let (change, index) = match &input.hd_path {
+                Some(value) => {
+                    let hd_path = DerivationPath::from_str(value.as_str())
+                        .map_err(|_| VaultNodeError::OtherInput("Invalid HDPath for input".to_string()))?;
+                    multisig.local_child(&hd_path)
+                        .ok_or(VaultNodeError::OtherInput(format!("Input HDPath is not on the multisig account: {}", value)))?
+                },
+                None => match &input.address {
+                    Some(value) => {
+                        let address = Address::from_str(value)
+                            .map_err(|_| VaultNodeError::OtherInput("Invalid input bitcoin address".to_string()))?
+                            .assume_checked();
+                        self.multisig_address_path(&multisig, &address)?
+                            .ok_or(VaultNodeError::OtherInput(format!("Unknown address: {:?}", address)))?
+                    },
+                    None => return Err(VaultNodeError::OtherInput("Neither HDPath nor Address is specified".to_string()))
+                }
+            };
+            inputs.push(MultisigInput {
+                output: OutPoint {
+                    txid: Txid::from_str(input.txid.as_str())
+                        .map_err(|_| VaultNodeError::OtherInput(format!("Invalid txid for input {}", i)))?,
+                    vout: input.vout,
+                },
+                amount: input.amount,
+                sequence: input.sequence,
+                change,
+                index,
+            });
+        }
+        let fee = unsigned_tx.required_fee()?;
+        let psbt = multisig.create_psbt(&inputs, convert_output(unsigned_tx.outputs)?, fee)?;
+        self.sign_multisig(&multisig, psbt, password, true)
+    }
+
+    /// Add the signatures of this vault to a PSBT made by another cosigner
+    fn sign_multisig_psbt(
+        &self,
+        id: Uuid,
+        psbt_base64: String,
+        password: Option<String>,
+        options: SignPsbtOptionsJson,
+    ) -> Result<SignedPsbtJson, VaultNodeError> {
+        let multisig = self.get_multisig(id)?;
+        let mut psbt = psbt::decode(psbt_base64.as_str())?;
+        multisig.prepare_psbt(&mut psbt)?;
+        self.sign_multisig(&multisig, psbt, password, options.finalize)
+    }
+
+    fn sign_multisig(&self, multisig: &Multisig, mut psbt: Psbt, password: Option<String>, finalize: bool) -> Result<SignedPsbtJson, VaultNodeError> {
+        let password = password.ok_or(VaultError::PasswordRequired)?;
+        let raw_seed = self.get_seed_bytes(multisig.seed_id(), password)?;
+        let signer = AccountSigner::new(&raw_seed, multisig.local_path().clone(), multisig.network())?;
+        if signer.fingerprint() != multisig.fingerprint() {
+            return Err(VaultNodeError::OtherProcessing("Seed doesn't match the multisig cosigner".to_string()));
+        }
+        let secp = Secp256k1::new();
+        let signed = psbt::sign(&mut psbt, &signer, &secp)?;
+        psbt::into_result(psbt, signed, finalize)
+    }
+
+    /// Private key of a Bitcoin entry address, which must be within the current account indexes plus the address gap
+    fn bitcoin_address_key(&self, wallet_id: Uuid, entry: &WalletEntry, address: &Address, password: String) -> Result<SecretKey, VaultNodeError> {
+        let storage = &self.cfg.get_storage();
+        let seed_ref = match &entry.key {
+            PKType::SeedHd(seed) => seed,
+            _ => return Err(VaultNodeError::OtherInput("Unsupported PK".to_string()))
+        };
+        let seed = storage.seeds().get(seed_ref.seed_id)?;
+        if let SeedSource::Ledger(_) = seed.source {
+            return Err(VaultNodeError::OtherInput("Message signing is not available for a Hardware Key".to_string()));
+        }
+        let hd_account = AccountHDPath::from(&seed_ref.hd_path);
+        let (change, index) = self.entry_address_path(wallet_id, entry, address)?
+            .ok_or(VaultNodeError::OtherInput(format!("Unknown address: {}", address)))?;
+
+        let raw = self.get_seed_bytes(seed.id, password)?;
+        let master = taproot::master_key(&raw, entry.blockchain.as_bitcoin_network_kind())?;
+        let path = DerivationPath::from_str(format!("{}/{}/{}", hd_account, change, index).as_str())
+            .map_err(|_| VaultNodeError::OtherProcessing("Invalid HDPath".to_string()))?;
+        master.derive_priv(&Secp256k1::new(), &path)
+            .map(|key| key.private_key)
+            .map_err(|_| VaultNodeError::OtherProcessing("Failed to derive key".to_string()))
+    }
+
+    /// Replace a signed transaction of the entry with the same one paying a higher fee (BIP-125). The fee is taken from the change,
+    /// and if it's not enough more UTXOs are spent.
+    fn bump_bitcoin_fee(
+        &self,
+        wallet_id: Uuid,
+        entry_id: usize,
+        options: BumpFeeJson,
+        password: Option<String>,
+    ) -> Result<Vec<u8>, VaultNodeError> {
+        let entry = self.get_entry(wallet_id, entry_id)?;
+        if entry.blockchain.get_type() != BlockchainType::Bitcoin {
+            return Err(VaultNodeError::OtherInput("Not a bitcoin entry".to_string()));
+        }
+        let network = match entry.blockchain.as_bitcoin_network_kind() {
+            NetworkKind::Main => Network::Bitcoin,
+            NetworkKind::Test => Network::Testnet,
+        };
+        let raw = Vec::from_hex(options.raw.trim())
+            .map_err(|_| VaultNodeError::InvalidArgumentValue("Transaction is not a hex".to_string()))?;
+        let original: Transaction = bitcoin::consensus::deserialize(&raw)
+            .map_err(|_| VaultNodeError::InvalidArgumentValue("Invalid transaction".to_string()))?;
+
+        // the change is an output to a change address of the same entry
+        let mut change = None;
+        for (i, output) in original.output.iter().enumerate() {
+            if let Ok(address) = Address::from_script(&output.script_pubkey, network)
+                && matches!(self.entry_address_path(wallet_id, &entry, &address)?, Some((1, _))) {
+                change = Some(i);
+                break;
+            }
+        }
+        let new_change = match &options.change_address {
+            Some(value) => Some(
+                Address::from_str(value)
+                    .map_err(|_| VaultNodeError::InvalidArgumentValue("Invalid change address".to_string()))?
+                    .assume_checked()
+                    .script_pubkey()
+            ),
+            None => None
+        };
+        let mut utxos = Vec::with_capacity(options.utxos.len());
+        for input in options.utxos.iter() {
+            utxos.push(fee_bump::Utxo {
+                output: OutPoint {
+                    txid: Txid::from_str(input.txid.as_str())
+                        .map_err(|_| VaultNodeError::OtherInput("Invalid txid".to_string()))?,
+                    vout: input.vout,
+                },
+                amount: input.amount,
+            });
+        }
+
+        let replacement = fee_bump::build(&original, &utxos, change, new_change, options.fee_rate)?;
+        let mut outputs = Vec::with_capacity(replacement.outputs.len());
+        for output in replacement.outputs {
+            let address = Address::from_script(&output.script_pubkey, network)
+                .map_err(|_| VaultNodeError::OtherInput("Unsupported output script".to_string()))?;
+            outputs.push(OutputJson {
+                address: address.to_string(),
+                amount: output.value.to_sat(),
+            });
+        }
+        let unsigned_tx = UnsignedBitcoinTxJson {
+            inputs: replacement.inputs.iter()
+                .map(|(pos, sequence)| InputJson {
+                    sequence: *sequence,
+                    ..options.utxos[*pos].clone()
+                })
+                .collect(),
+            outputs,
+            fee: Some(replacement.fee),
+            fee_rate: None,
+            rbf: false,
+        };
+        self.sign_bitcoin_tx(wallet_id, entry_id, unsigned_tx, password)
+    }
+
+    /// Make a transaction of the entry paying to `outputs`. The UTXOs to spend are selected from `utxos`, and the change
+    /// goes to the current change address of the entry.
+    fn build_bitcoin_tx(
+        &self,
+        wallet_id: Uuid,
+        entry_id: usize,
+        utxos: Vec<InputJson>,
+        outputs: Vec<OutputJson>,
+        fee_rate: f64,
+    ) -> Result<BuiltBitcoinTxJson, VaultNodeError> {
+        let entry = self.get_entry(wallet_id, entry_id)?;
+        if entry.blockchain.get_type() != BlockchainType::Bitcoin {
+            return Err(VaultNodeError::OtherInput("Not a bitcoin entry".to_string()));
+        }
+        let index = self.cfg.account_indexes.iter()
+            .find(|i| i.wallet_id == wallet_id && i.entry_id == entry_id);
+        let change = with_std_addresses(&entry, index).into_iter()
+            .find(|a| a.role == AddressRole::Change.to_string())
+            .ok_or(VaultNodeError::MissingData("no change address".to_string()))?;
+        let change_address = Address::from_str(change.address.as_str())
+            .map_err(|_| VaultNodeError::OtherProcessing("Invalid change address".to_string()))?
+            .assume_checked();
+        // all addresses of the entry are of the same type, so the inputs are the same as the change
+        let input_type = InputType::of_address(&change_address)
+            .ok_or(VaultNodeError::OtherInput("Unsupported type of address".to_string()))?;
+
+        let amounts: Vec<u64> = utxos.iter().map(|u| u.amount).collect();
+        let selection = coin_select::select(
+            &amounts, input_type, &convert_output(outputs.clone())?, &change_address.script_pubkey(), fee_rate,
+        )?;
+        let mut outputs = outputs;
+        if let Some(amount) = selection.change {
+            outputs.push(OutputJson {
+                address: change.address,
+                amount,
+            });
+        }
+        let tx = UnsignedBitcoinTxJson {
+            inputs: selection.inputs.iter().map(|pos| utxos[*pos].clone()).collect(),
+            outputs,
+            fee: Some(selection.fee),
+            fee_rate: None,
+            rbf: false,
+        };
+        Ok(BuiltBitcoinTxJson {
+            tx,
+            size: TxSizeJson::from(selection.weight),
+        })
+    }
+
+    /// Estimate the size of a transaction signed by the entry
+    fn estimate_bitcoin_tx(&self, wallet_id: Uuid, entry_id: usize, tx: UnsignedBitcoinTxJson) -> Result<TxSizeJson, VaultNodeError> {
+        let entry = self.get_entry(wallet_id, entry_id)?;
+        if entry.blockchain.get_type() != BlockchainType::Bitcoin {
+            return Err(VaultNodeError::OtherInput("Not a bitcoin entry".to_string()));
+        }
+        let weight = tx.estimate_size(entry_input_type(&entry)?)?;
+        Ok(TxSizeJson::from(weight))
+    }
+
+    /// Sets the fee of a transaction of the entry which specifies a fee rate, and returns it. If there is an output to a change
+    /// address of the entry it gets the rest after the fee.
+    fn resolve_fee(&self, wallet_id: Uuid, entry: &WalletEntry, tx: &mut UnsignedBitcoinTxJson) -> Result<u64, VaultNodeError> {
+        let fee_rate = match (tx.fee, tx.fee_rate) {
+            (None, Some(fee_rate)) => fee_rate,
+            _ => return tx.required_fee(),
+        };
+        if !fee_rate.is_finite() || fee_rate <= 0.0 {
+            return Err(VaultNodeError::InvalidArgumentValue("Invalid fee rate".to_string()));
+        }
+        let weight = tx.estimate_size(entry_input_type(entry)?)?;
+        let fee = fee_bump::fee_for(fee_rate, weight.to_vbytes_ceil());
+
+        let total_input = fee_bump::checked_sum(tx.inputs.iter().map(|i| i.amount))?;
+        let mut change = None;
+        for (i, output) in tx.outputs.iter().enumerate() {
+            if let Ok(address) = Address::from_str(output.address.as_str())
+                && matches!(self.entry_address_path(wallet_id, entry, address.assume_checked_ref())?, Some((1, _))) {
+                change = Some((i, address.assume_checked().script_pubkey().minimal_non_dust().to_sat()));
+                break;
+            }
+        }
+        let fee = match change {
+            Some((pos, dust)) => {
+                let others = fee_bump::checked_sum(tx.outputs.iter().enumerate()
+                    .filter(|(i, _)| *i != pos)
+                    .map(|(_, o)| o.amount))?;
+                let value = total_input.checked_sub(fee_bump::checked_sum([others, fee])?)
+                    .filter(|value| *value >= dust)
+                    .ok_or(VaultNodeError::OtherInput("Not enough funds to pay the fee".to_string()))?;
+                tx.outputs[pos].amount = value;
+                fee
+            },
+            None => {
+                let total_output = fee_bump::checked_sum(tx.outputs.iter().map(|o| o.amount))?;
+                total_input.checked_sub(total_output)
+                    .filter(|actual| *actual >= fee)
+                    .ok_or(VaultNodeError::OtherInput("Not enough funds to pay the fee".to_string()))?
+            }
+        };
+        tx.fee = Some(fee);
+        tx.fee_rate = None;
+        Ok(fee)
+    }
+
+    /// Vault cannot sign Taproot inputs, so the keys are derived from the seed and the transaction is signed here
+    fn sign_taproot_tx(
+        &self,
+        wallet_id: Uuid,
+        entry: &WalletEntry,
+        seed_id: Uuid,
+        hd_account: &AccountHDPath,
+        unsigned_tx: UnsignedBitcoinTxJson,
+        fee: u64,
+        password: Option<String>,
+    ) -> Result<Vec<u8>, VaultNodeError> {
+        let password = password.ok_or(VaultError::PasswordRequired)?;
+        let seed = self.get_seed_bytes(seed_id, password)?;
+        let secp = Secp256k1::new();
+        let master = taproot::master_key(&seed, entry.blockchain.as_bitcoin_network_kind())?;
+
+        let mut inputs = Vec::with_capacity(unsigned_tx.inputs.len());
+        for (i, input) in unsigned_tx.inputs.into_iter().enumerate() {
+            let (change, index) = match &input.hd_path {
+                Some(value) => {
+                    let hd_path = StandardHDPath::from_str(value.as_str())
+                        .map_err(|_| VaultNodeError::OtherInput(format!("Invalid HDPath for input {}", i)))?;
+                    if AccountHDPath::from(&hd_path).to_string() != hd_account.to_string() {
+                        return Err(VaultNodeError::OtherInput(format!("Input HDPath is not on the entry account: {}", value)));
+                    }
+                    (hd_path.change(), hd_path.index())
+                },
+                None => match &input.address {
+                    Some(value) => {
+                        let address = Address::from_str(value)
+                            .map_err(|_| VaultNodeError::OtherInput(format!("Invalid bitcoin address for input {}", i)))?
+                            .assume_checked();
+                        self.entry_address_path(wallet_id, entry, &address)?
+                            .ok_or(VaultNodeError::OtherInput(format!("Unknown address for input {}: {}", i, address)))?
+                    },
+                    None => return Err(VaultNodeError::OtherInput(format!("Neither HDPath nor Address is specified for input {}", i)))
+                }
+            };
+            let path = DerivationPath::from_str(format!("{}/{}/{}", hd_account, change, index).as_str())
+                .map_err(|_| VaultNodeError::OtherInput("Invalid HDPath for input".to_string()))?;
+            let key = master.derive_priv(&secp, &path)
+                .map_err(|_| VaultNodeError::OtherProcessing("Failed to derive key".to_string()))?;
+            inputs.push(TaprootInput {
+                output: OutPoint {
+                    txid: Txid::from_str(input.txid.as_str())
+                        .map_err(|_| VaultNodeError::OtherInput("Invalid txid".to_string()))?,
+                    vout: input.vout,
+                },
+                amount: input.amount,
+                sequence: input.sequence,
+                key: key.private_key,
+            });
+        }
+
+        let tx = taproot::sign_transaction(&inputs, convert_output(unsigned_tx.outputs)?, fee)?;
+        Ok(bitcoin::consensus::serialize(&tx))
+    }
+}
+
+/// Type of inputs spending from the entry addresses
+fn entry_input_type(entry: &WalletEntry) -> Result<InputType, VaultNodeError> {
+    let address = with_std_addresses(entry, None).into_iter()
+        .next()
+        .ok_or(VaultNodeError::MissingData("no address".to_string()))?;
+    let address = Address::from_str(address.address.as_str())
+        .map_err(|_| VaultNodeError::OtherProcessing("Invalid entry address".to_string()))?
+        .assume_checked();
+    InputType::of_address(&address)
+        .ok_or(VaultNodeError::OtherInput("Unsupported type of address".to_string()))
 }
 
 fn bitcoin_tx_hash(tx: &Vec<u8>) -> Result<String, VaultNodeError> {
@@ -352,7 +1267,7 @@ fn sign_tx_internal(vault: &WrappedVault, wallet_id: Uuid, entry_id: usize, tx_j
     let signed_tx = match entry.blockchain.get_type() {
         BlockchainType::Ethereum => {
             let unsigned_tx =
-                serde_json::from_str::<UnsignedEthereumTxJson>(tx_json.as_str())
+                serde_json::from_str::<EthereumTxSourceJson>(tx_json.as_str())
                     .map_err(|_| VaultNodeError::InvalidArgument(3))?;
             let signed_tx = vault.sign_ethereum_tx(wallet_id, entry_id, unsigned_tx, password)?;
             let hash = keccak256(signed_tx.as_slice());
@@ -384,6 +1299,10 @@ fn sign_msg_internal(vault: &WrappedVault, wallet_id: Uuid, entry_id: usize, msg
 
     let signed = match entry.blockchain.get_type() {
         BlockchainType::Ethereum => {
+            // a typed message may be a permit, which spends tokens without a transaction, so the policy cannot check it
+            if vault.has_policy(wallet_id, entry_id) {
+                return Err(VaultNodeError::PolicyRejected(PolicyRule::Method, "Message signing is not allowed with a spending policy".to_string()));
+            }
             let input = match &msg {
                 UnsignedMessageJson::EIP191 { message } => {
                     SignMessage::EIP191(message.clone())
@@ -392,6 +1311,7 @@ fn sign_msg_internal(vault: &WrappedVault, wallet_id: Uuid, entry_id: usize, msg
                     let data = parse_eip712(message)?;
                     SignMessage::EIP712(data)
                 }
+                _ => return Err(VaultNodeError::OtherInput("Bitcoin message cannot be signed with an Ethereum entry".to_string()))
             };
             let signature = entry.sign_message(input, password, storage)?;
             let address = entry.address.expect("No address").to_string(); //TODO
@@ -399,13 +1319,35 @@ fn sign_msg_internal(vault: &WrappedVault, wallet_id: Uuid, entry_id: usize, msg
                 UnsignedMessageJson::EIP191 { .. } => {
                     SignedMessageJson::EIP191 { signature, address }
                 },
-                UnsignedMessageJson::EIP712 { .. } => {
+                _ => {
                     SignedMessageJson::EIP712 { signature, address }
                 }
             }
         }
         BlockchainType::Bitcoin => {
-            return Err(VaultNodeError::OtherInput("Message signing with Bitcoin keys is not available".to_string()))
+            let (message, address) = match &msg {
+                UnsignedMessageJson::BIP137 { message, address } | UnsignedMessageJson::BIP322 { message, address } => (message, address),
+                _ => return Err(VaultNodeError::OtherInput("Ethereum message cannot be signed with a Bitcoin entry".to_string()))
+            };
+            let network = entry.blockchain.as_bitcoin_network_kind();
+            let address = Address::from_str(address.as_str())
+                .map_err(|_| VaultNodeError::InvalidArgumentValue("Invalid bitcoin address".to_string()))?;
+            if bitcoin_message::address_network(&address) != network {
+                return Err(VaultNodeError::InvalidArgumentValue("Address is for another network".to_string()));
+            }
+            let address = address.assume_checked();
+            let password = password.ok_or(VaultError::PasswordRequired)?;
+            let key = vault.bitcoin_address_key(wallet_id, &entry, &address, password)?;
+            match &msg {
+                UnsignedMessageJson::BIP137 { .. } => SignedMessageJson::BIP137 {
+                    signature: bitcoin_message::sign_bip137(&key, &address, network, message)?,
+                    address: address.to_string(),
+                },
+                _ => SignedMessageJson::BIP322 {
+                    signature: bitcoin_message::sign_bip322(&key, &address, network, message)?,
+                    address: address.to_string(),
+                },
+            }
         }
     };
 
@@ -414,17 +1356,52 @@ fn sign_msg_internal(vault: &WrappedVault, wallet_id: Uuid, entry_id: usize, msg
 
 fn signature_author_internal(msg: UnsignedMessageJson, signature: String) -> Result<String, VaultNodeError> {
 
-    let signature = EthereumBasicSignature::from_str(signature.as_str())?;
-    let author= match msg {
+    let author = match msg {
         UnsignedMessageJson::EIP191 { message } => {
-            signature.extract_signer(&message as &dyn SignableHash)?
+            let signature = EthereumBasicSignature::from_str(signature.as_str())?;
+            signature.extract_signer(&message as &dyn SignableHash)?.to_string()
         },
         UnsignedMessageJson::EIP712 { message } => {
-            signature.extract_signer(&parse_eip712(message)? as &dyn SignableHash)?
+            let signature = EthereumBasicSignature::from_str(signature.as_str())?;
+            signature.extract_signer(&parse_eip712(message)? as &dyn SignableHash)?.to_string()
+        },
+        UnsignedMessageJson::BIP137 { message, address } => {
+            let address = Address::from_str(address.as_str())
+                .map_err(|_| VaultNodeError::InvalidArgumentValue("Invalid bitcoin address".to_string()))?;
+            let network = bitcoin_message::address_network(&address);
+            bitcoin_message::recover_bip137(message.as_str(), signature.as_str(), network)?.to_string()
+        },
+        UnsignedMessageJson::BIP322 { message, address } => {
+            // it's not possible to recover the author, so only verify the signature for the address
+            let address = Address::from_str(address.as_str())
+                .map_err(|_| VaultNodeError::InvalidArgumentValue("Invalid bitcoin address".to_string()))?
+                .assume_checked();
+            if !bitcoin_message::verify_bip322(&address, message.as_str(), signature.as_str())? {
+                return Err(VaultNodeError::InvalidArgumentValue("Invalid signature".to_string()));
+            }
+            address.to_string()
         },
     };
 
-    Ok(author.to_string())
+    Ok(author)
+}
+
+fn decode_tx_internal(blockchain: Blockchain, raw: String) -> Result<DecodedTxJson, VaultNodeError> {
+    let raw = Vec::from_hex(to_even_str(trim_hex(raw.as_str())))
+        .map_err(|_| VaultNodeError::InvalidArgumentValue("Transaction is not a hex".to_string()))?;
+    let decoded = match blockchain.get_type() {
+        BlockchainType::Ethereum => {
+            let tx = tx_decode::decode_ethereum(&raw)?;
+            if let Some(chain_id) = tx.fields.chain_id {
+                check_chain_id(chain_id, blockchain)?;
+            }
+            DecodedTxJson::Ethereum(Box::new(tx))
+        },
+        BlockchainType::Bitcoin => {
+            DecodedTxJson::Bitcoin(tx_decode::decode_bitcoin(&raw, blockchain.as_bitcoin_network_kind())?)
+        }
+    };
+    Ok(decoded)
 }
 
 #[neon_frame_fn(channel=4)]
@@ -462,6 +1439,262 @@ pub fn sign_tx<H>(cx: &mut FunctionContext, handler: H) -> Result<(), VaultNodeE
     Ok(())
 }
 
+#[neon_frame_fn(channel=4)]
+pub fn sign_tx_batch<H>(cx: &mut FunctionContext, handler: H) -> Result<(), VaultNodeError>
+    where
+        H: FnOnce(Result<Vec<BatchSignedTxJson>, VaultNodeError>) + Send + 'static {
+
+    let vault = Instance::get_vault()?;
+
+    let (wallet_id, entry_id) = args_get_wallet_and_entry_ids(cx, 0)?;
+    let txs = cx
+        .argument::<JsString>(2)
+        .map_err(|_| VaultNodeError::ArgumentMissing(2, "txs".to_string()))?
+        .value(cx);
+    let txs: Vec<serde_json::Value> = serde_json::from_str(txs.as_str())
+        .map_err(|_| VaultNodeError::InvalidArgument(2))?;
+    let password = args_get_str(cx, 3);
+
+    std::thread::spawn(move || {
+        let vault = vault.lock().unwrap();
+        let result = vault.sign_tx_batch(wallet_id, entry_id, txs, password);
+        handler(result);
+    });
+
+    Ok(())
+}
+
+#[neon_frame_fn(channel=3)]
+pub fn sign_preview<H>(cx: &mut FunctionContext, handler: H) -> Result<(), VaultNodeError>
+    where
+        H: FnOnce(Result<TxPreviewJson, VaultNodeError>) + Send + 'static {
+
+    let vault = Instance::get_vault()?;
+
+    let (wallet_id, entry_id) = args_get_wallet_and_entry_ids(cx, 0)?;
+    let unsigned_tx = cx
+        .argument::<JsString>(2)
+        .map_err(|_| VaultNodeError::ArgumentMissing(2, "tx".to_string()))?
+        .value(cx);
+
+    std::thread::spawn(move || {
+        let vault = vault.lock().unwrap();
+        let result = vault.preview_tx(wallet_id, entry_id, unsigned_tx);
+        handler(result);
+    });
+
+    Ok(())
+}
+
+#[neon_frame_fn(channel=4)]
+pub fn sign_authorization<H>(cx: &mut FunctionContext, handler: H) -> Result<(), VaultNodeError>
+    where
+        H: FnOnce(Result<AuthorizationJson, VaultNodeError>) + Send + 'static {
+
+    let vault = Instance::get_vault()?;
+
+    let (wallet_id, entry_id) = args_get_wallet_and_entry_ids(cx, 0)?;
+    let authorization = cx
+        .argument::<JsString>(2)
+        .map_err(|_| VaultNodeError::ArgumentMissing(2, "authorization".to_string()))?
+        .value(cx);
+    let authorization = serde_json::from_str::<UnsignedAuthorizationJson>(authorization.as_str())
+        .map_err(|_| VaultNodeError::InvalidArgument(2))?;
+    let password = cx
+        .argument::<JsString>(3)
+        .map_err(|_| VaultNodeError::ArgumentMissing(3, "password".to_string()))?
+        .value(cx);
+
+    std::thread::spawn(move || {
+        let vault = vault.lock().unwrap();
+        let result = vault.sign_authorization(wallet_id, entry_id, authorization, password);
+        handler(result);
+    });
+
+    Ok(())
+}
+
+#[neon_frame_fn(channel=4)]
+pub fn sign_bump_fee<H>(cx: &mut FunctionContext, handler: H) -> Result<(), VaultNodeError>
+    where
+        H: FnOnce(Result<SignedTxJson, VaultNodeError>) + Send + 'static {
+
+    let vault = Instance::get_vault()?;
+
+    let (wallet_id, entry_id) = args_get_wallet_and_entry_ids(cx, 0)?;
+    let options = cx
+        .argument::<JsString>(2)
+        .map_err(|_| VaultNodeError::ArgumentMissing(2, "options".to_string()))?
+        .value(cx);
+    let options: BumpFeeJson = serde_json::from_str(options.as_str())
+        .map_err(|_| VaultNodeError::InvalidArgument(2))?;
+    let password = args_get_str(cx, 3);
+
+    std::thread::spawn(move || {
+        let vault = vault.lock().unwrap();
+        let result = vault.bump_bitcoin_fee(wallet_id, entry_id, options, password)
+            .and_then(|raw| Ok(SignedTxJson {
+                txid: bitcoin_tx_hash(&raw)?,
+                raw: hex::encode(raw),
+            }));
+        handler(result);
+    });
+
+    Ok(())
+}
+
+#[neon_frame_fn(channel=5)]
+pub fn build_bitcoin_tx<H>(cx: &mut FunctionContext, handler: H) -> Result<(), VaultNodeError>
+    where
+        H: FnOnce(Result<BuiltBitcoinTxJson, VaultNodeError>) + Send + 'static {
+
+    let vault = Instance::get_vault()?;
+
+    let (wallet_id, entry_id) = args_get_wallet_and_entry_ids(cx, 0)?;
+    let utxos = cx
+        .argument::<JsString>(2)
+        .map_err(|_| VaultNodeError::ArgumentMissing(2, "utxos".to_string()))?
+        .value(cx);
+    let utxos: Vec<InputJson> = serde_json::from_str(utxos.as_str())
+        .map_err(|_| VaultNodeError::InvalidArgument(2))?;
+    let outputs = cx
+        .argument::<JsString>(3)
+        .map_err(|_| VaultNodeError::ArgumentMissing(3, "outputs".to_string()))?
+        .value(cx);
+    let outputs: Vec<OutputJson> = serde_json::from_str(outputs.as_str())
+        .map_err(|_| VaultNodeError::InvalidArgument(3))?;
+    let fee_rate = cx
+        .argument::<JsNumber>(4)
+        .map_err(|_| VaultNodeError::ArgumentMissing(4, "feeRate".to_string()))?
+        .value(cx);
+
+    std::thread::spawn(move || {
+        let vault = vault.lock().unwrap();
+        let result = vault.build_bitcoin_tx(wallet_id, entry_id, utxos, outputs, fee_rate);
+        handler(result);
+    });
+
+    Ok(())
+}
+
+#[neon_frame_fn(channel=3)]
+pub fn estimate_bitcoin_tx<H>(cx: &mut FunctionContext, handler: H) -> Result<(), VaultNodeError>
+    where
+        H: FnOnce(Result<TxSizeJson, VaultNodeError>) + Send + 'static {
+
+    let vault = Instance::get_vault()?;
+
+    let (wallet_id, entry_id) = args_get_wallet_and_entry_ids(cx, 0)?;
+    let tx = cx
+        .argument::<JsString>(2)
+        .map_err(|_| VaultNodeError::ArgumentMissing(2, "tx".to_string()))?
+        .value(cx);
+    let tx: UnsignedBitcoinTxJson = serde_json::from_str(tx.as_str())
+        .map_err(|_| VaultNodeError::InvalidArgument(2))?;
+
+    std::thread::spawn(move || {
+        let vault = vault.lock().unwrap();
+        let result = vault.estimate_bitcoin_tx(wallet_id, entry_id, tx);
+        handler(result);
+    });
+
+    Ok(())
+}
+
+#[neon_frame_fn(channel=5)]
+pub fn sign_psbt<H>(cx: &mut FunctionContext, handler: H) -> Result<(), VaultNodeError>
+    where
+        H: FnOnce(Result<SignedPsbtJson, VaultNodeError>) + Send + 'static {
+
+    let vault = Instance::get_vault()?;
+
+    let wallet_id = cx
+        .argument::<JsString>(0)
+        .map_err(|_| VaultNodeError::ArgumentMissing(0, "walletId".to_string()))?
+        .value(cx);
+    let wallet_id = Uuid::from_str(wallet_id.as_str())
+        .map_err(|_| VaultNodeError::InvalidArgument(0))?;
+
+    let entry_id = cx
+        .argument::<JsNumber>(1)
+        .map_err(|_| VaultNodeError::ArgumentMissing(1, "entryId".to_string()))?
+        .value(cx) as usize;
+
+    let psbt = cx
+        .argument::<JsString>(2)
+        .map_err(|_| VaultNodeError::ArgumentMissing(2, "psbt".to_string()))?
+        .value(cx);
+
+    let password = args_get_str(cx, 3);
+
+    let options = match args_get_str(cx, 4) {
+        Some(json) => serde_json::from_str::<SignPsbtOptionsJson>(json.as_str())
+            .map_err(|_| VaultNodeError::InvalidArgument(4))?,
+        None => SignPsbtOptionsJson::default(),
+    };
+
+    std::thread::spawn(move || {
+        let vault = vault.lock().unwrap();
+        let result = vault.sign_psbt(wallet_id, entry_id, psbt, password, options);
+        handler(result);
+    });
+
+    Ok(())
+}
+
+#[neon_frame_fn(channel=3)]
+pub fn sign_multisig_tx<H>(cx: &mut FunctionContext, handler: H) -> Result<(), VaultNodeError>
+    where
+        H: FnOnce(Result<SignedPsbtJson, VaultNodeError>) + Send + 'static {
+
+    let vault = Instance::get_vault()?;
+
+    let id = args_get_uuid(cx, 0)?;
+    let unsigned_tx = cx
+        .argument::<JsString>(1)
+        .map_err(|_| VaultNodeError::ArgumentMissing(1, "tx".to_string()))?
+        .value(cx);
+    let unsigned_tx: UnsignedBitcoinTxJson = serde_json::from_str(unsigned_tx.as_str())
+        .map_err(|_| VaultNodeError::InvalidArgument(1))?;
+    let password = args_get_str(cx, 2);
+
+    std::thread::spawn(move || {
+        let vault = vault.lock().unwrap();
+        let result = vault.sign_multisig_tx(id, unsigned_tx, password);
+        handler(result);
+    });
+
+    Ok(())
+}
+
+#[neon_frame_fn(channel=4)]
+pub fn sign_multisig_psbt<H>(cx: &mut FunctionContext, handler: H) -> Result<(), VaultNodeError>
+    where
+        H: FnOnce(Result<SignedPsbtJson, VaultNodeError>) + Send + 'static {
+
+    let vault = Instance::get_vault()?;
+
+    let id = args_get_uuid(cx, 0)?;
+    let psbt = cx
+        .argument::<JsString>(1)
+        .map_err(|_| VaultNodeError::ArgumentMissing(1, "psbt".to_string()))?
+        .value(cx);
+    let password = args_get_str(cx, 2);
+    let options = match args_get_str(cx, 3) {
+        Some(json) => serde_json::from_str::<SignPsbtOptionsJson>(json.as_str())
+            .map_err(|_| VaultNodeError::InvalidArgument(3))?,
+        None => SignPsbtOptionsJson::default(),
+    };
+
+    std::thread::spawn(move || {
+        let vault = vault.lock().unwrap();
+        let result = vault.sign_multisig_psbt(id, psbt, password, options);
+        handler(result);
+    });
+
+    Ok(())
+}
+
 #[neon_frame_fn(channel=4)]
 pub fn sign_message<H>(cx: &mut FunctionContext, handler: H) -> Result<(), VaultNodeError>
     where
@@ -528,6 +1761,31 @@ pub fn signature_author<H>(cx: &mut FunctionContext, handler: H) -> Result<(), V
     Ok(())
 }
 
+#[neon_frame_fn(channel=2)]
+pub fn decode_tx<H>(cx: &mut FunctionContext, handler: H) -> Result<(), VaultNodeError>
+    where
+        H: FnOnce(Result<DecodedTxJson, VaultNodeError>) + Send + 'static {
+
+    let blockchain = cx
+        .argument::<JsNumber>(0)
+        .map_err(|_| VaultNodeError::ArgumentMissing(0, "blockchain".to_string()))?
+        .value(cx);
+    let blockchain = Blockchain::try_from(blockchain as u32)
+        .map_err(|_| VaultNodeError::InvalidArgument(0))?;
+
+    let raw = cx
+        .argument::<JsString>(1)
+        .map_err(|_| VaultNodeError::ArgumentMissing(1, "raw".to_string()))?
+        .value(cx);
+
+    std::thread::spawn(move || {
+        let result = decode_tx_internal(blockchain, raw);
+        handler(result);
+    });
+
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use crate::sign::bitcoin_tx_hash;
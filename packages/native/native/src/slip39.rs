@@ -0,0 +1,579 @@
+//! SLIP-39 Shamir's Secret-Sharing for the seeds, see https://github.com/satoshilabs/slips/blob/master/slip-0039.md
+//!
+//! The master secret is split into groups of mnemonic shares, and it can be recovered only when enough groups each have
+//! enough member shares. The recovered secret is used as the BIP-32 seed, i.e. the same bytes as the Vault keeps for a seed.
+
+use std::collections::HashMap;
+
+use bitcoin::hashes::{sha256, Hash, HashEngine, Hmac, HmacEngine};
+use lazy_static::lazy_static;
+use rand::RngCore;
+use crate::errors::VaultNodeError;
+
+const RADIX_BITS: usize = 10;
+const ID_LENGTH_BITS: usize = 15;
+const CHECKSUM_LENGTH_WORDS: usize = 3;
+/// identifier + iteration exponent (2 words), group and member details (2 words), and the checksum
+const METADATA_LENGTH_WORDS: usize = 4 + CHECKSUM_LENGTH_WORDS;
+const MIN_STRENGTH_BYTES: usize = 16;
+const MAX_SHARE_COUNT: u8 = 16;
+const MAX_ITERATION_EXPONENT: u8 = 15;
+const DIGEST_LENGTH_BYTES: usize = 4;
+const DIGEST_INDEX: u8 = 254;
+const SECRET_INDEX: u8 = 255;
+const ROUND_COUNT: u8 = 4;
+const BASE_ITERATION_COUNT: u32 = 10000;
+const CUSTOMIZATION_STRING: &[u8] = b"shamir";
+const CUSTOMIZATION_STRING_EXTENDABLE: &[u8] = b"shamir_extendable";
+
+lazy_static! {
+    static ref WORDLIST: Vec<&'static str> = include_str!("slip39_wordlist.txt").lines().collect();
+    static ref GF256: Gf256 = Gf256::new();
+}
+
+/// Number of member shares in a group and how many of them are required to recover the group secret
+#[derive(Deserialize, Clone, Copy, Debug, Eq, PartialEq)]
+pub struct GroupSpec {
+    pub threshold: u8,
+    pub count: u8,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+struct Share {
+    identifier: u16,
+    extendable: bool,
+    iteration_exponent: u8,
+    group_index: u8,
+    group_threshold: u8,
+    group_count: u8,
+    member_index: u8,
+    member_threshold: u8,
+    value: Vec<u8>,
+}
+
+struct Gf256 {
+    exp: [u8; 255],
+    log: [u8; 256],
+}
+
+impl Gf256 {
+    fn new() -> Gf256 {
+        let mut exp = [0u8; 255];
+        let mut log = [0u8; 256];
+        // x + 1 is a generator of the multiplicative group of GF(256) with the Rijndael polynomial
+        let mut poly: u16 = 1;
+        for (i, e) in exp.iter_mut().enumerate() {
+            *e = poly as u8;
+            log[poly as usize] = i as u8;
+            poly = (poly << 1) ^ poly;
+            if poly & 0x100 != 0 {
+                poly ^= 0x11b;
+            }
+        }
+        Gf256 { exp, log }
+    }
+
+    fn log(&self, x: u8) -> i32 {
+        self.log[x as usize] as i32
+    }
+
+    /// Lagrange interpolation of the shares, evaluated at `x`
+    fn interpolate(&self, shares: &[(u8, Vec<u8>)], x: u8) -> Result<Vec<u8>, VaultNodeError> {
+        if let Some((_, value)) = shares.iter().find(|(index, _)| *index == x) {
+            return Ok(value.clone());
+        }
+        let length = shares.first()
+            .map(|(_, value)| value.len())
+            .ok_or(VaultNodeError::OtherProcessing("No shares to interpolate".to_string()))?;
+        if shares.iter().any(|(_, value)| value.len() != length) {
+            return Err(VaultNodeError::InvalidArgumentValue("SLIP-39 shares have different length".to_string()));
+        }
+
+        let log_prod: i32 = shares.iter().map(|(index, _)| self.log(index ^ x)).sum();
+        let mut result = vec![0u8; length];
+        for (index, value) in shares {
+            let others: i32 = shares.iter()
+                .filter(|(other, _)| other != index)
+                .map(|(other, _)| self.log(index ^ other))
+                .sum();
+            let log_basis = (log_prod - self.log(index ^ x) - others).rem_euclid(255);
+            for (r, b) in result.iter_mut().zip(value.iter()) {
+                if *b != 0 {
+                    *r ^= self.exp[((self.log(*b) + log_basis) % 255) as usize];
+                }
+            }
+        }
+        Ok(result)
+    }
+}
+
+fn hmac_sha256(key: &[u8], data: &[u8]) -> [u8; 32] {
+    let mut engine = HmacEngine::<sha256::Hash>::new(key);
+    engine.input(data);
+    Hmac::<sha256::Hash>::from_engine(engine).to_byte_array()
+}
+
+fn pbkdf2_sha256(password: &[u8], salt: &[u8], iterations: u32, length: usize) -> Vec<u8> {
+    let mut result = Vec::with_capacity(length);
+    let mut block: u32 = 1;
+    while result.len() < length {
+        let mut engine = HmacEngine::<sha256::Hash>::new(password);
+        engine.input(salt);
+        engine.input(&block.to_be_bytes());
+        let mut u = Hmac::<sha256::Hash>::from_engine(engine).to_byte_array();
+        let mut t = u;
+        for _ in 1..iterations {
+            u = hmac_sha256(password, &u);
+            t.iter_mut().zip(u.iter()).for_each(|(a, b)| *a ^= b);
+        }
+        result.extend_from_slice(&t);
+        block += 1;
+    }
+    result.truncate(length);
+    result
+}
+
+fn rs1024_polymod(values: &[u16]) -> u32 {
+    const GEN: [u32; 10] = [
+        0xE0E040, 0x1C1C080, 0x3838100, 0x7070200, 0xE0E0009,
+        0x1C0C2412, 0x38086C24, 0x3090FC48, 0x21B1F890, 0x3F3F120,
+    ];
+    let mut chk: u32 = 1;
+    for v in values {
+        let b = chk >> 20;
+        chk = ((chk & 0xFFFFF) << 10) ^ (*v as u32);
+        for (i, g) in GEN.iter().enumerate() {
+            if (b >> i) & 1 == 1 {
+                chk ^= g;
+            }
+        }
+    }
+    chk
+}
+
+fn customization(extendable: bool) -> Vec<u16> {
+    let value = if extendable { CUSTOMIZATION_STRING_EXTENDABLE } else { CUSTOMIZATION_STRING };
+    value.iter().map(|c| *c as u16).collect()
+}
+
+fn rs1024_create_checksum(data: &[u16], extendable: bool) -> Vec<u16> {
+    let mut values = customization(extendable);
+    values.extend_from_slice(data);
+    values.extend_from_slice(&[0; CHECKSUM_LENGTH_WORDS]);
+    let polymod = rs1024_polymod(&values) ^ 1;
+    (0..CHECKSUM_LENGTH_WORDS)
+        .map(|i| ((polymod >> (RADIX_BITS * (CHECKSUM_LENGTH_WORDS - 1 - i))) & 1023) as u16)
+        .collect()
+}
+
+fn rs1024_verify_checksum(data: &[u16], extendable: bool) -> bool {
+    let mut values = customization(extendable);
+    values.extend_from_slice(data);
+    rs1024_polymod(&values) == 1
+}
+
+/// Salt for the Feistel rounds. Extendable backups don't depend on the identifier, so new shares may be created later with another one
+fn salt(identifier: u16, extendable: bool) -> Vec<u8> {
+    if extendable {
+        vec![]
+    } else {
+        let mut salt = CUSTOMIZATION_STRING.to_vec();
+        salt.extend_from_slice(&identifier.to_be_bytes());
+        salt
+    }
+}
+
+fn round_function(i: u8, passphrase: &[u8], iteration_exponent: u8, salt: &[u8], r: &[u8]) -> Vec<u8> {
+    let mut password = vec![i];
+    password.extend_from_slice(passphrase);
+    let mut round_salt = salt.to_vec();
+    round_salt.extend_from_slice(r);
+    let iterations = (BASE_ITERATION_COUNT << iteration_exponent) / ROUND_COUNT as u32;
+    pbkdf2_sha256(&password, &round_salt, iterations, r.len())
+}
+
+fn feistel(value: &[u8], passphrase: &[u8], iteration_exponent: u8, identifier: u16, extendable: bool, rounds: Vec<u8>) -> Vec<u8> {
+    let half = value.len() / 2;
+    let mut l = value[..half].to_vec();
+    let mut r = value[half..].to_vec();
+    let salt = salt(identifier, extendable);
+    for i in rounds {
+        let f = round_function(i, passphrase, iteration_exponent, &salt, &r);
+        let next: Vec<u8> = l.iter().zip(f.iter()).map(|(a, b)| a ^ b).collect();
+        l = r;
+        r = next;
+    }
+    let mut result = r;
+    result.extend_from_slice(&l);
+    result
+}
+
+fn encrypt(master_secret: &[u8], passphrase: &[u8], iteration_exponent: u8, identifier: u16, extendable: bool) -> Vec<u8> {
+    feistel(master_secret, passphrase, iteration_exponent, identifier, extendable, (0..ROUND_COUNT).collect())
+}
+
+fn decrypt(encrypted: &[u8], passphrase: &[u8], iteration_exponent: u8, identifier: u16, extendable: bool) -> Vec<u8> {
+    feistel(encrypted, passphrase, iteration_exponent, identifier, extendable, (0..ROUND_COUNT).rev().collect())
+}
+
+fn random_bytes<R: RngCore>(rng: &mut R, length: usize) -> Vec<u8> {
+    let mut value = vec![0u8; length];
+    rng.fill_bytes(&mut value);
+    value
+}
+
+fn split_secret<R: RngCore>(rng: &mut R, threshold: u8, count: u8, secret: &[u8]) -> Result<Vec<(u8, Vec<u8>)>, VaultNodeError> {
+    if threshold == 0 || threshold > count || count > MAX_SHARE_COUNT {
+        return Err(VaultNodeError::InvalidArgumentValue("Invalid SLIP-39 threshold".to_string()));
+    }
+    if threshold == 1 {
+        return Ok((0..count).map(|i| (i, secret.to_vec())).collect());
+    }
+    let random_share_count = threshold - 2;
+    let mut shares: Vec<(u8, Vec<u8>)> = (0..random_share_count)
+        .map(|i| (i, random_bytes(rng, secret.len())))
+        .collect();
+
+    let random_part = random_bytes(rng, secret.len() - DIGEST_LENGTH_BYTES);
+    let mut digest = hmac_sha256(&random_part, secret)[..DIGEST_LENGTH_BYTES].to_vec();
+    digest.extend_from_slice(&random_part);
+
+    let mut base = shares.clone();
+    base.push((DIGEST_INDEX, digest));
+    base.push((SECRET_INDEX, secret.to_vec()));
+
+    for i in random_share_count..count {
+        shares.push((i, GF256.interpolate(&base, i)?));
+    }
+    Ok(shares)
+}
+
+fn recover_secret(threshold: u8, shares: &[(u8, Vec<u8>)]) -> Result<Vec<u8>, VaultNodeError> {
+    if threshold == 1 {
+        return shares.first()
+            .map(|(_, value)| value.clone())
+            .ok_or(VaultNodeError::InvalidArgumentValue("Not enough SLIP-39 shares".to_string()));
+    }
+    let secret = GF256.interpolate(shares, SECRET_INDEX)?;
+    let digest = GF256.interpolate(shares, DIGEST_INDEX)?;
+    let expected = hmac_sha256(&digest[DIGEST_LENGTH_BYTES..], &secret);
+    if digest[..DIGEST_LENGTH_BYTES] != expected[..DIGEST_LENGTH_BYTES] {
+        return Err(VaultNodeError::InvalidArgumentValue("Invalid SLIP-39 shares, digest doesn't match".to_string()));
+    }
+    Ok(secret)
+}
+
+impl Share {
+    fn to_words(&self) -> Vec<u16> {
+        let id_exp = ((self.identifier as u32) << 5)
+            | ((self.extendable as u32) << 4)
+            | (self.iteration_exponent as u32);
+        let group = ((self.group_index as u32) << 16)
+            | (((self.group_threshold - 1) as u32) << 12)
+            | (((self.group_count - 1) as u32) << 8)
+            | ((self.member_index as u32) << 4)
+            | ((self.member_threshold - 1) as u32);
+        let mut words: Vec<u16> = vec![
+            (id_exp >> RADIX_BITS) as u16, (id_exp & 1023) as u16,
+            (group >> RADIX_BITS) as u16, (group & 1023) as u16,
+        ];
+
+        // the value is padded with zero bits on the left, to the full number of words
+        let word_count = (self.value.len() * 8).div_ceil(RADIX_BITS);
+        let padding = word_count * RADIX_BITS - self.value.len() * 8;
+        let mut acc: u32 = 0;
+        let mut bits = padding;
+        for b in &self.value {
+            acc = (acc << 8) | (*b as u32);
+            bits += 8;
+            while bits >= RADIX_BITS {
+                bits -= RADIX_BITS;
+                words.push(((acc >> bits) & 1023) as u16);
+            }
+        }
+
+        let checksum = rs1024_create_checksum(&words, self.extendable);
+        words.extend(checksum);
+        words
+    }
+
+    fn to_mnemonic(&self) -> String {
+        self.to_words().iter()
+            .map(|w| WORDLIST[*w as usize])
+            .collect::<Vec<&str>>()
+            .join(" ")
+    }
+
+    fn parse(mnemonic: &str) -> Result<Share, VaultNodeError> {
+        let invalid = |msg: &str| VaultNodeError::InvalidArgumentValue(format!("Invalid SLIP-39 share: {}", msg));
+        let words: Vec<u16> = mnemonic.split_whitespace()
+            .map(|w| {
+                let w = w.to_lowercase();
+                WORDLIST.binary_search(&w.as_str())
+                    .map(|i| i as u16)
+                    .map_err(|_| invalid(&format!("unknown word {}", w)))
+            })
+            .collect::<Result<Vec<u16>, VaultNodeError>>()?;
+
+        let min_words = METADATA_LENGTH_WORDS + (MIN_STRENGTH_BYTES * 8).div_ceil(RADIX_BITS);
+        if words.len() < min_words {
+            return Err(invalid("too short"));
+        }
+        let padding = (RADIX_BITS * (words.len() - METADATA_LENGTH_WORDS)) % 16;
+        if padding > 8 {
+            return Err(invalid("invalid length"));
+        }
+
+        let id_exp = ((words[0] as u32) << RADIX_BITS) | (words[1] as u32);
+        let identifier = (id_exp >> 5) as u16;
+        let extendable = (id_exp >> 4) & 1 == 1;
+        let iteration_exponent = (id_exp & 0x0f) as u8;
+        if !rs1024_verify_checksum(&words, extendable) {
+            return Err(invalid("checksum failed"));
+        }
+
+        let group = ((words[2] as u32) << RADIX_BITS) | (words[3] as u32);
+        let group_index = ((group >> 16) & 0x0f) as u8;
+        let group_threshold = ((group >> 12) & 0x0f) as u8 + 1;
+        let group_count = ((group >> 8) & 0x0f) as u8 + 1;
+        let member_index = ((group >> 4) & 0x0f) as u8;
+        let member_threshold = (group & 0x0f) as u8 + 1;
+        if group_threshold > group_count {
+            return Err(invalid("group threshold is larger than the group count"));
+        }
+
+        let value_words = &words[4..words.len() - CHECKSUM_LENGTH_WORDS];
+        let mut value = Vec::with_capacity(value_words.len() * RADIX_BITS / 8);
+        let mut acc: u32 = 0;
+        let mut bits: usize = 0;
+        let mut skip = padding;
+        for w in value_words {
+            acc = (acc << RADIX_BITS) | (*w as u32);
+            bits += RADIX_BITS;
+            if skip > 0 {
+                if acc >> (bits - skip) != 0 {
+                    return Err(invalid("invalid padding"));
+                }
+                bits -= skip;
+                acc &= (1 << bits) - 1;
+                skip = 0;
+            }
+            while bits >= 8 {
+                bits -= 8;
+                value.push(((acc >> bits) & 0xff) as u8);
+            }
+            acc &= (1 << bits) - 1;
+        }
+
+        Ok(Share {
+            identifier,
+            extendable,
+            iteration_exponent,
+            group_index,
+            group_threshold,
+            group_count,
+            member_index,
+            member_threshold,
+            value,
+        })
+    }
+}
+
+/// Split the master secret into mnemonic shares. Returns the list of shares for each group, in the same order as the `groups`
+pub fn split(master_secret: &[u8], passphrase: &str, group_threshold: u8, groups: &[GroupSpec], iteration_exponent: u8) -> Result<Vec<Vec<String>>, VaultNodeError> {
+    split_with_rng(&mut rand::thread_rng(), master_secret, passphrase, group_threshold, groups, iteration_exponent)
+}
+
+fn split_with_rng<R: RngCore>(rng: &mut R, master_secret: &[u8], passphrase: &str, group_threshold: u8, groups: &[GroupSpec], iteration_exponent: u8) -> Result<Vec<Vec<String>>, VaultNodeError> {
+    if master_secret.len() < MIN_STRENGTH_BYTES || !master_secret.len().is_multiple_of(2) {
+        return Err(VaultNodeError::InvalidArgumentValue("Secret must be at least 128 bits and a multiple of 16 bits".to_string()));
+    }
+    if iteration_exponent > MAX_ITERATION_EXPONENT {
+        return Err(VaultNodeError::InvalidArgumentValue("Iteration exponent is too large".to_string()));
+    }
+    if group_threshold == 0 || group_threshold as usize > groups.len() || groups.len() > MAX_SHARE_COUNT as usize {
+        return Err(VaultNodeError::InvalidArgumentValue("Invalid SLIP-39 group threshold".to_string()));
+    }
+    for group in groups {
+        if group.threshold == 1 && group.count > 1 {
+            return Err(VaultNodeError::InvalidArgumentValue("Multiple member shares with threshold 1 are not allowed, use 1-of-1 instead".to_string()));
+        }
+    }
+    if !passphrase.chars().all(|c| (' '..='~').contains(&c)) {
+        return Err(VaultNodeError::InvalidArgumentValue("Passphrase must contain only printable ASCII characters".to_string()));
+    }
+
+    let mut id_bytes = [0u8; 2];
+    rng.fill_bytes(&mut id_bytes);
+    let identifier = u16::from_be_bytes(id_bytes) & ((1 << ID_LENGTH_BITS) - 1);
+    let extendable = true;
+
+    let encrypted = encrypt(master_secret, passphrase.as_bytes(), iteration_exponent, identifier, extendable);
+    let group_shares = split_secret(rng, group_threshold, groups.len() as u8, &encrypted)?;
+
+    let mut result = Vec::with_capacity(groups.len());
+    for ((group_index, group_secret), spec) in group_shares.iter().zip(groups.iter()) {
+        let members = split_secret(rng, spec.threshold, spec.count, group_secret)?;
+        let mnemonics = members.into_iter()
+            .map(|(member_index, value)| Share {
+                identifier,
+                extendable,
+                iteration_exponent,
+                group_index: *group_index,
+                group_threshold,
+                group_count: groups.len() as u8,
+                member_index,
+                member_threshold: spec.threshold,
+                value,
+            }.to_mnemonic())
+            .collect();
+        result.push(mnemonics);
+    }
+    Ok(result)
+}
+
+/// Recover the master secret from mnemonic shares. The shares may come in any order and may include more than required.
+pub fn combine(mnemonics: &[String], passphrase: &str) -> Result<Vec<u8>, VaultNodeError> {
+    let shares = mnemonics.iter()
+        .map(|m| Share::parse(m.as_str()))
+        .collect::<Result<Vec<Share>, VaultNodeError>>()?;
+    let first = shares.first()
+        .ok_or(VaultNodeError::InvalidArgumentValue("No SLIP-39 shares provided".to_string()))?;
+
+    let compatible = shares.iter().all(|s| {
+        s.identifier == first.identifier
+            && s.extendable == first.extendable
+            && s.iteration_exponent == first.iteration_exponent
+            && s.group_threshold == first.group_threshold
+            && s.group_count == first.group_count
+            && s.value.len() == first.value.len()
+    });
+    if !compatible {
+        return Err(VaultNodeError::InvalidArgumentValue("SLIP-39 shares belong to different backups".to_string()));
+    }
+
+    let mut groups: HashMap<u8, Vec<&Share>> = HashMap::new();
+    for share in &shares {
+        let group = groups.entry(share.group_index).or_default();
+        if let Some(existing) = group.iter().find(|s| s.member_index == share.member_index) {
+            if existing.value != share.value {
+                return Err(VaultNodeError::InvalidArgumentValue("SLIP-39 shares have the same index but different values".to_string()));
+            }
+            continue;
+        }
+        if group.iter().any(|s| s.member_threshold != share.member_threshold) {
+            return Err(VaultNodeError::InvalidArgumentValue("SLIP-39 shares have different member threshold in a group".to_string()));
+        }
+        group.push(share);
+    }
+
+    let mut group_indexes: Vec<u8> = groups.keys().cloned().collect();
+    group_indexes.sort();
+    let mut group_secrets: Vec<(u8, Vec<u8>)> = Vec::new();
+    for index in group_indexes {
+        let members = &groups[&index];
+        let threshold = members[0].member_threshold;
+        if members.len() < threshold as usize {
+            continue;
+        }
+        let values: Vec<(u8, Vec<u8>)> = members.iter()
+            .take(threshold as usize)
+            .map(|s| (s.member_index, s.value.clone()))
+            .collect();
+        group_secrets.push((index, recover_secret(threshold, &values)?));
+        if group_secrets.len() == first.group_threshold as usize {
+            break;
+        }
+    }
+    if group_secrets.len() < first.group_threshold as usize {
+        return Err(VaultNodeError::InvalidArgumentValue(
+            format!("Not enough SLIP-39 shares, need {} complete group(s) but have {}", first.group_threshold, group_secrets.len())
+        ));
+    }
+
+    let encrypted = recover_secret(first.group_threshold, &group_secrets)?;
+    Ok(decrypt(&encrypted, passphrase.as_bytes(), first.iteration_exponent, first.identifier, first.extendable))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::slip39::{combine, split, GroupSpec, WORDLIST};
+
+    #[test]
+    fn wordlist_is_valid() {
+        assert_eq!(WORDLIST.len(), 1024);
+        let mut sorted = WORDLIST.clone();
+        sorted.sort();
+        assert_eq!(*WORDLIST, sorted);
+    }
+
+    #[test]
+    fn recover_standard_vector() {
+        let shares = vec![
+            "duckling enlarge academic academic agency result length solution fridge kidney coal piece deal husband erode duke ajar critical decision keyboard".to_string()
+        ];
+        let secret = combine(&shares, "TREZOR").expect("recovered");
+        assert_eq!(hex::encode(secret), "bb54aac4b89dc868ba37d9cc21b2cece");
+    }
+
+    #[test]
+    fn fail_on_invalid_checksum() {
+        let shares = vec![
+            "duckling enlarge academic academic agency result length solution fridge kidney coal piece deal husband erode duke ajar critical decision kidney".to_string()
+        ];
+        assert!(combine(&shares, "TREZOR").is_err());
+    }
+
+    #[test]
+    fn split_and_recover_seed() {
+        let seed = hex::decode("94f70a4e7c8f1f5e4f0a3b1c5e2d6f7a8b9c0d1e2f3a4b5c6d7e8f90a1b2c3d4e5f60718293a4b5c6d7e8f9a0b1c2d3e4f5061728394a5b6c7d8e9fa0b1c2d3e").unwrap();
+        let groups = vec![
+            GroupSpec { threshold: 1, count: 1 },
+            GroupSpec { threshold: 2, count: 3 },
+            GroupSpec { threshold: 3, count: 5 },
+        ];
+        let shares = split(&seed, "", 2, &groups, 0).expect("split");
+        assert_eq!(shares.len(), 3);
+        assert_eq!(shares[1].len(), 3);
+        assert_eq!(shares[2].len(), 5);
+        assert_eq!(shares[0][0].split(' ').count(), 59);
+
+        let recovered = combine(&[shares[0][0].clone(), shares[2][4].clone(), shares[2][0].clone(), shares[2][2].clone()], "").expect("recovered");
+        assert_eq!(recovered, seed);
+
+        let recovered = combine(&[shares[1][2].clone(), shares[2][1].clone(), shares[1][0].clone(), shares[2][3].clone(), shares[2][4].clone()], "").expect("recovered");
+        assert_eq!(recovered, seed);
+    }
+
+    #[test]
+    fn passphrase_changes_secret() {
+        let seed = hex::decode("00112233445566778899aabbccddeeff").unwrap();
+        let shares = split(&seed, "secret", 1, &[GroupSpec { threshold: 2, count: 3 }], 0).expect("split");
+        let with_passphrase = combine(&shares[0][0..2], "secret").expect("recovered");
+        assert_eq!(with_passphrase, seed);
+        let without = combine(&shares[0][1..3], "").expect("recovered");
+        assert_ne!(without, seed);
+    }
+
+    #[test]
+    fn fail_without_enough_shares() {
+        let seed = hex::decode("00112233445566778899aabbccddeeff").unwrap();
+        let groups = vec![
+            GroupSpec { threshold: 2, count: 3 },
+            GroupSpec { threshold: 2, count: 3 },
+        ];
+        let shares = split(&seed, "", 2, &groups, 0).expect("split");
+        // only one member from the second group
+        let result = combine(&[shares[0][0].clone(), shares[0][1].clone(), shares[1][0].clone()], "");
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn reject_invalid_groups() {
+        let seed = hex::decode("00112233445566778899aabbccddeeff").unwrap();
+        assert!(split(&seed, "", 2, &[GroupSpec { threshold: 2, count: 3 }], 0).is_err());
+        assert!(split(&seed, "", 1, &[GroupSpec { threshold: 1, count: 3 }], 0).is_err());
+        assert!(split(&seed, "", 1, &[GroupSpec { threshold: 4, count: 3 }], 0).is_err());
+        assert!(split(&seed[0..15], "", 1, &[GroupSpec { threshold: 2, count: 3 }], 0).is_err());
+    }
+}
@@ -1,25 +1,46 @@
+use std::fs;
 use std::fs::File;
 use std::io;
-use std::path::PathBuf;
+use std::io::Write;
+use std::path::{Path, PathBuf};
 use emerald_vault::error::VaultError;
 use emerald_vault::storage::vault::VaultStorage;
 use neon::context::{FunctionContext};
 use neon::prelude::{JsString};
 use crate::errors::VaultNodeError;
+use crate::fingerprint;
 use crate::instance::Instance;
-
-fn create_internal(storage: VaultStorage, target_file: String) -> Result<bool, VaultError> {
+use crate::multisig;
+use crate::policy;
+use crate::secret::SecretKind;
+use crate::sidecar;
+use crate::sidecar::SideFiles;
+
+/// Files kept next to the Vault storage which are a part of the snapshot. A seed backup is only temporary, so it's not included
+const SIDE_FILES: [&str; 5] = [
+    multisig::FILE_EXTENSION,
+    policy::FILE_EXTENSION,
+    fingerprint::FILE_EXTENSION,
+    SecretKind::Mnemonic.extension(),
+    SecretKind::PolicyKey.extension(),
+];
+
+fn create_internal(storage: VaultStorage, dir: &Path, target_file: String) -> Result<bool, VaultNodeError> {
     let target = PathBuf::from(&target_file);
     let mut target = File::create(target)
-        .map_err(|e| format!("Cannot create file {}. Error: {}", target_file, e))?;
+        .map_err(|e| VaultError::from(format!("Cannot create file {}. Error: {}", target_file, e)))?;
 
     let snapshots = storage.snapshots();
     let snapshot_id = snapshots.create()?;
 
     let mut reader = snapshots.read(snapshot_id)?;
+    let mut snapshot = Vec::new();
+    let _ = io::copy(&mut reader, &mut snapshot)
+        .map_err(|e| VaultError::from(format!("Failed to read snapshot. Error: {}", e)))?;
 
-    let _ = io::copy(&mut reader, &mut target)
-        .map_err(|e| format!("Failed to copy snapshot to {}. Error: {}", target_file, e))?;
+    let packed = sidecar::pack(&snapshot, &SideFiles::collect(dir, &SIDE_FILES)?)?;
+    target.write_all(&packed)
+        .map_err(|e| VaultError::from(format!("Failed to copy snapshot to {}. Error: {}", target_file, e)))?;
 
     Ok(true)
 }
@@ -38,34 +59,38 @@ pub fn create<H>(cx: &mut FunctionContext, handler: H) -> Result<(), VaultNodeEr
         let vault = vault.lock().unwrap();
         let storage = vault.cfg.get_storage();
 
-        let result = create_internal(storage, target_file)
-            .map_err(|e| VaultNodeError::from(e));
+        let result = create_internal(storage, Path::new(&vault.cfg.dir), target_file);
         handler(result);
     });
     Ok(())
 }
 
-fn restore_internal(storage: VaultStorage, source_file: String, password: String) -> Result<bool, VaultError> {
+fn restore_internal(storage: VaultStorage, dir: &Path, source_file: String, password: String) -> Result<bool, VaultNodeError> {
     let input = PathBuf::from(&source_file);
     if !input.exists() || !input.is_file() {
-        return Err(VaultError::FilesystemError(format!("Not a file: {}", source_file)))
+        return Err(VaultError::FilesystemError(format!("Not a file: {}", source_file)).into())
     }
 
-    let mut input = File::open(input)
-        .map_err(|e| format!("Cannot open file {}. Error: {}", source_file, e))?;
+    let data = fs::read(input)
+        .map_err(|e| VaultError::from(format!("Cannot open file {}. Error: {}", source_file, e)))?;
+    let (snapshot, side_files) = sidecar::unpack(&data)?;
 
     let snapshots = storage.snapshots();
     let mut restore = snapshots.restore()?;
 
-    let _ = io::copy(&mut input, &mut restore)
-        .map_err(|e| format!("Failed to copy source snapshot from {}. Error: {}", source_file, e))?;
+    restore.write_all(snapshot)
+        .map_err(|e| VaultError::from(format!("Failed to copy source snapshot from {}. Error: {}", source_file, e)))?;
 
     let is_password_valid = restore.verify_password(password)?;
     if !is_password_valid {
         return Ok(false);
     }
 
-    restore.complete().map(|_| true)
+    restore.complete()?;
+    if let Some(side_files) = side_files {
+        side_files.replace(dir, &SIDE_FILES)?;
+    }
+    Ok(true)
 }
 
 #[neon_frame_fn(channel=2)]
@@ -86,8 +111,7 @@ pub fn restore<H>(cx: &mut FunctionContext, handler: H) -> Result<(), VaultNodeE
     std::thread::spawn(move || {
         let vault = vault.lock().unwrap();
         let storage = vault.cfg.get_storage();
-        let result = restore_internal(storage, source_file, password)
-            .map_err(|e| VaultNodeError::from(e));
+        let result = restore_internal(storage, Path::new(&vault.cfg.dir), source_file, password);
         handler(result);
     });
     Ok(())
@@ -0,0 +1,245 @@
+//! Taproot (BIP-86) accounts, i.e. P2TR addresses with a single key and no script path.
+//!
+//! The Vault derives addresses and signs transactions only for the P2PKH/P2SH/P2WPKH address types, so for an entry on
+//! a `m/86'` account the addresses and key-path Schnorr signatures are produced here from the account key.
+
+use std::str::FromStr;
+use bitcoin::{Address, Amount, Network, NetworkKind, OutPoint, ScriptBuf, Sequence, Transaction, TxIn, TxOut, Witness};
+use bitcoin::absolute::LockTime;
+use bitcoin::address::KnownHrp;
+use bitcoin::bip32::{ChildNumber, DerivationPath, Xpriv, Xpub};
+use bitcoin::hashes::Hash;
+use bitcoin::key::{Keypair, TapTweak};
+use bitcoin::secp256k1::{All, Message, PublicKey, Secp256k1, SecretKey, Verification, XOnlyPublicKey};
+use bitcoin::sighash::{Prevouts, SighashCache, TapSighashType};
+use bitcoin::transaction::Version;
+use rand::RngCore;
+use crate::errors::VaultNodeError;
+
+pub const PURPOSE: u32 = 86;
+
+/// Check if the HD Path belongs to a Taproot account, i.e. starts with `m/86'`
+pub fn is_taproot_path(hd_path: &str) -> bool {
+    match DerivationPath::from_str(hd_path) {
+        Ok(path) => matches!(path.into_iter().next(), Some(ChildNumber::Hardened { index: PURPOSE })),
+        Err(_) => false
+    }
+}
+
+/// P2TR address for the key, tweaked without a script tree as BIP-86 defines
+pub fn address<C: Verification>(secp: &Secp256k1<C>, key: &PublicKey, network: NetworkKind) -> Address {
+    let hrp = match network {
+        NetworkKind::Main => KnownHrp::Mainnet,
+        NetworkKind::Test => KnownHrp::Testnets,
+    };
+    Address::p2tr(secp, XOnlyPublicKey::from(*key), None, hrp)
+}
+
+/// Derive the addresses of the account, where `change` is `0` for receive addresses and `1` for change
+pub fn derive_addresses(account: &Xpub, change: u32, start: u32, limit: u32) -> Result<Vec<(u32, Address)>, VaultNodeError> {
+    let secp = Secp256k1::verification_only();
+    let failed = |_| VaultNodeError::OtherProcessing("Failed to derive key".to_string());
+    let chain = ChildNumber::from_normal_idx(change)
+        .and_then(|c| account.ckd_pub(&secp, c))
+        .map_err(failed)?;
+    let mut result = Vec::with_capacity(limit as usize);
+    for index in start..start.saturating_add(limit) {
+        let key = ChildNumber::from_normal_idx(index)
+            .and_then(|c| chain.ckd_pub(&secp, c))
+            .map_err(failed)?;
+        result.push((index, address(&secp, &key.public_key, account.network)));
+    }
+    Ok(result)
+}
+
+/// Derive the address (for a full path) or the account key (for an account path) from the seed
+pub fn derive_from_seed(seed: &[u8], hd_path: &str, network: NetworkKind) -> Result<String, VaultNodeError> {
+    let secp = Secp256k1::new();
+    let path = DerivationPath::from_str(hd_path)
+        .map_err(|_| VaultNodeError::InvalidArgumentValue("Invalid HDPath".to_string()))?;
+    let key = master_key(seed, network)?.derive_priv(&secp, &path)
+        .map_err(|_| VaultNodeError::OtherProcessing("Failed to derive key".to_string()))?;
+    match path.len() {
+        3 => Ok(Xpub::from_priv(&secp, &key).to_string()),
+        5 => Ok(address(&secp, &key.private_key.public_key(&secp), network).to_string()),
+        _ => Err(VaultNodeError::InvalidArgumentValue("Invalid HDPath for Taproot".to_string()))
+    }
+}
+
+pub fn master_key(seed: &[u8], network: NetworkKind) -> Result<Xpriv, VaultNodeError> {
+    let network = match network {
+        NetworkKind::Main => Network::Bitcoin,
+        NetworkKind::Test => Network::Testnet,
+    };
+    Xpriv::new_master(network, seed)
+        .map_err(|_| VaultNodeError::OtherProcessing("Invalid seed".to_string()))
+}
+
+pub struct TaprootInput {
+    pub output: OutPoint,
+    pub amount: u64,
+    pub sequence: u32,
+    pub key: SecretKey,
+}
+
+/// Make a transaction spending P2TR inputs through the key path. The outputs must already include the change,
+/// so the difference between the inputs and outputs must be exactly the `expected_fee`
+pub fn sign_transaction(inputs: &[TaprootInput], outputs: Vec<TxOut>, expected_fee: u64) -> Result<Transaction, VaultNodeError> {
+    let secp = Secp256k1::new();
+    let total_input: u64 = inputs.iter().map(|i| i.amount).sum();
+    let total_output: u64 = outputs.iter().map(|o| o.value.to_sat()).sum();
+    if total_input.checked_sub(total_output) != Some(expected_fee) {
+        return Err(VaultNodeError::OtherInput(format!("Invalid tx: fee is {} but expected {}", total_input as i128 - total_output as i128, expected_fee)));
+    }
+
+    // the script of a P2TR output doesn't depend on the network
+    let prevouts: Vec<TxOut> = inputs.iter()
+        .map(|i| TxOut {
+            value: Amount::from_sat(i.amount),
+            script_pubkey: address(&secp, &i.key.public_key(&secp), NetworkKind::Main).script_pubkey(),
+        })
+        .collect();
+    let mut tx = Transaction {
+        version: Version::TWO,
+        lock_time: LockTime::ZERO,
+        input: inputs.iter()
+            .map(|i| TxIn {
+                previous_output: i.output,
+                script_sig: ScriptBuf::new(),
+                sequence: Sequence(i.sequence),
+                witness: Witness::new(),
+            })
+            .collect(),
+        output: outputs,
+    };
+
+    let mut witnesses = Vec::with_capacity(inputs.len());
+    let mut cache = SighashCache::new(&tx);
+    for (i, input) in inputs.iter().enumerate() {
+        let sighash = cache.taproot_key_spend_signature_hash(i, &Prevouts::All(&prevouts), TapSighashType::Default)
+            .map_err(|_| VaultNodeError::OtherProcessing("Failed to calculate sighash".to_string()))?;
+        let signature = sign_key_spend(&secp, &input.key, Message::from_digest(sighash.to_byte_array()));
+        witnesses.push(Witness::p2tr_key_spend(&bitcoin::taproot::Signature {
+            signature,
+            sighash_type: TapSighashType::Default,
+        }));
+    }
+    tx.input.iter_mut()
+        .zip(witnesses)
+        .for_each(|(input, witness)| input.witness = witness);
+    Ok(tx)
+}
+
+fn sign_key_spend(secp: &Secp256k1<All>, key: &SecretKey, msg: Message) -> bitcoin::secp256k1::schnorr::Signature {
+    let tweaked = Keypair::from_secret_key(secp, key).tap_tweak(secp, None);
+    // BIP-340 recommends fresh auxiliary randomness for each signature
+    let mut aux = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut aux);
+    secp.sign_schnorr_with_aux_rand(&msg, &tweaked.to_keypair(), &aux)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+    use bitcoin::{Address, Amount, NetworkKind, OutPoint, TxOut, Txid};
+    use bitcoin::bip32::{DerivationPath, Xpub};
+    use bitcoin::hashes::Hash;
+    use bitcoin::key::TapTweak;
+    use bitcoin::secp256k1::{Message, Secp256k1, XOnlyPublicKey};
+    use bitcoin::sighash::{Prevouts, SighashCache, TapSighashType};
+    use crate::taproot::{derive_addresses, derive_from_seed, is_taproot_path, master_key, sign_transaction, TaprootInput};
+
+    fn seed() -> Vec<u8> {
+        bip39::Mnemonic::parse_normalized(
+            "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about"
+        ).unwrap().to_seed("").to_vec()
+    }
+
+    #[test]
+    fn detects_taproot_path() {
+        assert!(is_taproot_path("m/86'/0'/0'"));
+        assert!(is_taproot_path("m/86'/1'/0'/0/5"));
+        assert!(!is_taproot_path("m/84'/0'/0'"));
+        assert!(!is_taproot_path("m/86/0'/0'"));
+        assert!(!is_taproot_path("invalid"));
+    }
+
+    // Test vectors from BIP-86
+    #[test]
+    fn derive_bip86_vectors() {
+        assert_eq!(
+            derive_from_seed(&seed(), "m/86'/0'/0'", NetworkKind::Main).unwrap(),
+            "xpub6BgBgsespWvERF3LHQu6CnqdvfEvtMcQjYrcRzx53QJjSxarj2afYWcLteoGVky7D3UKDP9QyrLprQ3VCECoY49yfdDEHGCtMMj92pReUsQ"
+        );
+        assert_eq!(
+            derive_from_seed(&seed(), "m/86'/0'/0'/0/0", NetworkKind::Main).unwrap(),
+            "bc1p5cyxnuxmeuwuvkwfem96lqzszd02n6xdcjrs20cac6yqjjwudpxqkedrcr"
+        );
+        assert_eq!(
+            derive_from_seed(&seed(), "m/86'/0'/0'/0/1", NetworkKind::Main).unwrap(),
+            "bc1p4qhjn9zdvkux4e44uhx8tc55attvtyu358kutcqkudyccelu0was9fqzwh"
+        );
+        assert_eq!(
+            derive_from_seed(&seed(), "m/86'/0'/0'/1/0", NetworkKind::Main).unwrap(),
+            "bc1p3qkhfews2uk44qtvauqyr2ttdsw7svhkl9nkm9s9c3x4ax5h60wqwruhk7"
+        );
+    }
+
+    #[test]
+    fn derive_from_account() {
+        let account = Xpub::from_str("xpub6BgBgsespWvERF3LHQu6CnqdvfEvtMcQjYrcRzx53QJjSxarj2afYWcLteoGVky7D3UKDP9QyrLprQ3VCECoY49yfdDEHGCtMMj92pReUsQ").unwrap();
+        let act = derive_addresses(&account, 0, 0, 2).unwrap();
+        assert_eq!(act.len(), 2);
+        assert_eq!(act[0].1.to_string(), "bc1p5cyxnuxmeuwuvkwfem96lqzszd02n6xdcjrs20cac6yqjjwudpxqkedrcr");
+        assert_eq!(act[1].0, 1);
+        assert_eq!(act[1].1.to_string(), "bc1p4qhjn9zdvkux4e44uhx8tc55attvtyu358kutcqkudyccelu0was9fqzwh");
+
+        let change = derive_addresses(&account, 1, 0, 1).unwrap();
+        assert_eq!(change[0].1.to_string(), "bc1p3qkhfews2uk44qtvauqyr2ttdsw7svhkl9nkm9s9c3x4ax5h60wqwruhk7");
+    }
+
+    #[test]
+    fn derive_testnet() {
+        let act = derive_from_seed(&seed(), "m/86'/1'/0'/0/0", NetworkKind::Test).unwrap();
+        assert!(act.starts_with("tb1p"));
+        let act = derive_from_seed(&seed(), "m/86'/1'/0'", NetworkKind::Test).unwrap();
+        assert!(act.starts_with("tpub"));
+    }
+
+    #[test]
+    fn sign_key_path() {
+        let secp = Secp256k1::new();
+        let key = master_key(&seed(), NetworkKind::Main).unwrap()
+            .derive_priv(&secp, &DerivationPath::from_str("m/86'/0'/0'/0/0").unwrap()).unwrap()
+            .private_key;
+        let input = || TaprootInput {
+            output: OutPoint {
+                txid: Txid::from_str("41e0bd5b8f9e9d9d1b38e0d4e6e1e4df31d5cf2c0b0e0b0a3c9e0b5f6a0c0e01").unwrap(),
+                vout: 1,
+            },
+            amount: 100_000,
+            sequence: 0xffff_fffd,
+            key,
+        };
+        let to = Address::from_str("bc1qxqz4qerrm662nt4hxh39mqltvqcffcvzzfc49z").unwrap().assume_checked();
+        let outputs = vec![TxOut { value: Amount::from_sat(99_000), script_pubkey: to.script_pubkey() }];
+
+        assert!(sign_transaction(&[input()], outputs.clone(), 999).is_err());
+
+        let tx = sign_transaction(&[input()], outputs, 1_000).unwrap();
+        assert_eq!(tx.input.len(), 1);
+        assert_eq!(tx.input[0].witness.len(), 1);
+        let witness = tx.input[0].witness.nth(0).unwrap();
+        // default sighash type doesn't add a byte to the signature
+        assert_eq!(witness.len(), 64);
+
+        let spent = Address::from_str("bc1p5cyxnuxmeuwuvkwfem96lqzszd02n6xdcjrs20cac6yqjjwudpxqkedrcr").unwrap().assume_checked();
+        let prevouts = vec![TxOut { value: Amount::from_sat(100_000), script_pubkey: spent.script_pubkey() }];
+        let sighash = SighashCache::new(&tx)
+            .taproot_key_spend_signature_hash(0, &Prevouts::All(&prevouts), TapSighashType::Default)
+            .unwrap();
+        let output_key = XOnlyPublicKey::from(key.public_key(&secp)).tap_tweak(&secp, None).0;
+        let signature = bitcoin::secp256k1::schnorr::Signature::from_slice(witness).unwrap();
+        assert!(secp.verify_schnorr(&signature, &Message::from_digest(sighash.to_byte_array()), &output_key.to_x_only_public_key()).is_ok());
+    }
+}
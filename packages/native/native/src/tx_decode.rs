@@ -0,0 +1,739 @@
+//! Decode raw signed transactions into JSON.
+//!
+//! - Ethereum: legacy (with or without EIP-155 chain id), EIP-2930, EIP-1559 and EIP-7702 transactions, with the sender recovered from the signature
+//! - Bitcoin: any consensus-encoded transaction, with addresses of the outputs and a best-effort address of the spent inputs
+
+use bitcoin::consensus::Decodable;
+use bitcoin::secp256k1::ecdsa::{RecoverableSignature, RecoveryId};
+use bitcoin::secp256k1::{Message, Secp256k1};
+use bitcoin::{Address, CompressedPublicKey, NetworkKind, Script, ScriptBuf, Transaction};
+use emerald_vault::keccak256;
+use num_bigint::BigUint;
+use rlp::{Rlp, RlpStream};
+use crate::bitcoin_message::known_hrp;
+use crate::errors::VaultNodeError;
+
+pub const TYPE_EIP2930: u8 = 0x01;
+pub const TYPE_EIP1559: u8 = 0x02;
+pub const TYPE_EIP7702: u8 = 0x04;
+
+/// Prefix of the signed data of an EIP-7702 authorization
+const AUTHORIZATION_MAGIC: u8 = 0x05;
+
+#[derive(Serialize, Clone, Debug, Eq, PartialEq)]
+pub struct DecodedAccessListItemJson {
+    pub address: String,
+    pub storage: Vec<String>,
+}
+
+/// Signed EIP-7702 authorization, which sets the code of the signer account to a delegation to the `address`
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq)]
+pub struct AuthorizationJson {
+    /// Chain Id, or `0` if the authorization is valid on any chain
+    #[serde(rename = "chainId")]
+    pub chain_id: u64,
+    /// Delegate, i.e., the contract with the code to use
+    pub address: String,
+    /// Nonce of the signer account when the authorization is applied
+    pub nonce: u64,
+    #[serde(rename = "yParity")]
+    pub y_parity: u8,
+    pub r: String,
+    pub s: String,
+}
+
+/// Fields of an Ethereum transaction, without the signature
+#[derive(Serialize, Clone, Debug, Eq, PartialEq)]
+pub struct EthereumTxFieldsJson {
+    /// EIP-2718 type, `0` for a legacy transaction
+    #[serde(rename = "type")]
+    pub tx_type: u8,
+    /// Chain Id, or none for a legacy transaction signed without EIP-155
+    #[serde(rename = "chainId")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub chain_id: Option<u64>,
+    pub nonce: u64,
+    pub gas: u64,
+    #[serde(rename = "gasPrice")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gas_price: Option<String>,
+    #[serde(rename = "maxGasPrice")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_gas_price: Option<String>,
+    #[serde(rename = "priorityGasPrice")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub priority_gas_price: Option<String>,
+    /// Target address, none for a contract creation
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub to: Option<String>,
+    pub value: String,
+    pub data: String,
+    #[serde(rename = "accessList")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub access_list: Option<Vec<DecodedAccessListItemJson>>,
+    /// Authorizations of an EIP-7702 transaction
+    #[serde(rename = "authorizationList")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub authorization_list: Option<Vec<AuthorizationJson>>,
+}
+
+impl EthereumTxFieldsJson {
+    /// The transaction pays with `maxGasPrice` and `priorityGasPrice` instead of the `gasPrice`
+    pub fn is_dynamic_fee(&self) -> bool {
+        self.tx_type == TYPE_EIP1559 || self.tx_type == TYPE_EIP7702
+    }
+}
+
+#[derive(Serialize, Clone, Debug, Eq, PartialEq)]
+pub struct DecodedEthereumTxJson {
+    #[serde(flatten)]
+    pub fields: EthereumTxFieldsJson,
+    pub from: String,
+    pub hash: String,
+    pub v: u64,
+    pub r: String,
+    pub s: String,
+}
+
+#[derive(Serialize, Clone, Debug, Eq, PartialEq)]
+pub struct DecodedInputJson {
+    pub txid: String,
+    pub vout: u32,
+    pub sequence: u32,
+    #[serde(rename = "scriptSig")]
+    pub script_sig: String,
+    pub witness: Vec<String>,
+    /// Address of the spent output if it can be deduced from the script and witness (P2PKH, P2SH-P2WPKH or P2WPKH)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub address: Option<String>,
+}
+
+#[derive(Serialize, Clone, Debug, Eq, PartialEq)]
+pub struct DecodedOutputJson {
+    pub amount: u64,
+    pub script: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub address: Option<String>,
+}
+
+#[derive(Serialize, Clone, Debug, Eq, PartialEq)]
+pub struct DecodedBitcoinTxJson {
+    pub txid: String,
+    pub wtxid: String,
+    pub version: i32,
+    pub locktime: u32,
+    pub size: usize,
+    pub vsize: usize,
+    pub weight: u64,
+    pub inputs: Vec<DecodedInputJson>,
+    pub outputs: Vec<DecodedOutputJson>,
+}
+
+#[derive(Serialize, Clone, Debug, Eq, PartialEq)]
+#[serde(untagged)]
+pub enum DecodedTxJson {
+    Ethereum(Box<DecodedEthereumTxJson>),
+    Bitcoin(DecodedBitcoinTxJson),
+}
+
+fn invalid(msg: &str) -> VaultNodeError {
+    VaultNodeError::InvalidArgumentValue(msg.to_string())
+}
+
+fn as_u64(item: &Rlp, name: &str) -> Result<u64, VaultNodeError> {
+    item.as_val::<u64>()
+        .map_err(|_| invalid(format!("Invalid {}", name).as_str()))
+}
+
+fn as_uint(item: &Rlp, name: &str) -> Result<BigUint, VaultNodeError> {
+    let data = as_bytes(item, name)?;
+    if data.len() > 32 || data.first() == Some(&0) {
+        return Err(invalid(format!("Invalid {}", name).as_str()));
+    }
+    Ok(BigUint::from_bytes_be(data))
+}
+
+fn as_bytes<'a>(item: &'a Rlp, name: &str) -> Result<&'a [u8], VaultNodeError> {
+    if item.is_list() {
+        return Err(invalid(format!("Invalid {}", name).as_str()));
+    }
+    item.data()
+        .map_err(|_| invalid(format!("Invalid {}", name).as_str()))
+}
+
+fn as_address(item: &Rlp, name: &str) -> Result<[u8; 20], VaultNodeError> {
+    as_bytes(item, name)?
+        .try_into()
+        .map_err(|_| invalid(format!("Invalid {}", name).as_str()))
+}
+
+fn as_optional_address(item: &Rlp, name: &str) -> Result<Option<[u8; 20]>, VaultNodeError> {
+    if as_bytes(item, name)?.is_empty() {
+        Ok(None)
+    } else {
+        as_address(item, name).map(Some)
+    }
+}
+
+fn to_hex(value: &[u8]) -> String {
+    format!("0x{}", hex::encode(value))
+}
+
+/// A signature value (`r` or `s`) as 32 bytes, which is encoded without leading zeroes in RLP
+fn as_signature_value(item: &Rlp, name: &str) -> Result<[u8; 32], VaultNodeError> {
+    let data = as_bytes(item, name)?;
+    if data.is_empty() || data.len() > 32 {
+        return Err(invalid(format!("Invalid {}", name).as_str()));
+    }
+    let mut result = [0u8; 32];
+    result[32 - data.len()..].copy_from_slice(data);
+    Ok(result)
+}
+
+fn parse_access_list(item: &Rlp) -> Result<Vec<DecodedAccessListItemJson>, VaultNodeError> {
+    if !item.is_list() {
+        return Err(invalid("Invalid accessList"));
+    }
+    let mut result = Vec::new();
+    for access in item.iter() {
+        if !access.is_list() || access.item_count() != Ok(2) {
+            return Err(invalid("Invalid accessList"));
+        }
+        let address = as_address(&access.at(0).map_err(|_| invalid("Invalid accessList"))?, "accessList[].address")?;
+        let keys = access.at(1).map_err(|_| invalid("Invalid accessList"))?;
+        if !keys.is_list() {
+            return Err(invalid("Invalid accessList"));
+        }
+        let mut storage = Vec::new();
+        for key in keys.iter() {
+            let key = as_bytes(&key, "accessList[].storage")?;
+            if key.len() != 32 {
+                return Err(invalid("Invalid accessList[].storage"));
+            }
+            storage.push(to_hex(key));
+        }
+        result.push(DecodedAccessListItemJson { address: to_hex(&address), storage });
+    }
+    Ok(result)
+}
+
+fn parse_authorization_list(item: &Rlp) -> Result<Vec<AuthorizationJson>, VaultNodeError> {
+    if !item.is_list() {
+        return Err(invalid("Invalid authorizationList"));
+    }
+    let mut result = Vec::new();
+    for authorization in item.iter() {
+        if !authorization.is_list() || authorization.item_count() != Ok(6) {
+            return Err(invalid("Invalid authorizationList"));
+        }
+        let items: Vec<Rlp> = authorization.iter().collect();
+        let y_parity = as_u64(&items[3], "authorizationList[].yParity")?;
+        if y_parity > 1 {
+            return Err(invalid("Invalid authorizationList[].yParity"));
+        }
+        result.push(AuthorizationJson {
+            chain_id: as_u64(&items[0], "authorizationList[].chainId")?,
+            address: to_hex(&as_address(&items[1], "authorizationList[].address")?),
+            nonce: as_u64(&items[2], "authorizationList[].nonce")?,
+            y_parity: y_parity as u8,
+            r: to_hex(&as_signature_value(&items[4], "authorizationList[].r")?),
+            s: to_hex(&as_signature_value(&items[5], "authorizationList[].s")?),
+        });
+    }
+    Ok(result)
+}
+
+/// Hash signed by an EIP-7702 authorization, i.e. `keccak256(0x05 || rlp([chainId, address, nonce]))`
+pub fn authorization_hash(chain_id: u64, address: &[u8; 20], nonce: u64) -> [u8; 32] {
+    let mut stream = RlpStream::new_list(3);
+    stream.append(&chain_id);
+    stream.append(&address.as_slice());
+    stream.append(&nonce);
+    let mut data = vec![AUTHORIZATION_MAGIC];
+    data.extend_from_slice(&stream.out());
+    keccak256(&data)
+}
+
+/// A signature value (`r` or `s`) as a hex string, which may be without leading zeroes
+pub fn parse_signature_value(value: &str, name: &str) -> Result<[u8; 32], VaultNodeError> {
+    let data = hex::decode(value.strip_prefix("0x").unwrap_or(value))
+        .map_err(|_| invalid(format!("Invalid {}", name).as_str()))?;
+    if data.len() > 32 {
+        return Err(invalid(format!("Invalid {}", name).as_str()));
+    }
+    let mut result = [0u8; 32];
+    result[32 - data.len()..].copy_from_slice(&data);
+    Ok(result)
+}
+
+/// Account that signed the authorization, i.e. the account which gets the delegation
+pub fn recover_authority(authorization: &AuthorizationJson) -> Result<String, VaultNodeError> {
+    let address = authorization.address.as_str();
+    let address: [u8; 20] = hex::decode(address.strip_prefix("0x").unwrap_or(address))
+        .ok()
+        .and_then(|value| value.try_into().ok())
+        .ok_or(invalid("Invalid authorizationList[].address"))?;
+    let r = parse_signature_value(authorization.r.as_str(), "authorizationList[].r")?;
+    let s = parse_signature_value(authorization.s.as_str(), "authorizationList[].s")?;
+    let hash = authorization_hash(authorization.chain_id, &address, authorization.nonce);
+    recover_sender(hash, authorization.y_parity as u64, &r, &s)
+}
+
+/// RLP list starting with the first `count` items of the transaction, i.e. its fields without the signature.
+/// The list has `size` items, so the caller appends the rest.
+fn encode_prefix(items: &[Rlp], count: usize, size: usize) -> RlpStream {
+    let mut stream = RlpStream::new_list(size);
+    for item in items.iter().take(count) {
+        stream.append_raw(item.as_raw(), 1);
+    }
+    stream
+}
+
+fn recover_sender(hash: [u8; 32], recovery_id: u64, r: &[u8; 32], s: &[u8; 32]) -> Result<String, VaultNodeError> {
+    let recovery_id = RecoveryId::from_i32(recovery_id as i32)
+        .map_err(|_| invalid("Invalid signature"))?;
+    let mut compact = [0u8; 64];
+    compact[0..32].copy_from_slice(r);
+    compact[32..64].copy_from_slice(s);
+    let signature = RecoverableSignature::from_compact(&compact, recovery_id)
+        .map_err(|_| invalid("Invalid signature"))?;
+    let secp = Secp256k1::verification_only();
+    let pubkey = secp.recover_ecdsa(&Message::from_digest(hash), &signature)
+        .map_err(|_| invalid("Invalid signature"))?;
+    let hash = keccak256(&pubkey.serialize_uncompressed()[1..]);
+    Ok(to_hex(&hash[12..]))
+}
+
+/// EIP-2718 type of the transaction and its RLP items
+fn split_typed(raw: &[u8]) -> Result<(u8, Vec<Rlp<'_>>), VaultNodeError> {
+    let (tx_type, payload) = match raw.first() {
+        None => return Err(invalid("Empty transaction")),
+        Some(b) if *b >= 0xc0 => (0u8, raw),
+        Some(&TYPE_EIP2930) => (TYPE_EIP2930, &raw[1..]),
+        Some(&TYPE_EIP1559) => (TYPE_EIP1559, &raw[1..]),
+        Some(&TYPE_EIP7702) => (TYPE_EIP7702, &raw[1..]),
+        Some(b) => return Err(invalid(format!("Unsupported transaction type: {}", b).as_str())),
+    };
+    let rlp = Rlp::new(payload);
+    if !rlp.is_list() || rlp.as_raw().len() != payload.len() {
+        return Err(invalid("Invalid RLP"));
+    }
+    Ok((tx_type, rlp.iter().collect()))
+}
+
+/// Number of fields before the signature (or before the EIP-155 chain id for a legacy transaction)
+fn fields_count(tx_type: u8) -> usize {
+    match tx_type {
+        TYPE_EIP2930 => 8,
+        TYPE_EIP1559 => 9,
+        TYPE_EIP7702 => 10,
+        _ => 6,
+    }
+}
+
+/// Parse the transaction fields. A legacy transaction doesn't have the chain id in its fields, so it's provided separately.
+fn parse_fields(tx_type: u8, items: &[Rlp], legacy_chain_id: Option<u64>) -> Result<EthereumTxFieldsJson, VaultNodeError> {
+    let fields = if tx_type == 0 {
+        // [nonce, gasPrice, gas, to, value, data]
+        EthereumTxFieldsJson {
+            tx_type,
+            chain_id: legacy_chain_id,
+            nonce: as_u64(&items[0], "nonce")?,
+            gas_price: Some(as_uint(&items[1], "gasPrice")?.to_string()),
+            max_gas_price: None,
+            priority_gas_price: None,
+            gas: as_u64(&items[2], "gas")?,
+            to: as_optional_address(&items[3], "to")?.map(|a| to_hex(&a)),
+            value: as_uint(&items[4], "value")?.to_string(),
+            data: to_hex(as_bytes(&items[5], "data")?),
+            access_list: None,
+            authorization_list: None,
+        }
+    } else {
+        // EIP-2930: [chainId, nonce, gasPrice, gas, to, value, data, accessList]
+        // EIP-1559: [chainId, nonce, maxPriorityFeePerGas, maxFeePerGas, gas, to, value, data, accessList]
+        // EIP-7702: the same as EIP-1559 followed by authorizationList, and `to` cannot be empty
+        // position of the `gas` field, the fields after it are the same for all types
+        let dynamic_fee = tx_type != TYPE_EIP2930;
+        let gas_pos = if dynamic_fee { 4 } else { 3 };
+        let to = if tx_type == TYPE_EIP7702 {
+            Some(as_address(&items[gas_pos + 1], "to")?)
+        } else {
+            as_optional_address(&items[gas_pos + 1], "to")?
+        };
+        EthereumTxFieldsJson {
+            tx_type,
+            chain_id: Some(as_u64(&items[0], "chainId")?),
+            nonce: as_u64(&items[1], "nonce")?,
+            gas_price: if dynamic_fee { None } else { Some(as_uint(&items[2], "gasPrice")?.to_string()) },
+            priority_gas_price: if dynamic_fee { Some(as_uint(&items[2], "maxPriorityFeePerGas")?.to_string()) } else { None },
+            max_gas_price: if dynamic_fee { Some(as_uint(&items[3], "maxFeePerGas")?.to_string()) } else { None },
+            gas: as_u64(&items[gas_pos], "gas")?,
+            to: to.map(|a| to_hex(&a)),
+            value: as_uint(&items[gas_pos + 2], "value")?.to_string(),
+            data: to_hex(as_bytes(&items[gas_pos + 3], "data")?),
+            access_list: Some(parse_access_list(&items[gas_pos + 4])?),
+            authorization_list: if tx_type == TYPE_EIP7702 { Some(parse_authorization_list(&items[gas_pos + 5])?) } else { None },
+        }
+    };
+    Ok(fields)
+}
+
+/// Decode a signed Ethereum transaction, legacy or EIP-2718 typed
+pub fn decode_ethereum(raw: &[u8]) -> Result<DecodedEthereumTxJson, VaultNodeError> {
+    let (tx_type, items) = split_typed(raw)?;
+    let count = fields_count(tx_type);
+    if items.len() != count + 3 {
+        return Err(invalid("Invalid number of fields in the transaction"));
+    }
+
+    let (v, r, s) = (
+        as_u64(&items[count], "v")?,
+        as_signature_value(&items[count + 1], "r")?,
+        as_signature_value(&items[count + 2], "s")?,
+    );
+
+    let (fields, from) = if tx_type == 0 {
+        let (chain_id, recovery_id) = match v {
+            27 | 28 => (None, v - 27),
+            v if v >= 35 => (Some((v - 35) / 2), (v - 35) % 2),
+            _ => return Err(invalid("Invalid v")),
+        };
+        let signed = match chain_id {
+            None => encode_prefix(&items, count, count),
+            Some(chain_id) => {
+                // EIP-155 signs the fields followed by [chainId, 0, 0]
+                let mut stream = encode_prefix(&items, count, count + 3);
+                stream.append(&chain_id);
+                stream.append(&0u8);
+                stream.append(&0u8);
+                stream
+            }
+        };
+        let from = recover_sender(keccak256(&signed.out()), recovery_id, &r, &s)?;
+        (parse_fields(tx_type, &items, chain_id)?, from)
+    } else {
+        if v > 1 {
+            return Err(invalid("Invalid yParity"));
+        }
+        let mut signed = vec![tx_type];
+        signed.extend_from_slice(&encode_prefix(&items, count, count).out());
+        let from = recover_sender(keccak256(&signed), v, &r, &s)?;
+        (parse_fields(tx_type, &items, None)?, from)
+    };
+
+    Ok(DecodedEthereumTxJson {
+        fields,
+        from,
+        hash: to_hex(&keccak256(raw)),
+        v,
+        r: to_hex(&r),
+        s: to_hex(&s),
+    })
+}
+
+/// Decode an unsigned Ethereum transaction, i.e., an EIP-2718 envelope without the signature, or a legacy
+/// transaction encoded for EIP-155 signing (`[nonce, gasPrice, gas, to, value, data, chainId, 0, 0]`)
+pub fn decode_unsigned_ethereum(raw: &[u8]) -> Result<EthereumTxFieldsJson, VaultNodeError> {
+    let (tx_type, items) = split_typed(raw)?;
+    let count = fields_count(tx_type);
+    if tx_type == 0 {
+        if items.len() == count {
+            return Err(invalid("Legacy transaction must include EIP-155 chain id"));
+        }
+        if items.len() != count + 3 {
+            return Err(invalid("Invalid number of fields in the transaction"));
+        }
+        if !as_bytes(&items[count + 1], "r")?.is_empty() || !as_bytes(&items[count + 2], "s")?.is_empty() {
+            return Err(invalid("Transaction is already signed"));
+        }
+        let chain_id = as_u64(&items[count], "chainId")?;
+        parse_fields(tx_type, &items, Some(chain_id))
+    } else {
+        if items.len() == count + 3 {
+            return Err(invalid("Transaction is already signed"));
+        }
+        if items.len() != count {
+            return Err(invalid("Invalid number of fields in the transaction"));
+        }
+        parse_fields(tx_type, &items, None)
+    }
+}
+
+/// Address that spent the input, if it's a standard single key spending
+fn input_address(script_sig: &Script, witness: &[Vec<u8>], network: NetworkKind) -> Option<Address> {
+    let pushes = script_sig.instructions()
+        .map(|i| i.ok().and_then(|i| i.push_bytes().map(|b| b.as_bytes().to_vec())))
+        .collect::<Option<Vec<Vec<u8>>>>()?;
+    match (pushes.len(), witness.len()) {
+        // P2WPKH
+        (0, 2) => {
+            let pk = CompressedPublicKey::from_slice(&witness[1]).ok()?;
+            Some(Address::p2wpkh(&pk, known_hrp(network)))
+        }
+        // P2SH-P2WPKH, the script sig is the redeem script `0 <key hash>` of the same key
+        (1, 2) => {
+            let pk = CompressedPublicKey::from_slice(&witness[1]).ok()?;
+            let redeem = ScriptBuf::new_p2wpkh(&pk.wpubkey_hash());
+            (pushes[0] == redeem.as_bytes()).then(|| Address::p2shwpkh(&pk, network))
+        }
+        // P2PKH
+        (2, 0) => {
+            let pk = CompressedPublicKey::from_slice(&pushes[1]).ok()?;
+            Some(Address::p2pkh(pk, network))
+        }
+        _ => None,
+    }
+}
+
+/// Decode a Bitcoin transaction, with the addresses encoded for the specified network
+pub fn decode_bitcoin(raw: &[u8], network: NetworkKind) -> Result<DecodedBitcoinTxJson, VaultNodeError> {
+    let mut data = raw;
+    let tx = Transaction::consensus_decode(&mut data)
+        .map_err(|_| invalid("Invalid transaction"))?;
+    if !data.is_empty() {
+        return Err(invalid("Invalid transaction"));
+    }
+    let params = match network {
+        NetworkKind::Main => bitcoin::params::Params::MAINNET,
+        NetworkKind::Test => bitcoin::params::Params::TESTNET3,
+    };
+    let inputs = tx.input.iter()
+        .map(|input| {
+            let witness: Vec<Vec<u8>> = input.witness.iter().map(|w| w.to_vec()).collect();
+            DecodedInputJson {
+                txid: input.previous_output.txid.to_string(),
+                vout: input.previous_output.vout,
+                sequence: input.sequence.0,
+                script_sig: hex::encode(input.script_sig.as_bytes()),
+                address: input_address(&input.script_sig, &witness, network).map(|a| a.to_string()),
+                witness: witness.iter().map(hex::encode).collect(),
+            }
+        })
+        .collect();
+    let outputs = tx.output.iter()
+        .map(|output| DecodedOutputJson {
+            amount: output.value.to_sat(),
+            script: hex::encode(output.script_pubkey.as_bytes()),
+            address: Address::from_script(&output.script_pubkey, &params).ok().map(|a| a.to_string()),
+        })
+        .collect();
+    Ok(DecodedBitcoinTxJson {
+        txid: tx.compute_txid().to_string(),
+        wtxid: tx.compute_wtxid().to_string(),
+        version: tx.version.0,
+        locktime: tx.lock_time.to_consensus_u32(),
+        size: tx.total_size(),
+        vsize: tx.vsize(),
+        weight: tx.weight().to_wu(),
+        inputs,
+        outputs,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use bitcoin::secp256k1::{Message, Secp256k1, SecretKey};
+    use bitcoin::NetworkKind;
+    use emerald_vault::keccak256;
+    use rlp::RlpStream;
+    use crate::tx_decode::{decode_bitcoin, decode_ethereum, decode_unsigned_ethereum};
+
+    // the key from EIP-155 example, address 0x9d8a62f656a8d1615c1294fd71e9cfb3e4855a4f
+    fn sign(hash: [u8; 32]) -> (u8, Vec<u8>, Vec<u8>) {
+        let key = SecretKey::from_slice(&[0x46; 32]).unwrap();
+        let secp = Secp256k1::new();
+        let (recovery_id, signature) = secp.sign_ecdsa_recoverable(&Message::from_digest(hash), &key).serialize_compact();
+        let trim = |v: &[u8]| v.iter().skip_while(|b| **b == 0).cloned().collect::<Vec<u8>>();
+        (recovery_id.to_i32() as u8, trim(&signature[0..32]), trim(&signature[32..64]))
+    }
+
+    #[test]
+    fn decode_eip155_example() {
+        let raw = hex::decode("f86c098504a817c800825208943535353535353535353535353535353535353535880de0b6b3a76400008025a028ef61340bd939bc2195fe537567866003e1a15d3c71ff63e1590620aa636276a067cbe9d8997f761aecb703304b3800ccf555c9f3dc64214b297fb1966a3b6d83").unwrap();
+        let act = decode_ethereum(&raw).unwrap();
+        assert_eq!(act.fields.tx_type, 0);
+        assert_eq!(act.fields.chain_id, Some(1));
+        assert_eq!(act.fields.nonce, 9);
+        assert_eq!(act.fields.gas_price, Some("20000000000".to_string()));
+        assert_eq!(act.fields.gas, 21000);
+        assert_eq!(act.fields.to, Some("0x3535353535353535353535353535353535353535".to_string()));
+        assert_eq!(act.fields.value, "1000000000000000000");
+        assert_eq!(act.fields.data, "0x");
+        assert_eq!(act.fields.access_list, None);
+        assert_eq!(act.v, 37);
+        assert_eq!(act.r, "0x28ef61340bd939bc2195fe537567866003e1a15d3c71ff63e1590620aa636276");
+        assert_eq!(act.from, "0x9d8a62f656a8d1615c1294fd71e9cfb3e4855a4f");
+    }
+
+    #[test]
+    fn decode_legacy() {
+        let raw = hex::decode("f863198477359400825208943eaf0b987b49c4d782ee134fdc1243fd0ccdfdd3808026a0f3357ca4028bcfd26de329b5405ed60342a3aad785e84ea3776ef650818e7de5a0469efc686f479b242f311480911668c8b1993188908f87bd1d1c56b82a0b4fa6").unwrap();
+        let act = decode_ethereum(&raw).unwrap();
+        assert_eq!(act.fields.chain_id, Some(1));
+        assert_eq!(act.fields.nonce, 0x19);
+        assert_eq!(act.fields.value, "0");
+        assert_eq!(act.from, "0x0cf0523fc884ad99f7df146848f08cb8608a38a7");
+        assert_eq!(act.hash, "0xc69cd98ebd2ee99a5392ba7d05f1a18ad2fa78068f71c6dcae9e379b0be9f640");
+    }
+
+    #[test]
+    fn decode_legacy_without_chain_id() {
+        let mut unsigned = RlpStream::new_list(6);
+        unsigned.append(&1u8).append(&1_000_000_000u64).append(&21000u64)
+            .append(&vec![0x35u8; 20]).append(&0u8).append_empty_data();
+        let (recovery_id, r, s) = sign(keccak256(&unsigned.out()));
+        let mut signed = RlpStream::new_list(9);
+        signed.append(&1u8).append(&1_000_000_000u64).append(&21000u64)
+            .append(&vec![0x35u8; 20]).append(&0u8).append_empty_data()
+            .append(&(27 + recovery_id)).append(&r).append(&s);
+        let act = decode_ethereum(&signed.out()).unwrap();
+        assert_eq!(act.fields.chain_id, None);
+        assert_eq!(act.from, "0x9d8a62f656a8d1615c1294fd71e9cfb3e4855a4f");
+    }
+
+    #[test]
+    fn decode_eip1559() {
+        let raw = hex::decode("02f8690119830186a08477359400825208943eaf0b987b49c4d782ee134fdc1243fd0ccdfdd38080c001a08a6736c1ec07c2017362eed9a89823f3d6220ab8bacf81fa8a14da9581421f70a04e5f507d99bb20f0956b4b257fa660f5759dfdb7e04c7e8fcf6645ab8845cf7f").unwrap();
+        let act = decode_ethereum(&raw).unwrap();
+        assert_eq!(act.fields.tx_type, 2);
+        assert_eq!(act.fields.chain_id, Some(1));
+        assert_eq!(act.fields.nonce, 0x19);
+        assert_eq!(act.fields.priority_gas_price, Some("100000".to_string()));
+        assert_eq!(act.fields.max_gas_price, Some("2000000000".to_string()));
+        assert_eq!(act.fields.gas_price, None);
+        assert_eq!(act.fields.gas, 21000);
+        assert_eq!(act.fields.to, Some("0x3eaf0b987b49c4d782ee134fdc1243fd0ccdfdd3".to_string()));
+        assert_eq!(act.fields.access_list, Some(vec![]));
+        assert_eq!(act.v, 1);
+        assert_eq!(act.from, "0xd4345abbeef14d2fd2e0deb898a67c26f1cbc4f1");
+        assert_eq!(act.hash, "0x7b0958868a76aee6803da842859c40e4594e762c8f26304d54e05cff60b1fac4");
+    }
+
+    #[test]
+    fn decode_eip2930_with_access_list() {
+        let fields = |stream: &mut RlpStream| {
+            stream.append(&5u8).append(&3u8).append(&1_000_000_000u64).append(&50000u64)
+                .append_empty_data().append(&0u8).append(&vec![0x60u8, 0x80]);
+            stream.begin_list(1).begin_list(2).append(&vec![0x11u8; 20]).begin_list(1).append(&vec![0x22u8; 32]);
+        };
+        let mut unsigned = RlpStream::new_list(8);
+        fields(&mut unsigned);
+        let mut prefixed = vec![0x01];
+        prefixed.extend_from_slice(&unsigned.out());
+        let (recovery_id, r, s) = sign(keccak256(&prefixed));
+        let mut signed = RlpStream::new_list(11);
+        fields(&mut signed);
+        signed.append(&recovery_id).append(&r).append(&s);
+        let mut raw = vec![0x01];
+        raw.extend_from_slice(&signed.out());
+
+        let act = decode_ethereum(&raw).unwrap();
+        assert_eq!(act.fields.tx_type, 1);
+        assert_eq!(act.fields.chain_id, Some(5));
+        assert_eq!(act.fields.nonce, 3);
+        assert_eq!(act.fields.gas_price, Some("1000000000".to_string()));
+        assert_eq!(act.fields.to, None);
+        assert_eq!(act.fields.data, "0x6080");
+        let access_list = act.fields.access_list.unwrap();
+        assert_eq!(access_list.len(), 1);
+        assert_eq!(access_list[0].address, format!("0x{}", "11".repeat(20)));
+        assert_eq!(access_list[0].storage, vec![format!("0x{}", "22".repeat(32))]);
+        assert_eq!(act.from, "0x9d8a62f656a8d1615c1294fd71e9cfb3e4855a4f");
+    }
+
+    #[test]
+    fn fails_on_invalid_ethereum() {
+        let raw = hex::decode("02f8690119830186a08477359400825208943eaf0b987b49c4d782ee134fdc1243fd0ccdfdd38080c001a08a6736c1ec07c2017362eed9a89823f3d6220ab8bacf81fa8a14da9581421f70a04e5f507d99bb20f0956b4b257fa660f5759dfdb7e04c7e8fcf6645ab8845cf7f").unwrap();
+        assert!(decode_ethereum(&[]).is_err());
+        assert!(decode_ethereum(&raw[0..raw.len() - 1]).is_err());
+        let mut other_type = raw.clone();
+        other_type[0] = 0x03;
+        assert!(decode_ethereum(&other_type).is_err());
+        // same fields, but as a legacy
+        assert!(decode_ethereum(&raw[1..]).is_err());
+    }
+
+    #[test]
+    fn decode_unsigned_eip1559() {
+        let signed = hex::decode("02f8690119830186a08477359400825208943eaf0b987b49c4d782ee134fdc1243fd0ccdfdd38080c001a08a6736c1ec07c2017362eed9a89823f3d6220ab8bacf81fa8a14da9581421f70a04e5f507d99bb20f0956b4b257fa660f5759dfdb7e04c7e8fcf6645ab8845cf7f").unwrap();
+        // same tx without the signature
+        let raw = hex::decode("02e60119830186a08477359400825208943eaf0b987b49c4d782ee134fdc1243fd0ccdfdd38080c0").unwrap();
+        let act = decode_unsigned_ethereum(&raw).unwrap();
+        assert_eq!(act, decode_ethereum(&signed).unwrap().fields);
+    }
+
+    #[test]
+    fn decode_unsigned_legacy() {
+        let signed = hex::decode("f86c098504a817c800825208943535353535353535353535353535353535353535880de0b6b3a76400008025a028ef61340bd939bc2195fe537567866003e1a15d3c71ff63e1590620aa636276a067cbe9d8997f761aecb703304b3800ccf555c9f3dc64214b297fb1966a3b6d83").unwrap();
+        // signing data from EIP-155 example
+        let raw = hex::decode("ec098504a817c800825208943535353535353535353535353535353535353535880de0b6b3a764000080018080").unwrap();
+        let act = decode_unsigned_ethereum(&raw).unwrap();
+        assert_eq!(act.chain_id, Some(1));
+        assert_eq!(act, decode_ethereum(&signed).unwrap().fields);
+    }
+
+    #[test]
+    fn fails_on_invalid_unsigned() {
+        // legacy without chain id
+        assert!(decode_unsigned_ethereum(&hex::decode("e9098504a817c800825208943535353535353535353535353535353535353535880de0b6b3a764000080").unwrap()).is_err());
+        // signed txes
+        assert!(decode_unsigned_ethereum(&hex::decode("02f8690119830186a08477359400825208943eaf0b987b49c4d782ee134fdc1243fd0ccdfdd38080c001a08a6736c1ec07c2017362eed9a89823f3d6220ab8bacf81fa8a14da9581421f70a04e5f507d99bb20f0956b4b257fa660f5759dfdb7e04c7e8fcf6645ab8845cf7f").unwrap()).is_err());
+        assert!(decode_unsigned_ethereum(&hex::decode("f86c098504a817c800825208943535353535353535353535353535353535353535880de0b6b3a76400008025a028ef61340bd939bc2195fe537567866003e1a15d3c71ff63e1590620aa636276a067cbe9d8997f761aecb703304b3800ccf555c9f3dc64214b297fb1966a3b6d83").unwrap()).is_err());
+        // missing field
+        assert!(decode_unsigned_ethereum(&hex::decode("02e50119830186a08477359400825208943eaf0b987b49c4d782ee134fdc1243fd0ccdfdd380c0").unwrap()).is_err());
+    }
+
+    #[test]
+    fn decode_bitcoin_p2wpkh() {
+        let raw = hex::decode("02000000000101ae52c699b61c43aa4f4c1428cb1e791d612e7b099a8b605b5462068f243b71d60100000000fdffffff027375120000000000160014817cbce72f18ec6760eb92c6e19779db33fb98e9db43833c00000000160014f60834ef165253c571b11ce9fa74e46692fc5ec10248304502210081d536bed9fb9fcb29aeb75832f065f1fd9cedb425cba09a87fd6aebe9a619b4022058c29c93a4ac81bd642fc1066f0a8aa2263474764fc801f6b80cd211d699b46c0121026e5628506ecd33242e5ceb5fdafe4d3066b5c0f159b3c05a621ef65f177ea28600000000").unwrap();
+        let act = decode_bitcoin(&raw, NetworkKind::Main).unwrap();
+        assert_eq!(act.txid, "18fd8cc6ad9fb31c5fafd0196615c323c790e0bed650352c0c356350b694d83a");
+        assert_ne!(act.wtxid, act.txid);
+        assert_eq!(act.version, 2);
+        assert_eq!(act.size, 223);
+        assert_eq!(act.weight, 562);
+        assert_eq!(act.vsize, 141);
+        assert_eq!(act.inputs.len(), 1);
+        assert_eq!(act.inputs[0].txid, "d6713b248f0662545b608b9a097b2e611d791ecb28144c4faa431cb699c652ae");
+        assert_eq!(act.inputs[0].vout, 1);
+        assert_eq!(act.inputs[0].sequence, 0xfffffffd);
+        assert_eq!(act.inputs[0].witness.len(), 2);
+        assert_eq!(act.inputs[0].address, Some("bc1q7cyrfmck2ffu2ud3rn5l5a8yv6f0chkp0zpemf".to_string()));
+        assert_eq!(act.outputs.len(), 2);
+        assert_eq!(act.outputs[0].amount, 1209715);
+        assert_eq!(act.outputs[0].address, Some("bc1qs97teee0rrkxwc8tjtrwr9memvelhx8faepjgx".to_string()));
+        // the change goes back to the same address
+        assert_eq!(act.outputs[1].address, act.inputs[0].address);
+    }
+
+    #[test]
+    fn decode_bitcoin_p2pkh_testnet() {
+        let raw = hex::decode("0100000001065dc13cecdaa1c70d6786bc0630c0cac852a50e904b541de99d49bbf657450c030000006b48304502210081191bd5c20510846322ab38eabf7d4de7ae9b8759f4bf296419285366e43e14022049dbd681fb905fd90f527e1ede3f47ccf483ef5ddfb015a7b14456397e15c9010121037435c194e9b01b3d7f7a2802d6684a3af68d05bbf4ec8f17021980d777691f1dfdffffff042530000000000000536a4c5054325b4fab475bb321cfae6ae84b733c49369f24a8b49b131d547b38041fb490d588b4b618e4e3c9d790ff426ee92917cac4e2c6ff7e01cf4667375bedf477ce9aadf70023d060000e0023c7fb00162cfd0e0000000000001976a914000000000000000000000000000000000000000088acfd0e0000000000001976a914000000000000000000000000000000000000000088ac83207f47000000001976a914ba27f99e007c7f605a8305e318c1abde3cd220ac88ac00000000").unwrap();
+        let act = decode_bitcoin(&raw, NetworkKind::Test).unwrap();
+        assert_eq!(act.txid, "52f49ce2579c53ca21a173ffa957cd97dad5efa2b883def65173d49023212d41");
+        assert_eq!(act.txid, act.wtxid);
+        assert_eq!(act.size, act.vsize);
+        assert!(act.inputs[0].witness.is_empty());
+        assert!(act.inputs[0].address.is_some());
+        assert_eq!(act.outputs.len(), 4);
+        // OP_RETURN
+        assert_eq!(act.outputs[0].address, None);
+        assert_eq!(act.outputs[3].address, act.inputs[0].address);
+    }
+
+    #[test]
+    fn decode_bitcoin_p2sh_p2wpkh_testnet() {
+        let raw = hex::decode("01000000000101f932958cce1453df13d2860d56ad11bef28811d7a2a73809a95778cd4db3196d0100000017160014854f66d66c9ef68560d72a0bf9736a9822063ea6ffffffff027e9909000000000017a91439abfb47b25303828f7289a7f0943ec489f1c7b887307500000000000017a914aaccfe2dd25d7c030ab5691aab601b8760e4db2e8702483045022100c894a86da2c6283289e1394f3e2222096684718387436efcaa288df376b92b9c02206c9cacbf4da0b9eb514b0c4b5ac8bfa1aeb356506117c09bb75daceeea7dce66012102a947c0181cfdf7358b63a2567c1babd31878618e814de3ba5da460e9f42be84800000000").unwrap();
+        let act = decode_bitcoin(&raw, NetworkKind::Test).unwrap();
+        assert_eq!(act.txid, "e9e711e7bd74faabc71ca0bfaa6efdd79295fad1e93abec5e3f9656362f36812");
+        assert!(act.inputs[0].address.as_ref().unwrap().starts_with('2'));
+        assert!(act.outputs.iter().all(|o| o.address.as_ref().unwrap().starts_with('2')));
+    }
+
+    #[test]
+    fn fails_on_invalid_bitcoin() {
+        assert!(decode_bitcoin(&[], NetworkKind::Main).is_err());
+        assert!(decode_bitcoin(&hex::decode("0200000001").unwrap(), NetworkKind::Main).is_err());
+    }
+}
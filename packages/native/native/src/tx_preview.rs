@@ -0,0 +1,441 @@
+//! Preview of a transaction before signing, i.e., what the user approves with the signature.
+//!
+//! For Ethereum it decodes the calldata of the common token methods (ERC-20, ERC-721 and ERC-1155 transfers, `approve` and
+//! `setApprovalForAll`). Other calls are shown only as the target address and the value. An EIP-7702 transaction also shows
+//! the accounts it delegates, because with a delegation the account can be controlled by the code of the delegate.
+
+use std::str::FromStr;
+use num_bigint::BigUint;
+use crate::errors::VaultNodeError;
+use crate::tx_decode::{recover_authority, EthereumTxFieldsJson};
+
+/// `transfer(address,uint256)`
+const TRANSFER: [u8; 4] = [0xa9, 0x05, 0x9c, 0xbb];
+/// `transferFrom(address,address,uint256)`, which is the same for ERC-20 and ERC-721
+const TRANSFER_FROM: [u8; 4] = [0x23, 0xb8, 0x72, 0xdd];
+/// `safeTransferFrom(address,address,uint256)` of ERC-721
+const SAFE_TRANSFER_FROM: [u8; 4] = [0x42, 0x84, 0x2e, 0x0e];
+/// `safeTransferFrom(address,address,uint256,bytes)` of ERC-721
+const SAFE_TRANSFER_FROM_DATA: [u8; 4] = [0xb8, 0x8d, 0x4f, 0xde];
+/// `safeTransferFrom(address,address,uint256,uint256,bytes)` of ERC-1155
+const SAFE_TRANSFER_FROM_1155: [u8; 4] = [0xf2, 0x42, 0x43, 0x2a];
+/// `safeBatchTransferFrom(address,address,uint256[],uint256[],bytes)` of ERC-1155
+const SAFE_BATCH_TRANSFER_FROM: [u8; 4] = [0x2e, 0xb2, 0xc2, 0xd6];
+/// `approve(address,uint256)`
+const APPROVE: [u8; 4] = [0x09, 0x5e, 0xa7, 0xb3];
+/// `setApprovalForAll(address,bool)`
+const SET_APPROVAL_FOR_ALL: [u8; 4] = [0xa2, 0x2c, 0xb4, 0x65];
+
+/// An allowance of 2^128 or more is never a real amount, and it's used by applications as an unlimited approval
+const UNLIMITED_APPROVAL_BITS: u64 = 129;
+
+/// Token method called by the transaction. Amounts and token ids are decimal strings.
+#[derive(Serialize, Clone, Debug, Eq, PartialEq)]
+#[serde(tag = "type")]
+pub enum ContractCallJson {
+    #[serde(rename = "erc20Transfer")]
+    Erc20Transfer { to: String, amount: String },
+    /// ERC-20 or ERC-721 transfer, where the value is an amount or a token id
+    #[serde(rename = "transferFrom")]
+    TransferFrom { from: String, to: String, value: String },
+    #[serde(rename = "erc721Transfer")]
+    Erc721Transfer {
+        from: String,
+        to: String,
+        #[serde(rename = "tokenId")]
+        token_id: String,
+    },
+    #[serde(rename = "erc1155Transfer")]
+    Erc1155Transfer { from: String, to: String, id: String, amount: String },
+    #[serde(rename = "erc1155BatchTransfer")]
+    Erc1155BatchTransfer { from: String, to: String, ids: Vec<String>, amounts: Vec<String> },
+    #[serde(rename = "approve")]
+    Approve { spender: String, amount: String, unlimited: bool },
+    #[serde(rename = "setApprovalForAll")]
+    SetApprovalForAll { operator: String, approved: bool },
+}
+
+#[derive(Serialize, Clone, Debug, Eq, PartialEq)]
+pub struct EthereumPreviewJson {
+    #[serde(rename = "chainId")]
+    pub chain_id: u64,
+    pub from: String,
+    /// Target address, none for a contract creation
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub to: Option<String>,
+    #[serde(rename = "toLabel", skip_serializing_if = "Option::is_none")]
+    pub to_label: Option<String>,
+    /// Value in wei
+    pub value: String,
+    /// Max fee in wei, i.e. the gas limit by the max gas price
+    #[serde(rename = "maxFee")]
+    pub max_fee: String,
+    /// Value plus the max fee
+    #[serde(rename = "maxCost")]
+    pub max_cost: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub call: Option<ContractCallJson>,
+    /// Address which gets the tokens or the allowance of the decoded call, or the target address otherwise
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub recipient: Option<String>,
+    #[serde(rename = "recipientLabel", skip_serializing_if = "Option::is_none")]
+    pub recipient_label: Option<String>,
+    /// The call gives an unlimited allowance, or access to all tokens of the collection
+    #[serde(rename = "unlimitedApproval")]
+    pub unlimited_approval: bool,
+    /// Delegations set by an EIP-7702 transaction
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub delegations: Vec<DelegationJson>,
+}
+
+#[derive(Serialize, Clone, Debug, Eq, PartialEq)]
+pub struct DelegationJson {
+    /// Account that signed the authorization, or none if the signature is invalid and the authorization is skipped
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub authority: Option<String>,
+    /// Delegate with the code for the account
+    pub address: String,
+    /// Chain Id of the authorization, where `0` means any chain
+    #[serde(rename = "chainId")]
+    pub chain_id: u64,
+}
+
+#[derive(Serialize, Clone, Debug, Eq, PartialEq)]
+pub struct BitcoinPreviewOutputJson {
+    pub address: String,
+    pub amount: u64,
+    /// The output is to a change address of the entry
+    pub change: bool,
+}
+
+#[derive(Serialize, Clone, Debug, Eq, PartialEq)]
+pub struct BitcoinPreviewJson {
+    pub outputs: Vec<BitcoinPreviewOutputJson>,
+    /// Sum of the outputs except the change
+    pub sent: u64,
+    pub fee: u64,
+}
+
+#[derive(Serialize, Clone, Debug, Eq, PartialEq)]
+#[serde(untagged)]
+pub enum TxPreviewJson {
+    Ethereum(Box<EthereumPreviewJson>),
+    Bitcoin(BitcoinPreviewJson),
+}
+
+fn parse_uint(value: Option<&String>, name: &str) -> Result<BigUint, VaultNodeError> {
+    let value = value.ok_or(VaultNodeError::InvalidArgumentValue(format!("Missing {}", name)))?;
+    BigUint::from_str(value.as_str())
+        .map_err(|_| VaultNodeError::InvalidArgumentValue(format!("Invalid {}", name)))
+}
+
+/// Preview of the transaction fields as they are signed, without address labels
+pub fn ethereum(fields: &EthereumTxFieldsJson, from: String) -> Result<EthereumPreviewJson, VaultNodeError> {
+    let chain_id = fields.chain_id.ok_or(VaultNodeError::InvalidArgumentValue("Missing chainId".to_string()))?;
+    let gas_price = if fields.is_dynamic_fee() {
+        parse_uint(fields.max_gas_price.as_ref(), "maxGasPrice")?
+    } else {
+        parse_uint(fields.gas_price.as_ref(), "gasPrice")?
+    };
+    let value = parse_uint(Some(&fields.value), "value")?;
+    let max_fee = gas_price * fields.gas;
+    let max_cost = &value + &max_fee;
+
+    let data = hex::decode(fields.data.strip_prefix("0x").unwrap_or(fields.data.as_str()))
+        .map_err(|_| VaultNodeError::InvalidArgumentValue("Invalid data".to_string()))?;
+    let call = decode_call(&data);
+    let recipient = match &call {
+        Some(ContractCallJson::Erc20Transfer { to, .. })
+        | Some(ContractCallJson::TransferFrom { to, .. })
+        | Some(ContractCallJson::Erc721Transfer { to, .. })
+        | Some(ContractCallJson::Erc1155Transfer { to, .. })
+        | Some(ContractCallJson::Erc1155BatchTransfer { to, .. }) => Some(to.clone()),
+        Some(ContractCallJson::Approve { spender, .. }) => Some(spender.clone()),
+        Some(ContractCallJson::SetApprovalForAll { operator, .. }) => Some(operator.clone()),
+        None => fields.to.clone(),
+    };
+    let unlimited_approval = matches!(
+        call,
+        Some(ContractCallJson::Approve { unlimited: true, .. }) | Some(ContractCallJson::SetApprovalForAll { approved: true, .. })
+    );
+    let delegations = fields.authorization_list.iter()
+        .flatten()
+        .map(|authorization| DelegationJson {
+            authority: recover_authority(authorization).ok(),
+            address: authorization.address.clone(),
+            chain_id: authorization.chain_id,
+        })
+        .collect();
+
+    Ok(EthereumPreviewJson {
+        chain_id,
+        from,
+        to: fields.to.clone(),
+        to_label: None,
+        value: value.to_string(),
+        max_fee: max_fee.to_string(),
+        max_cost: max_cost.to_string(),
+        call,
+        recipient,
+        recipient_label: None,
+        unlimited_approval,
+        delegations,
+    })
+}
+
+/// ABI encoded arguments of a call, i.e., the calldata after the selector
+struct Arguments<'a> {
+    data: &'a [u8],
+}
+
+impl Arguments<'_> {
+    fn word(&self, pos: usize) -> Option<&[u8]> {
+        self.data.get(pos..pos.checked_add(32)?)
+    }
+
+    fn uint(&self, i: usize) -> Option<BigUint> {
+        self.word(i * 32).map(BigUint::from_bytes_be)
+    }
+
+    fn usize_at(&self, pos: usize) -> Option<usize> {
+        let word = self.word(pos)?;
+        if word[..24].iter().any(|b| *b != 0) {
+            return None;
+        }
+        usize::try_from(u64::from_be_bytes(word[24..].try_into().ok()?)).ok()
+    }
+
+    fn address(&self, i: usize) -> Option<String> {
+        let word = self.word(i * 32)?;
+        if word[..12].iter().any(|b| *b != 0) {
+            return None;
+        }
+        Some(format!("0x{}", hex::encode(&word[12..])))
+    }
+
+    fn bool(&self, i: usize) -> Option<bool> {
+        let value = self.uint(i)?;
+        if value.bits() > 1 {
+            return None;
+        }
+        Some(value.bits() == 1)
+    }
+
+    /// Dynamic `uint256[]` referenced by the offset in the argument `i`
+    fn uint_array(&self, i: usize) -> Option<Vec<String>> {
+        let start = self.usize_at(i * 32)?;
+        let len = self.usize_at(start)?;
+        // each item takes a word, so a longer array cannot fit into the data
+        if len > self.data.len() / 32 {
+            return None;
+        }
+        (0..len)
+            .map(|n| self.word(start + 32 + n * 32).map(|w| BigUint::from_bytes_be(w).to_string()))
+            .collect()
+    }
+}
+
+/// Decode a call of a token method, or none if it's another method or the arguments are invalid
+pub fn decode_call(data: &[u8]) -> Option<ContractCallJson> {
+    if data.len() < 4 {
+        return None;
+    }
+    let selector: [u8; 4] = data[..4].try_into().ok()?;
+    let args = Arguments { data: &data[4..] };
+    let call = match selector {
+        TRANSFER => ContractCallJson::Erc20Transfer {
+            to: args.address(0)?,
+            amount: args.uint(1)?.to_string(),
+        },
+        TRANSFER_FROM => ContractCallJson::TransferFrom {
+            from: args.address(0)?,
+            to: args.address(1)?,
+            value: args.uint(2)?.to_string(),
+        },
+        SAFE_TRANSFER_FROM | SAFE_TRANSFER_FROM_DATA => ContractCallJson::Erc721Transfer {
+            from: args.address(0)?,
+            to: args.address(1)?,
+            token_id: args.uint(2)?.to_string(),
+        },
+        SAFE_TRANSFER_FROM_1155 => ContractCallJson::Erc1155Transfer {
+            from: args.address(0)?,
+            to: args.address(1)?,
+            id: args.uint(2)?.to_string(),
+            amount: args.uint(3)?.to_string(),
+        },
+        SAFE_BATCH_TRANSFER_FROM => ContractCallJson::Erc1155BatchTransfer {
+            from: args.address(0)?,
+            to: args.address(1)?,
+            ids: args.uint_array(2)?,
+            amounts: args.uint_array(3)?,
+        },
+        APPROVE => {
+            let amount = args.uint(1)?;
+            ContractCallJson::Approve {
+                spender: args.address(0)?,
+                unlimited: amount.bits() >= UNLIMITED_APPROVAL_BITS,
+                amount: amount.to_string(),
+            }
+        },
+        SET_APPROVAL_FOR_ALL => ContractCallJson::SetApprovalForAll {
+            operator: args.address(0)?,
+            approved: args.bool(1)?,
+        },
+        _ => return None,
+    };
+    Some(call)
+}
+
+#[cfg(test)]
+mod tests {
+    use bitcoin::secp256k1::SecretKey;
+    use crate::ethereum_tx;
+    use crate::tx_decode::EthereumTxFieldsJson;
+    use crate::tx_preview::{decode_call, ethereum, ContractCallJson, DelegationJson};
+
+    const ALICE: &str = "0x3535353535353535353535353535353535353535";
+    const TOKEN: &str = "0x1111111111111111111111111111111111111111";
+
+    fn address(value: &str) -> String {
+        format!("{:0>64}", value.trim_start_matches("0x"))
+    }
+
+    fn uint(value: u64) -> String {
+        format!("{:064x}", value)
+    }
+
+    fn call(selector: &str, args: &[String]) -> Vec<u8> {
+        hex::decode(format!("{}{}", selector, args.concat())).unwrap()
+    }
+
+    fn tx(data: Vec<u8>) -> EthereumTxFieldsJson {
+        EthereumTxFieldsJson {
+            tx_type: 2,
+            chain_id: Some(1),
+            nonce: 0,
+            gas: 50_000,
+            gas_price: None,
+            max_gas_price: Some("30000000000".to_string()),
+            priority_gas_price: Some("1000000000".to_string()),
+            to: Some(TOKEN.to_string()),
+            value: "0".to_string(),
+            data: format!("0x{}", hex::encode(data)),
+            access_list: None,
+            authorization_list: None,
+        }
+    }
+
+    #[test]
+    fn decode_erc20_transfer() {
+        let act = decode_call(&call("a9059cbb", &[address(ALICE), uint(1_000_000)]));
+        assert_eq!(act, Some(ContractCallJson::Erc20Transfer { to: ALICE.to_string(), amount: "1000000".to_string() }));
+    }
+
+    #[test]
+    fn decode_erc721_transfer() {
+        let args = [address(TOKEN), address(ALICE), uint(42)];
+        let expected = Some(ContractCallJson::Erc721Transfer {
+            from: TOKEN.to_string(),
+            to: ALICE.to_string(),
+            token_id: "42".to_string(),
+        });
+        assert_eq!(decode_call(&call("42842e0e", &args)), expected);
+        let with_data = [address(TOKEN), address(ALICE), uint(42), uint(0x80), uint(0)];
+        assert_eq!(decode_call(&call("b88d4fde", &with_data)), expected);
+    }
+
+    #[test]
+    fn decode_erc1155_batch_transfer() {
+        let args = [
+            address(TOKEN), address(ALICE), uint(0xa0), uint(0x100), uint(0x160),
+            uint(2), uint(1), uint(2),
+            uint(2), uint(10), uint(20),
+            uint(0),
+        ];
+        let act = decode_call(&call("2eb2c2d6", &args));
+        assert_eq!(act, Some(ContractCallJson::Erc1155BatchTransfer {
+            from: TOKEN.to_string(),
+            to: ALICE.to_string(),
+            ids: vec!["1".to_string(), "2".to_string()],
+            amounts: vec!["10".to_string(), "20".to_string()],
+        }));
+    }
+
+    #[test]
+    fn decode_unlimited_approve() {
+        let act = decode_call(&call("095ea7b3", &[address(ALICE), "ff".repeat(32)])).unwrap();
+        assert!(matches!(act, ContractCallJson::Approve { unlimited: true, .. }));
+        let act = decode_call(&call("095ea7b3", &[address(ALICE), uint(1_000)])).unwrap();
+        assert!(matches!(act, ContractCallJson::Approve { unlimited: false, .. }));
+    }
+
+    #[test]
+    fn ignore_invalid_calls() {
+        // unknown method
+        assert_eq!(decode_call(&call("12345678", &[address(ALICE)])), None);
+        // not enough arguments
+        assert_eq!(decode_call(&call("a9059cbb", &[address(ALICE)])), None);
+        // not an address
+        assert_eq!(decode_call(&call("a9059cbb", &[uint(u64::MAX).replace("0000", "ffff"), uint(1)])), None);
+        // array longer than the data
+        let args = [address(TOKEN), address(ALICE), uint(0xa0), uint(0xc0), uint(0xe0), uint(1000)];
+        assert_eq!(decode_call(&call("2eb2c2d6", &args)), None);
+        assert_eq!(decode_call(&[0xa9]), None);
+    }
+
+    #[test]
+    fn preview_approval_for_all() {
+        let act = ethereum(&tx(call("a22cb465", &[address(ALICE), uint(1)])), ALICE.to_string()).unwrap();
+        assert!(act.unlimited_approval);
+        assert_eq!(act.recipient, Some(ALICE.to_string()));
+        assert_eq!(act.max_fee, "1500000000000000");
+        assert_eq!(act.max_cost, "1500000000000000");
+    }
+
+    #[test]
+    fn preview_legacy_transfer() {
+        let mut fields = tx(vec![]);
+        fields.tx_type = 0;
+        fields.gas = 21_000;
+        fields.gas_price = Some("20000000000".to_string());
+        fields.value = "1000000000000000000".to_string();
+        let act = ethereum(&fields, ALICE.to_string()).unwrap();
+        assert_eq!(act.call, None);
+        assert_eq!(act.recipient, Some(TOKEN.to_string()));
+        assert!(!act.unlimited_approval);
+        assert_eq!(act.max_fee, "420000000000000");
+        assert_eq!(act.max_cost, "1000420000000000000");
+    }
+
+    #[test]
+    fn preview_delegation() {
+        let key = SecretKey::from_slice(&[0x46; 32]).unwrap();
+        let mut fields = tx(vec![]);
+        fields.tx_type = 4;
+        fields.to = Some(ALICE.to_string());
+        let mut invalid = ethereum_tx::sign_authorization(0, TOKEN, 1, &key).unwrap();
+        invalid.s = format!("0x{}", "ff".repeat(32));
+        fields.authorization_list = Some(vec![
+            ethereum_tx::sign_authorization(1, TOKEN, 1, &key).unwrap(),
+            invalid,
+        ]);
+        let act = ethereum(&fields, ALICE.to_string()).unwrap();
+        assert_eq!(act.max_fee, "1500000000000000");
+        assert_eq!(act.delegations, vec![
+            DelegationJson {
+                authority: Some("0x9d8a62f656a8d1615c1294fd71e9cfb3e4855a4f".to_string()),
+                address: TOKEN.to_string(),
+                chain_id: 1,
+            },
+            DelegationJson { authority: None, address: TOKEN.to_string(), chain_id: 0 },
+        ]);
+        assert!(ethereum(&tx(vec![]), ALICE.to_string()).unwrap().delegations.is_empty());
+    }
+
+    #[test]
+    fn fails_without_gas_price() {
+        let mut fields = tx(vec![]);
+        fields.max_gas_price = None;
+        assert!(ethereum(&fields, ALICE.to_string()).is_err());
+    }
+}
@@ -0,0 +1,149 @@
+//! Estimation of a Bitcoin transaction size before signing.
+//!
+//! The size of a signed input depends on the script type only, except the signature which is 71-72 bytes for ECDSA.
+//! The estimation always counts the maximal size, so the actual fee rate of the signed transaction is the same or a bit higher.
+
+use bitcoin::{Address, AddressType, ScriptBuf, TxOut, VarInt, Weight};
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum InputType {
+    P2pkh,
+    P2shP2wpkh,
+    P2wpkh,
+    P2tr,
+}
+
+#[derive(Serialize, Clone, Debug, Eq, PartialEq)]
+pub struct TxSizeJson {
+    pub weight: u64,
+    pub vsize: u64,
+}
+
+impl InputType {
+    /// Type of the input spending an output of the address. Only single-key addresses are supported
+    pub fn of_address(address: &Address) -> Option<InputType> {
+        match address.address_type()? {
+            AddressType::P2pkh => Some(InputType::P2pkh),
+            AddressType::P2sh => Some(InputType::P2shP2wpkh),
+            AddressType::P2wpkh => Some(InputType::P2wpkh),
+            AddressType::P2tr => Some(InputType::P2tr),
+            _ => None
+        }
+    }
+
+    fn is_segwit(&self) -> bool {
+        !matches!(self, InputType::P2pkh)
+    }
+
+    /// Weight of the signed input
+    pub fn weight(&self) -> Weight {
+        // outpoint, sequence and the script length
+        let base = 32 + 4 + 4 + 1;
+        let (script_sig, witness) = match self {
+            // signature with sighash type and compressed key
+            InputType::P2pkh => (1 + 73 + 1 + 33, 0),
+            // push of the P2WPKH script
+            InputType::P2shP2wpkh => (1 + 22, 1 + 1 + 73 + 1 + 33),
+            InputType::P2wpkh => (0, 1 + 1 + 73 + 1 + 33),
+            // key-path spend with the default sighash
+            InputType::P2tr => (0, 1 + 1 + 64),
+        };
+        Weight::from_non_witness_data_size(base + script_sig) + Weight::from_witness_data_size(witness)
+    }
+}
+
+/// Estimated weight of the signed transaction
+pub fn estimate(inputs: &[InputType], outputs: &[ScriptBuf]) -> Weight {
+    let header = 4 + 4 + VarInt(inputs.len() as u64).size() + VarInt(outputs.len() as u64).size();
+    let mut weight = Weight::from_non_witness_data_size(header as u64);
+    if inputs.iter().any(|i| i.is_segwit()) {
+        // segwit marker and flag
+        weight += Weight::from_witness_data_size(2);
+        // an input without witness still has the number of items in it
+        weight += Weight::from_witness_data_size(inputs.iter().filter(|i| !i.is_segwit()).count() as u64);
+    }
+    for input in inputs {
+        weight += input.weight();
+    }
+    for script in outputs {
+        weight += output_weight(script);
+    }
+    weight
+}
+
+pub fn output_weight(script: &ScriptBuf) -> Weight {
+    TxOut { value: bitcoin::Amount::ZERO, script_pubkey: script.clone() }.weight()
+}
+
+impl From<Weight> for TxSizeJson {
+    fn from(value: Weight) -> Self {
+        TxSizeJson {
+            weight: value.to_wu(),
+            vsize: value.to_vbytes_ceil(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+    use bitcoin::{Address, Amount, OutPoint, ScriptBuf, Sequence, Transaction, TxIn, TxOut, Witness};
+    use bitcoin::absolute::LockTime;
+    use bitcoin::transaction::Version;
+    use crate::tx_size::{estimate, InputType};
+
+    fn script(address: &str) -> ScriptBuf {
+        Address::from_str(address).unwrap().assume_checked().script_pubkey()
+    }
+
+    #[test]
+    fn input_sizes() {
+        assert_eq!(InputType::P2pkh.weight().to_wu(), 149 * 4);
+        assert_eq!(InputType::P2shP2wpkh.weight().to_wu(), 64 * 4 + 109);
+        assert_eq!(InputType::P2wpkh.weight().to_wu(), 41 * 4 + 109);
+        assert_eq!(InputType::P2tr.weight().to_wu(), 41 * 4 + 66);
+    }
+
+    #[test]
+    fn type_of_address() {
+        let address = Address::from_str("bc1q5c4g4njf4g7a2ugu0tq5rjjdg3j0yexus7x3f4").unwrap().assume_checked();
+        assert_eq!(InputType::of_address(&address), Some(InputType::P2wpkh));
+        let address = Address::from_str("1BvBMSEYstWetqTFn5Au4m4GFg7xJaNVN2").unwrap().assume_checked();
+        assert_eq!(InputType::of_address(&address), Some(InputType::P2pkh));
+    }
+
+    #[test]
+    fn estimate_p2wpkh() {
+        // same as a signed 1-in 2-out P2WPKH transaction with a 72-bytes signature
+        let mut witness = Witness::new();
+        witness.push([1u8; 72]);
+        witness.push([2u8; 33]);
+        let outputs = vec![
+            script("bc1q5c4g4njf4g7a2ugu0tq5rjjdg3j0yexus7x3f4"),
+            script("bc1qxhmdufsvnuaaaer4ynz88fspdsxq2h9e9cetdj"),
+        ];
+        let tx = Transaction {
+            version: Version::TWO,
+            lock_time: LockTime::ZERO,
+            input: vec![TxIn { previous_output: OutPoint::null(), script_sig: ScriptBuf::new(), sequence: Sequence::MAX, witness }],
+            output: outputs.iter().map(|s| TxOut { value: Amount::ZERO, script_pubkey: s.clone() }).collect(),
+        };
+        let act = estimate(&[InputType::P2wpkh], &outputs);
+        assert_eq!(act.to_wu(), tx.weight().to_wu() + 1);
+        assert_eq!(act.to_vbytes_ceil(), 141);
+    }
+
+    #[test]
+    fn estimate_legacy() {
+        let act = estimate(&[InputType::P2pkh], &[script("1BvBMSEYstWetqTFn5Au4m4GFg7xJaNVN2")]);
+        // no witness data at all
+        assert_eq!(act.to_wu() % 4, 0);
+        assert_eq!(act.to_vbytes_ceil(), 10 + 149 + 34);
+    }
+
+    #[test]
+    fn estimate_mixed() {
+        let act = estimate(&[InputType::P2pkh, InputType::P2tr], &[script("bc1q5c4g4njf4g7a2ugu0tq5rjjdg3j0yexus7x3f4")]);
+        assert_eq!(act.to_wu(), 10 * 4 + 2 + 1 + 149 * 4 + 41 * 4 + 66 + 31 * 4);
+    }
+}
@@ -1,7 +1,7 @@
 use std::convert::{TryFrom, TryInto};
 use std::str::FromStr;
 
-use neon::prelude::{FunctionContext, JsString};
+use neon::prelude::{FunctionContext, JsNumber, JsString};
 use uuid::Uuid;
 
 use crate::access::{args_get_str, args_get_wallet_and_entry_ids, args_get_uuid};
@@ -17,20 +17,28 @@ use emerald_vault::{
     structs::{
         wallet::Wallet,
         wallet::{EntryId, PKType, ReservedPath, WalletEntry, AddressRole, EntryAddress},
-        seed::{LedgerSource, Seed, SeedSource},
+        seed::{Seed, SeedSource},
         book::AddressRef,
     },
     trim_hex,
     EthereumAddress,
     EthereumPrivateKey,
 };
-use hdpath::{StandardHDPath, AccountHDPath};
-use crate::seeds::{SeedDefinitionOrReferenceJson, SeedDefinitionOrReferenceType};
+use hdpath::{StandardHDPath, AccountHDPath, CustomHDPath};
+use crate::seeds::{connected_ledger_source, derive_ethereum_key, SeedDefinitionOrReferenceJson, SeedDefinitionOrReferenceType};
 use crate::address::AddressRefJson;
+use crate::entries::{address_identity, seed_identity};
 use bitcoin::Address;
 use emerald_vault::blockchain::bitcoin::XPub;
 use crate::errors::{VaultNodeError, JsonError};
 use crate::instance::{AccountIndex, Instance, WrappedVault};
+use crate::multisig::{CosignerJson, Multisig, MultisigJson, MultisigScript, MultisigSetupJson};
+use crate::taproot;
+use crate::xpub;
+use crate::xpub::XpubFormat;
+
+/// Action refused for a key used by an entry with a spending policy
+const SAME_KEY_ACTION: &str = "Another entry with the same key";
 
 #[derive(Deserialize, Clone)]
 pub struct AddEntryJson {
@@ -66,6 +74,28 @@ pub struct SeedEntry {
     pub address: Option<String>,
 }
 
+#[derive(Deserialize, Clone)]
+pub struct AddMultisigJson {
+    pub blockchain: u32,
+    pub label: Option<String>,
+    pub script: MultisigScript,
+    pub threshold: usize,
+    /// Cosigners of other vaults
+    pub cosigners: Vec<CosignerJson>,
+    /// Seed of this vault, which becomes one of the cosigners
+    pub seed: MultisigSeedJson,
+}
+
+#[derive(Deserialize, Clone)]
+pub struct MultisigSeedJson {
+    #[serde(rename = "seedId")]
+    pub seed_id: Uuid,
+    pub password: String,
+    /// Account path, by default it's BIP-48 `m/48'/coin'/0'/type'`
+    #[serde(rename = "hdPath")]
+    pub hd_path: Option<String>,
+}
+
 #[derive(Serialize, Clone)]
 pub struct WalletEntryJson {
     pub id: String,
@@ -124,6 +154,9 @@ pub struct WalletJson {
     pub name: Option<String>,
     pub entries: Vec<WalletEntryJson>,
     pub reserved: Vec<ReservedAccountJson>,
+    /// Multisig setups of the wallet, which are kept separately from the entries
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub multisig: Vec<MultisigJson>,
     #[serde(rename = "createdAt")]
     pub created_at: DateTime<Utc>,
 }
@@ -258,6 +291,7 @@ impl From<(Wallet, &Vec<AccountIndex>)> for WalletJson {
             name: wallet.label,
             entries,
             reserved,
+            multisig: vec![],
             created_at: wallet.created_at,
         }
     }
@@ -265,7 +299,41 @@ impl From<(Wallet, &Vec<AccountIndex>)> for WalletJson {
 
 
 
-fn with_std_addresses(entry: &WalletEntry, index: Option<&AccountIndex>) -> Vec<CurrentAddressJson> {
+/// Account of a Taproot entry. The Vault doesn't know P2TR addresses, so they are derived from the account key instead
+pub(crate) fn taproot_account(entry: &WalletEntry) -> Option<(AccountHDPath, bitcoin::bip32::Xpub)> {
+    let seed = match &entry.key {
+        PKType::SeedHd(seed) => seed,
+        _ => return None
+    };
+    if !taproot::is_taproot_path(seed.hd_path.to_string().as_str()) {
+        return None;
+    }
+    match &entry.address {
+        Some(AddressRef::ExtendedPub(value)) => xpub::decode_any(value.to_string().as_str())
+            .ok()
+            .map(|value| (AccountHDPath::from(&seed.hd_path), value)),
+        _ => None
+    }
+}
+
+pub(crate) fn get_taproot_addresses(account: &AccountHDPath,
+                                    xpub: &bitcoin::bip32::Xpub,
+                                    role: &AddressRole,
+                                    start: u32,
+                                    limit: u32) -> Result<Vec<CurrentAddressJson>, VaultNodeError> {
+    let change = if matches!(role, AddressRole::Change) { 1 } else { 0 };
+    let addresses = taproot::derive_addresses(xpub, change, start, limit)?
+        .into_iter()
+        .map(|(index, address)| CurrentAddressJson {
+            address: address.to_string(),
+            hd_path: format!("{}/{}/{}", account, change, index),
+            role: role.to_string(),
+        })
+        .collect();
+    Ok(addresses)
+}
+
+pub(crate) fn with_std_addresses(entry: &WalletEntry, index: Option<&AccountIndex>) -> Vec<CurrentAddressJson> {
     let index = match index {
         None =>
         //start from beginning
@@ -280,6 +348,15 @@ fn with_std_addresses(entry: &WalletEntry, index: Option<&AccountIndex>) -> Vec<
     match entry.blockchain.get_type() {
         BlockchainType::Bitcoin => {
             // we need two addresses, one for Receiving, and one for Change
+            if let Some((account, xpub)) = taproot_account(entry) {
+                let mut results = get_taproot_addresses(&account, &xpub, &AddressRole::Receive, index.receive, 1)
+                    .unwrap_or_default();
+                results.extend(
+                    get_taproot_addresses(&account, &xpub, &AddressRole::Change, index.change, 1)
+                        .unwrap_or_default()
+                );
+                return results;
+            }
             let mut results = vec![];
             entry.get_addresses::<Address>(AddressRole::Receive, index.receive, 1)
                 .or_else::<Vec<Address>, _>(|_| Ok(vec![]))
@@ -320,7 +397,64 @@ impl WrappedVault {
             .map(|_| id)
     }
 
+    /// Add the entry to the wallet. It fails for a key already used by an entry with a spending policy, because the new
+    /// entry would sign with the same key without the policy. It's checked before the entry is added.
     fn create_entry(&self, wallet_id: Uuid, entry: AddEntryJson) -> Result<usize, VaultNodeError> {
+        let identities = self.new_entry_identities(&entry)?;
+        self.ensure_no_policy_identities(&identities, SAME_KEY_ACTION)?;
+        self.add_entry(wallet_id, entry)
+    }
+
+    /// Identities of the key of a new entry (see `key_identities`) which are known before it's added, i.e. the address of
+    /// a private key and the seed with the HD Path. A key on a non-standard HD Path is checked when it's derived
+    /// (see `create_custom_path_entry`), and a Web3 JSON is identified by the address in the file.
+    fn new_entry_identities(&self, entry: &AddEntryJson) -> Result<Vec<String>, VaultNodeError> {
+        let blockchain = Blockchain::try_from(entry.blockchain)
+            .map_err(|_| VaultNodeError::InvalidArgumentByName("Blockchain".to_string()))?;
+        let mut result = vec![];
+        match &entry.key_value {
+            AddEntryType::EthereumJson(json) => {
+                if let Some(address) = EthereumJsonV3File::try_from(json.clone()).ok().and_then(|json| json.address) {
+                    result.push(address_identity(&address));
+                }
+            }
+            AddEntryType::RawHex(hex) => {
+                let key = hex::decode(trim_hex(hex.as_str())).ok()
+                    .and_then(|pk| EthereumPrivateKey::try_from(pk.as_slice()).ok());
+                if let Some(key) = key {
+                    result.push(address_identity(&key.to_address()));
+                }
+            }
+            AddEntryType::HdPath(hd) => {
+                if let Some(address) = hd.address.as_ref().and_then(|a| EthereumAddress::from_str(a.as_str()).ok()) {
+                    result.push(address_identity(&address));
+                }
+                let path = match blockchain.get_type() {
+                    BlockchainType::Ethereum => StandardHDPath::from_str(hd.hd_path.as_str()).ok().map(|p| p.to_string()),
+                    BlockchainType::Bitcoin => AccountHDPath::from_str(hd.hd_path.as_str()).ok().map(|p| p.to_string()),
+                };
+                let seeds = match &hd.seed.value {
+                    SeedDefinitionOrReferenceType::Reference(seed_id) => vec![*seed_id],
+                    // it's any of the Ledger seeds, depending on the connected device
+                    SeedDefinitionOrReferenceType::Ledger => self.cfg.get_storage().seeds().list_entries()?
+                        .into_iter()
+                        .filter(|s| matches!(s.source, SeedSource::Ledger(_)))
+                        .map(|s| s.id)
+                        .collect(),
+                    _ => vec![],
+                };
+                if let Some(path) = path {
+                    result.extend(seeds.into_iter().map(|seed_id| seed_identity(seed_id, path.clone())));
+                }
+            }
+            // a new random key is never used by another entry
+            AddEntryType::GenerateRandom => {}
+        }
+        Ok(result)
+    }
+
+    /// Add the entry. A key known before it's added is checked for a spending policy by `create_entry`
+    fn add_entry(&self, wallet_id: Uuid, entry: AddEntryJson) -> Result<usize, VaultNodeError> {
         let blockchain = Blockchain::try_from(entry.blockchain)
             .map_err(|_| VaultNodeError::InvalidArgumentByName("Blockchain".to_string()))?;
         let storage = &self.cfg.get_storage();
@@ -363,34 +497,69 @@ impl WrappedVault {
                     SeedDefinitionOrReferenceType::Reference(seed_id) => {
                         //TODO duplicate with ledger
                         match blockchain.get_type() {
-                            BlockchainType::Ethereum =>
-                                storage.add_ethereum_entry(wallet_id).seed_hd(
-                                    seed_id,
-                                    StandardHDPath::from_str(hd.hd_path.as_str())?,
-                                    blockchain,
-                                    hd.seed.password,
-                                    expected_ethereum_address,
-                                )?,
-                            BlockchainType::Bitcoin =>
+                            BlockchainType::Ethereum => match StandardHDPath::from_str(hd.hd_path.as_str()) {
+                                Ok(hd_path) =>
+                                    storage.add_ethereum_entry(wallet_id).seed_hd(
+                                        seed_id,
+                                        hd_path,
+                                        blockchain,
+                                        hd.seed.password,
+                                        expected_ethereum_address,
+                                    )?,
+                                Err(_) =>
+                                    self.create_custom_path_entry(
+                                        wallet_id,
+                                        seed_id,
+                                        CustomHDPath::from_str(hd.hd_path.as_str())?,
+                                        blockchain,
+                                        hd.seed.password,
+                                        entry.password,
+                                        expected_ethereum_address,
+                                    )?,
+                            },
+                            BlockchainType::Bitcoin => {
+                                let hd_path = AccountHDPath::from_str(hd.hd_path.as_str())?;
+                                let bitcoin_opts = if taproot::is_taproot_path(hd.hd_path.as_str()) {
+                                    AddEntryOptions {
+                                        xpub: Some(self.taproot_account_xpub(
+                                            seed_id,
+                                            hd.hd_path.as_str(),
+                                            blockchain,
+                                            hd.seed.password,
+                                            bitcoin_opts.xpub.as_ref(),
+                                        )?),
+                                        ..bitcoin_opts
+                                    }
+                                } else {
+                                    bitcoin_opts
+                                };
                                 storage.add_bitcoin_entry(wallet_id).seed_hd(
                                     seed_id,
-                                    AccountHDPath::from_str(hd.hd_path.as_str())?,
+                                    hd_path,
                                     blockchain,
                                     bitcoin_opts,
                                 )?
+                            }
                         }
                     }
                     SeedDefinitionOrReferenceType::Ledger => {
+                        if taproot::is_taproot_path(hd.hd_path.as_str()) {
+                            return Err(VaultNodeError::OtherInput("Taproot is not supported by Hardware Key".to_string()));
+                        }
                         let seeds = storage.seeds().list_entries()?;
-                        let ledger = seeds.iter().find(|s| match s.source {
-                            SeedSource::Ledger(_) => true,
-                            _ => false,
-                        });
+                        let ledger = match self.connect_ledger_seed(&seeds)? {
+                            Some(seed_id) => Some(seed_id),
+                            // without a device it cannot be matched, so it's the existing one as before
+                            None if !WrappedVault::is_ledger_connected().unwrap_or(false) => seeds.iter()
+                                .find(|s| matches!(s.source, SeedSource::Ledger(_)))
+                                .map(|s| s.id),
+                            None => None,
+                        };
                         let seed_id = match ledger {
-                            Some(seed) => seed.id,
+                            Some(seed_id) => seed_id,
                             None => storage.seeds().add(Seed {
                                 id: Uuid::new_v4(),
-                                source: SeedSource::Ledger(LedgerSource::default()),
+                                source: SeedSource::Ledger(connected_ledger_source()),
                                 label: None,
                                 created_at: Utc::now(),
                             })?,
@@ -413,7 +582,7 @@ impl WrappedVault {
                                 )?
                         }
                     }
-                    SeedDefinitionOrReferenceType::Mnemonic(_) =>
+                    SeedDefinitionOrReferenceType::Mnemonic(_) | SeedDefinitionOrReferenceType::Slip39(_) =>
                         return Err(VaultNodeError::OtherInput("Direct creation from Mnemonic is not implemented. Create Seed first".to_string()))
                 }
             }
@@ -432,6 +601,131 @@ impl WrappedVault {
         Ok(result)
     }
 
+    /// Vault keeps seed based entries only on a standard HD Path. For a non-standard path (ex. legacy MEW `m/44'/60'/0'/x`)
+    /// the key is derived from the seed and stored as an individual key, encrypted with the Global Key.
+    fn create_custom_path_entry(&self,
+                                wallet_id: Uuid,
+                                seed_id: Uuid,
+                                hd_path: CustomHDPath,
+                                blockchain: Blockchain,
+                                seed_password: Option<String>,
+                                password: Option<String>,
+                                expected_address: Option<EthereumAddress>) -> Result<usize, VaultNodeError> {
+        let storage = &self.cfg.get_storage();
+        let seed_password = seed_password.ok_or(VaultError::PasswordRequired)?;
+        let seed = self.get_seed_bytes(seed_id, seed_password.clone())?;
+        let key = derive_ethereum_key(&seed, &hd_path)?;
+        if let Some(expected) = expected_address {
+            if key.to_address() != expected {
+                return Err(VaultNodeError::InvalidArgumentValue("Address doesn't match the HD Path".to_string()));
+            }
+        }
+        self.ensure_no_policy_identities(&[address_identity(&key.to_address())], SAME_KEY_ACTION)?;
+        // for a seed encrypted with the Global Key it's the same password, but for a legacy seed it must be provided separately
+        let password = password.unwrap_or(seed_password);
+        let id = storage.add_ethereum_entry(wallet_id).raw_pk(
+            key.0.to_vec(),
+            password.as_str(),
+            blockchain,
+        )?;
+        Ok(id)
+    }
+
+    /// Vault cannot derive the account key for Taproot, so it's derived here and provided to the Vault as a known value
+    fn taproot_account_xpub(&self,
+                            seed_id: Uuid,
+                            hd_path: &str,
+                            blockchain: Blockchain,
+                            seed_password: Option<String>,
+                            expected: Option<&XPub>) -> Result<XPub, VaultNodeError> {
+        let seed_password = seed_password.ok_or(VaultError::PasswordRequired)?;
+        let seed = self.get_seed_bytes(seed_id, seed_password)?;
+        let value = taproot::derive_from_seed(&seed, hd_path, blockchain.as_bitcoin_network_kind())?;
+        if let Some(expected) = expected {
+            if xpub::decode_any(expected.to_string().as_str())? != xpub::decode_any(value.as_str())? {
+                return Err(VaultNodeError::InvalidArgumentValue("Address doesn't match the HD Path".to_string()));
+            }
+        }
+        XPub::from_str(value.as_str())
+            .map_err(|_| VaultNodeError::OtherProcessing("Invalid Extended Public Key".to_string()))
+    }
+
+    /// Multisig where one of the cosigners is a seed of this vault. Only the public key of the seed account is kept with the setup
+    fn create_multisig(&self, wallet_id: Uuid, options: AddMultisigJson) -> Result<MultisigJson, VaultNodeError> {
+        let storage = &self.cfg.get_storage();
+        // make sure the wallet exists
+        storage.wallets().get(wallet_id)?;
+        let blockchain = Blockchain::try_from(options.blockchain)
+            .map_err(|_| VaultNodeError::InvalidArgumentByName("Blockchain".to_string()))?;
+        if blockchain.get_type() != BlockchainType::Bitcoin {
+            return Err(VaultNodeError::OtherInput("Multisig is supported only for Bitcoin".to_string()));
+        }
+        let network = blockchain.as_bitcoin_network_kind();
+        let hd_path = match options.seed.hd_path {
+            Some(value) => value,
+            None => {
+                let coin = if network == bitcoin::NetworkKind::Main { 0 } else { 1 };
+                format!("m/48'/{}'/0'/{}'", coin, options.script.bip48_script_type())
+            }
+        };
+        let hd_path = bitcoin::bip32::DerivationPath::from_str(hd_path.as_str())
+            .map_err(|_| VaultNodeError::InvalidArgumentValue(format!("Invalid HDPath: {}", hd_path)))?;
+        let seed = self.get_seed_bytes(options.seed.seed_id, options.seed.password)?;
+        let account = xpub::export_from_seed(&seed, &hd_path, network, Some(XpubFormat::X))?;
+
+        let mut cosigners = vec![CosignerJson {
+            xpub: account.xpub,
+            fingerprint: account.fingerprint,
+            path: account.account_path,
+            seed_id: Some(options.seed.seed_id),
+        }];
+        cosigners.extend(options.cosigners.into_iter().map(|c| CosignerJson { seed_id: None, ..c }));
+        let setup = MultisigSetupJson {
+            id: Uuid::new_v4(),
+            wallet_id,
+            blockchain: options.blockchain,
+            label: options.label,
+            script: options.script,
+            threshold: options.threshold,
+            cosigners,
+        };
+        let multisig = Multisig::parse(setup, network)?;
+        self.cfg.get_multisig_storage().save(&multisig.setup)?;
+        Ok(multisig.to_json())
+    }
+
+    pub(crate) fn get_multisig(&self, id: Uuid) -> Result<Multisig, VaultNodeError> {
+        let setup = self.cfg.get_multisig_storage().get(id)?;
+        let blockchain = Blockchain::try_from(setup.blockchain)
+            .map_err(|_| VaultNodeError::OtherProcessing("Invalid multisig blockchain".to_string()))?;
+        Multisig::parse(setup, blockchain.as_bitcoin_network_kind())
+    }
+
+    fn list_multisig(&self, wallet_id: Uuid) -> Result<Vec<MultisigJson>, VaultNodeError> {
+        let result = self.cfg.get_multisig_storage().list_for_wallet(wallet_id)?
+            .iter()
+            .filter_map(|setup| self.get_multisig(setup.id).ok())
+            .map(|multisig| multisig.to_json())
+            .collect();
+        Ok(result)
+    }
+
+    fn list_multisig_addresses(&self, id: Uuid, role: String, start: u32, limit: u32) -> Result<Vec<CurrentAddressJson>, VaultNodeError> {
+        let multisig = self.get_multisig(id)?;
+        let role = AddressRole::from_str(role.as_str()).map_err(VaultError::from)?;
+        let change = if matches!(role, AddressRole::Change) { 1 } else { 0 };
+        let account = format!("m/{}", xpub::path_string(multisig.local_path(), "'"));
+        let addresses = multisig.addresses(change, start, limit)?
+            .into_iter()
+            .map(|(index, address)| CurrentAddressJson {
+                address: address.to_string(),
+                hd_path: format!("{}/{}/{}", account, change, index),
+                role: role.to_string(),
+            })
+            .collect();
+        Ok(addresses)
+    }
+
     fn set_title(&self, wallet_id: Uuid, title: Option<String>) -> Result<(), VaultError> {
         let storage = &self.cfg.get_storage();
         let mut wallet = storage.wallets().get(wallet_id)?;
@@ -440,20 +734,50 @@ impl WrappedVault {
         Ok(())
     }
 
-    fn remove_entry(&self, wallet_id: Uuid, entry_id: usize) -> Result<bool, VaultError> {
+    /// Removes the entry, unless it has a spending policy, which must be removed first with the global password
+    fn remove_entry(&self, wallet_id: Uuid, entry_id: usize) -> Result<bool, VaultNodeError> {
         let storage = &self.cfg.get_storage();
         let mut wallet = storage.wallets().get(wallet_id)?;
         let index = wallet.entries.iter().position(|a| a.id == entry_id);
         if index.is_none() {
             return Ok(false);
         }
+        if self.has_policy(wallet_id, entry_id) {
+            return Err(VaultNodeError::OtherInput("Entry has a spending policy".to_string()));
+        }
         wallet.entries.remove(index.unwrap());
-        storage.wallets().update(wallet)
+        let removed = storage.wallets().update(wallet)?;
+        Ok(removed)
     }
 
-    fn remove(&self, wallet_id: Uuid) -> Result<bool, VaultError> {
+    /// Removes the multisig of the wallet, unless any of the wallet entries has a spending policy, as for the wallet itself
+    fn remove_multisig(&self, wallet_id: Uuid, id: Uuid) -> Result<bool, VaultNodeError> {
+        let wallet = self.cfg.get_storage().wallets().get(wallet_id)?;
+        let multisig = self.cfg.get_multisig_storage();
+        if !multisig.get(id).is_ok_and(|setup| setup.wallet_id == wallet_id) {
+            return Ok(false);
+        }
+        if wallet.entries.iter().any(|entry| self.has_policy(wallet_id, entry.id)) {
+            return Err(VaultNodeError::OtherInput("Wallet has an entry with a spending policy".to_string()));
+        }
+        multisig.remove(id)
+    }
+
+    /// Removes the wallet, unless any of its entries has a spending policy
+    fn remove(&self, wallet_id: Uuid) -> Result<bool, VaultNodeError> {
         let storage = &self.cfg.get_storage();
-        storage.remove_wallet(wallet_id)
+        if let Ok(wallet) = storage.wallets().get(wallet_id)
+            && wallet.entries.iter().any(|entry| self.has_policy(wallet_id, entry.id)) {
+            return Err(VaultNodeError::OtherInput("Wallet has an entry with a spending policy".to_string()));
+        }
+        let removed = storage.remove_wallet(wallet_id)?;
+        if removed {
+            let multisig = self.cfg.get_multisig_storage();
+            for setup in multisig.list_for_wallet(wallet_id)? {
+                multisig.remove(setup.id)?;
+            }
+        }
+        Ok(removed)
     }
 }
 
@@ -466,12 +790,15 @@ pub fn list<H>(_cx: &mut FunctionContext, handler: H) -> Result<(), VaultNodeErr
     std::thread::spawn(move || {
         let vault = vault.lock().unwrap();
         let cfg = &vault.cfg;
-        let wallets = vault.load_wallets().map(|wallets| {
+        let wallets = vault.load_wallets().and_then(|wallets| {
             let mut result = Vec::new();
             for w in wallets {
-                result.push(WalletJson::from((w, &cfg.account_indexes)));
+                let wallet_id = w.id;
+                let mut wallet = WalletJson::from((w, &cfg.account_indexes));
+                wallet.multisig = vault.list_multisig(wallet_id)?;
+                result.push(wallet);
             }
-            result
+            Ok(result)
         });
 
         handler(wallets);
@@ -553,8 +880,7 @@ pub fn remove_entry<H>(cx: &mut FunctionContext, handler: H) -> Result<(), Vault
 
     std::thread::spawn(move || {
         let vault = vault.lock().unwrap();
-        let result = vault.remove_entry(wallet_id, entry_id)
-            .map_err(VaultNodeError::from);
+        let result = vault.remove_entry(wallet_id, entry_id);
         handler(result);
     });
 
@@ -570,8 +896,88 @@ pub fn remove<H>(cx: &mut FunctionContext, handler: H) -> Result<(), VaultNodeEr
 
     std::thread::spawn(move || {
         let vault = vault.lock().unwrap();
-        let result = vault.remove(wallet_id)
-            .map_err(VaultNodeError::from);
+        let result = vault.remove(wallet_id);
+        handler(result);
+    });
+
+    Ok(())
+}
+
+#[neon_frame_fn(channel=2)]
+pub fn add_multisig<H>(cx: &mut FunctionContext, handler: H) -> Result<(), VaultNodeError>
+    where
+        H: FnOnce(Result<MultisigJson, VaultNodeError>) + Send + 'static {
+    let vault = Instance::get_vault()?;
+    let wallet_id = args_get_uuid(cx, 0)?;
+    let json = cx
+        .argument::<JsString>(1)
+        .map_err(|_| VaultNodeError::ArgumentMissing(1, "multisig".to_string()))?
+        .value(cx);
+    let parsed: AddMultisigJson = serde_json::from_str(json.as_str())
+        .map_err(|_| JsonError::InvalidData)?;
+
+    std::thread::spawn(move || {
+        let vault = vault.lock().unwrap();
+        let result = vault.create_multisig(wallet_id, parsed);
+        handler(result);
+    });
+
+    Ok(())
+}
+
+#[neon_frame_fn(channel=1)]
+pub fn list_multisig<H>(cx: &mut FunctionContext, handler: H) -> Result<(), VaultNodeError>
+    where
+        H: FnOnce(Result<Vec<MultisigJson>, VaultNodeError>) + Send + 'static {
+    let vault = Instance::get_vault()?;
+    let wallet_id = args_get_uuid(cx, 0)?;
+
+    std::thread::spawn(move || {
+        let vault = vault.lock().unwrap();
+        let result = vault.list_multisig(wallet_id);
+        handler(result);
+    });
+
+    Ok(())
+}
+
+#[neon_frame_fn(channel=2)]
+pub fn remove_multisig<H>(cx: &mut FunctionContext, handler: H) -> Result<(), VaultNodeError>
+    where
+        H: FnOnce(Result<bool, VaultNodeError>) + Send + 'static {
+    let vault = Instance::get_vault()?;
+    let wallet_id = args_get_uuid(cx, 0)?;
+    let id = args_get_uuid(cx, 1)?;
+
+    std::thread::spawn(move || {
+        let vault = vault.lock().unwrap();
+        let result = vault.remove_multisig(wallet_id, id);
+        handler(result);
+    });
+
+    Ok(())
+}
+
+#[neon_frame_fn(channel=4)]
+pub fn list_multisig_addresses<H>(cx: &mut FunctionContext, handler: H) -> Result<(), VaultNodeError>
+    where
+        H: FnOnce(Result<Vec<CurrentAddressJson>, VaultNodeError>) + Send + 'static {
+    let vault = Instance::get_vault()?;
+    let id = args_get_uuid(cx, 0)?;
+    let role = args_get_str(cx, 1)
+        .ok_or(VaultNodeError::ArgumentMissing(1, "address_role".to_string()))?;
+    let start = cx
+        .argument::<JsNumber>(2)
+        .map_err(|_| VaultNodeError::ArgumentMissing(2, "start".to_string()))?
+        .value(cx) as u32;
+    let limit = cx
+        .argument::<JsNumber>(3)
+        .map_err(|_| VaultNodeError::ArgumentMissing(3, "limit".to_string()))?
+        .value(cx) as u32;
+
+    std::thread::spawn(move || {
+        let vault = vault.lock().unwrap();
+        let result = vault.list_multisig_addresses(id, role, start, limit);
         handler(result);
     });
 
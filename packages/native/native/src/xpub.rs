@@ -0,0 +1,338 @@
+//! Export of an account Extended Public Key in the formats used by other wallets, i.e. with SLIP-132 version bytes
+//! (`xpub`, `ypub`, `zpub` and testnet `tpub`, `upub`, `vpub`), with the key origin and output descriptors.
+
+use bitcoin::base58;
+use bitcoin::bip32::{ChildNumber, DerivationPath, Fingerprint, Xpriv, Xpub};
+use bitcoin::secp256k1::Secp256k1;
+use bitcoin::{Network, NetworkKind};
+use crate::errors::VaultNodeError;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum XpubFormat {
+    /// BIP-44, P2PKH; and BIP-86, P2TR, which doesn't have its own version
+    X,
+    /// BIP-49, P2SH-P2WPKH
+    Y,
+    /// BIP-84, P2WPKH
+    Z,
+}
+
+#[derive(Serialize, Clone, Debug, Eq, PartialEq)]
+pub struct AccountXpubJson {
+    pub xpub: String,
+    pub format: String,
+    #[serde(rename = "accountPath")]
+    pub account_path: String,
+    /// Master Key fingerprint, as 8 hex chars
+    pub fingerprint: String,
+    /// Key origin as `[fingerprint/path]`
+    #[serde(rename = "keyOrigin")]
+    pub key_origin: String,
+    /// Output descriptor for the receive addresses
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub descriptor: Option<String>,
+    /// Output descriptor for the change addresses
+    #[serde(rename = "changeDescriptor")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub change_descriptor: Option<String>,
+}
+
+impl XpubFormat {
+    /// Parse the format name of the network, i.e. `tpub`, `upub` or `vpub` for a testnet. Multisig formats (`Ypub`, `Zpub`)
+    /// are never produced, so they are not accepted.
+    pub fn parse(value: &str, network: NetworkKind) -> Result<XpubFormat, VaultNodeError> {
+        [XpubFormat::X, XpubFormat::Y, XpubFormat::Z].into_iter()
+            .find(|format| format.name(network) == value)
+            .ok_or_else(|| VaultNodeError::InvalidArgumentValue(format!("Unsupported xpub format: {}", value)))
+    }
+
+    /// The format used by other wallets for the BIP-43 purpose of the account
+    pub fn for_purpose(purpose: u32) -> Option<XpubFormat> {
+        match purpose {
+            44 => Some(XpubFormat::X),
+            49 => Some(XpubFormat::Y),
+            84 => Some(XpubFormat::Z),
+            86 => Some(XpubFormat::X),
+            _ => None
+        }
+    }
+
+    fn version(&self, network: NetworkKind) -> [u8; 4] {
+        match (self, network) {
+            (XpubFormat::X, NetworkKind::Main) => [0x04, 0x88, 0xb2, 0x1e],
+            (XpubFormat::Y, NetworkKind::Main) => [0x04, 0x9d, 0x7c, 0xb2],
+            (XpubFormat::Z, NetworkKind::Main) => [0x04, 0xb2, 0x47, 0x46],
+            (XpubFormat::X, NetworkKind::Test) => [0x04, 0x35, 0x87, 0xcf],
+            (XpubFormat::Y, NetworkKind::Test) => [0x04, 0x4a, 0x52, 0x62],
+            (XpubFormat::Z, NetworkKind::Test) => [0x04, 0x5f, 0x1c, 0xf6],
+        }
+    }
+
+    fn name(&self, network: NetworkKind) -> &'static str {
+        match (self, network) {
+            (XpubFormat::X, NetworkKind::Main) => "xpub",
+            (XpubFormat::Y, NetworkKind::Main) => "ypub",
+            (XpubFormat::Z, NetworkKind::Main) => "zpub",
+            (XpubFormat::X, NetworkKind::Test) => "tpub",
+            (XpubFormat::Y, NetworkKind::Test) => "upub",
+            (XpubFormat::Z, NetworkKind::Test) => "vpub",
+        }
+    }
+
+    /// Encode the key with the version bytes of the format
+    pub fn encode(&self, xpub: &Xpub) -> String {
+        let mut data = xpub.encode();
+        data[0..4].copy_from_slice(&self.version(xpub.network));
+        base58::encode_check(&data)
+    }
+}
+
+/// SLIP-132 versions of multisig keys (`Ypub`, `Zpub` and testnet `Upub`, `Vpub`). They are accepted on import, but never produced.
+const MULTISIG_VERSIONS: [([u8; 4], NetworkKind); 4] = [
+    ([0x02, 0x95, 0xb4, 0x3f], NetworkKind::Main),
+    ([0x02, 0xaa, 0x7e, 0xd3], NetworkKind::Main),
+    ([0x02, 0x42, 0x89, 0xef], NetworkKind::Test),
+    ([0x02, 0x57, 0x54, 0x83], NetworkKind::Test),
+];
+
+/// Parse an Extended Public Key in any of the SLIP-132 formats
+pub fn decode_any(value: &str) -> Result<Xpub, VaultNodeError> {
+    let invalid = || VaultNodeError::InvalidArgumentValue("Invalid Extended Public Key".to_string());
+    let mut data = base58::decode_check(value).map_err(|_| invalid())?;
+    if data.len() != 78 {
+        return Err(invalid());
+    }
+    let network = [XpubFormat::X, XpubFormat::Y, XpubFormat::Z].iter()
+        .find_map(|f| {
+            if data[0..4] == f.version(NetworkKind::Main) {
+                Some(NetworkKind::Main)
+            } else if data[0..4] == f.version(NetworkKind::Test) {
+                Some(NetworkKind::Test)
+            } else {
+                None
+            }
+        })
+        .or_else(|| MULTISIG_VERSIONS.iter()
+            .find(|(version, _)| data[0..4] == *version)
+            .map(|(_, network)| *network))
+        .ok_or_else(invalid)?;
+    data[0..4].copy_from_slice(&XpubFormat::X.version(network));
+    Xpub::decode(&data).map_err(|_| invalid())
+}
+
+/// Descriptor checksum as defined by BIP-380
+fn descriptor_checksum(descriptor: &str) -> Option<String> {
+    const INPUT_CHARSET: &str = "0123456789()[],'/*abcdefgh@:$%{}IJKLMNOPQRSTUVWXYZ&+-.;<=>?!^_|~ijklmnopqrstuvwxyzABCDEFGH`#\"\\ ";
+    const CHECKSUM_CHARSET: &[u8] = b"qpzry9x8gf2tvdw0s3jn54khce6mua7l";
+    const GENERATOR: [u64; 5] = [0xf5dee51989, 0xa9fdca3312, 0x1bab10e32d, 0x3706b1677a, 0x644d626ffd];
+
+    fn polymod(chk: u64, value: u64) -> u64 {
+        let top = chk >> 35;
+        let mut chk = ((chk & 0x7ffffffff) << 5) ^ value;
+        for (i, g) in GENERATOR.iter().enumerate() {
+            if (top >> i) & 1 == 1 {
+                chk ^= g;
+            }
+        }
+        chk
+    }
+
+    let mut chk = 1u64;
+    let mut groups: Vec<u64> = Vec::with_capacity(3);
+    for c in descriptor.chars() {
+        let pos = INPUT_CHARSET.find(c)? as u64;
+        chk = polymod(chk, pos & 31);
+        groups.push(pos >> 5);
+        if groups.len() == 3 {
+            chk = polymod(chk, groups[0] * 9 + groups[1] * 3 + groups[2]);
+            groups.clear();
+        }
+    }
+    match groups.len() {
+        1 => chk = polymod(chk, groups[0]),
+        2 => chk = polymod(chk, groups[0] * 3 + groups[1]),
+        _ => {}
+    }
+    for _ in 0..8 {
+        chk = polymod(chk, 0);
+    }
+    chk ^= 1;
+    let checksum = (0..8)
+        .map(|i| CHECKSUM_CHARSET[((chk >> (5 * (7 - i))) & 31) as usize] as char)
+        .collect();
+    Some(checksum)
+}
+
+pub(crate) fn with_checksum(descriptor: String) -> String {
+    match descriptor_checksum(descriptor.as_str()) {
+        Some(checksum) => format!("{}#{}", descriptor, checksum),
+        None => descriptor
+    }
+}
+
+fn purpose_of(account: &DerivationPath) -> Option<u32> {
+    match account.into_iter().next() {
+        Some(ChildNumber::Hardened { index }) => Some(*index),
+        _ => None
+    }
+}
+
+/// Path without `m/` and with the specified marker for hardened indexes (descriptors use `h`)
+pub(crate) fn path_string(account: &DerivationPath, hardened: &str) -> String {
+    account.into_iter()
+        .map(|c| match c {
+            ChildNumber::Hardened { index } => format!("{}{}", index, hardened),
+            ChildNumber::Normal { index } => index.to_string(),
+        })
+        .collect::<Vec<String>>()
+        .join("/")
+}
+
+fn descriptor(purpose: u32, key: String, chain: u32) -> Option<String> {
+    let key = format!("{}/{}/*", key, chain);
+    let descriptor = match purpose {
+        44 => format!("pkh({})", key),
+        49 => format!("sh(wpkh({}))", key),
+        84 => format!("wpkh({})", key),
+        86 => format!("tr({})", key),
+        _ => return None
+    };
+    Some(with_checksum(descriptor))
+}
+
+/// Prepare the export of the account key, with `master` as the Master Key fingerprint.
+/// If `format` is not set it's chosen by the purpose of the account.
+fn export(xpub: &Xpub, account: &DerivationPath, master: Fingerprint, format: Option<XpubFormat>) -> Result<AccountXpubJson, VaultNodeError> {
+    let purpose = purpose_of(account).unwrap_or_default();
+    let format = format
+        .or_else(|| XpubFormat::for_purpose(purpose))
+        .ok_or_else(|| VaultNodeError::InvalidArgumentValue("Format must be specified for the account".to_string()))?;
+    // descriptors define the script type themselves, so they always use the standard xpub/tpub encoding
+    let key = format!("[{}/{}]{}", master, path_string(account, "h"), xpub);
+    Ok(AccountXpubJson {
+        xpub: format.encode(xpub),
+        format: format.name(xpub.network).to_string(),
+        account_path: format!("m/{}", path_string(account, "'")),
+        fingerprint: master.to_string(),
+        key_origin: format!("[{}/{}]", master, path_string(account, "'")),
+        descriptor: descriptor(purpose, key.clone(), 0),
+        change_descriptor: descriptor(purpose, key, 1),
+    })
+}
+
+/// Derive the account key from the seed and prepare its export
+pub fn export_from_seed(seed: &[u8], account: &DerivationPath, network: NetworkKind, format: Option<XpubFormat>) -> Result<AccountXpubJson, VaultNodeError> {
+    let secp = Secp256k1::new();
+    let network = match network {
+        NetworkKind::Main => Network::Bitcoin,
+        NetworkKind::Test => Network::Testnet,
+    };
+    let master = Xpriv::new_master(network, seed)
+        .map_err(|_| VaultNodeError::OtherProcessing("Invalid seed".to_string()))?;
+    let key = master.derive_priv(&secp, account)
+        .map_err(|_| VaultNodeError::OtherProcessing("Failed to derive key".to_string()))?;
+    let xpub = Xpub::from_priv(&secp, &key);
+    export(&xpub, account, master.fingerprint(&secp), format)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+    use bitcoin::bip32::DerivationPath;
+    use bitcoin::NetworkKind;
+    use crate::xpub::{decode_any, descriptor_checksum, export_from_seed, XpubFormat};
+
+    fn seed() -> Vec<u8> {
+        bip39::Mnemonic::parse_normalized(
+            "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about"
+        ).unwrap().to_seed("").to_vec()
+    }
+
+    #[test]
+    fn checksum_for_descriptor() {
+        assert_eq!(descriptor_checksum("raw(deadbeef)"), Some("89f8spxm".to_string()));
+    }
+
+    #[test]
+    fn export_bip84() {
+        let act = export_from_seed(&seed(), &DerivationPath::from_str("m/84'/0'/0'").unwrap(), NetworkKind::Main, None).unwrap();
+        assert_eq!(act.xpub, "zpub6rFR7y4Q2AijBEqTUquhVz398htDFrtymD9xYYfG1m4wAcvPhXNfE3EfH1r1ADqtfSdVCToUG868RvUUkgDKf31mGDtKsAYz2oz2AGutZYs");
+        assert_eq!(act.format, "zpub");
+        assert_eq!(act.account_path, "m/84'/0'/0'");
+        assert_eq!(act.fingerprint, "73c5da0a");
+        assert_eq!(act.key_origin, "[73c5da0a/84'/0'/0']");
+        assert!(act.descriptor.unwrap().starts_with("wpkh([73c5da0a/84h/0h/0h]xpub"));
+        assert!(act.change_descriptor.unwrap().contains("/1/*)#"));
+    }
+
+    #[test]
+    fn export_bip49() {
+        let act = export_from_seed(&seed(), &DerivationPath::from_str("m/49'/0'/0'").unwrap(), NetworkKind::Main, None).unwrap();
+        assert_eq!(act.xpub, "ypub6Ww3ibxVfGzLrAH1PNcjyAWenMTbbAosGNB6VvmSEgytSER9azLDWCxoJwW7Ke7icmizBMXrzBx9979FfaHxHcrArf3zbeJJJUZPf663zsP");
+        assert_eq!(act.format, "ypub");
+    }
+
+    #[test]
+    fn export_bip44() {
+        let act = export_from_seed(&seed(), &DerivationPath::from_str("m/44'/0'/0'").unwrap(), NetworkKind::Main, None).unwrap();
+        assert_eq!(act.xpub, "xpub6BosfCnifzxcFwrSzQiqu2DBVTshkCXacvNsWGYJVVhhawA7d4R5WSWGFNbi8Aw6ZRc1brxMyWMzG3DSSSSoekkudhUd9yLb6qx39T9nMdj");
+        assert_eq!(act.format, "xpub");
+    }
+
+    #[test]
+    fn export_bip86() {
+        let act = export_from_seed(&seed(), &DerivationPath::from_str("m/86'/0'/0'").unwrap(), NetworkKind::Main, None).unwrap();
+        assert_eq!(act.xpub, "xpub6BgBgsespWvERF3LHQu6CnqdvfEvtMcQjYrcRzx53QJjSxarj2afYWcLteoGVky7D3UKDP9QyrLprQ3VCECoY49yfdDEHGCtMMj92pReUsQ");
+        assert!(act.descriptor.unwrap().starts_with("tr([73c5da0a/86h/0h/0h]xpub6BgBgsespWvERF3LHQu6CnqdvfEvtMcQjYrcRzx53QJjSxarj2afYWcLteoGVky7D3UKDP9QyrLprQ3VCECoY49yfdDEHGCtMMj92pReUsQ/0/*)#"));
+    }
+
+    #[test]
+    fn export_with_other_format() {
+        let act = export_from_seed(&seed(), &DerivationPath::from_str("m/84'/0'/0'").unwrap(), NetworkKind::Main, Some(XpubFormat::X)).unwrap();
+        assert!(act.xpub.starts_with("xpub"));
+        assert_eq!(act.format, "xpub");
+        // the same key in any format
+        assert_eq!(
+            decode_any(act.xpub.as_str()).unwrap(),
+            decode_any("zpub6rFR7y4Q2AijBEqTUquhVz398htDFrtymD9xYYfG1m4wAcvPhXNfE3EfH1r1ADqtfSdVCToUG868RvUUkgDKf31mGDtKsAYz2oz2AGutZYs").unwrap()
+        );
+    }
+
+    #[test]
+    fn export_testnet() {
+        let act = export_from_seed(&seed(), &DerivationPath::from_str("m/84'/1'/0'").unwrap(), NetworkKind::Test, None).unwrap();
+        assert!(act.xpub.starts_with("vpub"));
+        assert_eq!(act.format, "vpub");
+        assert!(act.descriptor.unwrap().starts_with("wpkh([73c5da0a/84h/1h/0h]tpub"));
+    }
+
+    #[test]
+    fn requires_format_for_unknown_purpose() {
+        let path = DerivationPath::from_str("m/45'/0'/0'").unwrap();
+        assert!(export_from_seed(&seed(), &path, NetworkKind::Main, None).is_err());
+        let act = export_from_seed(&seed(), &path, NetworkKind::Main, Some(XpubFormat::X)).unwrap();
+        assert_eq!(act.descriptor, None);
+    }
+
+    #[test]
+    fn decode_multisig_format() {
+        let xpub = decode_any("xpub6BosfCnifzxcFwrSzQiqu2DBVTshkCXacvNsWGYJVVhhawA7d4R5WSWGFNbi8Aw6ZRc1brxMyWMzG3DSSSSoekkudhUd9yLb6qx39T9nMdj").unwrap();
+        let mut data = xpub.encode();
+        data[0..4].copy_from_slice(&[0x02, 0xaa, 0x7e, 0xd3]);
+        let zpub = bitcoin::base58::encode_check(&data);
+        assert!(zpub.starts_with("Zpub"));
+        assert_eq!(decode_any(zpub.as_str()).unwrap(), xpub);
+    }
+
+    #[test]
+    fn parse_format() {
+        assert_eq!(XpubFormat::parse("zpub", NetworkKind::Main).unwrap(), XpubFormat::Z);
+        assert_eq!(XpubFormat::parse("vpub", NetworkKind::Test).unwrap(), XpubFormat::Z);
+        assert!(XpubFormat::parse("ZPUB", NetworkKind::Main).is_err());
+        assert!(XpubFormat::parse("Ypub", NetworkKind::Main).is_err());
+        assert!(XpubFormat::parse("Zpub", NetworkKind::Main).is_err());
+        assert!(XpubFormat::parse("tpub", NetworkKind::Main).is_err());
+        assert!(XpubFormat::parse("zpub", NetworkKind::Test).is_err());
+        assert!(XpubFormat::parse("xprv", NetworkKind::Main).is_err());
+    }
+}
@@ -9,6 +9,20 @@ import {
     SeedDetails,
     UnsignedTx,
     SignedTx,
+    SignPsbtOptions,
+    SignedPsbt,
+    AddMultisig,
+    Multisig,
+    UnsignedBitcoinTx,
+    BumpFee,
+    BatchSignedTx,
+    TxPreview,
+    Authorization,
+    UnsignedAuthorization,
+    SpendingPolicy,
+    BuiltBitcoinTx,
+    BitcoinTxSize,
+    DecodedTx,
     Uuid,
     Wallet,
     WalletsOp,
@@ -30,7 +44,16 @@ import {
     IdSeedReference,
     isIdSeedReference,
     SignedMessage,
-    UnsignedMessage, IconDetails, WatchRequest, WatchEvent
+    UnsignedMessage, IconDetails, WatchRequest, WatchEvent,
+    SeedRemoveResult,
+    SeedAddress,
+    XpubFormat,
+    AccountXpub,
+    Bip85Options,
+    Bip85Child,
+    MnemonicEntropyOptions,
+    MnemonicFromEntropy,
+    Slip39SplitOptions
 } from "@emeraldpay/emerald-vault-core";
 import {neonFrameHandlerCall, neonFrameDirectCall} from "@emeraldpay/neon-frame";
 import {atob} from "buffer";
@@ -157,11 +180,70 @@ export class EmeraldVaultNative implements IEmeraldVault {
         return neonFrameHandlerCall(this.addon, "entries_updateReceiveDisabled", [op.extractWalletId(), op.extractEntryInternalId(), disabled])
     }
 
+    addMultisig(walletId: Uuid, multisig: AddMultisig): Promise<Multisig> {
+        return neonFrameHandlerCall(this.addon, "wallets_addMultisig", [walletId, JSON.stringify(multisig)])
+    }
+
+    listMultisig(walletId: Uuid): Promise<Multisig[]> {
+        return neonFrameHandlerCall(this.addon, "wallets_listMultisig", [walletId])
+    }
+
+    removeMultisig(walletId: Uuid, id: Uuid): Promise<boolean> {
+        return neonFrameHandlerCall(this.addon, "wallets_removeMultisig", [walletId, id])
+    }
+
+    listMultisigAddresses(id: Uuid, role: AddressRole, start: number, limit: number): Promise<CurrentAddress[]> {
+        return neonFrameHandlerCall(this.addon, "wallets_listMultisigAddresses", [id, role, start, limit])
+    }
+
     signTx(entryId: EntryId, tx: UnsignedTx, password?: string): Promise<SignedTx> {
         let op = EntryIdOp.of(entryId);
         return neonFrameHandlerCall(this.addon, "sign_tx", [op.extractWalletId(), op.extractEntryInternalId(), JSON.stringify(tx), password]);
     }
 
+    signTxBatch(entryId: EntryId, txs: UnsignedTx[], password?: string): Promise<BatchSignedTx[]> {
+        let op = EntryIdOp.of(entryId);
+        return neonFrameHandlerCall(this.addon, "sign_txBatch", [op.extractWalletId(), op.extractEntryInternalId(), JSON.stringify(txs), password]);
+    }
+
+    signAuthorization(entryId: EntryId, authorization: UnsignedAuthorization, password: string): Promise<Authorization> {
+        let op = EntryIdOp.of(entryId);
+        return neonFrameHandlerCall(this.addon, "sign_authorization", [op.extractWalletId(), op.extractEntryInternalId(), JSON.stringify(authorization), password]);
+    }
+
+    previewTx(entryId: EntryId, tx: UnsignedTx): Promise<TxPreview> {
+        let op = EntryIdOp.of(entryId);
+        return neonFrameHandlerCall(this.addon, "sign_preview", [op.extractWalletId(), op.extractEntryInternalId(), JSON.stringify(tx)]);
+    }
+
+    bumpFee(entryId: EntryId, options: BumpFee, password?: string): Promise<SignedTx> {
+        let op = EntryIdOp.of(entryId);
+        return neonFrameHandlerCall(this.addon, "sign_bumpFee", [op.extractWalletId(), op.extractEntryInternalId(), JSON.stringify(options), password]);
+    }
+
+    buildBitcoinTx(entryId: EntryId, utxos: UnsignedBitcoinTx["inputs"], outputs: UnsignedBitcoinTx["outputs"], feeRate: number): Promise<BuiltBitcoinTx> {
+        let op = EntryIdOp.of(entryId);
+        return neonFrameHandlerCall(this.addon, "bitcoin_buildTx", [op.extractWalletId(), op.extractEntryInternalId(), JSON.stringify(utxos), JSON.stringify(outputs), feeRate]);
+    }
+
+    estimateBitcoinTx(entryId: EntryId, tx: UnsignedBitcoinTx): Promise<BitcoinTxSize> {
+        let op = EntryIdOp.of(entryId);
+        return neonFrameHandlerCall(this.addon, "bitcoin_estimateTx", [op.extractWalletId(), op.extractEntryInternalId(), JSON.stringify(tx)]);
+    }
+
+    signPsbt(entryId: EntryId, psbt: string, password?: string, options?: SignPsbtOptions): Promise<SignedPsbt> {
+        let op = EntryIdOp.of(entryId);
+        return neonFrameHandlerCall(this.addon, "sign_psbt", [op.extractWalletId(), op.extractEntryInternalId(), psbt, password, JSON.stringify(options || {})]);
+    }
+
+    signMultisigTx(id: Uuid, tx: UnsignedBitcoinTx, password?: string): Promise<SignedPsbt> {
+        return neonFrameHandlerCall(this.addon, "sign_multisigTx", [id, JSON.stringify(tx), password]);
+    }
+
+    signMultisigPsbt(id: Uuid, psbt: string, password?: string, options?: SignPsbtOptions): Promise<SignedPsbt> {
+        return neonFrameHandlerCall(this.addon, "sign_multisigPsbt", [id, psbt, password, JSON.stringify(options || {})]);
+    }
+
     signMessage(entryId: string, msg: UnsignedMessage, password?: string): Promise<SignedMessage> {
         let op = EntryIdOp.of(entryId);
         return neonFrameHandlerCall(this.addon, "sign_message", [op.extractWalletId(), op.extractEntryInternalId(), JSON.stringify(msg), password]);
@@ -171,6 +253,21 @@ export class EmeraldVaultNative implements IEmeraldVault {
         return neonFrameHandlerCall(this.addon, "sign_signature_author", [JSON.stringify(msg), signature]);
     }
 
+    decodeTx(blockchain: number, raw: string): Promise<DecodedTx> {
+        return neonFrameHandlerCall(this.addon, "tx_decode", [blockchain, raw]);
+    }
+
+    getPolicy(entryId: EntryId): Promise<SpendingPolicy | undefined> {
+        let op = EntryIdOp.of(entryId);
+        return neonFrameHandlerCall(this.addon, "entries_getPolicy", [op.extractWalletId(), op.extractEntryInternalId()])
+            .then((policy: SpendingPolicy | null) => policy || undefined);
+    }
+
+    setPolicy(entryId: EntryId, policy: SpendingPolicy | null, globalPassword: string): Promise<boolean> {
+        let op = EntryIdOp.of(entryId);
+        return neonFrameHandlerCall(this.addon, "entries_setPolicy", [op.extractWalletId(), op.extractEntryInternalId(), policy ? JSON.stringify(policy) : null, globalPassword]);
+    }
+
     exportRawPk(entryId: EntryId, password: string): Promise<string> {
         let op = EntryIdOp.of(entryId);
         return neonFrameHandlerCall(this.addon, "entries_exportPk", [op.extractWalletId(), op.extractEntryInternalId(), password]);
@@ -237,6 +334,10 @@ export class EmeraldVaultNative implements IEmeraldVault {
         return neonFrameHandlerCall(this.addon, "seed_generateMnemonic", [size]);
     }
 
+    generateMnemonicFromEntropy(options: MnemonicEntropyOptions): Promise<MnemonicFromEntropy> {
+        return neonFrameHandlerCall(this.addon, "seed_generateMnemonicFromEntropy", [JSON.stringify(options)]);
+    }
+
     listSeeds(): Promise<SeedDescription[]> {
         return neonFrameHandlerCall(this.addon, "seed_list", []);
     }
@@ -260,7 +361,7 @@ export class EmeraldVaultNative implements IEmeraldVault {
         return neonFrameHandlerCall(this.addon, "seed_isAvailable", [JSON.stringify(ref)])
     }
 
-    listSeedAddresses(seed: Uuid | SeedReference | SeedDefinition, blockchain: number, hdpath: string[]): Promise<{ [key: string]: string }> {
+    listSeedAddresses(seed: Uuid | SeedReference | SeedDefinition, blockchain: number, hdpath: string[]): Promise<SeedAddress[]> {
         let ref = seed;
         if (isReference(seed)) {
             ref = {
@@ -271,6 +372,17 @@ export class EmeraldVaultNative implements IEmeraldVault {
         return neonFrameHandlerCall(this.addon, "seed_listAddresses", [JSON.stringify(ref), blockchain, hdpath])
     }
 
+    exportAccountXpub(seed: Uuid | SeedReference | SeedDefinition, blockchain: number, accountPath: string, format?: XpubFormat): Promise<AccountXpub> {
+        let ref = seed;
+        if (isReference(seed)) {
+            ref = {
+                type: "id",
+                value: seed
+            }
+        }
+        return neonFrameHandlerCall(this.addon, "seed_exportAccountXpub", [JSON.stringify(ref), blockchain, accountPath, format])
+    }
+
     updateSeed(seed: Uuid | IdSeedReference, details: Partial<SeedDetails>): Promise<boolean> {
         let seed_id = seed;
         if (isIdSeedReference(seed)) {
@@ -279,6 +391,46 @@ export class EmeraldVaultNative implements IEmeraldVault {
         return neonFrameHandlerCall(this.addon, "seed_update", [seed_id, JSON.stringify(details)])
     }
 
+    removeSeed(seed: Uuid | IdSeedReference, force?: boolean): Promise<SeedRemoveResult> {
+        let seed_id = seed;
+        if (isIdSeedReference(seed)) {
+            seed_id = seed.value;
+        }
+        return neonFrameHandlerCall(this.addon, "seed_remove", [seed_id, force === true])
+    }
+
+    revealMnemonic(seed: Uuid | IdSeedReference, password: string): Promise<string> {
+        let seed_id = seed;
+        if (isIdSeedReference(seed)) {
+            seed_id = seed.value;
+        }
+        return neonFrameHandlerCall(this.addon, "seed_revealMnemonic", [seed_id, password])
+    }
+
+    splitSeedShares(seed: Uuid | IdSeedReference, password: string, options: Slip39SplitOptions): Promise<string[][]> {
+        let seed_id = seed;
+        if (isIdSeedReference(seed)) {
+            seed_id = seed.value;
+        }
+        return neonFrameHandlerCall(this.addon, "seed_splitShares", [seed_id, password, JSON.stringify(options)])
+    }
+
+    deriveBip85(seed: Uuid | IdSeedReference, password: string, options: Bip85Options): Promise<Bip85Child> {
+        let seed_id = seed;
+        if (isIdSeedReference(seed)) {
+            seed_id = seed.value;
+        }
+        return neonFrameHandlerCall(this.addon, "seed_deriveBip85", [seed_id, password, JSON.stringify(options)])
+    }
+
+    changeSeedPassword(seed: Uuid | IdSeedReference, existingPassword: string, newPassword: string): Promise<boolean> {
+        let seed_id = seed;
+        if (isIdSeedReference(seed)) {
+            seed_id = seed.value;
+        }
+        return neonFrameHandlerCall(this.addon, "seed_changePassword", [seed_id, existingPassword, newPassword])
+    }
+
     createGlobalKey(password: String): Promise<boolean> {
         return neonFrameHandlerCall(this.addon, "global_create", [password])
     }
@@ -1,5 +1,5 @@
 import {EmeraldVaultNative} from "../EmeraldVaultNative";
-import {tempPath} from "../__tests__/_commons";
+import {tempPath, byHdPath} from "../__tests__/_commons";
 import {
     LedgerSeedReference,
     SeedPKRef,
@@ -72,11 +72,11 @@ describe("Bitcoin Integration Test", () => {
                 console.warn("Ignore Ledger tests");
                 return;
             }
-            const act = await vault.listSeedAddresses(ledgerReference, 1, [
+            const act = byHdPath(await vault.listSeedAddresses(ledgerReference, 1, [
                 "m/84'/0'/0'/0/0",
                 "m/84'/0'/0'/0/1",
                 "m/84'/0'/0'/1/0",
-            ]);
+            ]));
             console.log(act);
             expect(act["m/84'/0'/0'/0/0"]).toBe("bc1qaaayykrrx84clgnpcfqu00nmf2g3mf7f53pk3n");
             expect(act["m/84'/0'/0'/0/1"]).toBe("bc1q9qr2nfsa4eumzf28n79wacw2p5756lj5wmgum8");
@@ -88,10 +88,10 @@ describe("Bitcoin Integration Test", () => {
                 console.warn("Ignore Ledger tests");
                 return;
             }
-            const act = await vault.listSeedAddresses(ledgerReference, 1, [
+            const act = byHdPath(await vault.listSeedAddresses(ledgerReference, 1, [
                 "m/84'/0'/1'/0/0",
                 "m/84'/0'/1'/0/1",
-            ]);
+            ]));
             console.log(act);
             expect(act["m/84'/0'/1'/0/0"]).toBe("bc1q3tfxzcx47yspaejvlvyylvhdn237safumetuch");
             expect(act["m/84'/0'/1'/0/1"]).toBe("bc1q4gtzmz6rujfcpaseddgjuemywx3lqscvc4mtxk");
@@ -103,10 +103,10 @@ describe("Bitcoin Integration Test", () => {
                 return;
             }
             let id: Uuid = await vault.importSeed(ledgerReference)
-            const act = await vault.listSeedAddresses(id, 1, [
+            const act = byHdPath(await vault.listSeedAddresses(id, 1, [
                 "m/84'/0'/0'/0/0",
                 "m/84'/0'/0'/0/1",
-            ]);
+            ]));
             console.log(act);
             expect(act["m/84'/0'/0'/0/0"]).toBe("bc1qaaayykrrx84clgnpcfqu00nmf2g3mf7f53pk3n");
             expect(act["m/84'/0'/0'/0/1"]).toBe("bc1q9qr2nfsa4eumzf28n79wacw2p5756lj5wmgum8");
@@ -130,10 +130,10 @@ describe("Bitcoin Integration Test", () => {
         };
 
         test("Just list", async () => {
-            const act = await vault.listSeedAddresses(ledgerReference, 1, [
+            const act = byHdPath(await vault.listSeedAddresses(ledgerReference, 1, [
                 "m/84'/0'/0'",
                 "m/84'/0'/1'",
-            ]);
+            ]));
             console.log(act);
             expect(act["m/84'/0'/0'"]).toBe("zpub6rRF9XhDBRQSKiGLTD9vTaBfdpRrxJA9eG5YHmTwFfRN2Rbv7w7XNgCZg93Gk7CdRdfjY5hwM5ugrwXak9RgVsx5fwHfAdHdbf5UKmokEtJ");
             expect(act["m/84'/0'/1'"]).toBe("zpub6rRF9XhDBRQSP251N6St8X4MpUvexnmdu9qFAMXf2xetav5BQ4PNQjKQrC3Sa265foZmoTdtt5sNZVtz5FjwFqrQWiG14th53GB53wQ6E4M");
@@ -141,10 +141,10 @@ describe("Bitcoin Integration Test", () => {
 
         test("List with created ledger", async () => {
             let id: Uuid = await vault.importSeed(ledgerReference)
-            const act = await vault.listSeedAddresses(id, 1, [
+            const act = byHdPath(await vault.listSeedAddresses(id, 1, [
                 "m/84'/0'/0'",
                 "m/84'/0'/1'",
-            ]);
+            ]));
             console.log(act);
             expect(act["m/84'/0'/0'"]).toBe("zpub6rRF9XhDBRQSKiGLTD9vTaBfdpRrxJA9eG5YHmTwFfRN2Rbv7w7XNgCZg93Gk7CdRdfjY5hwM5ugrwXak9RgVsx5fwHfAdHdbf5UKmokEtJ");
             expect(act["m/84'/0'/1'"]).toBe("zpub6rRF9XhDBRQSP251N6St8X4MpUvexnmdu9qFAMXf2xetav5BQ4PNQjKQrC3Sa265foZmoTdtt5sNZVtz5FjwFqrQWiG14th53GB53wQ6E4M");
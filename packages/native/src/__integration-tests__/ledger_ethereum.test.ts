@@ -1,5 +1,5 @@
 import {EmeraldVaultNative} from "../EmeraldVaultNative";
-import {tempPath} from "../__tests__/_commons";
+import {tempPath, byHdPath} from "../__tests__/_commons";
 import {
     BitcoinEntry,
     EthereumEntry,
@@ -96,11 +96,11 @@ describe("Ethereum Integration Test", () => {
         };
 
         test("List ethereum", async () => {
-            const act = await vault.listSeedAddresses(ledgerReference, 100, [
+            const act = byHdPath(await vault.listSeedAddresses(ledgerReference, 100, [
                 "m/44'/60'/0'/0/0",
                 "m/44'/60'/0'/0/1",
                 "m/44'/60'/0'/0/2",
-            ]);
+            ]));
             console.log(act);
             expect(act["m/44'/60'/0'/0/0"]).toBe(testAddresses["m/44'/60'/0'/0/0"].toLowerCase());
             expect(act["m/44'/60'/0'/0/1"]).toBe(testAddresses["m/44'/60'/0'/0/1"].toLowerCase());
@@ -110,11 +110,11 @@ describe("Ethereum Integration Test", () => {
 
         test("List ethereum with created ledger", async () => {
             let id: Uuid = await vault.importSeed(ledgerReference)
-            const act = await vault.listSeedAddresses(id, 100, [
+            const act = byHdPath(await vault.listSeedAddresses(id, 100, [
                 "m/44'/60'/0'/0/0",
                 "m/44'/60'/0'/0/1",
                 "m/44'/60'/0'/0/2",
-            ]);
+            ]));
             console.log(act);
             expect(act["m/44'/60'/0'/0/0"]).toBe(testAddresses["m/44'/60'/0'/0/0"].toLowerCase());
             expect(act["m/44'/60'/0'/0/1"]).toBe(testAddresses["m/44'/60'/0'/0/1"].toLowerCase());
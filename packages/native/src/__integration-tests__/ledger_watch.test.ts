@@ -1,5 +1,5 @@
 import {EmeraldVaultNative} from "../EmeraldVaultNative";
-import {tempPath} from "../__tests__/_commons";
+import {tempPath, byHdPath} from "../__tests__/_commons";
 import {BlockchainId, isLedgerDetails} from "@emeraldpay/emerald-vault-core";
 
 describe("Watch Ledger", () => {
@@ -75,7 +75,7 @@ describe("Watch Ledger", () => {
                 })
                 .then((addresses) => {
                     console.log("Bitcoin addresses", JSON.stringify(addresses));
-                    return addresses["m/84'/0'/0'/0/0"];
+                    return byHdPath(addresses)["m/84'/0'/0'/0/0"];
                 });
 
             let ethereum = vault
@@ -86,7 +86,7 @@ describe("Watch Ledger", () => {
                 })
                 .then((addresses) => {
                     console.log("Ethereum addresses", JSON.stringify(addresses));
-                    return addresses["m/44'/60'/0'/0/0"];
+                    return byHdPath(addresses)["m/44'/60'/0'/0/0"];
                 });
 
             let results = await Promise.all([bitcoin, ethereum]);
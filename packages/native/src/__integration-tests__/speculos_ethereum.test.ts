@@ -1,6 +1,6 @@
-import { LedgerSeedReference } from '@emeraldpay/emerald-vault-core';
+import { LedgerSeedReference, SeedAddress } from '@emeraldpay/emerald-vault-core';
 import { EmeraldVaultNative } from '../EmeraldVaultNative';
-import { tempPath } from '../__tests__/_commons';
+import { tempPath, byHdPath } from '../__tests__/_commons';
 
 /** Build by command `npm run build:rs:hwkeyemulate` */
 
@@ -37,11 +37,11 @@ describe('Speculos Ethereum integration', () => {
     test('List addresses', async () => {
       const addresses = await vault.listSeedAddresses(ledgerReference, 100, hdPaths);
 
-      hdPaths.forEach((hdPath) => expect(addresses[hdPath]).toBe(expectedAddresses[hdPath].toLowerCase()));
+      hdPaths.forEach((hdPath) => expect(byHdPath(addresses)[hdPath]).toBe(expectedAddresses[hdPath].toLowerCase()));
     });
 
     test('List addresses with 2 parallel calls with delay', async () => {
-      const promises: Promise<Record<string, string>>[] = [vault.listSeedAddresses(ledgerReference, 100, hdPaths)];
+      const promises: Promise<SeedAddress[]>[] = [vault.listSeedAddresses(ledgerReference, 100, hdPaths)];
 
       await new Promise((resolve) => setTimeout(resolve, 250));
 
@@ -50,7 +50,7 @@ describe('Speculos Ethereum integration', () => {
       const results = await Promise.all(promises);
 
       results.forEach((addresses) =>
-        hdPaths.forEach((hdPath) => expect(addresses[hdPath]).toBe(expectedAddresses[hdPath].toLowerCase())),
+        hdPaths.forEach((hdPath) => expect(byHdPath(addresses)[hdPath]).toBe(expectedAddresses[hdPath].toLowerCase())),
       );
     });
   });
@@ -1,3 +1,5 @@
+import {SeedAddress} from "@emeraldpay/emerald-vault-core";
+
 const fs = require('fs');
 const path = require('path');
 const fse = require('fs-extra');
@@ -17,4 +19,10 @@ export function copy(base: string) {
     const copy = `./testdata/tmp-${base}-${ts}-${seq}`;
     fse.copySync(`./testdata/${base}`, copy);
     return copy;
-}
\ No newline at end of file
+}
+
+export function byHdPath(addresses: SeedAddress[]): { [key: string]: string } {
+    const result: { [key: string]: string } = {};
+    addresses.forEach((a) => result[a.hdPath] = a.address);
+    return result;
+}
@@ -13,6 +13,8 @@ import {
     BlockchainId
 } from "@emeraldpay/emerald-vault-core";
 import {tempPath} from "./_commons";
+import {TransactionFactory} from "@ethereumjs/tx";
+import {Common} from "@ethereumjs/common";
 
 
 describe("Entries", () => {
@@ -70,6 +72,65 @@ describe("Entries", () => {
             expect(key.hdPath).toBe("m/44'/60'/0'/1/1");
         });
 
+        test("uses non-standard path", async () => {
+            let walletId = await vault.addWallet("wallet 1");
+            let seedId = await vault.importSeed({
+                type: "mnemonic",
+                value: {
+                    value: "ordinary tuition injury hockey setup magnet vibrant exit win turkey success caught direct rich field evil ranch crystal step album charge daughter setup sea",
+                },
+                password: "test"
+            })
+            await vault.addEntry(walletId, {
+                type: "hd-path",
+                blockchain: 100,
+                key: {
+                    seed: {type: "id", value: seedId, password: "test"},
+                    hdPath: "m/44'/60'/0'/1",
+                    address: "0x41f443f7546d7858f39b79cb82416b72a6706a02",
+                }
+            });
+            let wallet = await vault.getWallet(walletId);
+            let entry = wallet.entries[0] as EthereumEntry;
+            // the vault keeps seed entries only on a standard path, so it's stored as an individual key
+            expect(entry.key.type).toBe("pk");
+            expect(entry.address.value).toBe("0x41f443f7546d7858f39b79cb82416b72a6706a02");
+
+            let tx = {
+                from: "0x41f443f7546d7858f39b79cb82416b72a6706a02",
+                to: "0x3eaf0b987b49c4d782ee134fdc1243fd0ccdfdd3",
+                value: "0",
+                gas: 0x5208,
+                gasPrice: "2000000000",
+                nonce: 0x19,
+            };
+            let raw = await vault.signTx(entry.id, tx, "test");
+
+            let chainConfig = new Common({chain: 'mainnet', hardfork: 'shanghai'});
+            let parsed = TransactionFactory.fromSerializedData(Buffer.from(raw.raw.slice(2), 'hex'), {common: chainConfig});
+            expect(parsed.getSenderAddress().toString().toLowerCase()).toBe("0x41f443f7546d7858f39b79cb82416b72a6706a02");
+        });
+
+        test("fails on non-standard path with another address", async () => {
+            let walletId = await vault.addWallet("wallet 1");
+            let seedId = await vault.importSeed({
+                type: "mnemonic",
+                value: {
+                    value: "ordinary tuition injury hockey setup magnet vibrant exit win turkey success caught direct rich field evil ranch crystal step album charge daughter setup sea",
+                },
+                password: "test"
+            })
+            await expect(vault.addEntry(walletId, {
+                type: "hd-path",
+                blockchain: 100,
+                key: {
+                    seed: {type: "id", value: seedId, password: "test"},
+                    hdPath: "m/44'/60'/0'/2",
+                    address: "0x41f443f7546d7858f39b79cb82416b72a6706a02",
+                }
+            })).rejects.toThrow();
+        });
+
         test("set label", async () => {
             let walletId = await vault.addWallet("wallet 1");
             let entryId = await vault.addEntry(walletId, {
@@ -177,6 +238,52 @@ describe("Entries", () => {
                 }
             );
         })
+
+        test("Uses taproot account", async () => {
+            let id = await vault.importSeed({
+                type: "mnemonic",
+                value: {
+                    value: "ordinary tuition injury hockey setup magnet vibrant exit win turkey success caught direct rich field evil ranch crystal step album charge daughter setup sea"
+                },
+                password: "test"
+            });
+
+            let walletId = await vault.addWallet("test taproot");
+            await vault.addEntry(walletId, {
+                blockchain: 1,
+                type: "hd-path",
+                key: {
+                    seed: {type: "id", value: id, password: "test"},
+                    hdPath: "m/86'/0'/0'",
+                }
+            });
+            vault.setState({
+                accountIndexes: [
+                    {walletId, entryId: 0, change: 0, receive: 5}
+                ]
+            })
+            let wallets = await vault.listWallets();
+            let wallet = WalletsOp.of(wallets).getWallet(walletId).value;
+            let entry = wallet.entries[0] as BitcoinEntry;
+            expect(entry.addresses).toEqual([
+                {
+                    "address": "bc1p8pysk0u5qefx79z4wp3t8gq2wgkpkugdrs25ht36xffl6yal5u3qtx2exe",
+                    "hdPath": "m/86'/0'/0'/0/5",
+                    "role": "receive"
+                },
+                {
+                    "address": "bc1ppq3x8hxnsh37fzhvm00n0yjlxqrvslpayttzhn25wv6enevak43ssqz076",
+                    "hdPath": "m/86'/0'/0'/1/0",
+                    "role": "change"
+                }
+            ]);
+
+            let addresses = await vault.listEntryAddresses(`${walletId}-0`, "receive", 0, 2);
+            expect(addresses.map((a) => a.address)).toEqual([
+                "bc1pty7fsu0pr8yhyy37wm4me5ryuknj450gw9udq4jvv0ways6gd9cqdrdn62",
+                "bc1pxzj72gklyewvj85hz5ukgg5xrf2q4kq9gz8athxcylw8xkcdeqssvsdcmv",
+            ]);
+        })
     });
 
     describe("List", () => {
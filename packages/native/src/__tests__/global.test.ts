@@ -68,6 +68,30 @@ describe("Global Key", () => {
             expect(raw.raw).toBe("0xf865028477359400825208943eaf0b987b49c4d782ee134fdc1243fd0ccdfdd38210518026a0a8f7aacd400789614602925c4331cf7ccf83548401632da7ee36b634fd5e2ce1a05c554688d38bd762af2e4885b70a2b3e608dffe80a2c33c93606afe4add040c6");
         });
 
+        test('Change password of odd seed', async () => {
+            let seedsBefore = await vault.listSeeds();
+
+            let changed1 = await vault.changeSeedPassword("14780c33-0364-4bff-9244-a7a495c0cf33", "wrong", "test-new");
+            expect(changed1).toBeFalsy();
+
+            let changed2 = await vault.changeSeedPassword("14780c33-0364-4bff-9244-a7a495c0cf33", "test", "test-new");
+            expect(changed2).toBeTruthy();
+
+            // the backup is not kept as a seed
+            let seeds = await vault.listSeeds();
+            expect(seeds.length).toBe(seedsBefore.length);
+
+            let created = await vault.createGlobalKey("test-global");
+            expect(created).toBeTruthy();
+
+            // the old password doesn't work anymore
+            let upgraded1 = await vault.tryUpgradeOddItems("test", "test-global");
+            expect(upgraded1.length).toBe(0);
+
+            let upgraded2 = await vault.tryUpgradeOddItems("test-new", "test-global");
+            expect(upgraded2.length).toBe(1);
+        });
+
     });
 
     describe("Create new", () => {
@@ -96,6 +120,25 @@ describe("Global Key", () => {
             expect(invalid).toBeFalsy();
         });
 
+        test("Change password of a seed with global key", async () => {
+            await vault.createGlobalKey("test-global");
+            let seedId = await vault.importSeed({
+                type: "mnemonic",
+                value: {
+                    value: "ordinary tuition injury hockey setup magnet vibrant exit win turkey success caught direct rich field evil ranch crystal step album charge daughter setup sea"
+                },
+                password: "test-global"
+            });
+
+            await expect(vault.changeSeedPassword(seedId, "test-global", "test-new")).rejects.toBeDefined();
+
+            // the seed is still encrypted with the global key
+            let seeds = await vault.listSeeds();
+            expect(seeds.length).toBe(1);
+            let odd = await vault.getOddPasswordItems();
+            expect(odd.length).toBe(0);
+        });
+
         test("Create global key and seed", async () => {
 
             let created = await vault.createGlobalKey("test-global");
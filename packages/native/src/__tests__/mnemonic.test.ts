@@ -49,6 +49,62 @@ describe("Mnemonic", () => {
 
     });
 
+    describe('Test generate mnemonic from entropy', () => {
+        let vault: EmeraldVaultNative;
+        beforeAll(async () => {
+            vault = new EmeraldVaultNative({
+                dir: tempPath("gen-mnemonic-entropy")
+            });
+        });
+        afterAll(() => {
+            vault.close()
+        });
+
+        test("uses hex as is", async () => {
+            let result = await vault.generateMnemonicFromEntropy({
+                type: "hex",
+                value: "7f7f7f7f7f7f7f7f7f7f7f7f7f7f7f7f",
+                size: 12,
+            });
+            expect(result.mnemonic).toBe("legal winner thank year wave sausage worth useful legal winner thank yellow");
+            expect(result.record.length).toBeGreaterThan(0);
+        });
+
+        test("uses dice rolls", async () => {
+            let result = await vault.generateMnemonicFromEntropy({
+                type: "dice",
+                value: "3614522631".repeat(5),
+                size: 12,
+            });
+            expect(result.mnemonic.split(" ").length).toBe(12);
+        });
+
+        test("mixed with system random is different each time", async () => {
+            let options = {
+                type: "hex" as const,
+                value: "7f7f7f7f7f7f7f7f7f7f7f7f7f7f7f7f",
+                size: 12,
+                mixSystem: true,
+            };
+            let m1 = await vault.generateMnemonicFromEntropy(options);
+            let m2 = await vault.generateMnemonicFromEntropy(options);
+            expect(m1.mnemonic).not.toBe(m2.mnemonic);
+        });
+
+        test("errors for not enough entropy", async () => {
+            await expect(vault.generateMnemonicFromEntropy({
+                type: "coins",
+                value: "HTHTHHTT",
+                size: 12,
+            })).rejects.toThrow();
+            await expect(vault.generateMnemonicFromEntropy({
+                type: "hex",
+                value: "7f7f7f7f",
+                size: 24,
+            })).rejects.toThrow();
+        });
+    });
+
     // reference: https://iancoleman.io/bip39/#english
     describe('Test import mnemonic', () => {
 
@@ -338,4 +394,65 @@ describe("Mnemonic", () => {
 
         });
     });
+
+    describe('Reveal mnemonic', () => {
+
+        let vault: EmeraldVaultNative;
+        beforeAll(async () => {
+            vault = new EmeraldVaultNative({
+                dir: tempPath("reveal-mnemonic")
+            });
+            await vault.createGlobalKey("test-global")
+        });
+        afterAll(() => {
+            vault.close()
+        });
+
+        const mnemonic = "fever misery evidence miss toddler fold scatter mail believe fire cabbage story verify tunnel echo";
+
+        test("reveal stored phrase", async () => {
+            let seedId = await vault.importSeed({
+                type: "mnemonic",
+                password: "test-global",
+                value: {
+                    value: mnemonic,
+                    revealable: true,
+                }
+            });
+
+            let seed = (await vault.listSeeds()).find((s) => s.id == seedId);
+            expect(seed.revealable).toBeTruthy();
+
+            let revealed = await vault.revealMnemonic(seedId, "test-global");
+            expect(revealed).toBe(mnemonic);
+        });
+
+        test("requires valid password", async () => {
+            let seedId = await vault.importSeed({
+                type: "mnemonic",
+                password: "test-global",
+                value: {
+                    value: mnemonic,
+                    revealable: true,
+                }
+            });
+
+            await expect(vault.revealMnemonic(seedId, "wrong")).rejects.toThrow();
+        });
+
+        test("unavailable when not stored", async () => {
+            let seedId = await vault.importSeed({
+                type: "mnemonic",
+                password: "test-global",
+                value: {
+                    value: mnemonic,
+                }
+            });
+
+            let seed = (await vault.listSeeds()).find((s) => s.id == seedId);
+            expect(seed.revealable).toBeFalsy();
+
+            await expect(vault.revealMnemonic(seedId, "test-global")).rejects.toThrow();
+        });
+    });
 });
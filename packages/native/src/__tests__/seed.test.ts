@@ -1,8 +1,8 @@
 import {EmeraldVaultNative} from "../EmeraldVaultNative";
-import {tempPath} from "./_commons";
+import {tempPath, byHdPath} from "./_commons";
 import {
     SeedReference,
-    MnemonicSeedDefinition, BlockchainId
+    MnemonicSeedDefinition, BlockchainId, XpubFormat
 } from "@emeraldpay/emerald-vault-core";
 
 describe("Seeds", () => {
@@ -40,7 +40,7 @@ describe("Seeds", () => {
             describe('List addresses', () => {
 
                 test("List ethereum", async () => {
-                    const act = await vault.listSeedAddresses(type, BlockchainId.ETHEREUM, [
+                    const act = byHdPath(await vault.listSeedAddresses(type, BlockchainId.ETHEREUM, [
                         "m/44'/60'/0'/0/0",
                         "m/44'/60'/0'/0/1",
                         "m/44'/60'/0'/0/2",
@@ -48,7 +48,7 @@ describe("Seeds", () => {
                         "m/44'/60'/0'/1/1",
                         "m/44'/60'/1'/42/100",
                         "m/44'/60'/1'/42/101",
-                    ]);
+                    ]));
                     // console.log(act);
                     expect(act["m/44'/60'/0'/0/0"]).toBe('0x110c84fCC6A775f788d3CA6A9492Abd5B3fEc588'.toLowerCase());
                     expect(act["m/44'/60'/0'/0/1"]).toBe('0xb4BbAaC4Acd7E86AF282e80C7a62fda78D071950'.toLowerCase());
@@ -59,12 +59,68 @@ describe("Seeds", () => {
                     expect(act["m/44'/60'/1'/42/101"]).toBe('0x14bBd231A213c0A6715c67DB3b7f191C052C9E17'.toLowerCase());
                 });
 
-                test("List bitcoin", async () => {
+                test("List ethereum on non-standard paths", async () => {
+                    const act = byHdPath(await vault.listSeedAddresses(type, BlockchainId.ETHEREUM, [
+                        // Legacy MEW
+                        "m/44'/60'/0'/1",
+                        "m/44'/60'/0'/2",
+                        // Ledger Live
+                        "m/44'/60'/1'/0/0",
+                    ]));
+                    expect(act["m/44'/60'/0'/1"]).toBe('0x41f443f7546d7858f39b79cb82416b72a6706a02');
+                    expect(act["m/44'/60'/0'/2"]).toBe('0xf5d3603977acfa631b1aacde9bf5ddb213122744');
+                    expect(act["m/44'/60'/1'/0/0"]).toBe('0x2eace5def8d8387e480c6490ffd70aea04da31a9');
+                });
+
+                test("List ethereum with range", async () => {
+                    const act = await vault.listSeedAddresses(type, BlockchainId.ETHEREUM, [
+                        "m/44'/60'/0'/1/1",
+                        "m/44'/60'/0'/0/{0..2}",
+                    ]);
+                    expect(act).toEqual([
+                        {hdPath: "m/44'/60'/0'/1/1", address: '0xe84A870Fa3057B5212B735D0c574F70aA32913dD'.toLowerCase()},
+                        {hdPath: "m/44'/60'/0'/0/0", address: '0x110c84fCC6A775f788d3CA6A9492Abd5B3fEc588'.toLowerCase()},
+                        {hdPath: "m/44'/60'/0'/0/1", address: '0xb4BbAaC4Acd7E86AF282e80C7a62fda78D071950'.toLowerCase()},
+                        {hdPath: "m/44'/60'/0'/0/2", address: '0xd1bdBfb39e13aD7969e7D49bf7896AE6A868610C'.toLowerCase()},
+                    ]);
+                });
+
+                test("Reports invalid paths", async () => {
+                    const act = await vault.listSeedAddresses(type, BlockchainId.ETHEREUM, [
+                        "m/44'/60'/0'/0/0",
+                        "m/44'/60'/x'/0/0",
+                        "test",
+                    ]);
+                    expect(act.length).toBe(3);
+                    expect(act[0]).toEqual({hdPath: "m/44'/60'/0'/0/0", address: '0x110c84fCC6A775f788d3CA6A9492Abd5B3fEc588'.toLowerCase()});
+                    expect(act[1].hdPath).toBe("m/44'/60'/x'/0/0");
+                    expect(act[1].address).toBeUndefined();
+                    expect(act[1].error).toBe("Invalid HD Path");
+                    expect(act[2].hdPath).toBe("test");
+                    expect(act[2].error).toBe("Invalid HD Path");
+                });
+
+                test("Reports path not usable for bitcoin", async () => {
                     const act = await vault.listSeedAddresses(type, BlockchainId.BITCOIN, [
+                        "m/84'/0'/0'/0",
+                        "m/84'/0'/0'/0/0",
+                    ]);
+                    expect(act[0]).toEqual({hdPath: "m/84'/0'/0'/0", error: "Invalid HD Path"});
+                    expect(act[1]).toEqual({hdPath: "m/84'/0'/0'/0/0", address: 'bc1qxqz4qerrm662nt4hxh39mqltvqcffcvzzfc49z'});
+                });
+
+                test("Fails with too large range", async () => {
+                    await expect(vault.listSeedAddresses(type, BlockchainId.ETHEREUM, [
+                        "m/44'/60'/0'/0/{0..5000}",
+                    ])).rejects.toThrow();
+                });
+
+                test("List bitcoin", async () => {
+                    const act = byHdPath(await vault.listSeedAddresses(type, BlockchainId.BITCOIN, [
                         "m/84'/0'/0'/0/0",
                         "m/84'/0'/0'/0/1",
                         "m/84'/0'/1'/0/0"
-                    ]);
+                    ]));
                     // console.log(act);
                     expect(act["m/84'/0'/0'/0/0"]).toBe('bc1qxqz4qerrm662nt4hxh39mqltvqcffcvzzfc49z');
                     expect(act["m/84'/0'/0'/0/1"]).toBe('bc1qj4zhepcsjp6gpqf252329daum6ey6hhqagccaf');
@@ -72,12 +128,12 @@ describe("Seeds", () => {
                 });
 
                 test("List bitcoin xpub", async () => {
-                    const act = await vault.listSeedAddresses(type, BlockchainId.BITCOIN, [
+                    const act = byHdPath(await vault.listSeedAddresses(type, BlockchainId.BITCOIN, [
                         "m/84'/0'/0'",
                         "m/84'/0'/1'",
                         "m/44'/0'/0'",
                         "m/49'/0'/0'",
-                    ]);
+                    ]));
                     // console.log(act);
                     expect(act["m/84'/0'/0'"]).toBe('zpub6rgquuQgjiNdUjkU7qZck9t3JU5K9U9EG2aVAwzDy2BJKHKMekVNsyZF2e4dw9L9AoT9WHy5iDVdUHz2XkrANy5LRVGLt3XMkar752N2hvq');
                     expect(act["m/84'/0'/1'"]).toBe('zpub6rgquuQgjiNdWtkm1t1XUnnTC5qDJoDrVwoUnMC6bEb8cWSRRDYcqGM4BpXbzhfHkWAGWh2VjZiYKyh9qVdpTkJEvgXgB9g5U2iRE3jiDgQ');
@@ -86,6 +142,65 @@ describe("Seeds", () => {
                 });
             });
 
+            describe('Taproot', () => {
+                test("List taproot addresses", async () => {
+                    const act = byHdPath(await vault.listSeedAddresses(type, BlockchainId.BITCOIN, [
+                        "m/86'/0'/0'/0/0",
+                        "m/86'/0'/0'/0/1",
+                        "m/86'/0'/0'/1/0",
+                        "m/86'/0'/0'",
+                    ]));
+                    expect(act["m/86'/0'/0'/0/0"]).toBe('bc1pty7fsu0pr8yhyy37wm4me5ryuknj450gw9udq4jvv0ways6gd9cqdrdn62');
+                    expect(act["m/86'/0'/0'/0/1"]).toBe('bc1pxzj72gklyewvj85hz5ukgg5xrf2q4kq9gz8athxcylw8xkcdeqssvsdcmv');
+                    expect(act["m/86'/0'/0'/1/0"]).toBe('bc1ppq3x8hxnsh37fzhvm00n0yjlxqrvslpayttzhn25wv6enevak43ssqz076');
+                    expect(act["m/86'/0'/0'"]).toBe('xpub6CjAvf6uUJxS5dAos2CMmNgx64dVU2eMvVFMLHwBfnEnq6tFdFZ558aWsTQNPENxw2pnG26oTUmMq7vQG8WPuMUmT1gKJonjo3qzYNbJzqm');
+                });
+
+                test("List taproot with other addresses", async () => {
+                    const act = await vault.listSeedAddresses(type, BlockchainId.BITCOIN, [
+                        "m/84'/0'/0'/0/0",
+                        "m/86'/0'/0'/0/0",
+                    ]);
+                    expect(act).toEqual([
+                        {hdPath: "m/84'/0'/0'/0/0", address: "bc1qxqz4qerrm662nt4hxh39mqltvqcffcvzzfc49z"},
+                        {hdPath: "m/86'/0'/0'/0/0", address: "bc1pty7fsu0pr8yhyy37wm4me5ryuknj450gw9udq4jvv0ways6gd9cqdrdn62"},
+                    ]);
+                });
+            });
+
+            describe('Export account xpub', () => {
+                test("Export zpub with origin", async () => {
+                    const act = await vault.exportAccountXpub(type, BlockchainId.BITCOIN, "m/84'/0'/0'");
+                    expect(act.xpub).toBe('zpub6rgquuQgjiNdUjkU7qZck9t3JU5K9U9EG2aVAwzDy2BJKHKMekVNsyZF2e4dw9L9AoT9WHy5iDVdUHz2XkrANy5LRVGLt3XMkar752N2hvq');
+                    expect(act.format).toBe("zpub");
+                    expect(act.fingerprint).toBe("a6220b11");
+                    expect(act.keyOrigin).toBe("[a6220b11/84'/0'/0']");
+                    expect(act.descriptor).toMatch(/^wpkh\(\[a6220b11\/84h\/0h\/0h]xpub6D2KJa4rSMHfn9NET7zNKyh2xXnRGEAERoY3cACTD1RYD5gu9SAFdrExzE9TwL2JMXDY1LmxntnXhiku6N28nVi8gosViDtPD8ipHrRDj5b\/0\/\*\)#[a-z0-9]{8}$/);
+                    expect(act.changeDescriptor).toMatch(/\/1\/\*\)#[a-z0-9]{8}$/);
+                });
+
+                test("Export in requested format", async () => {
+                    const act = await vault.exportAccountXpub(type, BlockchainId.BITCOIN, "m/84'/0'/0'", "xpub");
+                    expect(act.xpub).toBe('xpub6D2KJa4rSMHfn9NET7zNKyh2xXnRGEAERoY3cACTD1RYD5gu9SAFdrExzE9TwL2JMXDY1LmxntnXhiku6N28nVi8gosViDtPD8ipHrRDj5b');
+                    expect(act.format).toBe("xpub");
+                });
+
+                test("Export ypub", async () => {
+                    const act = await vault.exportAccountXpub(type, BlockchainId.BITCOIN, "m/49'/0'/0'");
+                    expect(act.xpub).toBe('ypub6XKWqjEULzxUZ1AaNausD7JFWzg8jKCFmdycJpojoiRLDCNuLxKREUXnvTD26q3AAsiSBDymo2E21yhAiUY8Vrnu4UHQvfTrKRcvzyV2Pd2');
+                    expect(act.descriptor).toMatch(/^sh\(wpkh\(\[a6220b11\/49h\/0h\/0h]xpub/);
+                });
+
+                test("Fails for ethereum", async () => {
+                    await expect(vault.exportAccountXpub(type, BlockchainId.ETHEREUM, "m/44'/60'/0'")).rejects.toThrow();
+                });
+
+                test("Fails for multisig or testnet format", async () => {
+                    await expect(vault.exportAccountXpub(type, BlockchainId.BITCOIN, "m/84'/0'/0'", "Zpub" as XpubFormat)).rejects.toThrow();
+                    await expect(vault.exportAccountXpub(type, BlockchainId.BITCOIN, "m/84'/0'/0'", "vpub")).rejects.toThrow();
+                });
+            });
+
         });
 
         describe('24 words with password', () => {
@@ -107,11 +222,11 @@ describe("Seeds", () => {
             describe('List addresses', () => {
 
                 test("List ethereum", async () => {
-                    const act = await vault.listSeedAddresses(type, BlockchainId.ETHEREUM, [
+                    const act = byHdPath(await vault.listSeedAddresses(type, BlockchainId.ETHEREUM, [
                         "m/44'/60'/0'/0/0",
                         "m/44'/60'/0'/0/1",
                         "m/44'/60'/0'/0/2",
-                    ]);
+                    ]));
                     // console.log(act);
                     expect(act["m/44'/60'/0'/0/0"]).toBe('0x5B1E304FB5923feE02aB6F2d0048096a34330cEF'.toLowerCase());
                     expect(act["m/44'/60'/0'/0/1"]).toBe('0x8A66db65fc9da4122ECa06e6089F4989d661AD45'.toLowerCase());
@@ -119,11 +234,11 @@ describe("Seeds", () => {
                 });
 
                 test("List Goerli Testnet", async () => {
-                    const act = await vault.listSeedAddresses(type, BlockchainId.GOERLI_TESTNET, [
+                    const act = byHdPath(await vault.listSeedAddresses(type, BlockchainId.GOERLI_TESTNET, [
                         "m/44'/60'/160720'/0/0",
                         "m/44'/60'/160720'/0/1",
                         "m/44'/60'/160720'/0/2",
-                    ]);
+                    ]));
                     // console.log(act);
                     expect(act["m/44'/60'/160720'/0/0"]).toBe('0x4D5C1AA948De28c61CF86FEeEe8fc79061Df9398'.toLowerCase());
                     expect(act["m/44'/60'/160720'/0/1"]).toBe('0xE755197C5152a3D10ab9C37aE86778ee083D04B6'.toLowerCase());
@@ -131,11 +246,11 @@ describe("Seeds", () => {
                 });
 
                 test("List bitcoin", async () => {
-                    const act = await vault.listSeedAddresses(type, BlockchainId.BITCOIN, [
+                    const act = byHdPath(await vault.listSeedAddresses(type, BlockchainId.BITCOIN, [
                         "m/84'/0'/0'/0/0",
                         "m/84'/0'/0'/0/1",
                         "m/84'/0'/1'/0/0"
-                    ]);
+                    ]));
                     // console.log(act);
                     expect(act["m/84'/0'/0'/0/0"]).toBe('bc1qgjxdwjyqr647m73vp5yvlyaetfmksj4ra5ttyt');
                     expect(act["m/84'/0'/0'/0/1"]).toBe('bc1q9s5nnp3ynsh6emhq82gja64geqtljp04jvqdp7');
@@ -157,11 +272,11 @@ describe("Seeds", () => {
             describe('List addresses', () => {
 
                 test("List ethereum", async () => {
-                    const act = await vault.listSeedAddresses(type, BlockchainId.ETHEREUM_CLASSIC, [
+                    const act = byHdPath(await vault.listSeedAddresses(type, BlockchainId.ETHEREUM_CLASSIC, [
                         "m/44'/61'/1'/0/0",
                         "m/44'/61'/1'/0/1",
                         "m/44'/61'/1'/0/2",
-                    ]);
+                    ]));
                     // console.log(act);
                     expect(act["m/44'/61'/1'/0/0"]).toBe('0x50449D9039660fe13Afc2D75f698F7c0eDdb8818'.toLowerCase());
                     expect(act["m/44'/61'/1'/0/1"]).toBe('0xEa6C68Ca34400f7e05C773bce1E4AF6A05D116d4'.toLowerCase());
@@ -214,10 +329,24 @@ describe("Seeds", () => {
                 password: "test-global"
             }
 
-            let addresses = await vault.listSeedAddresses(ref, BlockchainId.ETHEREUM, ["m/44'/60'/0'/0/1"]);
+            let addresses = byHdPath(await vault.listSeedAddresses(ref, BlockchainId.ETHEREUM, ["m/44'/60'/0'/0/1"]));
             expect(addresses["m/44'/60'/0'/0/1"].toLowerCase()).toBe("0xb4BbAaC4Acd7E86AF282e80C7a62fda78D071950".toLowerCase())
         });
 
+        test("Has fingerprint after import", async () => {
+            let id = await vault.importSeed({
+                type: "mnemonic",
+                value: {
+                    value: "ordinary tuition injury hockey setup magnet vibrant exit win turkey success caught direct rich field evil ranch crystal step album charge daughter setup sea"
+                },
+                password: "test-global"
+            });
+
+            let seeds = await vault.listSeeds();
+            expect(seeds[0].id).toBe(id);
+            expect(seeds[0].fingerprint).toBe("a6220b11");
+        });
+
         test("Create with label", async () => {
             let id = await vault.importSeed({
                 type: "mnemonic",
@@ -325,4 +454,232 @@ describe("Seeds", () => {
         });
     });
 
+
+    describe("Remove Seed", () => {
+        let vault: EmeraldVaultNative;
+        beforeEach(async () => {
+            vault = new EmeraldVaultNative({
+                dir: tempPath("seed-remove")
+            });
+            await vault.createGlobalKey("test-global")
+        });
+        afterEach(() => {
+            vault.close()
+        });
+
+        test("Remove unused", async () => {
+            let id = await vault.importSeed({
+                type: "mnemonic",
+                value: {
+                    value: "ordinary tuition injury hockey setup magnet vibrant exit win turkey success caught direct rich field evil ranch crystal step album charge daughter setup sea"
+                },
+                password: "test-global",
+            });
+
+            let result = await vault.removeSeed(id);
+            expect(result.removed).toBeTruthy();
+            expect(result.usedBy).toEqual([]);
+
+            let seeds = await vault.listSeeds();
+            expect(seeds.length).toBe(0);
+        });
+
+        test("Keep used by an entry", async () => {
+            let id = await vault.importSeed({
+                type: "mnemonic",
+                value: {
+                    value: "ordinary tuition injury hockey setup magnet vibrant exit win turkey success caught direct rich field evil ranch crystal step album charge daughter setup sea"
+                },
+                password: "test-global",
+            });
+            let walletId = await vault.addWallet("test seed");
+            let entryId = await vault.addEntry(walletId, {
+                blockchain: 100,
+                type: "hd-path",
+                key: {
+                    seed: {type: "id", value: id, password: "test-global"},
+                    hdPath: "m/44'/60'/0'/0/1",
+                }
+            });
+
+            let result = await vault.removeSeed(id);
+            expect(result.removed).toBeFalsy();
+            expect(result.usedBy).toContainEqual({walletId, entryId});
+            expect(result.usedBy).toContainEqual({walletId, accountId: 0});
+
+            let seeds = await vault.listSeeds();
+            expect(seeds.length).toBe(1);
+        });
+
+        test("Force removal of a used seed", async () => {
+            let id = await vault.importSeed({
+                type: "mnemonic",
+                value: {
+                    value: "ordinary tuition injury hockey setup magnet vibrant exit win turkey success caught direct rich field evil ranch crystal step album charge daughter setup sea"
+                },
+                password: "test-global",
+            });
+            let walletId = await vault.addWallet("test seed");
+            await vault.addEntry(walletId, {
+                blockchain: 100,
+                type: "hd-path",
+                key: {
+                    seed: {type: "id", value: id, password: "test-global"},
+                    hdPath: "m/44'/60'/0'/0/1",
+                }
+            });
+
+            let result = await vault.removeSeed(id, true);
+            expect(result.removed).toBeTruthy();
+            expect(result.usedBy.length).toBe(2);
+
+            let seeds = await vault.listSeeds();
+            expect(seeds.length).toBe(0);
+
+            let wallet = await vault.getWallet(walletId);
+            expect(wallet.entries.length).toBe(0);
+            expect(wallet.reserved).toEqual([]);
+        });
+    });
+
+
+    describe("SLIP-39 shares", () => {
+        let vault: EmeraldVaultNative;
+        beforeEach(async () => {
+            vault = new EmeraldVaultNative({
+                dir: tempPath("seed-slip39")
+            });
+            await vault.createGlobalKey("test-global")
+        });
+        afterEach(() => {
+            vault.close()
+        });
+
+        test("Split and recover", async () => {
+            let id = await vault.importSeed({
+                type: "mnemonic",
+                value: {
+                    value: "ordinary tuition injury hockey setup magnet vibrant exit win turkey success caught direct rich field evil ranch crystal step album charge daughter setup sea"
+                },
+                password: "test-global",
+            });
+
+            let shares = await vault.splitSeedShares(id, "test-global", {
+                groupThreshold: 2,
+                groups: [
+                    {threshold: 1, count: 1},
+                    {threshold: 2, count: 3},
+                    {threshold: 2, count: 3},
+                ],
+            });
+            expect(shares.length).toBe(3);
+            expect(shares[0].length).toBe(1);
+            expect(shares[1].length).toBe(3);
+
+            let recovered = await vault.importSeed({
+                type: "slip39",
+                value: {
+                    shares: [shares[2][2], shares[0][0], shares[2][0]],
+                },
+                password: "test-global",
+            });
+            expect(recovered).not.toBe(id);
+
+            const original = byHdPath(await vault.listSeedAddresses(id, BlockchainId.ETHEREUM, ["m/44'/60'/0'/0/0"]));
+            const act = byHdPath(await vault.listSeedAddresses(recovered, BlockchainId.ETHEREUM, ["m/44'/60'/0'/0/0"]));
+            expect(act["m/44'/60'/0'/0/0"]).toBe(original["m/44'/60'/0'/0/0"]);
+        });
+
+        test("Doesn't recover without enough shares", async () => {
+            let id = await vault.importSeed({
+                type: "mnemonic",
+                value: {
+                    value: "ordinary tuition injury hockey setup magnet vibrant exit win turkey success caught direct rich field evil ranch crystal step album charge daughter setup sea"
+                },
+                password: "test-global",
+            });
+
+            let shares = await vault.splitSeedShares(id, "test-global", {
+                groupThreshold: 1,
+                groups: [
+                    {threshold: 3, count: 5},
+                ],
+            });
+
+            await expect(vault.importSeed({
+                type: "slip39",
+                value: {
+                    shares: [shares[0][1], shares[0][4]],
+                },
+                password: "test-global",
+            })).rejects.toThrow();
+        });
+    });
+
+
+    describe("BIP-85", () => {
+        let vault: EmeraldVaultNative;
+        let id: string;
+        beforeEach(async () => {
+            vault = new EmeraldVaultNative({
+                dir: tempPath("seed-bip85")
+            });
+            await vault.createGlobalKey("test-global");
+            id = await vault.importSeed({
+                type: "mnemonic",
+                value: {
+                    value: "ordinary tuition injury hockey setup magnet vibrant exit win turkey success caught direct rich field evil ranch crystal step album charge daughter setup sea"
+                },
+                password: "test-global",
+            });
+        });
+        afterEach(() => {
+            vault.close()
+        });
+
+        test("Derives same mnemonic for same index", async () => {
+            let first = await vault.deriveBip85(id, "test-global", {application: "bip39", index: 0, words: 12});
+            expect(first.path).toBe("m/83696968'/39'/0'/12'/0'");
+            expect(first.value.split(" ").length).toBe(12);
+
+            let again = await vault.deriveBip85(id, "test-global", {application: "bip39", index: 0, words: 12});
+            expect(again.value).toBe(first.value);
+
+            let other = await vault.deriveBip85(id, "test-global", {application: "bip39", index: 1, words: 12});
+            expect(other.value).not.toBe(first.value);
+        });
+
+        test("Derives WIF and hex", async () => {
+            let wif = await vault.deriveBip85(id, "test-global", {application: "wif", index: 0});
+            expect(wif.path).toBe("m/83696968'/2'/0'");
+            expect(wif.value).toMatch(/^[KL][1-9A-HJ-NP-Za-km-z]{51}$/);
+
+            let hex = await vault.deriveBip85(id, "test-global", {application: "hex", index: 0, length: 16});
+            expect(hex.value).toMatch(/^[0-9a-f]{32}$/);
+        });
+
+        test("Imports child as a new seed", async () => {
+            let child = await vault.deriveBip85(id, "test-global", {
+                application: "bip39", index: 3,
+                import: {password: "test-global", label: "App Wallet"}
+            });
+            expect(child.seedId).toBeDefined();
+
+            let seeds = await vault.listSeeds();
+            expect(seeds.length).toBe(2);
+            let imported = seeds.find((s) => s.id === child.seedId);
+            expect(imported.label).toBe("App Wallet");
+
+            const expected = byHdPath(await vault.listSeedAddresses(
+                {type: "mnemonic", value: {value: child.value}}, BlockchainId.ETHEREUM, ["m/44'/60'/0'/0/0"]
+            ));
+            const act = byHdPath(await vault.listSeedAddresses(child.seedId, BlockchainId.ETHEREUM, ["m/44'/60'/0'/0/0"]));
+            expect(act["m/44'/60'/0'/0/0"]).toBe(expected["m/44'/60'/0'/0/0"]);
+        });
+
+        test("Fails with invalid password", async () => {
+            await expect(vault.deriveBip85(id, "wrong", {application: "wif", index: 0})).rejects.toThrow();
+        });
+    });
+
 });
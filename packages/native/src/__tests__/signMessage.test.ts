@@ -1,5 +1,5 @@
 import {EmeraldVaultNative} from "../EmeraldVaultNative";
-import {tempPath} from "./_commons";
+import {tempPath, byHdPath} from "./_commons";
 import {
     UnsignedMessage,
 } from "@emeraldpay/emerald-vault-core";
@@ -188,4 +188,120 @@ describe("Sign message", () => {
         })
     });
 
+    describe('Bitcoin', () => {
+
+        let vault: EmeraldVaultNative;
+        let seedId: string;
+        beforeAll(async () => {
+            vault = new EmeraldVaultNative({
+                dir: tempPath("sign-msg-btc")
+            });
+            await vault.createGlobalKey("test-global");
+            seedId = await vault.importSeed({
+                type: "mnemonic",
+                value: {
+                    value: "ordinary tuition injury hockey setup magnet vibrant exit win turkey success caught direct rich field evil ranch crystal step album charge daughter setup sea"
+                },
+                password: "test-global"
+            });
+        });
+        afterAll(() => {
+            vault.close()
+        });
+
+        async function createEntry(hdPath: string): Promise<string> {
+            let walletId = await vault.addWallet();
+            return await vault.addEntry(walletId, {
+                blockchain: 1,
+                type: "hd-path",
+                key: {
+                    seed: {type: "id", value: seedId, password: "test-global"},
+                    hdPath,
+                }
+            });
+        }
+
+        test("sign and verify BIP-137", async () => {
+            let entryId = await createEntry("m/84'/0'/0'/0/0");
+            let address = byHdPath(await vault.listSeedAddresses(seedId, 1, ["m/84'/0'/0'/0/3"]))["m/84'/0'/0'/0/3"];
+
+            let msg: UnsignedMessage = {
+                type: "bip137",
+                message: "I own this address",
+                address,
+            };
+            let signature = await vault.signMessage(entryId, msg, "test-global");
+
+            expect(signature.type).toBe("bip137");
+            expect(signature.address).toBe(address);
+            expect(Buffer.from(signature.signature, "base64").length).toBe(65);
+
+            let signer = await vault.extractMessageSigner(msg, signature.signature);
+            expect(signer).toBe(address);
+
+            let other = await vault.extractMessageSigner({...msg, message: "I own another address"}, signature.signature);
+            expect(other).not.toBe(address);
+        });
+
+        test("sign and verify BIP-322", async () => {
+            let entryId = await createEntry("m/84'/0'/0'/0/0");
+            let address = byHdPath(await vault.listSeedAddresses(seedId, 1, ["m/84'/0'/0'/1/0"]))["m/84'/0'/0'/1/0"];
+
+            let msg: UnsignedMessage = {
+                type: "bip322",
+                message: "I own this address",
+                address,
+            };
+            let signature = await vault.signMessage(entryId, msg, "test-global");
+            expect(signature.type).toBe("bip322");
+            expect(signature.address).toBe(address);
+
+            let signer = await vault.extractMessageSigner(msg, signature.signature);
+            expect(signer).toBe(address);
+
+            await expect(vault.extractMessageSigner({...msg, message: "I own another address"}, signature.signature)).rejects.toThrow();
+        });
+
+        test("sign BIP-322 with taproot", async () => {
+            let entryId = await createEntry("m/86'/0'/0'");
+            let msg: UnsignedMessage = {
+                type: "bip322",
+                message: "Hello World",
+                address: "bc1pty7fsu0pr8yhyy37wm4me5ryuknj450gw9udq4jvv0ways6gd9cqdrdn62",
+            };
+            let signature = await vault.signMessage(entryId, msg, "test-global");
+
+            let signer = await vault.extractMessageSigner(msg, signature.signature);
+            expect(signer).toBe("bc1pty7fsu0pr8yhyy37wm4me5ryuknj450gw9udq4jvv0ways6gd9cqdrdn62");
+        });
+
+        test("verify BIP-322 test vector", async () => {
+            let signer = await vault.extractMessageSigner({
+                    type: "bip322",
+                    message: "Hello World",
+                    address: "bc1q9vza2e8x573nczrlzms0wvx3gsqjx7vavgkx0l",
+                },
+                "AkcwRAIgZRfIY3p7/DoVTty6YZbWS71bc5Vct9p9Fia83eRmw2QCICK/ENGfwLtptFluMGs2KsqoNSk89pO7F29zJLUx9a/sASECx/EgAxlkQpQ9hYjgGu6EBCPMVPwVIVJqO4XCsMvViHI="
+            );
+            expect(signer).toBe("bc1q9vza2e8x573nczrlzms0wvx3gsqjx7vavgkx0l");
+        });
+
+        test("fails to sign with address of another entry", async () => {
+            let entryId = await createEntry("m/84'/0'/0'/0/0");
+            await expect(vault.signMessage(entryId, {
+                type: "bip322",
+                message: "Hello World",
+                address: "bc1q9vza2e8x573nczrlzms0wvx3gsqjx7vavgkx0l",
+            }, "test-global")).rejects.toThrow();
+        });
+
+        test("fails to sign ethereum message", async () => {
+            let entryId = await createEntry("m/84'/0'/0'/0/0");
+            await expect(vault.signMessage(entryId, {
+                type: "eip191",
+                message: "Hello World",
+            }, "test-global")).rejects.toThrow();
+        });
+    });
+
 })
\ No newline at end of file
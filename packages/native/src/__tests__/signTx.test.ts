@@ -1,17 +1,20 @@
 import {EmeraldVaultNative} from "../EmeraldVaultNative";
 import {tempPath} from "./_commons";
-import {BlockchainId, UnsignedBitcoinTx, WalletsOp} from "@emeraldpay/emerald-vault-core";
+import {BlockchainId, MultisigCosigner, UnsignedBitcoinTx, UnsignedEthereumTx, WalletsOp} from "@emeraldpay/emerald-vault-core";
 import {TransactionFactory, TypedTransaction} from '@ethereumjs/tx';
 import {Common, Hardfork} from "@ethereumjs/common";
+const fs = require('fs');
 
 
 describe("Sign transaction", () => {
     describe('Import and sign', () => {
 
         let vault: EmeraldVaultNative;
+        let dir: string;
         beforeEach(async () => {
+            dir = tempPath("import-sign");
             vault = new EmeraldVaultNative({
-                dir: tempPath("import-sign")
+                dir
             });
             await vault.createGlobalKey("test-global")
         });
@@ -62,6 +65,248 @@ describe("Sign transaction", () => {
             expect(raw.txid).toBe("0xc69cd98ebd2ee99a5392ba7d05f1a18ad2fa78068f71c6dcae9e379b0be9f640");
         });
 
+        test("sign batch", async () => {
+            let pk = {
+                "version": 3,
+                "id": "f2528b47-3058-4405-bb82-731f74e8ffea",
+                "address": "0cf0523fc884ad99f7df146848f08cb8608a38a7",
+                "crypto": {
+                    "ciphertext": "47072ffb47493b0fcb2b051aa1d76b5864c094518f9a5f0478ccd667406eaf59",
+                    "cipherparams": {"iv": "b6962fd8cf43f371031e0db458c11677"},
+                    "cipher": "aes-128-ctr",
+                    "kdf": "scrypt",
+                    "kdfparams": {
+                        "dklen": 32,
+                        "salt": "37744fdd151b14a12e07a78e94902018a2a98d6465bf2f2d1b607a3be94d5265",
+                        "n": 8192,
+                        "r": 8,
+                        "p": 1
+                    },
+                    "mac": "1c2e771fd7f602288baa5ba5d5ae9dba685fc09d17ec6608d185355be306135e"
+                }
+            };
+            let walletId = await vault.addWallet("test sign batch");
+            let entryId = await vault.addEntry(walletId, {
+                blockchain: 100,
+                type: "ethereum-json",
+                key: JSON.stringify(pk),
+                jsonPassword: "sign with scrypt",
+                password: "test-global"
+            });
+
+            let tx = {
+                from: "0x0cf0523fc884ad99f7df146848f08cb8608a38a7",
+                to: "0x3eaf0b987b49c4d782ee134fdc1243fd0ccdfdd3",
+                value: "0",
+                gas: 21000,
+                gasPrice: "2000000000",
+                nonce: 0x19,
+            };
+            let txs = [
+                tx,
+                {...tx, nonce: 0x1a},
+                {...tx, from: "0x3eaf0b987b49c4d782ee134fdc1243fd0ccdfdd3"},
+                {...tx, nonce: 0x1b, maxGasPrice: "2000000000", priorityGasPrice: "1000000000"},
+            ];
+            let results = await vault.signTxBatch(entryId, txs, "test-global");
+
+            expect(results.length).toBe(4);
+            expect(results[0]).toEqual({
+                raw: "0xf863198477359400825208943eaf0b987b49c4d782ee134fdc1243fd0ccdfdd3808026a0f3357ca4028bcfd26de329b5405ed60342a3aad785e84ea3776ef650818e7de5a0469efc686f479b242f311480911668c8b1993188908f87bd1d1c56b82a0b4fa6",
+                txid: "0xc69cd98ebd2ee99a5392ba7d05f1a18ad2fa78068f71c6dcae9e379b0be9f640",
+            });
+            expect(results[1]).toEqual(await vault.signTx(entryId, txs[1], "test-global"));
+            expect("error" in results[2] && results[2].error.message).toBe("Different from address");
+            expect(results[3]).toEqual(await vault.signTx(entryId, txs[3], "test-global"));
+        });
+
+        test("batch signs the same bytes as a single tx", async () => {
+            let walletId = await vault.addWallet("test sign batch same");
+            let entryId = await vault.addEntry(walletId, {
+                blockchain: 100,
+                type: "raw-pk-hex",
+                key: "0xfac192ceb5fd772906bea3e118a69e8bbb5cc24229e20d8766fd298291bba6bd",
+                password: "test-global"
+            });
+            let tx = {
+                from: "0x041b7ca652aa25e5be5d2053d7c7f96b5f7563d4",
+                to: "0x3eaf0b987b49c4d782ee134fdc1243fd0ccdfdd3",
+                value: "1000",
+                gas: 21000,
+                gasPrice: "2000000000",
+                nonce: 0,
+            };
+            let txs: UnsignedEthereumTx[] = [
+                tx,
+                {...tx, nonce: 1, data: "0xa9059cbb", maxGasPrice: "2000000000", priorityGasPrice: "1000000000"},
+                {raw: "0xe5028477359400825208943eaf0b987b49c4d782ee134fdc1243fd0ccdfdd38203e880648080"},
+                {raw: "0x01e264038477359400827530943eaf0b987b49c4d782ee134fdc1243fd0ccdfdd38080c0"},
+            ];
+            let results = await vault.signTxBatch(entryId, txs, "test-global");
+
+            expect(results.length).toBe(txs.length);
+            for (let i = 0; i < txs.length; i++) {
+                expect(results[i]).toEqual(await vault.signTx(entryId, txs[i], "test-global"));
+            }
+        });
+
+        test("preview unlimited approve", async () => {
+            let walletId = await vault.addWallet("test preview");
+            let entryId = await vault.addEntry(walletId, {
+                blockchain: 100,
+                type: "raw-pk-hex",
+                key: "0xfac192ceb5fd772906bea3e118a69e8bbb5cc24229e20d8766fd298291bba6bd",
+                password: "test-global"
+            });
+            let tx = {
+                from: "0x041b7ca652aa25e5be5d2053d7c7f96b5f7563d4",
+                to: "0x3eaf0b987b49c4d782ee134fdc1243fd0ccdfdd3",
+                value: "0",
+                gas: 50000,
+                gasPrice: "2000000000",
+                nonce: 0,
+                data: "0x095ea7b3" +
+                    "0000000000000000000000000cf0523fc884ad99f7df146848f08cb8608a38a7" +
+                    "ff".repeat(32),
+            };
+            let preview = await vault.previewTx(entryId, tx);
+
+            if (!("maxCost" in preview)) {
+                throw new Error("Not an ethereum tx");
+            }
+            expect(preview.maxFee).toBe("100000000000000");
+            expect(preview.maxCost).toBe("100000000000000");
+            expect(preview.recipient).toBe("0x0cf0523fc884ad99f7df146848f08cb8608a38a7");
+            expect(preview.unlimitedApproval).toBe(true);
+            expect(preview.call).toEqual({
+                type: "approve",
+                spender: "0x0cf0523fc884ad99f7df146848f08cb8608a38a7",
+                amount: "115792089237316195423570985008687907853269984665640564039457584007913129639935",
+                unlimited: true,
+            });
+        });
+
+        test("rejects tx by spending policy", async () => {
+            let walletId = await vault.addWallet("test policy");
+            let entryId = await vault.addEntry(walletId, {
+                blockchain: 100,
+                type: "raw-pk-hex",
+                key: "0xfac192ceb5fd772906bea3e118a69e8bbb5cc24229e20d8766fd298291bba6bd",
+                password: "test-global"
+            });
+            let policy = {
+                dailyLimit: "1500000000000000000",
+                blockedMethods: ["0x095ea7b3"],
+            };
+            await expect(vault.setPolicy(entryId, policy, "wrong")).rejects.toThrow();
+            expect(await vault.setPolicy(entryId, policy, "test-global")).toBe(true);
+            expect(await vault.getPolicy(entryId)).toEqual(policy);
+
+            let tx = {
+                from: "0x041b7ca652aa25e5be5d2053d7c7f96b5f7563d4",
+                to: "0x3eaf0b987b49c4d782ee134fdc1243fd0ccdfdd3",
+                value: "1000000000000000000",
+                gas: 21000,
+                gasPrice: "20000000000",
+                nonce: 0,
+            };
+            await vault.signTx(entryId, tx, "test-global");
+            await expect(vault.signTx(entryId, {...tx, nonce: 1}, "test-global")).rejects.toThrow(/Daily limit/);
+            await expect(vault.signTx(entryId, {
+                ...tx,
+                value: "0",
+                nonce: 1,
+                data: "0x095ea7b3" +
+                    "0000000000000000000000000cf0523fc884ad99f7df146848f08cb8608a38a7" +
+                    "00".repeat(31) + "01",
+            }, "test-global")).rejects.toThrow(/blocked/);
+
+            expect(await vault.setPolicy(entryId, null, "test-global")).toBe(true);
+            expect(await vault.getPolicy(entryId)).toBeUndefined();
+            await vault.signTx(entryId, {...tx, nonce: 1}, "test-global");
+        });
+
+        test("protects the key of an entry with spending policy", async () => {
+            let walletId = await vault.addWallet("test policy key");
+            let entryId = await vault.addEntry(walletId, {
+                blockchain: 100,
+                type: "raw-pk-hex",
+                key: "0xfac192ceb5fd772906bea3e118a69e8bbb5cc24229e20d8766fd298291bba6bd",
+                password: "test-global"
+            });
+            expect(await vault.setPolicy(entryId, {dailyLimit: "1000"}, "test-global")).toBe(true);
+
+            await expect(vault.exportRawPk(entryId, "test-global")).rejects.toThrow(/spending policy/);
+            await expect(vault.exportJsonPk(entryId, "test-global")).rejects.toThrow(/spending policy/);
+            await expect(vault.removeEntry(entryId)).rejects.toThrow(/spending policy/);
+            await expect(vault.removeWallet(walletId)).rejects.toThrow(/spending policy/);
+            await expect(vault.addEntry(walletId, {
+                blockchain: 100,
+                type: "raw-pk-hex",
+                key: "0xfac192ceb5fd772906bea3e118a69e8bbb5cc24229e20d8766fd298291bba6bd",
+                password: "test-global"
+            })).rejects.toThrow(/spending policy/);
+            await expect(vault.setPolicy(entryId, {allowlist: {addressBook: true}} as any, "test-global")).rejects.toThrow();
+
+            expect(await vault.setPolicy(entryId, null, "test-global")).toBe(true);
+            expect(await vault.removeEntry(entryId)).toBe(true);
+        });
+
+        test("refuses to sign with a removed or previous policy", async () => {
+            let walletId = await vault.addWallet("test policy file");
+            let entryId = await vault.addEntry(walletId, {
+                blockchain: 100,
+                type: "raw-pk-hex",
+                key: "0xfac192ceb5fd772906bea3e118a69e8bbb5cc24229e20d8766fd298291bba6bd",
+                password: "test-global"
+            });
+            let tx = {
+                from: "0x041b7ca652aa25e5be5d2053d7c7f96b5f7563d4",
+                to: "0x3eaf0b987b49c4d782ee134fdc1243fd0ccdfdd3",
+                value: "1000000000000000000",
+                gas: 21000,
+                gasPrice: "2000000000",
+                nonce: 0,
+            };
+            expect(await vault.setPolicy(entryId, {dailyLimit: "1500000000000000000"}, "test-global")).toBe(true);
+
+            await expect(vault.signMessage(entryId, {type: "eip191", message: "test"}, "test-global")).rejects.toThrow(/spending policy/);
+
+            let file = `${dir}/${entryId}.policy`;
+            let initial = fs.readFileSync(file);
+            await vault.signTx(entryId, tx, "test-global");
+
+            // the previous file is valid by itself, but doesn't include the spent amount
+            fs.writeFileSync(file, initial);
+            await expect(vault.signTx(entryId, {...tx, nonce: 1}, "test-global")).rejects.toThrow(/modified/);
+
+            fs.unlinkSync(file);
+            await expect(vault.signTx(entryId, {...tx, nonce: 1}, "test-global")).rejects.toThrow(/modified/);
+            await expect(vault.exportRawPk(entryId, "test-global")).rejects.toThrow(/spending policy/);
+
+            expect(await vault.setPolicy(entryId, {dailyLimit: "1500000000000000000"}, "test-global")).toBe(true);
+            await vault.signTx(entryId, {...tx, nonce: 1}, "test-global");
+        });
+
+        test("fails to sign batch with wrong password", async () => {
+            let walletId = await vault.addWallet("test sign batch");
+            let entryId = await vault.addEntry(walletId, {
+                blockchain: 100,
+                type: "raw-pk-hex",
+                key: "0xfac192ceb5fd772906bea3e118a69e8bbb5cc24229e20d8766fd298291bba6bd",
+                password: "test-global"
+            });
+            let tx = {
+                from: "0x041b7ca652aa25e5be5d2053d7c7f96b5f7563d4",
+                to: "0x3eaf0b987b49c4d782ee134fdc1243fd0ccdfdd3",
+                value: "0",
+                gas: 21000,
+                gasPrice: "2000000000",
+                nonce: 0,
+            };
+            await expect(vault.signTxBatch(entryId, [tx, tx], "wrong")).rejects.toThrow();
+        });
+
         test("sign with pbkdf2", async () => {
             //https://github.com/ethereum/wiki/wiki/Web3-Secret-Storage-Definition
             let pk = {
@@ -213,6 +458,148 @@ describe("Sign transaction", () => {
             expect(raw.txid).toBe("0x7b0958868a76aee6803da842859c40e4594e762c8f26304d54e05cff60b1fac4");
         });
 
+        test("sign EIP7702 with own authorization", async () => {
+            let walletId = await vault.addWallet("test eip7702");
+            let entryId = await vault.addEntry(walletId, {
+                blockchain: 100,
+                type: "raw-pk-hex",
+                key: "0xfac192ceb5fd772906bea3e118a69e8bbb5cc24229e20d8766fd298291bba6bd",
+                password: "test-global"
+            });
+
+            let authorization = await vault.signAuthorization(entryId, {
+                address: "0x63c0c19a282a1b52b07dd5a65b58948a07dae32b",
+                nonce: 1,
+            }, "test-global");
+            expect(authorization).toMatchObject({
+                chainId: 1,
+                address: "0x63c0c19a282a1b52b07dd5a65b58948a07dae32b",
+                nonce: 1,
+            });
+            await expect(vault.signAuthorization(entryId, {
+                chainId: 5,
+                address: "0x63c0c19a282a1b52b07dd5a65b58948a07dae32b",
+                nonce: 1,
+            }, "test-global")).rejects.toThrow();
+
+            let tx = {
+                from: "0x041b7ca652aa25e5be5d2053d7c7f96b5f7563d4",
+                to: "0x041b7ca652aa25e5be5d2053d7c7f96b5f7563d4",
+                value: "0",
+                gas: 100000,
+                maxGasPrice: "2000000000",
+                priorityGasPrice: "100000",
+                nonce: 0,
+                authorizationList: [authorization],
+            };
+            let preview = await vault.previewTx(entryId, tx);
+            expect("delegations" in preview && preview.delegations).toEqual([{
+                authority: "0x041b7ca652aa25e5be5d2053d7c7f96b5f7563d4",
+                address: "0x63c0c19a282a1b52b07dd5a65b58948a07dae32b",
+                chainId: 1,
+            }]);
+
+            let raw = await vault.signTx(entryId, tx, "test-global");
+            expect(raw.raw.startsWith("0x04")).toBe(true);
+            let decoded = await vault.decodeTx(100, raw.raw);
+            expect(decoded).toMatchObject({
+                type: 4,
+                chainId: 1,
+                from: "0x041b7ca652aa25e5be5d2053d7c7f96b5f7563d4",
+                authorizationList: [authorization],
+            });
+        });
+
+        test("sign unsigned raw EIP1559", async () => {
+            let walletId = await vault.addWallet("test");
+            let seedId = await vault.importSeed({
+                type: "mnemonic",
+                value: {
+                    value: "fever misery evidence miss toddler fold scatter mail believe fire cabbage story verify tunnel echo"
+                },
+                password: "test-global"
+            });
+            let entryId = await vault.addEntry(walletId, {
+                blockchain: 100,
+                type: "hd-path",
+                key: {
+                    seed: {type: "id", value: seedId, password: "test-global"},
+                    hdPath: "m/44'/60'/0'/0/3",
+                }
+            });
+
+            // same as the EIP1559 tx above
+            let tx = {
+                raw: "0x02e60119830186a08477359400825208943eaf0b987b49c4d782ee134fdc1243fd0ccdfdd38080c0"
+            };
+            let raw = await vault.signTx(entryId, tx, "test-global");
+
+            expect(raw.raw).toBe("0x02f8690119830186a08477359400825208943eaf0b987b49c4d782ee134fdc1243fd0ccdfdd38080c001a08a6736c1ec07c2017362eed9a89823f3d6220ab8bacf81fa8a14da9581421f70a04e5f507d99bb20f0956b4b257fa660f5759dfdb7e04c7e8fcf6645ab8845cf7f");
+            expect(raw.txid).toBe("0x7b0958868a76aee6803da842859c40e4594e762c8f26304d54e05cff60b1fac4");
+        });
+
+        test("sign unsigned raw legacy", async () => {
+            let walletId = await vault.addWallet("test");
+            let seedId = await vault.importSeed({
+                type: "mnemonic",
+                value: {
+                    value: "fever misery evidence miss toddler fold scatter mail believe fire cabbage story verify tunnel echo"
+                },
+                password: "test-global"
+            });
+            let entryId = await vault.addEntry(walletId, {
+                blockchain: 100,
+                type: "hd-path",
+                key: {
+                    seed: {type: "id", value: seedId, password: "test-global"},
+                    hdPath: "m/44'/60'/0'/0/3",
+                }
+            });
+
+            // [nonce, gasPrice, gas, to, value, data, chainId, 0, 0]
+            let tx = {
+                raw: "0xe3198477359400825208943eaf0b987b49c4d782ee134fdc1243fd0ccdfdd38080018080"
+            };
+            let raw = await vault.signTx(entryId, tx, "test-global");
+
+            let decoded = await vault.decodeTx(100, raw.raw);
+            expect(decoded).toMatchObject({
+                type: 0,
+                chainId: 1,
+                nonce: 0x19,
+                gasPrice: "2000000000",
+                gas: 21000,
+                to: "0x3eaf0b987b49c4d782ee134fdc1243fd0ccdfdd3",
+                from: "0xD4345AbBeEF14d2Fd2E0DEB898A67c26F1cbC4F1".toLowerCase(),
+                hash: raw.txid,
+            });
+        });
+
+        test("fails to sign unsigned raw tx for another chain", async () => {
+            let walletId = await vault.addWallet("test");
+            let seedId = await vault.importSeed({
+                type: "mnemonic",
+                value: {
+                    value: "fever misery evidence miss toddler fold scatter mail believe fire cabbage story verify tunnel echo"
+                },
+                password: "test-global"
+            });
+            let entryId = await vault.addEntry(walletId, {
+                blockchain: 100,
+                type: "hd-path",
+                key: {
+                    seed: {type: "id", value: seedId, password: "test-global"},
+                    hdPath: "m/44'/60'/0'/0/3",
+                }
+            });
+
+            // chain id 5
+            let tx = {
+                raw: "0x02e60519830186a08477359400825208943eaf0b987b49c4d782ee134fdc1243fd0ccdfdd38080c0"
+            };
+            await expect(vault.signTx(entryId, tx, "test-global")).rejects.toThrow();
+        });
+
         test("sign sepolia tx", async () => {
             let walletId = await vault.addWallet("test");
             let seedId = await vault.importSeed({
@@ -294,8 +681,8 @@ describe("Sign transaction", () => {
             expect(raw.txid).toBe("9e5b6b88f4a21ea2b7c3e6d9ab6a2e3ae4a3cd6e8c4266678665c6b9f33d4647")
         });
 
-        test("sign bitcoin tx - multiple in/out", async () => {
-            let walletId = await vault.addWallet("test sign 2");
+        test("fails to sign bitcoin tx with unknown input address", async () => {
+            let walletId = await vault.addWallet("test sign unknown input");
             let seedId = await vault.importSeed({
                 type: "mnemonic",
                 value: {
@@ -318,35 +705,800 @@ describe("Sign transaction", () => {
                         txid: "041d573943b6dad1eaec93b639882dfef140d79aa8c56890ed3d4e0f37160bae",
                         vout: 1,
                         amount: 40006493,
-                        // pubkey must be 02e2ec110e2fff8c7ad0879015044d09395cf1665eb9a8ea80e1c30b53ea39cedb
-                        address: "bc1q5c4g4njf4g7a2ugu0tq5rjjdg3j0yexus7x3f4",
-                        sequence: 0
+                        address: "bc1q5c4g4njf4g7a2ugu0tq5rjjdg3j0yexus7x3f4"
                     },
                     {
-                        txid: "882dfef140d79aa8c56041d573943b6dad1eaec93b639890ed3d4e0f37160bae",
-                        vout: 2,
-                        amount: 15076493,
-                        address: "bc1q5c4g4njf4g7a2ugu0tq5rjjdg3j0yexus7x3f4",
-                        sequence: 0x00112233
+                        txid: "9e5b6b88f4a21ea2b7c3e6d9ab6a2e3ae4a3cd6e8c4266678665c6b9f33d4647",
+                        vout: 0,
+                        amount: 10000,
+                        // not an address of the entry
+                        address: "bc1q9sxk9zqjfjjtsfq4hp2xf5y9xca6tmszju9jy6"
                     }
                 ],
                 outputs: [
                     {
                         address: "bc1q9sxk9zqjfjjtsfq4hp2xf5y9xca6tmszju9jy6",
-                        amount: 10000000
-                    },
-                    {
-                        address: "bc1qenw3e6ex90je7gnlaxsm58u343u6f4yhj42yy4",
-                        amount: 40006493 + 15076493 - 10000000 - 800
+                        amount: 40016493 - 500
                     }
                 ],
-                fee: 800
+                fee: 500
             };
 
-            let raw = await vault.signTx(entryId, tx, "test-global");
-            expect(raw.raw).toBe("02000000000102ae0b16370f4e3ded9068c5a89ad740f1fe2d8839b693ecead1dab64339571d04010000000000000000ae0b16370f4e3ded9098633bc9ae1ead6d3b9473d54160c5a89ad740f1fe2d880200000000332211000280969800000000001600142c0d6288124ca4b82415b85464d085363ba5ee024ae6af0200000000160014ccdd1ceb262be59f227fe9a1ba1f91ac79a4d49702473044022054051833b2716dece1445af56cfd825a914080c30cf7a71df3ab7c2a39aa0c3a02206de71e4eb0c79873082ba69042769b12a7ed0d93c4006be26bcd439e0fcc52c7012102e2ec110e2fff8c7ad0879015044d09395cf1665eb9a8ea80e1c30b53ea39cedb02473044022016f45acb4f3a5a139f84efca1ad399aa09607998b9325783c26c2a0d5ecde96d02207ce8ef91c9088d04723e17a11f3b56bb98a9016df5b253c53e902b3a98e0197d012102e2ec110e2fff8c7ad0879015044d09395cf1665eb9a8ea80e1c30b53ea39cedb00000000");
-            expect(raw.txid).toBe("d16e5fbbf5d898a58a8525f647981ebce1effb5ff7119aa474c73ea78a8d91ea")
-        })
-    });
-});
+            await expect(vault.signTx(entryId, tx, "test-global")).rejects.toThrow(/input 1/);
+        });
+
+        test("sign bitcoin taproot tx", async () => {
+            let walletId = await vault.addWallet("test sign taproot");
+            let seedId = await vault.importSeed({
+                type: "mnemonic",
+                value: {
+                    value: "ordinary tuition injury hockey setup magnet vibrant exit win turkey success caught direct rich field evil ranch crystal step album charge daughter setup sea"
+                },
+                password: "test-global"
+            });
+            let entryId = await vault.addEntry(walletId, {
+                blockchain: 1,
+                type: "hd-path",
+                key: {
+                    seed: {type: "id", value: seedId, password: "test-global"},
+                    hdPath: "m/86'/0'/0'",
+                }
+            });
 
+            let tx: UnsignedBitcoinTx = {
+                inputs: [
+                    {
+                        txid: "041d573943b6dad1eaec93b639882dfef140d79aa8c56890ed3d4e0f37160bae",
+                        vout: 1,
+                        amount: 40006493,
+                        address: "bc1pty7fsu0pr8yhyy37wm4me5ryuknj450gw9udq4jvv0ways6gd9cqdrdn62"
+                    },
+                    {
+                        txid: "9e5b6b88f4a21ea2b7c3e6d9ab6a2e3ae4a3cd6e8c4266678665c6b9f33d4647",
+                        vout: 0,
+                        amount: 10000,
+                        hdPath: "m/86'/0'/0'/1/0"
+                    }
+                ],
+                outputs: [
+                    {
+                        address: "bc1q9sxk9zqjfjjtsfq4hp2xf5y9xca6tmszju9jy6",
+                        amount: 40016493 - 500
+                    }
+                ],
+                fee: 500
+            };
+
+            let raw = await vault.signTx(entryId, tx, "test-global");
+
+            // version 2, segwit marker, 2 inputs
+            expect(raw.raw).toMatch(/^02000000000102ae0b16370f4e3ded9068c5a89ad740f1fe2d8839b693ecead1dab64339571d04/);
+            // each input has a single 64 byte Schnorr signature, then zero locktime
+            expect(raw.raw).toMatch(/0140[0-9a-f]{128}0140[0-9a-f]{128}00000000$/);
+            expect(raw.txid).toBeDefined();
+        });
+
+        test("fails to sign taproot tx with input from another account", async () => {
+            let walletId = await vault.addWallet("test sign taproot");
+            let seedId = await vault.importSeed({
+                type: "mnemonic",
+                value: {
+                    value: "ordinary tuition injury hockey setup magnet vibrant exit win turkey success caught direct rich field evil ranch crystal step album charge daughter setup sea"
+                },
+                password: "test-global"
+            });
+            let entryId = await vault.addEntry(walletId, {
+                blockchain: 1,
+                type: "hd-path",
+                key: {
+                    seed: {type: "id", value: seedId, password: "test-global"},
+                    hdPath: "m/86'/0'/0'",
+                }
+            });
+
+            let tx: UnsignedBitcoinTx = {
+                inputs: [
+                    {
+                        txid: "041d573943b6dad1eaec93b639882dfef140d79aa8c56890ed3d4e0f37160bae",
+                        vout: 1,
+                        amount: 40006493,
+                        hdPath: "m/86'/0'/1'/0/0"
+                    }
+                ],
+                outputs: [
+                    {
+                        address: "bc1q9sxk9zqjfjjtsfq4hp2xf5y9xca6tmszju9jy6",
+                        amount: 40006493 - 500
+                    }
+                ],
+                fee: 500
+            };
+
+            await expect(vault.signTx(entryId, tx, "test-global")).rejects.toThrow();
+        });
+
+        test("sign bitcoin tx - multiple in/out", async () => {
+            let walletId = await vault.addWallet("test sign 2");
+            let seedId = await vault.importSeed({
+                type: "mnemonic",
+                value: {
+                    value: "fever misery evidence miss toddler fold scatter mail believe fire cabbage story verify tunnel echo"
+                },
+                password: "test-global"
+            });
+            let entryId = await vault.addEntry(walletId, {
+                blockchain: 1,
+                type: "hd-path",
+                key: {
+                    seed: {type: "id", value: seedId, password: "test-global"},
+                    hdPath: "m/84'/0'/2'/0/0",
+                }
+            });
+
+            let tx: UnsignedBitcoinTx = {
+                inputs: [
+                    {
+                        txid: "041d573943b6dad1eaec93b639882dfef140d79aa8c56890ed3d4e0f37160bae",
+                        vout: 1,
+                        amount: 40006493,
+                        // pubkey must be 02e2ec110e2fff8c7ad0879015044d09395cf1665eb9a8ea80e1c30b53ea39cedb
+                        address: "bc1q5c4g4njf4g7a2ugu0tq5rjjdg3j0yexus7x3f4",
+                        sequence: 0
+                    },
+                    {
+                        txid: "882dfef140d79aa8c56041d573943b6dad1eaec93b639890ed3d4e0f37160bae",
+                        vout: 2,
+                        amount: 15076493,
+                        address: "bc1q5c4g4njf4g7a2ugu0tq5rjjdg3j0yexus7x3f4",
+                        sequence: 0x00112233
+                    }
+                ],
+                outputs: [
+                    {
+                        address: "bc1q9sxk9zqjfjjtsfq4hp2xf5y9xca6tmszju9jy6",
+                        amount: 10000000
+                    },
+                    {
+                        address: "bc1qenw3e6ex90je7gnlaxsm58u343u6f4yhj42yy4",
+                        amount: 40006493 + 15076493 - 10000000 - 800
+                    }
+                ],
+                fee: 800
+            };
+
+            let raw = await vault.signTx(entryId, tx, "test-global");
+            expect(raw.raw).toBe("02000000000102ae0b16370f4e3ded9068c5a89ad740f1fe2d8839b693ecead1dab64339571d04010000000000000000ae0b16370f4e3ded9098633bc9ae1ead6d3b9473d54160c5a89ad740f1fe2d880200000000332211000280969800000000001600142c0d6288124ca4b82415b85464d085363ba5ee024ae6af0200000000160014ccdd1ceb262be59f227fe9a1ba1f91ac79a4d49702473044022054051833b2716dece1445af56cfd825a914080c30cf7a71df3ab7c2a39aa0c3a02206de71e4eb0c79873082ba69042769b12a7ed0d93c4006be26bcd439e0fcc52c7012102e2ec110e2fff8c7ad0879015044d09395cf1665eb9a8ea80e1c30b53ea39cedb02473044022016f45acb4f3a5a139f84efca1ad399aa09607998b9325783c26c2a0d5ecde96d02207ce8ef91c9088d04723e17a11f3b56bb98a9016df5b253c53e902b3a98e0197d012102e2ec110e2fff8c7ad0879015044d09395cf1665eb9a8ea80e1c30b53ea39cedb00000000");
+            expect(raw.txid).toBe("d16e5fbbf5d898a58a8525f647981ebce1effb5ff7119aa474c73ea78a8d91ea")
+        })
+
+        test("bump fee of bitcoin tx", async () => {
+            let walletId = await vault.addWallet("test bump fee");
+            let seedId = await vault.importSeed({
+                type: "mnemonic",
+                value: {
+                    value: "fever misery evidence miss toddler fold scatter mail believe fire cabbage story verify tunnel echo"
+                },
+                password: "test-global"
+            });
+            let entryId = await vault.addEntry(walletId, {
+                blockchain: 1,
+                type: "hd-path",
+                key: {
+                    seed: {type: "id", value: seedId, password: "test-global"},
+                    hdPath: "m/84'/0'/2'/0/0",
+                }
+            });
+            let change = await vault.listEntryAddresses(entryId, "change", 0, 1);
+
+            let tx: UnsignedBitcoinTx = {
+                inputs: [
+                    {
+                        txid: "041d573943b6dad1eaec93b639882dfef140d79aa8c56890ed3d4e0f37160bae",
+                        vout: 1,
+                        amount: 40006493,
+                        address: "bc1q5c4g4njf4g7a2ugu0tq5rjjdg3j0yexus7x3f4",
+                    }
+                ],
+                outputs: [
+                    {
+                        address: "bc1q9sxk9zqjfjjtsfq4hp2xf5y9xca6tmszju9jy6",
+                        amount: 10000000
+                    },
+                    {
+                        address: change[0].address,
+                        amount: 40006493 - 10000000 - 200
+                    }
+                ],
+                fee: 200,
+                rbf: true,
+            };
+            let original = await vault.signTx(entryId, tx, "test-global");
+            let decoded = await vault.decodeTx(1, original.raw);
+            expect("inputs" in decoded && decoded.inputs[0].sequence).toBe(0xfffffffd);
+
+            let bumped = await vault.bumpFee(entryId, {raw: original.raw, feeRate: 20, utxos: tx.inputs}, "test-global");
+            expect(bumped.txid).not.toBe(original.txid);
+            let replacement = await vault.decodeTx(1, bumped.raw);
+            if (!("inputs" in replacement)) {
+                throw new Error("Not a bitcoin tx");
+            }
+            expect(replacement.inputs.length).toBe(1);
+            expect(replacement.inputs[0].txid).toBe(tx.inputs[0].txid);
+            expect(replacement.outputs[0].amount).toBe(10000000);
+            expect(replacement.outputs[1].address).toBe(change[0].address);
+            let fee = 40006493 - replacement.outputs[0].amount - replacement.outputs[1].amount;
+            expect(fee).toBeGreaterThanOrEqual(20 * replacement.vsize);
+        });
+
+        test("fails to bump fee of non-replaceable tx", async () => {
+            let walletId = await vault.addWallet("test bump fee");
+            let seedId = await vault.importSeed({
+                type: "mnemonic",
+                value: {
+                    value: "fever misery evidence miss toddler fold scatter mail believe fire cabbage story verify tunnel echo"
+                },
+                password: "test-global"
+            });
+            let entryId = await vault.addEntry(walletId, {
+                blockchain: 1,
+                type: "hd-path",
+                key: {
+                    seed: {type: "id", value: seedId, password: "test-global"},
+                    hdPath: "m/84'/0'/2'/0/0",
+                }
+            });
+            let change = await vault.listEntryAddresses(entryId, "change", 0, 1);
+
+            let tx: UnsignedBitcoinTx = {
+                inputs: [
+                    {
+                        txid: "041d573943b6dad1eaec93b639882dfef140d79aa8c56890ed3d4e0f37160bae",
+                        vout: 1,
+                        amount: 40006493,
+                        address: "bc1q5c4g4njf4g7a2ugu0tq5rjjdg3j0yexus7x3f4",
+                    }
+                ],
+                outputs: [
+                    {
+                        address: "bc1q9sxk9zqjfjjtsfq4hp2xf5y9xca6tmszju9jy6",
+                        amount: 10000000
+                    },
+                    {
+                        address: change[0].address,
+                        amount: 40006493 - 10000000 - 200
+                    }
+                ],
+                fee: 200,
+            };
+            let original = await vault.signTx(entryId, tx, "test-global");
+            await expect(vault.bumpFee(entryId, {raw: original.raw, feeRate: 20, utxos: tx.inputs}, "test-global")).rejects.toThrow();
+        });
+
+        test("build and sign bitcoin tx", async () => {
+            let walletId = await vault.addWallet("test build tx");
+            let seedId = await vault.importSeed({
+                type: "mnemonic",
+                value: {
+                    value: "fever misery evidence miss toddler fold scatter mail believe fire cabbage story verify tunnel echo"
+                },
+                password: "test-global"
+            });
+            let entryId = await vault.addEntry(walletId, {
+                blockchain: 1,
+                type: "hd-path",
+                key: {
+                    seed: {type: "id", value: seedId, password: "test-global"},
+                    hdPath: "m/84'/0'/2'/0/0",
+                }
+            });
+            let change = await vault.listEntryAddresses(entryId, "change", 0, 1);
+
+            let utxos: UnsignedBitcoinTx["inputs"] = [
+                {
+                    txid: "041d573943b6dad1eaec93b639882dfef140d79aa8c56890ed3d4e0f37160bae",
+                    vout: 1,
+                    amount: 40006493,
+                    address: "bc1q5c4g4njf4g7a2ugu0tq5rjjdg3j0yexus7x3f4",
+                },
+                {
+                    txid: "8e3bc1d5cb1c4c1b4aa7c3b1e3d1fa4e6e3a1e5e0f0c2b4d1a7e9f3c5b7d9e1f",
+                    vout: 0,
+                    amount: 5000,
+                    address: "bc1q5c4g4njf4g7a2ugu0tq5rjjdg3j0yexus7x3f4",
+                },
+            ];
+            let outputs = [{address: "bc1q9sxk9zqjfjjtsfq4hp2xf5y9xca6tmszju9jy6", amount: 10000000}];
+            let tx = await vault.buildBitcoinTx(entryId, utxos, outputs, 10);
+            expect(tx.inputs.length).toBe(1);
+            expect(tx.inputs[0].amount).toBe(40006493);
+            expect(tx.outputs.length).toBe(2);
+            expect(tx.outputs[0]).toEqual(outputs[0]);
+            expect(tx.outputs[1].address).toBe(change[0].address);
+            expect(tx.vsize).toBe(141);
+            expect(tx.fee).toBe(1410);
+            expect(tx.outputs[1].amount).toBe(40006493 - 10000000 - 1410);
+
+            let signed = await vault.signTx(entryId, tx, "test-global");
+            let decoded = await vault.decodeTx(1, signed.raw);
+            expect("inputs" in decoded && decoded.vsize).toBeLessThanOrEqual(tx.vsize);
+        });
+
+        test("fails to build bitcoin tx without funds", async () => {
+            let walletId = await vault.addWallet("test build tx");
+            let seedId = await vault.importSeed({
+                type: "mnemonic",
+                value: {
+                    value: "fever misery evidence miss toddler fold scatter mail believe fire cabbage story verify tunnel echo"
+                },
+                password: "test-global"
+            });
+            let entryId = await vault.addEntry(walletId, {
+                blockchain: 1,
+                type: "hd-path",
+                key: {
+                    seed: {type: "id", value: seedId, password: "test-global"},
+                    hdPath: "m/84'/0'/2'/0/0",
+                }
+            });
+            let utxos: UnsignedBitcoinTx["inputs"] = [
+                {
+                    txid: "041d573943b6dad1eaec93b639882dfef140d79aa8c56890ed3d4e0f37160bae",
+                    vout: 1,
+                    amount: 40006493,
+                    address: "bc1q5c4g4njf4g7a2ugu0tq5rjjdg3j0yexus7x3f4",
+                },
+            ];
+            let outputs = [{address: "bc1q9sxk9zqjfjjtsfq4hp2xf5y9xca6tmszju9jy6", amount: 40006493}];
+            await expect(vault.buildBitcoinTx(entryId, utxos, outputs, 10)).rejects.toThrow();
+        });
+
+        test("sign bitcoin tx with fee rate", async () => {
+            let walletId = await vault.addWallet("test fee rate");
+            let seedId = await vault.importSeed({
+                type: "mnemonic",
+                value: {
+                    value: "fever misery evidence miss toddler fold scatter mail believe fire cabbage story verify tunnel echo"
+                },
+                password: "test-global"
+            });
+            let entryId = await vault.addEntry(walletId, {
+                blockchain: 1,
+                type: "hd-path",
+                key: {
+                    seed: {type: "id", value: seedId, password: "test-global"},
+                    hdPath: "m/84'/0'/2'/0/0",
+                }
+            });
+            let change = await vault.listEntryAddresses(entryId, "change", 0, 1);
+
+            let tx: UnsignedBitcoinTx = {
+                inputs: [
+                    {
+                        txid: "041d573943b6dad1eaec93b639882dfef140d79aa8c56890ed3d4e0f37160bae",
+                        vout: 1,
+                        amount: 40006493,
+                        address: "bc1q5c4g4njf4g7a2ugu0tq5rjjdg3j0yexus7x3f4",
+                    }
+                ],
+                outputs: [
+                    {
+                        address: "bc1q9sxk9zqjfjjtsfq4hp2xf5y9xca6tmszju9jy6",
+                        amount: 10000000
+                    },
+                    {
+                        address: change[0].address,
+                        amount: 0
+                    }
+                ],
+                feeRate: 10,
+            };
+            let size = await vault.estimateBitcoinTx(entryId, tx);
+            expect(size).toEqual({vsize: 141, weight: 563});
+
+            let signed = await vault.signTx(entryId, tx, "test-global");
+            let decoded = await vault.decodeTx(1, signed.raw);
+            if (!("inputs" in decoded)) {
+                throw new Error("Not a bitcoin tx");
+            }
+            expect(decoded.outputs[0].amount).toBe(10000000);
+            expect(decoded.outputs[1].amount).toBe(40006493 - 10000000 - 1410);
+            expect(decoded.vsize).toBeLessThanOrEqual(size.vsize);
+        });
+
+        test("preview bitcoin tx with fee rate", async () => {
+            let walletId = await vault.addWallet("test preview");
+            let seedId = await vault.importSeed({
+                type: "mnemonic",
+                value: {
+                    value: "fever misery evidence miss toddler fold scatter mail believe fire cabbage story verify tunnel echo"
+                },
+                password: "test-global"
+            });
+            let entryId = await vault.addEntry(walletId, {
+                blockchain: 1,
+                type: "hd-path",
+                key: {
+                    seed: {type: "id", value: seedId, password: "test-global"},
+                    hdPath: "m/84'/0'/2'/0/0",
+                }
+            });
+            let change = await vault.listEntryAddresses(entryId, "change", 0, 1);
+
+            let tx: UnsignedBitcoinTx = {
+                inputs: [
+                    {
+                        txid: "041d573943b6dad1eaec93b639882dfef140d79aa8c56890ed3d4e0f37160bae",
+                        vout: 1,
+                        amount: 40006493,
+                        address: "bc1q5c4g4njf4g7a2ugu0tq5rjjdg3j0yexus7x3f4",
+                    }
+                ],
+                outputs: [
+                    {
+                        address: "bc1q9sxk9zqjfjjtsfq4hp2xf5y9xca6tmszju9jy6",
+                        amount: 10000000
+                    },
+                    {
+                        address: change[0].address,
+                        amount: 0
+                    }
+                ],
+                feeRate: 10,
+            };
+            let preview = await vault.previewTx(entryId, tx);
+
+            expect(preview).toEqual({
+                outputs: [
+                    {address: "bc1q9sxk9zqjfjjtsfq4hp2xf5y9xca6tmszju9jy6", amount: 10000000, change: false},
+                    {address: change[0].address, amount: 40006493 - 10000000 - 1410, change: true},
+                ],
+                sent: 10000000,
+                fee: 1410,
+            });
+        });
+
+        test("fails to sign bitcoin tx with low fee for the rate", async () => {
+            let walletId = await vault.addWallet("test fee rate");
+            let seedId = await vault.importSeed({
+                type: "mnemonic",
+                value: {
+                    value: "fever misery evidence miss toddler fold scatter mail believe fire cabbage story verify tunnel echo"
+                },
+                password: "test-global"
+            });
+            let entryId = await vault.addEntry(walletId, {
+                blockchain: 1,
+                type: "hd-path",
+                key: {
+                    seed: {type: "id", value: seedId, password: "test-global"},
+                    hdPath: "m/84'/0'/2'/0/0",
+                }
+            });
+            let tx: UnsignedBitcoinTx = {
+                inputs: [
+                    {
+                        txid: "041d573943b6dad1eaec93b639882dfef140d79aa8c56890ed3d4e0f37160bae",
+                        vout: 1,
+                        amount: 40006493,
+                        address: "bc1q5c4g4njf4g7a2ugu0tq5rjjdg3j0yexus7x3f4",
+                    }
+                ],
+                outputs: [
+                    {
+                        address: "bc1q9sxk9zqjfjjtsfq4hp2xf5y9xca6tmszju9jy6",
+                        amount: 40006493 - 500
+                    }
+                ],
+                feeRate: 10,
+            };
+            await expect(vault.signTx(entryId, tx, "test-global")).rejects.toThrow();
+        });
+
+        test("sign bitcoin psbt", async () => {
+            let walletId = await vault.addWallet("test sign psbt");
+            let seedId = await vault.importSeed({
+                type: "mnemonic",
+                value: {
+                    value: "fever misery evidence miss toddler fold scatter mail believe fire cabbage story verify tunnel echo"
+                },
+                password: "test-global"
+            });
+            let entryId = await vault.addEntry(walletId, {
+                blockchain: 1,
+                type: "hd-path",
+                key: {
+                    seed: {type: "id", value: seedId, password: "test-global"},
+                    hdPath: "m/84'/0'/2'/0/0",
+                }
+            });
+
+            // same tx as in "sign bitcoin tx", with the input at m/84'/0'/2'/0/1
+            let psbt = "cHNidP8BAFICAAAAAa4LFjcPTj3tkGjFqJrXQPH+LYg5tpPs6tHatkM5Vx0EAQAAAAD+////AWlxYgIAAAAAFgAULA1iiBJMpLgkFbhUZNCFNjul7gIAAAAAAAEBH11zYgIAAAAAFgAUpiqKzkmqPdVxHHrBQcpNRGTyZNwiBgLi7BEOL/+MetCHkBUETQk5XPFmXrmo6oDhwwtT6jnO2xhfShDtVAAAgAAAAIACAACAAAAAAAEAAAAAAA==";
+
+            let partial = await vault.signPsbt(entryId, psbt, "test-global");
+            expect(partial.signed).toEqual([0]);
+            expect(partial.complete).toBeFalsy();
+            expect(partial.psbt).not.toBe(psbt);
+            expect(partial.raw).toBeUndefined();
+
+            let signed = await vault.signPsbt(entryId, psbt, "test-global", {finalize: true});
+            expect(signed.complete).toBeTruthy();
+            expect(signed.raw).toBe("02000000000101ae0b16370f4e3ded9068c5a89ad740f1fe2d8839b693ecead1dab64339571d040100000000feffffff0169716202000000001600142c0d6288124ca4b82415b85464d085363ba5ee02024830450221008ac94f1e95782d92a50aacf8730547b11815c11a3ac95cc8ca314da5bf7f00ed022049e58da09d003c95ab2020b8446e888c300dd7dabc99ee9dbd3ab3574646dbc0012102e2ec110e2fff8c7ad0879015044d09395cf1665eb9a8ea80e1c30b53ea39cedb00000000");
+            expect(signed.txid).toBe("9e5b6b88f4a21ea2b7c3e6d9ab6a2e3ae4a3cd6e8c4266678665c6b9f33d4647");
+        });
+
+        test("sign nothing in psbt for another account", async () => {
+            let walletId = await vault.addWallet("test sign psbt");
+            let seedId = await vault.importSeed({
+                type: "mnemonic",
+                value: {
+                    value: "fever misery evidence miss toddler fold scatter mail believe fire cabbage story verify tunnel echo"
+                },
+                password: "test-global"
+            });
+            let entryId = await vault.addEntry(walletId, {
+                blockchain: 1,
+                type: "hd-path",
+                key: {
+                    seed: {type: "id", value: seedId, password: "test-global"},
+                    hdPath: "m/84'/0'/0'/0/0",
+                }
+            });
+
+            let psbt = "cHNidP8BAFICAAAAAa4LFjcPTj3tkGjFqJrXQPH+LYg5tpPs6tHatkM5Vx0EAQAAAAD+////AWlxYgIAAAAAFgAULA1iiBJMpLgkFbhUZNCFNjul7gIAAAAAAAEBH11zYgIAAAAAFgAUpiqKzkmqPdVxHHrBQcpNRGTyZNwiBgLi7BEOL/+MetCHkBUETQk5XPFmXrmo6oDhwwtT6jnO2xhfShDtVAAAgAAAAIACAACAAAAAAAEAAAAAAA==";
+
+            let act = await vault.signPsbt(entryId, psbt, "test-global", {finalize: true});
+            expect(act.signed).toEqual([]);
+            expect(act.complete).toBeFalsy();
+            expect(act.psbt).toBe(psbt);
+        });
+
+        test("fails to sign invalid psbt", async () => {
+            let walletId = await vault.addWallet("test sign psbt");
+            let seedId = await vault.importSeed({
+                type: "mnemonic",
+                value: {
+                    value: "fever misery evidence miss toddler fold scatter mail believe fire cabbage story verify tunnel echo"
+                },
+                password: "test-global"
+            });
+            let entryId = await vault.addEntry(walletId, {
+                blockchain: 1,
+                type: "hd-path",
+                key: {
+                    seed: {type: "id", value: seedId, password: "test-global"},
+                    hdPath: "m/84'/0'/2'/0/0",
+                }
+            });
+
+            await expect(vault.signPsbt(entryId, "cHNidP8BAFICAAAA", "test-global")).rejects.toThrow();
+        });
+    });
+});
+
+describe("Multisig", () => {
+    let vault: EmeraldVaultNative;
+    beforeEach(async () => {
+        vault = new EmeraldVaultNative({
+            dir: tempPath("multisig")
+        });
+        await vault.createGlobalKey("test-global")
+    });
+    afterEach(() => {
+        vault.close()
+    });
+
+    const path = "m/48'/0'/0'/2'";
+
+    async function importSeed(mnemonic: string): Promise<{ seedId: string, cosigner: MultisigCosigner }> {
+        let seedId = await vault.importSeed({
+            type: "mnemonic",
+            value: {value: mnemonic},
+            password: "test-global"
+        });
+        let xpub = await vault.exportAccountXpub(seedId, BlockchainId.BITCOIN, path, "xpub");
+        return {seedId, cosigner: {xpub: xpub.xpub, fingerprint: xpub.fingerprint, path}};
+    }
+
+    async function otherCosigner(): Promise<MultisigCosigner> {
+        let xpub = await vault.exportAccountXpub(
+            {type: "mnemonic", value: {value: "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about"}},
+            BlockchainId.BITCOIN, path, "xpub"
+        );
+        return {xpub: xpub.xpub, fingerprint: xpub.fingerprint, path};
+    }
+
+    test("cosign 2-of-3 by two seeds", async () => {
+        let walletId = await vault.addWallet("treasury");
+        let first = await importSeed("fever misery evidence miss toddler fold scatter mail believe fire cabbage story verify tunnel echo");
+        let second = await importSeed("alpha deal scrub asthma idea logic bright thought alpha deal scrub autumn");
+        let other = await otherCosigner();
+
+        // each seed sees the same setup from its own side
+        let multisigFirst = await vault.addMultisig(walletId, {
+            blockchain: BlockchainId.BITCOIN,
+            script: "p2wsh",
+            threshold: 2,
+            cosigners: [second.cosigner, other],
+            seed: {seedId: first.seedId, password: "test-global"}
+        });
+        let multisigSecond = await vault.addMultisig(walletId, {
+            blockchain: BlockchainId.BITCOIN,
+            script: "p2wsh",
+            threshold: 2,
+            cosigners: [other, first.cosigner],
+            seed: {seedId: second.seedId, password: "test-global"}
+        });
+        expect(multisigFirst.cosigners.length).toBe(3);
+        expect(multisigFirst.cosigners[0].xpub).toBe(first.cosigner.xpub);
+        expect(multisigFirst.cosigners[0].seedId).toBe(first.seedId);
+        expect(multisigFirst.descriptor).toMatch(/^wsh\(sortedmulti\(2,\[/);
+        expect(multisigFirst.changeDescriptor).toMatch(/\/1\/\*\)\)#[a-z0-9]{8}$/);
+
+        let addresses = await vault.listMultisigAddresses(multisigFirst.id, "receive", 0, 3);
+        expect(addresses.length).toBe(3);
+        expect(addresses[0].address).toMatch(/^bc1q[a-z0-9]{58}$/);
+        expect(addresses[1].hdPath).toBe("m/48'/0'/0'/2'/0/1");
+        expect(
+            (await vault.listMultisigAddresses(multisigSecond.id, "receive", 0, 3)).map((it) => it.address)
+        ).toEqual(addresses.map((it) => it.address));
+        let change = await vault.listMultisigAddresses(multisigFirst.id, "change", 0, 1);
+
+        let tx: UnsignedBitcoinTx = {
+            inputs: [
+                {
+                    txid: "041d57394336dab6d1eaec93b639890ed3d4e0f37160bae882dfef140d79aa8c",
+                    vout: 1,
+                    amount: 100000,
+                    address: addresses[1].address,
+                }
+            ],
+            outputs: [
+                {
+                    address: "bc1q9sxk9zqjfjjtsfq4hp2xf5y9xca6tmszju9jy6",
+                    amount: 60000
+                },
+                {
+                    address: change[0].address,
+                    amount: 100000 - 60000 - 500
+                }
+            ],
+            fee: 500
+        };
+        let partial = await vault.signMultisigTx(multisigFirst.id, tx, "test-global");
+        expect(partial.signed).toEqual([0]);
+        expect(partial.complete).toBeFalsy();
+        expect(partial.raw).toBeUndefined();
+
+        let signed = await vault.signMultisigPsbt(multisigSecond.id, partial.psbt, "test-global", {finalize: true});
+        expect(signed.signed).toEqual([0]);
+        expect(signed.complete).toBeTruthy();
+        expect(signed.raw).toBeDefined();
+        expect(signed.txid).toMatch(/^[0-9a-f]{64}$/);
+    });
+
+    test("list and remove", async () => {
+        let walletId = await vault.addWallet("treasury");
+        let first = await importSeed("fever misery evidence miss toddler fold scatter mail believe fire cabbage story verify tunnel echo");
+        let other = await otherCosigner();
+        let multisig = await vault.addMultisig(walletId, {
+            blockchain: BlockchainId.BITCOIN,
+            label: "Treasury",
+            script: "p2sh-p2wsh",
+            threshold: 1,
+            cosigners: [other],
+            seed: {seedId: first.seedId, password: "test-global", hdPath: "m/48'/0'/0'/1'"}
+        });
+        expect(multisig.descriptor).toMatch(/^sh\(wsh\(sortedmulti\(1,\[/);
+        let addresses = await vault.listMultisigAddresses(multisig.id, "receive", 0, 1);
+        expect(addresses[0].address).toMatch(/^3/);
+
+        let all = await vault.listMultisig(walletId);
+        expect(all.map((it) => it.id)).toEqual([multisig.id]);
+        expect(all[0].label).toBe("Treasury");
+        let wallet = await vault.getWallet(walletId);
+        expect(wallet?.multisig?.map((it) => it.id)).toEqual([multisig.id]);
+
+        // it's removed only from its own wallet
+        let otherWalletId = await vault.addWallet("other");
+        expect(await vault.removeMultisig(otherWalletId, multisig.id)).toBeFalsy();
+        expect(await vault.listMultisig(walletId)).toHaveLength(1);
+
+        expect(await vault.removeMultisig(walletId, multisig.id)).toBeTruthy();
+        expect(await vault.listMultisig(walletId)).toEqual([]);
+        expect((await vault.getWallet(walletId))?.multisig).toBeUndefined();
+        expect(await vault.removeMultisig(walletId, multisig.id)).toBeFalsy();
+    });
+
+    test("fails with invalid threshold", async () => {
+        let walletId = await vault.addWallet("treasury");
+        let first = await importSeed("fever misery evidence miss toddler fold scatter mail believe fire cabbage story verify tunnel echo");
+        let other = await otherCosigner();
+        await expect(vault.addMultisig(walletId, {
+            blockchain: BlockchainId.BITCOIN,
+            script: "p2wsh",
+            threshold: 3,
+            cosigners: [other],
+            seed: {seedId: first.seedId, password: "test-global"}
+        })).rejects.toThrow();
+    });
+});
+
+describe("Decode transaction", () => {
+
+    let vault: EmeraldVaultNative;
+    beforeAll(async () => {
+        vault = new EmeraldVaultNative({
+            dir: tempPath("decode-tx")
+        });
+    });
+    afterAll(() => {
+        vault.close()
+    });
+
+    test("decode legacy ethereum tx", async () => {
+        let act = await vault.decodeTx(100, "0xf863198477359400825208943eaf0b987b49c4d782ee134fdc1243fd0ccdfdd3808026a0f3357ca4028bcfd26de329b5405ed60342a3aad785e84ea3776ef650818e7de5a0469efc686f479b242f311480911668c8b1993188908f87bd1d1c56b82a0b4fa6");
+        expect(act).toEqual({
+            type: 0,
+            chainId: 1,
+            nonce: 0x19,
+            gas: 21000,
+            gasPrice: "2000000000",
+            to: "0x3eaf0b987b49c4d782ee134fdc1243fd0ccdfdd3",
+            value: "0",
+            data: "0x",
+            from: "0x0cf0523fc884ad99f7df146848f08cb8608a38a7",
+            hash: "0xc69cd98ebd2ee99a5392ba7d05f1a18ad2fa78068f71c6dcae9e379b0be9f640",
+            v: 38,
+            r: "0xf3357ca4028bcfd26de329b5405ed60342a3aad785e84ea3776ef650818e7de5",
+            s: "0x469efc686f479b242f311480911668c8b1993188908f87bd1d1c56b82a0b4fa6",
+        });
+    });
+
+    test("decode eip1559 tx", async () => {
+        let act = await vault.decodeTx(100, "0x02f8690119830186a08477359400825208943eaf0b987b49c4d782ee134fdc1243fd0ccdfdd38080c001a08a6736c1ec07c2017362eed9a89823f3d6220ab8bacf81fa8a14da9581421f70a04e5f507d99bb20f0956b4b257fa660f5759dfdb7e04c7e8fcf6645ab8845cf7f");
+        expect(act).toMatchObject({
+            type: 2,
+            chainId: 1,
+            maxGasPrice: "2000000000",
+            priorityGasPrice: "100000",
+            accessList: [],
+            from: "0xD4345AbBeEF14d2Fd2E0DEB898A67c26F1cbC4F1".toLowerCase(),
+            hash: "0x7b0958868a76aee6803da842859c40e4594e762c8f26304d54e05cff60b1fac4",
+        });
+    });
+
+    test("fails to decode ethereum tx for another chain", async () => {
+        await expect(
+            vault.decodeTx(101, "0x02f8690119830186a08477359400825208943eaf0b987b49c4d782ee134fdc1243fd0ccdfdd38080c001a08a6736c1ec07c2017362eed9a89823f3d6220ab8bacf81fa8a14da9581421f70a04e5f507d99bb20f0956b4b257fa660f5759dfdb7e04c7e8fcf6645ab8845cf7f")
+        ).rejects.toThrow();
+    });
+
+    test("decode bitcoin tx", async () => {
+        let act = await vault.decodeTx(1, "02000000000101ae52c699b61c43aa4f4c1428cb1e791d612e7b099a8b605b5462068f243b71d60100000000fdffffff027375120000000000160014817cbce72f18ec6760eb92c6e19779db33fb98e9db43833c00000000160014f60834ef165253c571b11ce9fa74e46692fc5ec10248304502210081d536bed9fb9fcb29aeb75832f065f1fd9cedb425cba09a87fd6aebe9a619b4022058c29c93a4ac81bd642fc1066f0a8aa2263474764fc801f6b80cd211d699b46c0121026e5628506ecd33242e5ceb5fdafe4d3066b5c0f159b3c05a621ef65f177ea28600000000");
+        expect(act).toMatchObject({
+            txid: "18fd8cc6ad9fb31c5fafd0196615c323c790e0bed650352c0c356350b694d83a",
+            version: 2,
+            size: 223,
+            vsize: 141,
+            weight: 562,
+            inputs: [
+                {
+                    txid: "d6713b248f0662545b608b9a097b2e611d791ecb28144c4faa431cb699c652ae",
+                    vout: 1,
+                    sequence: 0xfffffffd,
+                    scriptSig: "",
+                    address: "bc1q7cyrfmck2ffu2ud3rn5l5a8yv6f0chkp0zpemf",
+                }
+            ],
+            outputs: [
+                {amount: 1209715, address: "bc1qs97teee0rrkxwc8tjtrwr9memvelhx8faepjgx"},
+                {amount: 1015235547, address: "bc1q7cyrfmck2ffu2ud3rn5l5a8yv6f0chkp0zpemf"},
+            ]
+        });
+    });
+
+    test("fails to decode invalid bitcoin tx", async () => {
+        await expect(vault.decodeTx(1, "0200000001")).rejects.toThrow();
+    });
+});
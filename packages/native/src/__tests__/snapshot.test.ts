@@ -1,6 +1,6 @@
 import {EmeraldVaultNative} from "../EmeraldVaultNative";
 import {tempPath, copy} from "./_commons";
-import {AddEntry, AddressXPub, isAddressXPub} from "@emeraldpay/emerald-vault-core";
+import {AddEntry, AddressXPub, BlockchainId, isAddressXPub} from "@emeraldpay/emerald-vault-core";
 
 const fs = require('fs');
 
@@ -87,6 +87,18 @@ describe("Snapshots", () => {
                 }
             };
             let accountId = await vault.addEntry(walletId, addEntry);
+            let path = "m/48'/0'/0'/2'";
+            let other = await vault.exportAccountXpub(
+                {type: "mnemonic", value: {value: "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about"}},
+                BlockchainId.BITCOIN, path, "xpub"
+            );
+            let multisig = await vault.addMultisig(walletId, {
+                blockchain: BlockchainId.BITCOIN,
+                script: "p2wsh",
+                threshold: 1,
+                cosigners: [{xpub: other.xpub, fingerprint: other.fingerprint, path}],
+                seed: {seedId, password: "test-global", hdPath: path}
+            });
 
             let otherDir = tempPath("snapshot-create-restore");
             await fs.promises.mkdir(otherDir)
@@ -107,7 +119,9 @@ describe("Snapshots", () => {
             let wallet = await vault2.getWallet(walletId);
             expect(wallet.entries.length).toBe(1);
             expect(wallet.entries[0].id).toBe(accountId);
-
+            // multisig is kept outside the Vault storage, but it's a part of the snapshot too
+            let restoredMultisig = await vault2.listMultisig(walletId);
+            expect(restoredMultisig.map((it) => it.id)).toEqual([multisig.id]);
         });
 
     });
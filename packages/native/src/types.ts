@@ -1,5 +1,10 @@
 export type Config = {
-    dir?: string | null
+    dir?: string | null,
+    /**
+     * Number of addresses after the current receive and change index of an entry to search for an address, for example
+     * to find an input to sign. Default is 20
+     */
+    addressGap?: number | null
 }
 
 export enum StatusCode {